@@ -4,7 +4,6 @@ use crate::errors::{process_running, ErrorButtons, ErrorFerris};
 #[cfg(target_os = "windows")]
 use crate::helper::ProcessName;
 use crate::helper::ProcessState;
-use crate::SECOND;
 use egui::CentralPanel;
 use log::debug;
 
@@ -19,9 +18,9 @@ impl eframe::App for App {
         // Handle Keys
         let (key, wants_input) = self.keys_handle(ctx);
 
-        // Refresh AT LEAST once a second
-        debug!("App | Refreshing frame once per second");
-        ctx.request_repaint_after(SECOND);
+        // No timer here, the frame is refreshed when the [Helper] publishes
+        // stats that changed (see [App::repaint_on_snapshot()]). Only the tabs
+        // showing [Sys] ask for one (Status, About).
 
         // Get P2Pool/XMRig process state.
         // These values are checked multiple times so
@@ -89,6 +88,33 @@ impl eframe::App for App {
             self.quit_error_panel(ctx, p2pool_is_alive, xmrig_is_alive, &key);
             return;
         }
+        // XvB mode set through the control socket, [og] is already saved with it.
+        if let Some(mode) = self.helper.lock().unwrap().xvb_mode_request.take() {
+            self.state.xvb.set_mode(mode);
+        }
+        // Profile picked in the [Gupaxx] tab or by a second launch.
+        let profile = self.helper.lock().unwrap().profile_request.take();
+        if let Some(name) = profile {
+            self.switch_profile(&name);
+        }
+        // Configuration archive accepted in the [Gupaxx] tab.
+        let import = {
+            let mut window = self.config_window.lock().unwrap();
+            if std::mem::take(&mut window.apply) {
+                let programs = window.programs;
+                window.import.take().map(|(import, _)| (import, programs))
+            } else {
+                None
+            }
+        };
+        if let Some((import, programs)) = import {
+            self.import_config(&import, programs);
+        }
+        // Launched again, show ourselves instead.
+        if std::mem::take(&mut self.helper.lock().unwrap().focus_request) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
         // Compare [og == state] & [node_vec/pool_vec] and enable diff if found.
         // The struct fields are compared directly because [Version]
         // contains Arc<Mutex>'s that cannot be compared easily.
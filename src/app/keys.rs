@@ -148,9 +148,10 @@ impl App {
         } else if key.is_c() && !wants_input {
             match self.tab {
                 Tab::Status => match self.state.status.submenu {
-                    Submenu::Processes => self.state.status.submenu = Submenu::Benchmarks,
+                    Submenu::Processes => self.state.status.submenu = Submenu::History,
                     Submenu::P2pool => self.state.status.submenu = Submenu::Processes,
                     Submenu::Benchmarks => self.state.status.submenu = Submenu::P2pool,
+                    Submenu::History => self.state.status.submenu = Submenu::Benchmarks,
                 },
                 Tab::Gupax => flip!(self.state.gupax.simple),
                 Tab::Node => flip!(self.state.node.simple),
@@ -166,7 +167,8 @@ impl App {
                 Tab::Status => match self.state.status.submenu {
                     Submenu::Processes => self.state.status.submenu = Submenu::P2pool,
                     Submenu::P2pool => self.state.status.submenu = Submenu::Benchmarks,
-                    Submenu::Benchmarks => self.state.status.submenu = Submenu::Processes,
+                    Submenu::Benchmarks => self.state.status.submenu = Submenu::History,
+                    Submenu::History => self.state.status.submenu = Submenu::Processes,
                 },
                 Tab::Gupax => flip!(self.state.gupax.simple),
                 Tab::P2pool => flip!(self.state.p2pool.simple),
@@ -1,33 +1,45 @@
+use crate::app::panels::middle::console::Console;
 use crate::cli::parse_args;
+use crate::cli::second_instance;
 use crate::cli::Cli;
-use crate::components::gupax::FileWindow;
+use crate::components::gupax::{ConfigWindow, FileWindow};
 use crate::components::node::Ping;
 use crate::components::node::RemoteNode;
 use crate::components::node::REMOTE_NODES;
 use crate::components::update::Update;
+use crate::disk::archive::Import;
 use crate::disk::consts::NODE_TOML;
 use crate::disk::consts::POOL_TOML;
+use crate::disk::consts::PROCESSES_TOML;
 use crate::disk::consts::STATE_TOML;
 use crate::disk::get_gupax_data_path;
 use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use crate::disk::history::History;
+use crate::disk::lock::{InstanceLock, LockError};
 use crate::disk::node::Node;
 use crate::disk::pool::Pool;
+use crate::disk::processes::Processes;
+use crate::disk::profiles;
 use crate::disk::state::State;
 use crate::errors::ErrorButtons;
 use crate::errors::ErrorFerris;
 use crate::errors::ErrorState;
+use crate::helper::control::{BackupHosts, ProcessAction};
+use crate::helper::custom::Custom;
 use crate::helper::node::PubNodeApi;
 use crate::helper::p2pool::ImgP2pool;
 use crate::helper::p2pool::PubP2poolApi;
+use crate::helper::snapshot::StatsSnapshot;
 use crate::helper::xrig::xmrig::ImgXmrig;
 use crate::helper::xrig::xmrig::PubXmrigApi;
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
-use crate::helper::xvb::priv_stats::RuntimeMode;
 use crate::helper::xvb::PubXvbApi;
 use crate::helper::Helper;
 use crate::helper::Process;
 use crate::helper::ProcessName;
+use crate::helper::ProcessSignal;
 use crate::helper::Sys;
+use crate::inits::init_sysinfo;
 use crate::inits::init_text_styles;
 use crate::miscs::cmp_f64;
 use crate::miscs::get_exe;
@@ -53,6 +65,7 @@ use std::process::exit;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Instant;
+use tokio::sync::watch;
 
 pub mod eframe_impl;
 pub mod keys;
@@ -84,12 +97,13 @@ pub struct App {
     pub state: State,               // state = Working state (current settings)
     pub update: Arc<Mutex<Update>>, // State for update data [update.rs]
     pub file_window: Arc<Mutex<FileWindow>>, // State for the path selector in [Gupax]
-    pub ping: Arc<Mutex<Ping>>,     // Ping data found in [node.rs]
-    pub og_node_vec: Vec<(String, Node)>, // Manual Node database
-    pub node_vec: Vec<(String, Node)>, // Manual Node database
-    pub og_pool_vec: Vec<(String, Pool)>, // Manual Pool database
-    pub pool_vec: Vec<(String, Pool)>, // Manual Pool database
-    pub diff: bool,                 // This bool indicates state changes
+    pub config_window: Arc<Mutex<ConfigWindow>>, // State for the configuration export/import in [Gupax]
+    pub ping: Arc<Mutex<Ping>>,                  // Ping data found in [node.rs]
+    pub og_node_vec: Vec<(String, Node)>,        // Manual Node database
+    pub node_vec: Vec<(String, Node)>,           // Manual Node database
+    pub og_pool_vec: Vec<(String, Pool)>,        // Manual Pool database
+    pub pool_vec: Vec<(String, Pool)>,           // Manual Pool database
+    pub diff: bool,                              // This bool indicates state changes
     // Restart state:
     // If Gupax updated itself, this represents that the
     // user should (but isn't required to) restart Gupax.
@@ -103,6 +117,7 @@ pub struct App {
     // This holds everything related to the data processed by the "helper thread".
     // This includes the "helper" threads public P2Pool/XMRig's API.
     pub helper: Arc<Mutex<Helper>>, // [Helper] state, mostly for Gupax uptime
+    pub stats: watch::Receiver<Arc<StatsSnapshot>>, // Last stats published by the [Helper]
     pub pub_sys: Arc<Mutex<Sys>>,   // [Sys] state, read by [Status], mutated by [Helper]
     pub node: Arc<Mutex<Process>>,  // [Node] process state
     pub p2pool: Arc<Mutex<Process>>, // [P2Pool] process state
@@ -121,16 +136,30 @@ pub struct App {
     pub p2pool_stdin: String, // The buffer between the p2pool console and the [Helper]
     pub xmrig_stdin: String, // The buffer between the xmrig console and the [Helper]
     pub xmrig_proxy_stdin: String, // The buffer between the xmrig-proxy console and the [Helper]
+    // Console views, their search and filters
+    pub node_console: Console,
+    pub p2pool_console: Console,
+    pub xmrig_console: Console,
+    pub xmrig_proxy_console: Console,
+    pub xvb_console: Console,
+    // Custom processes from [processes.toml]
+    pub custom: Vec<Custom>,
+    pub custom_stdin: Vec<String>, // The buffers between the custom processes consoles and the [Helper]
     // Sudo State
     pub sudo: Arc<Mutex<SudoState>>, // This is just a dummy struct on [Windows].
     // State from [--flags]
     pub no_startup: bool,
+    // Held for as long as we run, see [disk/lock.rs].
+    pub instance_lock: Option<InstanceLock>,
     // Gupax-P2Pool API
     // Gupax's P2Pool API (e.g: ~/.local/share/gupax/p2pool/)
     // This is a file-based API that contains data for permanent stats.
     // The below struct holds everything needed for it, the paths, the
     // actual stats, and all the functions needed to mutate them.
     pub gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
+    // Stats history (e.g: ~/.local/share/gupax/history/)
+    // Recorded every second by the [Helper], shown as graphs in the [Status] tab.
+    pub history: Arc<Mutex<History>>,
     // Static stuff
     pub benchmarks: Vec<Benchmark>,     // XMRig CPU benchmarks
     pub pid: sysinfo::Pid,              // Gupax's PID
@@ -143,9 +172,14 @@ pub struct App {
     pub admin: bool,                    // Are we admin? (for Windows)
     pub os_data_path: PathBuf,          // OS data path (e.g: ~/.local/share/gupax/)
     pub gupax_p2pool_api_path: PathBuf, // Gupax-P2Pool API path (e.g: ~/.local/share/gupax/p2pool/)
+    pub history_path: PathBuf,          // Stats history path (e.g: ~/.local/share/gupax/history/)
+    pub logs_path: PathBuf,             // Process logs path (e.g: ~/.local/share/gupax/logs/)
+    pub profiles_path: PathBuf,         // State profiles path (e.g: ~/.local/share/gupax/profiles/)
+    pub profile_name: String,           // Name typed for a new profile in the [Gupaxx] tab
     pub state_path: PathBuf,            // State file path
     pub node_path: PathBuf,             // Node file path
     pub pool_path: PathBuf,             // Pool file path
+    pub processes_path: PathBuf,        // Custom processes file path
     pub version: &'static str,          // Gupax version
     pub name_version: String,           // [Gupax vX.X.X]
     #[cfg(target_os = "windows")]
@@ -162,9 +196,23 @@ impl App {
             crate::miscs::clamp_scale(app.state.gupax.selected_scale),
         );
         cc.egui_ctx.set_visuals(VISUALS.clone());
+        // Repaint every time the [Helper] publishes new stats.
+        let snapshot = app.stats.clone();
+        let ctx = cc.egui_ctx.clone();
+        std::thread::spawn(move || Self::repaint_on_snapshot(snapshot, ctx));
         Self { resolution, ..app }
     }
 
+    #[tokio::main(flavor = "current_thread")]
+    async fn repaint_on_snapshot(
+        mut snapshot: watch::Receiver<Arc<StatsSnapshot>>,
+        ctx: egui::Context,
+    ) {
+        while snapshot.changed().await.is_ok() {
+            ctx.request_repaint();
+        }
+    }
+
     #[cold]
     #[inline(never)]
     pub fn save_before_quit(&mut self) {
@@ -195,7 +243,7 @@ impl App {
             PathBuf::new()
         ));
         let xmrig_proxy = arc_mut!(Process::new(
-            ProcessName::Xmrig,
+            ProcessName::XmrigProxy,
             String::new(),
             PathBuf::new()
         ));
@@ -219,20 +267,7 @@ impl App {
 
         info!("App Init | Sysinfo...");
         // We give this to the [Helper] thread.
-        let mut sysinfo = sysinfo::System::new_with_specifics(
-            sysinfo::RefreshKind::new()
-                .with_cpu(sysinfo::CpuRefreshKind::everything())
-                .with_processes(sysinfo::ProcessRefreshKind::new().with_cpu())
-                .with_memory(sysinfo::MemoryRefreshKind::everything()),
-        );
-        sysinfo.refresh_all();
-        let pid = match sysinfo::get_current_pid() {
-            Ok(pid) => pid,
-            Err(e) => {
-                error!("App Init | Failed to get sysinfo PID: {}", e);
-                exit(1)
-            }
-        };
+        let (sysinfo, pid) = init_sysinfo();
         let pub_sys = arc_mut!(Sys::new());
 
         // CPU Benchmark data initialization.
@@ -246,6 +281,26 @@ impl App {
         };
         info!("App Init | Assuming user's CPU is: {}", benchmarks[0].cpu);
 
+        let helper = arc_mut!(Helper::new(
+            now,
+            pub_sys.clone(),
+            p2pool.clone(),
+            xmrig.clone(),
+            xmrig_proxy.clone(),
+            xvb.clone(),
+            node.clone(),
+            p2pool_api.clone(),
+            xmrig_api.clone(),
+            xvb_api.clone(),
+            xmrig_proxy_api.clone(),
+            node_api.clone(),
+            p2pool_img.clone(),
+            xmrig_img.clone(),
+            arc_mut!(GupaxP2poolApi::new()),
+            arc_mut!(History::new())
+        ));
+        let stats = helper.lock().unwrap().snapshot.subscribe();
+
         info!("App Init | The rest of the [App]...");
         let mut app = Self {
             tab: Tab::default(),
@@ -262,6 +317,7 @@ impl App {
                 PathBuf::new()
             )),
             file_window: FileWindow::new(),
+            config_window: ConfigWindow::new(),
             og_node_vec: Node::new_vec(),
             node_vec: Node::new_vec(),
             og_pool_vec: Pool::new_vec(),
@@ -269,23 +325,8 @@ impl App {
             restart: arc_mut!(Restart::No),
             diff: false,
             error_state: ErrorState::new(),
-            helper: arc_mut!(Helper::new(
-                now,
-                pub_sys.clone(),
-                p2pool.clone(),
-                xmrig.clone(),
-                xmrig_proxy.clone(),
-                xvb.clone(),
-                node.clone(),
-                p2pool_api.clone(),
-                xmrig_api.clone(),
-                xvb_api.clone(),
-                xmrig_proxy_api.clone(),
-                node_api.clone(),
-                p2pool_img.clone(),
-                xmrig_img.clone(),
-                arc_mut!(GupaxP2poolApi::new())
-            )),
+            stats,
+            helper,
             node,
             p2pool,
             xmrig,
@@ -302,11 +343,20 @@ impl App {
             p2pool_stdin: String::with_capacity(10),
             xmrig_stdin: String::with_capacity(10),
             xmrig_proxy_stdin: String::with_capacity(10),
+            node_console: Console::default(),
+            p2pool_console: Console::default(),
+            xmrig_console: Console::default(),
+            xmrig_proxy_console: Console::default(),
+            xvb_console: Console::default(),
+            custom: vec![],
+            custom_stdin: vec![],
             sudo: arc_mut!(SudoState::new()),
             resizing: false,
             alpha: 0,
             no_startup: false,
+            instance_lock: None,
             gupax_p2pool_api: arc_mut!(GupaxP2poolApi::new()),
+            history: arc_mut!(History::new()),
             pub_sys,
             benchmarks,
             pid,
@@ -319,9 +369,14 @@ impl App {
             os: OS,
             os_data_path: PathBuf::new(),
             gupax_p2pool_api_path: PathBuf::new(),
+            history_path: PathBuf::new(),
+            logs_path: PathBuf::new(),
+            profiles_path: PathBuf::new(),
+            profile_name: String::new(),
             state_path: PathBuf::new(),
             node_path: PathBuf::new(),
             pool_path: PathBuf::new(),
+            processes_path: PathBuf::new(),
             version: GUPAX_VERSION,
             name_version: format!("Gupaxx {}", GUPAX_VERSION),
             #[cfg(target_os = "windows")]
@@ -369,19 +424,47 @@ impl App {
         app.node_path.push(NODE_TOML);
         app.pool_path.clone_from(&app.os_data_path);
         app.pool_path.push(POOL_TOML);
+        app.processes_path.clone_from(&app.os_data_path);
+        app.processes_path.push(PROCESSES_TOML);
         // Set GupaxP2poolApi path
         app.gupax_p2pool_api_path = crate::disk::get_gupax_p2pool_path(&app.os_data_path);
         app.gupax_p2pool_api
             .lock()
             .unwrap()
             .fill_paths(&app.gupax_p2pool_api_path);
+        // Set History path
+        app.history_path = crate::disk::get_gupax_history_path(&app.os_data_path);
+        app.history.lock().unwrap().fill_paths(&app.history_path);
+        // Set logs path
+        app.logs_path = crate::disk::get_gupax_logs_path(&app.os_data_path);
+        // Set profiles path
+        app.profiles_path = crate::disk::get_gupax_profiles_path(&app.os_data_path);
 
         // Apply arg state
         // It's not safe to [--reset] if any of the previous variables
         // are unset (null path), so make sure we just abort if the [panic] String contains something.
         info!("App Init | Applying argument state...");
+        let profile = args.profile.clone();
         let mut app = parse_args(app, args, panic);
 
+        // Only one instance per data directory, a second one hands its arguments over.
+        info!("App Init | Locking the data directory...");
+        match InstanceLock::acquire(&app.os_data_path) {
+            Ok(lock) => app.instance_lock = Some(lock),
+            Err(LockError::Io(e)) => warn!("App Init | Could not lock the data directory: {}", e),
+            Err(e) => second_instance(&app.os_data_path, e),
+        }
+
+        // [--profile], it becomes the state read below.
+        if let Some(name) = profile {
+            info!("App Init | Switching to profile [{}]...", name);
+            if let Err(e) = profiles::activate(&app.profiles_path, &name, &app.state_path) {
+                error!("Profile [{}]: {}", name, e);
+                eprintln!("Could not switch to profile [{}]: {}", name, e);
+                exit(1);
+            }
+        }
+
         use crate::disk::errors::TomlError::*;
         // Read disk state
         info!("App Init | Reading disk state...");
@@ -453,6 +536,38 @@ impl App {
         app.og_pool_vec.clone_from(&app.pool_vec);
         debug!("Pool Vec:");
         debug!("{:#?}", app.pool_vec);
+        // Read custom process list
+        info!("App Init | Reading custom process list...");
+        let processes = match Processes::get(&app.processes_path) {
+            Ok(toml) => toml,
+            Err(err) => {
+                error!("Processes ... {}", err);
+                let (e, ferris, button) = match err {
+                    Io(e) => (e.to_string(), ErrorFerris::Panic, ErrorButtons::Quit),
+                    Path(e) => (e.to_string(), ErrorFerris::Panic, ErrorButtons::Quit),
+                    Serialize(e) => (e.to_string(), ErrorFerris::Panic, ErrorButtons::Quit),
+                    Deserialize(e) => (e.to_string(), ErrorFerris::Error, ErrorButtons::Okay),
+                    Format(e) => (e.to_string(), ErrorFerris::Panic, ErrorButtons::Quit),
+                    Merge(e) => (e.to_string(), ErrorFerris::Error, ErrorButtons::Okay),
+                    Parse(e) => (e.to_string(), ErrorFerris::Error, ErrorButtons::Okay),
+                };
+                app.error_state.set(format!("Custom process list: {}\n\nFix or delete: {}\n\nNo custom process will be started until then.\n\n", e, app.processes_path.display()), ferris, button);
+                vec![]
+            }
+        };
+        debug!("Processes Vec:");
+        debug!("{:#?}", processes);
+        app.custom = Custom::from_configs(processes);
+        app.custom_stdin = vec![String::new(); app.custom.len()];
+        app.helper.lock().unwrap().custom = app.custom.clone();
+        app.helper.lock().unwrap().dependencies = app.state.gupax.dependencies;
+        app.helper
+            .lock()
+            .unwrap()
+            .notifier
+            .set_policy(&app.state.gupax.notifications);
+        app.helper.lock().unwrap().logs_path = app.logs_path.clone();
+        app.helper.lock().unwrap().logs = app.state.gupax.logs;
 
         //----------------------------------------------------------------------------------------------------
         // Read [GupaxP2poolApi] disk files
@@ -498,6 +613,17 @@ impl App {
         drop(gupax_p2pool_api);
         app.helper.lock().unwrap().gupax_p2pool_api = Arc::clone(&app.gupax_p2pool_api);
 
+        //----------------------------------------------------------------------------------------------------
+        // Read [History] disk files
+        // Not fatal, the graphs will just start empty.
+        info!("App Init | Reading stats history files...");
+        if let Err(e) = History::create_all_files(&app.history_path)
+            .and_then(|_| app.history.lock().unwrap().read_all_files_and_update())
+        {
+            error!("History ... {}", e);
+        }
+        app.helper.lock().unwrap().history = Arc::clone(&app.history);
+
         //----------------------------------------------------------------------------------------------------
         let mut og = app.og.lock().unwrap(); // Lock [og]
                                              // Handle max threads
@@ -598,15 +724,7 @@ impl App {
         // Set saved Hero mode to runtime.
         debug!("Setting runtime_mode & runtime_manual_amount");
         // apply hero if simple mode saved with checkbox true, will let default to auto otherwise
-        if app.state.xvb.simple {
-            if app.state.xvb.simple_hero_mode {
-                app.xvb_api.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::Hero
-            }
-        } else {
-            app.xvb_api.lock().unwrap().stats_priv.runtime_mode = app.state.xvb.mode.clone().into();
-        }
-        app.xvb_api.lock().unwrap().stats_priv.runtime_manual_amount =
-            app.state.xvb.manual_amount_raw;
+        app.xvb_api.lock().unwrap().set_runtime_mode(&app.state.xvb);
         // Check if [P2pool.node] exists
         info!("App Init | Checking if saved remote node still exists...");
         app.state.p2pool.node = RemoteNode::check_exists(&app.state.p2pool.node);
@@ -617,6 +735,22 @@ impl App {
         info!("Helper | Spawning helper thread...");
         Helper::spawn_helper(&app.helper, sysinfo, app.pid, app.max_threads);
         info!("Helper ... OK");
+        // The saved node list is read when P2Pool starts, like the saved state.
+        let (ping, node_path) = (Arc::clone(&app.ping), app.node_path.clone());
+        let backup: BackupHosts = Arc::new(move |p2pool| {
+            let node_vec = Node::get(&node_path).unwrap_or_default();
+            backup_hosts(p2pool, &ping, &node_vec)
+        });
+        Helper::spawn_http_api(&app.helper, &app.og, &app.sudo, Arc::clone(&backup));
+        Helper::spawn_control_socket(
+            &app.helper,
+            &app.og,
+            &app.state_path,
+            &app.os_data_path,
+            &app.sudo,
+            backup,
+            true,
+        );
 
         // Check for privilege. Should be Admin on [Windows] and NOT root on Unix.
         info!("App Init | Checking for privilege level...");
@@ -645,68 +779,188 @@ impl App {
         app
     }
 
-    #[cold]
-    #[inline(never)]
-    pub fn gather_backup_hosts(&self) -> Option<Vec<Node>> {
-        if !self.state.p2pool.backup_host {
-            return None;
+    // Switches to the profile [name], dropping the unsaved changes,
+    // and restarts the processes whose settings changed.
+    pub fn switch_profile(&mut self, name: &str) {
+        match Helper::switch_profile(
+            &self.helper,
+            &self.og,
+            &self.state_path,
+            &self.profiles_path,
+            name,
+        ) {
+            Ok(old) => {
+                self.state = self.og.lock().unwrap().clone();
+                self.restart_changed(&old, "Profile");
+            }
+            Err(e) => self.error_state.set(
+                format!("Profile [{}]: {}", name, e),
+                ErrorFerris::Error,
+                ErrorButtons::Okay,
+            ),
         }
+    }
 
-        // INVARIANT:
-        // We must ensure all nodes are capable of
-        // sending/receiving valid JSON-RPC requests.
-        //
-        // This is done during the `Ping` phase, meaning
-        // all the nodes listed in our `self.ping` should
-        // have ping data. We can use this data to filter
-        // out "dead" nodes.
-        //
-        // The user must have at least pinged once so that
-        // we actually have this data to work off of, else,
-        // this "backup host" feature will return here
-        // with 0 extra nodes as we can't be sure that any
-        // of them are actually online.
-        //
-        // Realistically, most of them are, but we can't be sure,
-        // and checking here without explicitly asking the user
-        // to connect to nodes is a no-go (also, non-async environment).
-        if !self.ping.lock().unwrap().pinged {
-            warn!("Backup hosts ... simple node backup: no ping data available, returning None");
-            return None;
+    // Writes the configuration [import] accepted in the [Gupaxx] tab, reloads
+    // what it replaced and restarts the processes whose settings changed.
+    // The programs to run are only replaced if [programs] was allowed.
+    pub fn import_config(&mut self, import: &Import, programs: bool) {
+        let current = self.og.lock().unwrap().clone();
+        let new = match import.apply(&current, programs, &self.state_path, &self.os_data_path) {
+            Ok(new) => new,
+            Err(e) => {
+                self.error_state.set(
+                    format!("Configuration import: {}", e),
+                    ErrorFerris::Error,
+                    ErrorButtons::Okay,
+                );
+                return;
+            }
+        };
+        let old = Helper::replace_state(&self.helper, &self.og, new);
+        self.state = self.og.lock().unwrap().clone();
+        if let Some(nodes) = &import.nodes {
+            self.og_node_vec.clone_from(nodes);
+            self.node_vec.clone_from(nodes);
         }
+        if let Some(pools) = &import.pools {
+            self.og_pool_vec.clone_from(pools);
+            self.pool_vec.clone_from(pools);
+        }
+        if import.payouts.is_some() {
+            if let Err(e) = self
+                .gupax_p2pool_api
+                .lock()
+                .unwrap()
+                .read_all_files_and_update()
+            {
+                self.error_state.set(
+                    format!("Gupaxx P2Pool Stats: {}", e),
+                    ErrorFerris::Error,
+                    ErrorButtons::Okay,
+                );
+            }
+        }
+        self.restart_changed(&old, "Import");
+    }
 
-        if self.state.p2pool.simple {
-            let mut vec = Vec::with_capacity(REMOTE_NODES.len());
+    // Restarts the alive processes whose settings differ between [old] and [state],
+    // XMRig through the [sudo] prompt like its tab does, and shows the failures.
+    fn restart_changed(&mut self, old: &State, origin: &str) {
+        let new = self.state.clone();
+        let mut failed = vec![];
+        let mut ask_sudo = false;
+        for name in Helper::changed_processes(old, &new) {
+            if !Helper::is_alive(&self.helper, name) {
+                continue;
+            }
+            if name == ProcessName::Xmrig && SudoState::needed() {
+                ask_sudo = true;
+                continue;
+            }
+            if let Err(e) = Helper::process_action(
+                &self.helper,
+                &new,
+                &self.sudo,
+                || self.gather_backup_hosts(),
+                name,
+                ProcessAction::Restart,
+                origin,
+            ) {
+                warn!("{} | Not restarting: {}", origin, e);
+                failed.push(e.to_string());
+            }
+        }
+        if !failed.is_empty() {
+            if ask_sudo {
+                failed.push("XMRig has to be restarted from its tab".to_string());
+            }
+            self.error_state.set(
+                format!(
+                    "{}: the new settings are saved, but these processes were not restarted:\n\n{}",
+                    origin,
+                    failed.join("\n")
+                ),
+                ErrorFerris::Error,
+                ErrorButtons::Okay,
+            );
+        } else if ask_sudo {
+            self.sudo.lock().unwrap().signal = ProcessSignal::Restart;
+            self.error_state.ask_sudo(&self.sudo);
+        }
+    }
 
-            // Locking during this entire loop should be fine,
-            // only a few nodes to iter through.
-            for pinged_node in self.ping.lock().unwrap().nodes.iter() {
-                // Continue if this node is not green/yellow.
-                if pinged_node.ms > crate::components::node::RED_NODE_PING {
-                    continue;
-                }
+    #[cold]
+    #[inline(never)]
+    pub fn gather_backup_hosts(&self) -> Option<Vec<Node>> {
+        backup_hosts(&self.state.p2pool, &self.ping, &self.node_vec)
+    }
+}
+
+// See [App::gather_backup_hosts], without borrowing all of [App].
+pub fn backup_hosts(
+    p2pool: &crate::disk::state::P2pool,
+    ping: &Arc<Mutex<Ping>>,
+    node_vec: &[(String, Node)],
+) -> Option<Vec<Node>> {
+    if !p2pool.backup_host {
+        return None;
+    }
 
-                let (ip, rpc, zmq) = RemoteNode::get_ip_rpc_zmq(pinged_node.ip);
+    // INVARIANT:
+    // We must ensure all nodes are capable of
+    // sending/receiving valid JSON-RPC requests.
+    //
+    // This is done during the `Ping` phase, meaning
+    // all the nodes listed in our `ping` should
+    // have ping data. We can use this data to filter
+    // out "dead" nodes.
+    //
+    // The user must have at least pinged once so that
+    // we actually have this data to work off of, else,
+    // this "backup host" feature will return here
+    // with 0 extra nodes as we can't be sure that any
+    // of them are actually online.
+    //
+    // Realistically, most of them are, but we can't be sure,
+    // and checking here without explicitly asking the user
+    // to connect to nodes is a no-go (also, non-async environment).
+    if !ping.lock().unwrap().pinged {
+        warn!("Backup hosts ... simple node backup: no ping data available, returning None");
+        return None;
+    }
 
-                let node = Node {
-                    ip: ip.into(),
-                    rpc: rpc.into(),
-                    zmq: zmq.into(),
-                };
+    if p2pool.simple {
+        let mut vec = Vec::with_capacity(REMOTE_NODES.len());
 
-                vec.push(node);
+        // Locking during this entire loop should be fine,
+        // only a few nodes to iter through.
+        for pinged_node in ping.lock().unwrap().nodes.iter() {
+            // Continue if this node is not green/yellow.
+            if pinged_node.ms > crate::components::node::RED_NODE_PING {
+                continue;
             }
 
-            if vec.is_empty() {
-                warn!("Backup hosts ... simple node backup: no viable nodes found");
-                None
-            } else {
-                info!("Backup hosts ... simple node backup list: {vec:#?}");
-                Some(vec)
-            }
+            let (ip, rpc, zmq) = RemoteNode::get_ip_rpc_zmq(pinged_node.ip);
+
+            let node = Node {
+                ip: ip.into(),
+                rpc: rpc.into(),
+                zmq: zmq.into(),
+            };
+
+            vec.push(node);
+        }
+
+        if vec.is_empty() {
+            warn!("Backup hosts ... simple node backup: no viable nodes found");
+            None
         } else {
-            Some(self.node_vec.iter().map(|(_, node)| node.clone()).collect())
+            info!("Backup hosts ... simple node backup list: {vec:#?}");
+            Some(vec)
         }
+    } else {
+        Some(node_vec.iter().map(|(_, node)| node.clone()).collect())
     }
 }
 //---------------------------------------------------------------------------------------------------- [Tab] Enum + Impl
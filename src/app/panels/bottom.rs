@@ -1,12 +1,13 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::app::{keys::KeyPressed, Restart};
 use crate::disk::node::Node;
 use crate::disk::pool::Pool;
+use crate::disk::profiles;
 use crate::disk::state::{Gupax, State};
 use crate::disk::status::Submenu;
 use crate::errors::process_running;
-use crate::helper::{Helper, ProcessSignal, ProcessState};
+use crate::helper::{Helper, Process, ProcessSignal, ProcessState};
 use crate::utils::constants::*;
 use crate::utils::errors::{ErrorButtons, ErrorFerris};
 use crate::utils::regex::Regexes;
@@ -195,11 +196,33 @@ impl crate::app::App {
                             let mut og = self.og.lock().unwrap();
                             og.status = self.state.status.clone();
                             og.gupax = self.state.gupax.clone();
+                            self.helper.lock().unwrap().dependencies =
+                                self.state.gupax.dependencies;
+                            self.helper
+                                .lock()
+                                .unwrap()
+                                .notifier
+                                .set_policy(&self.state.gupax.notifications);
+                            self.helper.lock().unwrap().logs = self.state.gupax.logs;
                             og.node = self.state.node.clone();
                             og.p2pool = self.state.p2pool.clone();
                             og.xmrig = self.state.xmrig.clone();
                             og.xmrig_proxy = self.state.xmrig_proxy.clone();
                             og.xvb = self.state.xvb.clone();
+                            // The profile in use follows the state.
+                            if !self.state.gupax.profile.is_empty() {
+                                if let Err(e) = profiles::save(
+                                    &self.profiles_path,
+                                    &self.state.gupax.profile,
+                                    &self.state,
+                                ) {
+                                    self.error_state.set(
+                                        format!("Profile [{}]: {}", self.state.gupax.profile, e),
+                                        ErrorFerris::Error,
+                                        ErrorButtons::Okay,
+                                    );
+                                }
+                            }
                         }
                         Err(e) => {
                             self.error_state.set(
@@ -233,6 +256,17 @@ impl crate::app::App {
         // ui.style_mut().wrap = Some(true);
         ui.group(|ui| {
             let size = vec2(0.0, height);
+            if ui
+                .add_sized(
+                    size,
+                    SelectableLabel::new(self.state.status.submenu == Submenu::History, "History"),
+                )
+                .on_hover_text(STATUS_SUBMENU_HISTORY)
+                .clicked()
+            {
+                self.state.status.submenu = Submenu::History;
+            }
+            ui.separator();
             if ui
                 .add_sized(
                     size,
@@ -396,17 +430,15 @@ impl crate::app::App {
                 {
                     Helper::stop_p2pool(&self.helper);
                 }
-                ui.add_enabled_ui(false, |ui| {
-                    ui.add_sized(size, Button::new("▶"))
-                        .on_disabled_hover_text("Start P2Pool");
-                });
+                start_blocked(ui, size, &self.p2pool, "P2Pool");
             } else {
                 ui.add_enabled_ui(false, |ui| {
                     ui.add_sized(size, Button::new("⟲"))
                         .on_disabled_hover_text("Restart P2Pool");
-                    ui.add_sized(size, Button::new("⏹"))
-                        .on_disabled_hover_text("Stop P2Pool");
                 });
+                if stop_respawning(ui, size, &self.p2pool, "P2Pool") {
+                    Helper::stop_p2pool(&self.helper);
+                }
                 // Check if address and path is okay before allowing to start.
                 let mut text = String::new();
                 let mut ui_enabled = true;
@@ -499,9 +531,10 @@ impl crate::app::App {
                 ui.add_enabled_ui(false, |ui| {
                     ui.add_sized(size, Button::new("⟲"))
                         .on_disabled_hover_text("Restart node");
-                    ui.add_sized(size, Button::new("⏹"))
-                        .on_disabled_hover_text("Stop node");
                 });
+                if stop_respawning(ui, size, &self.node, "node") {
+                    Helper::stop_node(&self.helper);
+                }
                 // Check if path is okay before allowing to start.
                 let mut text = String::new();
                 let mut ui_enabled = true;
@@ -645,17 +678,15 @@ impl crate::app::App {
                         Helper::stop_xmrig(&self.helper);
                     }
                 }
-                ui.add_enabled_ui(false, |ui| {
-                    ui.add_sized(size, Button::new("▶"))
-                        .on_disabled_hover_text("Start XMRig");
-                });
+                start_blocked(ui, size, &self.xmrig, "XMRig");
             } else {
                 ui.add_enabled_ui(false, |ui| {
                     ui.add_sized(size, Button::new("⟲"))
                         .on_disabled_hover_text("Restart XMRig");
-                    ui.add_sized(size, Button::new("⏹"))
-                        .on_disabled_hover_text("Stop XMRig");
                 });
+                if stop_respawning(ui, size, &self.xmrig, "XMRig") {
+                    Helper::stop_xmrig(&self.helper);
+                }
                 let mut text = String::new();
                 let mut ui_enabled = true;
                 if !Gupax::path_is_file(&self.state.gupax.xmrig_path) {
@@ -845,17 +876,15 @@ impl crate::app::App {
                 {
                     Helper::stop_xp(&self.helper);
                 }
-                ui.add_enabled_ui(false, |ui| {
-                    ui.add_sized(size, Button::new("▶"))
-                        .on_disabled_hover_text("Start XMRig-Proxy");
-                });
+                start_blocked(ui, size, &self.xmrig_proxy, "XMRig-Proxy");
             } else {
                 ui.add_enabled_ui(false, |ui| {
                     ui.add_sized(size, Button::new("⟲"))
                         .on_disabled_hover_text("Restart XMRig-Proxy");
-                    ui.add_sized(size, Button::new("⏹"))
-                        .on_disabled_hover_text("Stop XMRig-Proxy");
                 });
+                if stop_respawning(ui, size, &self.xmrig_proxy, "XMRig-Proxy") {
+                    Helper::stop_xp(&self.helper);
+                }
 
                 let mut text = String::new();
                 let mut ui_enabled = true;
@@ -914,6 +943,10 @@ fn status_p2pool(state: ProcessState, ui: &mut Ui, size: Vec2) {
             color = ORANGE;
             P2POOL_SYNCING
         }
+        Blocked => {
+            color = YELLOW;
+            P2POOL_BLOCKED
+        }
         Middle | Waiting | NotMining | OfflineNodesAll => {
             color = YELLOW;
             P2POOL_MIDDLE
@@ -940,7 +973,7 @@ fn status_node(state: ProcessState, ui: &mut Ui, size: Vec2) {
             color = ORANGE;
             NODE_SYNCING
         }
-        Middle | Waiting | NotMining | OfflineNodesAll => {
+        Middle | Waiting | NotMining | OfflineNodesAll | Blocked => {
             color = YELLOW;
             NODE_MIDDLE
         }
@@ -967,6 +1000,10 @@ fn status_xmrig(state: ProcessState, ui: &mut Ui, size: Vec2) {
             color = ORANGE;
             XMRIG_NOT_MINING
         }
+        Blocked => {
+            color = YELLOW;
+            XMRIG_BLOCKED
+        }
         Middle | Waiting | Syncing => {
             color = YELLOW;
             XMRIG_MIDDLE
@@ -994,6 +1031,10 @@ fn status_xp(state: ProcessState, ui: &mut Ui, size: Vec2) {
             color = ORANGE;
             XMRIG_PROXY_NOT_MINING
         }
+        Blocked => {
+            color = YELLOW;
+            XMRIG_PROXY_BLOCKED
+        }
         Middle | Waiting | Syncing => {
             color = YELLOW;
             XMRIG_PROXY_MIDDLE
@@ -1020,7 +1061,7 @@ fn status_xvb(state: ProcessState, ui: &mut Ui, size: Vec2) {
             color = ORANGE;
             XVB_PUBLIC_ONLY
         }
-        Middle | Waiting => {
+        Middle | Waiting | Blocked => {
             color = YELLOW;
             XVB_MIDDLE
         }
@@ -1028,6 +1069,33 @@ fn status_xvb(state: ProcessState, ui: &mut Ui, size: Vec2) {
     status(ui, color, hover_text, size, "XvB  ⏺");
 }
 
+// Enabled only while the process waits for its dependencies, to start it right away.
+fn start_blocked(ui: &mut Ui, size: Vec2, process: &Arc<Mutex<Process>>, name: &str) {
+    let blocked = process.lock().unwrap().state == Blocked;
+    ui.add_enabled_ui(blocked, |ui| {
+        if ui
+            .add_sized(size, Button::new("▶"))
+            .on_hover_text(format!("Start {} now, without waiting", name))
+            .on_disabled_hover_text(format!("Start {}", name))
+            .clicked()
+        {
+            Helper::skip_dependencies(process);
+        }
+    });
+}
+
+// Only enabled while waiting to be restarted by the [RestartPolicy], returns [true] if clicked.
+fn stop_respawning(ui: &mut Ui, size: Vec2, process: &Arc<Mutex<Process>>, name: &str) -> bool {
+    let respawning = process.lock().unwrap().respawning;
+    ui.add_enabled_ui(respawning, |ui| {
+        ui.add_sized(size, Button::new("⏹"))
+            .on_hover_text(format!("Cancel the restart of {}", name))
+            .on_disabled_hover_text(format!("Stop {}", name))
+            .clicked()
+    })
+    .inner
+}
+
 fn status(ui: &mut Ui, color: Color32, hover_text: &str, _size: Vec2, text: &str) {
     ui.label(RichText::new(text).color(color))
         .on_hover_text(hover_text);
@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+use egui::text::LayoutJob;
+use egui::TextStyle::{self, Name};
+use egui::{Button, Color32, Label, RichText, ScrollArea, TextEdit, TextFormat, Ui};
+use regex::Regex;
+
+use crate::helper::console::ConsoleBuffer;
+use crate::regex::{contains_error, contains_timeout, contains_warning};
+use crate::utils::constants::{
+    CONSOLE_COPY, CONSOLE_ERRORS, CONSOLE_FILTER, CONSOLE_PAUSE, CONSOLE_SEARCH, DARK_GRAY,
+    LIGHT_GRAY, ORANGE, RED, YELLOW,
+};
+
+//---------------------------------------------------------------------------------------------------- Console
+// The console of a process: its lines, coloured by severity, with a search, a regex filter,
+// a warnings/errors only toggle, a pause of the auto-scroll and a copy button.
+//
+// Filtering every line on every frame would be too slow for a full console,
+// so the ids of the lines shown are cached and only new lines get scanned.
+#[derive(Debug, Default)]
+pub struct Console {
+    search: String,
+    filter: String,
+    errors_only: bool,
+    paused: bool,
+    // [filter] compiled, [None] if empty or invalid.
+    regex: Option<Regex>,
+    regex_error: Option<String>,
+    // What the cache below was built with.
+    key: (String, String, bool),
+    // Lines before this id were scanned.
+    scanned: u64,
+    // Ids of the lines passing the filters.
+    shown: VecDeque<u64>,
+    // Ids of the lines shown containing [search], and the selected one.
+    hits: VecDeque<u64>,
+    hit: usize,
+    // Scroll to the selected hit on the next frame.
+    jump: bool,
+}
+
+impl Console {
+    // Toolbar, then the lines, all within [width]x[height].
+    pub fn show(&mut self, ui: &mut Ui, output: &ConsoleBuffer, width: f32, height: f32) {
+        self.toolbar(ui, output, width);
+        // The toolbar takes its row from the lines.
+        let height = (height - ui.spacing().interact_size.y - ui.spacing().item_spacing.y).max(0.0);
+        self.update(output);
+        let row_height = ui.text_style_height(&Name("MonospaceSmall".into()));
+        egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
+            ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
+            let mut area = ScrollArea::vertical()
+                .stick_to_bottom(!self.paused && self.search.is_empty())
+                .max_width(width)
+                .max_height(height)
+                .auto_shrink([false; 2]);
+            if std::mem::take(&mut self.jump) {
+                if let Some(row) = self
+                    .hits
+                    .get(self.hit)
+                    .and_then(|id| self.shown.binary_search(id).ok())
+                {
+                    let spacing = ui.spacing().item_spacing.y;
+                    let offset = row as f32 * (row_height + spacing) - height / 2.0;
+                    area = area.vertical_scroll_offset(offset.max(0.0));
+                }
+            }
+            area.show_rows(ui, row_height, self.shown.len(), |ui, row_range| {
+                for row in row_range {
+                    let Some(id) = self.shown.get(row) else {
+                        continue;
+                    };
+                    let Some(line) = output.get(*id) else {
+                        continue;
+                    };
+                    let color = severity(line);
+                    if self.search.is_empty() {
+                        let text = match color {
+                            Some(color) => RichText::new(line).color(color),
+                            None => RichText::new(line),
+                        };
+                        ui.add(Label::new(text).selectable(true));
+                    } else {
+                        let selected = self.hits.get(self.hit) == Some(id);
+                        let job = self.highlight(ui, line, color, selected);
+                        ui.add(Label::new(job).selectable(true));
+                    }
+                }
+            });
+        });
+    }
+
+    fn toolbar(&mut self, ui: &mut Ui, output: &ConsoleBuffer, width: f32) {
+        ui.horizontal(|ui| {
+            let field = (width / 5.0).max(80.0);
+            let response = ui
+                .add_sized(
+                    [field, ui.spacing().interact_size.y],
+                    TextEdit::singleline(&mut self.search).hint_text("Search"),
+                )
+                .on_hover_text(CONSOLE_SEARCH);
+            if response.changed() {
+                // Incremental, the last hit is selected once the cache is rebuilt.
+                self.hit = usize::MAX;
+                self.jump = true;
+            }
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if enter {
+                response.request_focus();
+            }
+            let searching = !self.search.is_empty() && !self.hits.is_empty();
+            ui.add_enabled_ui(searching, |ui| {
+                if ui.button("⏶").on_hover_text("Previous match").clicked()
+                    || (enter && ui.input(|i| i.modifiers.shift))
+                {
+                    self.step(false);
+                } else if ui.button("⏷").on_hover_text("Next match").clicked() || enter {
+                    self.step(true);
+                }
+            });
+            if !self.search.is_empty() {
+                let text = if self.hits.is_empty() {
+                    RichText::new("0/0").color(RED)
+                } else {
+                    let hit = self.hit.min(self.hits.len() - 1) + 1;
+                    RichText::new(format!("{}/{}", hit, self.hits.len())).color(LIGHT_GRAY)
+                };
+                ui.label(text);
+            }
+            ui.separator();
+            let response = ui
+                .add_sized(
+                    [field, ui.spacing().interact_size.y],
+                    TextEdit::singleline(&mut self.filter)
+                        .hint_text("Filter (regex)")
+                        .text_color_opt(self.regex_error.as_ref().map(|_| RED)),
+                )
+                .on_hover_text(CONSOLE_FILTER);
+            if let Some(e) = &self.regex_error {
+                response.on_hover_text(e.as_str());
+            }
+            ui.checkbox(&mut self.errors_only, "Warnings/errors")
+                .on_hover_text(CONSOLE_ERRORS);
+            let pause = if self.paused {
+                "▶ Resume"
+            } else {
+                "⏸ Pause"
+            };
+            if ui
+                .add(Button::new(pause).selected(self.paused))
+                .on_hover_text(CONSOLE_PAUSE)
+                .clicked()
+            {
+                self.paused = !self.paused;
+            }
+            if ui
+                .add_enabled(!output.is_empty(), Button::new("Copy"))
+                .on_hover_text(CONSOLE_COPY)
+                .clicked()
+            {
+                let mut text = String::new();
+                for line in self.shown.iter().filter_map(|id| output.get(*id)) {
+                    text.push_str(line);
+                    text.push('\n');
+                }
+                ui.ctx().copy_text(text);
+            }
+        });
+    }
+
+    // Brings the cache up to date with the filters and the new lines of [output].
+    fn update(&mut self, output: &ConsoleBuffer) {
+        if (&self.key.0, &self.key.1, self.key.2) != (&self.search, &self.filter, self.errors_only)
+        {
+            let key = (self.search.clone(), self.filter.clone(), self.errors_only);
+            if key.1 != self.key.1 {
+                (self.regex, self.regex_error) = match Regex::new(&key.1) {
+                    _ if key.1.is_empty() => (None, None),
+                    Ok(regex) => (Some(regex), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+            }
+            self.key = key;
+            self.scanned = 0;
+            self.shown.clear();
+            self.hits.clear();
+        }
+        // Drop what left the ring buffer.
+        let first = output.first_id();
+        while self.shown.front().is_some_and(|id| *id < first) {
+            self.shown.pop_front();
+        }
+        while self.hits.front().is_some_and(|id| *id < first) {
+            self.hits.pop_front();
+            self.hit = self.hit.saturating_sub(1);
+        }
+        let search = self.search.to_ascii_lowercase();
+        for id in self.scanned.max(first)..output.next_id() {
+            let Some(line) = output.get(id) else {
+                continue;
+            };
+            if !self.passes(line) {
+                continue;
+            }
+            self.shown.push_back(id);
+            if !search.is_empty() && line.to_ascii_lowercase().contains(&search) {
+                self.hits.push_back(id);
+            }
+        }
+        self.scanned = output.next_id();
+        if self.hit >= self.hits.len() {
+            self.hit = self.hits.len().saturating_sub(1);
+        }
+    }
+
+    // Selects the next or previous hit, wrapping around. [hit] can be past
+    // the end until the cache is rebuilt, and the Enter key works even
+    // while the buttons are disabled.
+    fn step(&mut self, forward: bool) {
+        let len = self.hits.len();
+        if len == 0 {
+            return;
+        }
+        let hit = self.hit.min(len - 1);
+        self.hit = if forward {
+            (hit + 1) % len
+        } else {
+            (hit + len - 1) % len
+        };
+        self.jump = true;
+    }
+
+    fn passes(&self, line: &str) -> bool {
+        if self.errors_only && severity(line).is_none() {
+            return false;
+        }
+        self.regex
+            .as_ref()
+            .map_or(true, |regex| regex.is_match(line))
+    }
+
+    // [line] in [color] with the matches of [search] highlighted.
+    fn highlight(&self, ui: &Ui, line: &str, color: Option<Color32>, selected: bool) -> LayoutJob {
+        let font = TextStyle::Name("MonospaceSmall".into()).resolve(ui.style());
+        let color = color.unwrap_or_else(|| ui.visuals().text_color());
+        let normal = TextFormat::simple(font.clone(), color);
+        let found = TextFormat {
+            background: if selected {
+                ORANGE
+            } else {
+                ORANGE.gamma_multiply(0.4)
+            },
+            ..TextFormat::simple(font, Color32::BLACK)
+        };
+        let mut job = LayoutJob::default();
+        let lower = line.to_ascii_lowercase();
+        let search = self.search.to_ascii_lowercase();
+        let mut start = 0;
+        // ASCII lowercase keeps the byte offsets of [line].
+        for (i, _) in lower.match_indices(&search) {
+            job.append(&line[start..i], 0.0, normal.clone());
+            job.append(&line[i..i + search.len()], 0.0, found.clone());
+            start = i + search.len();
+        }
+        job.append(&line[start..], 0.0, normal);
+        job
+    }
+}
+
+// The colour of an error, warning or timeout line, [None] for the others.
+fn severity(line: &str) -> Option<Color32> {
+    let lower = line.to_ascii_lowercase();
+    if contains_error(&lower) {
+        Some(RED)
+    } else if contains_warning(&lower) {
+        Some(ORANGE)
+    } else if contains_timeout(&lower) {
+        Some(YELLOW)
+    } else {
+        None
+    }
+}
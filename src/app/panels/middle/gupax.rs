@@ -3,7 +3,10 @@ use crate::app::ErrorState;
 use crate::app::Restart;
 use crate::components::gupax::*;
 use crate::components::update::Update;
+use crate::disk::archive::PROGRAM;
+use crate::disk::profiles;
 use crate::disk::state::*;
+use crate::helper::Helper;
 use log::debug;
 use std::path::Path;
 use std::sync::Arc;
@@ -15,10 +18,15 @@ impl Gupax {
         &mut self,
         og: &Arc<Mutex<State>>,
         state_path: &Path,
+        logs_path: &Path,
+        profiles_path: &Path,
+        profile_name: &mut String,
         update: &Arc<Mutex<Update>>,
         file_window: &Arc<Mutex<FileWindow>>,
+        config_window: &Arc<Mutex<ConfigWindow>>,
         error_state: &mut ErrorState,
         restart: &Arc<Mutex<Restart>>,
+        helper: &Arc<Mutex<Helper>>,
         size: Vec2,
         _frame: &mut eframe::Frame,
         _ctx: &egui::Context,
@@ -545,6 +553,395 @@ impl Gupax {
                     }
                 })
             });
+
+            // Profiles
+            debug!("Gupaxx Tab | Rendering profiles");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(RichText::new("Profiles").underline().color(LIGHT_GRAY)),
+                )
+                .on_hover_text(GUPAX_PROFILES);
+                ui.separator();
+                let width = size.x / 10.0;
+                let size = vec2(width, height / 3.5);
+                ui.horizontal(|ui| {
+                    let selected = if self.profile.is_empty() {
+                        "None"
+                    } else {
+                        self.profile.as_str()
+                    };
+                    egui::ComboBox::from_id_salt("profiles")
+                        .selected_text(selected)
+                        .width(width * 2.0)
+                        .show_ui(ui, |ui| {
+                            for name in profiles::list(profiles_path) {
+                                if ui.selectable_label(name == self.profile, &name).clicked()
+                                    && name != self.profile
+                                {
+                                    // The [App] switches, it owns the whole state.
+                                    helper.lock().unwrap().profile_request = Some(name);
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(GUPAX_PROFILE_SWITCH);
+                    ui.separator();
+                    ui.add_sized(
+                        [width * 2.0, size.y],
+                        TextEdit::singleline(profile_name).hint_text("Name"),
+                    )
+                    .on_hover_text(GUPAX_PROFILE_NAME);
+                    if ui
+                        .add_enabled_ui(profiles::valid_name(profile_name), |ui| {
+                            ui.add_sized(size, Button::new("Save as"))
+                                .on_hover_text(GUPAX_PROFILE_SAVE_AS)
+                        })
+                        .inner
+                        .clicked()
+                    {
+                        let mut og = og.lock().unwrap();
+                        match profiles::save(profiles_path, profile_name, &og).and_then(|_| {
+                            og.gupax.profile.clone_from(profile_name);
+                            og.save(state_path)
+                        }) {
+                            Ok(_) => {
+                                self.profile = std::mem::take(profile_name);
+                            }
+                            Err(e) => error_state.set(
+                                format!("Profile [{}]: {}", profile_name, e),
+                                ErrorFerris::Error,
+                                ErrorButtons::Okay,
+                            ),
+                        }
+                    }
+                    if ui
+                        .add_enabled_ui(!self.profile.is_empty(), |ui| {
+                            ui.add_sized(size, Button::new("Delete"))
+                                .on_hover_text(GUPAX_PROFILE_DELETE)
+                        })
+                        .inner
+                        .clicked()
+                    {
+                        let mut og = og.lock().unwrap();
+                        match profiles::delete(profiles_path, &self.profile).and_then(|_| {
+                            og.gupax.profile.clear();
+                            og.save(state_path)
+                        }) {
+                            Ok(_) => self.profile.clear(),
+                            Err(e) => error_state.set(
+                                format!("Profile [{}]: {}", self.profile, e),
+                                ErrorFerris::Error,
+                                ErrorButtons::Okay,
+                            ),
+                        }
+                    }
+                });
+            });
+
+            // Configuration export/import
+            debug!("Gupaxx Tab | Rendering configuration export/import");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(RichText::new("Configuration").underline().color(LIGHT_GRAY)),
+                )
+                .on_hover_text(GUPAX_CONFIG);
+                ui.separator();
+                let width = size.x / 10.0;
+                let size = vec2(width, height / 3.5);
+                let mut guard = config_window.lock().unwrap();
+                let window = &mut *guard;
+                if let Some(error) = window.error.take() {
+                    error_state.set(error, ErrorFerris::Error, ErrorButtons::Okay);
+                }
+                let idle = !window.thread && window.import.is_none();
+                ui.horizontal(|ui| {
+                    ui.add_sized(size, Checkbox::new(&mut window.payouts, "Payout history"))
+                        .on_hover_text(GUPAX_CONFIG_PAYOUTS);
+                    ui.separator();
+                    ui.add_enabled_ui(idle, |ui| {
+                        if ui
+                            .add_sized(size, Button::new("Export"))
+                            .on_hover_text(GUPAX_CONFIG_EXPORT)
+                            .clicked()
+                        {
+                            window.thread = true;
+                            Self::spawn_export_config_thread(
+                                og,
+                                state_path,
+                                config_window,
+                                window.payouts,
+                            );
+                        }
+                        if ui
+                            .add_sized(size, Button::new("Import"))
+                            .on_hover_text(GUPAX_CONFIG_IMPORT)
+                            .clicked()
+                        {
+                            window.thread = true;
+                            Self::spawn_import_config_thread(og, state_path, config_window);
+                        }
+                    });
+                });
+                let mut cancel = false;
+                if let Some((import, changes)) = &window.import {
+                    ui.separator();
+                    ui.label(format!(
+                        "Archive of Gupaxx {} made {}, {} changes:",
+                        import.manifest.gupaxx,
+                        import.manifest.created,
+                        changes.len()
+                    ));
+                    egui::ScrollArea::vertical()
+                        .id_salt("config_import")
+                        .max_height(height * 2.0)
+                        .show(ui, |ui| {
+                            for change in changes {
+                                ui.monospace(change);
+                            }
+                        });
+                    // Off until ticked, an archive from elsewhere could run anything.
+                    if changes.iter().any(|c| c.starts_with(PROGRAM)) {
+                        ui.add(Checkbox::new(
+                            &mut window.programs,
+                            "Also replace the programs to run ([program] changes)",
+                        ))
+                        .on_hover_text(GUPAX_CONFIG_PROGRAMS);
+                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled_ui(!changes.is_empty(), |ui| {
+                                ui.add_sized(size, Button::new("Apply"))
+                                    .on_hover_text(GUPAX_CONFIG_APPLY)
+                            })
+                            .inner
+                            .clicked()
+                        {
+                            // The [App] imports, it owns the node/pool lists.
+                            window.apply = true;
+                        }
+                        cancel = ui.add_sized(size, Button::new("Cancel")).clicked();
+                    });
+                }
+                if cancel {
+                    window.import = None;
+                }
+            });
+
+            // Local HTTP API
+            debug!("Gupaxx Tab | Rendering HTTP API settings");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(RichText::new("HTTP API").underline().color(LIGHT_GRAY)),
+                )
+                .on_hover_text(GUPAX_HTTP_API);
+                ui.separator();
+                let width = size.x / 10.0;
+                let size = vec2(width, height / 3.5);
+                ui.horizontal(|ui| {
+                    ui.add_sized(size, Checkbox::new(&mut self.http_api, "Enable"))
+                        .on_hover_text(GUPAX_HTTP_API);
+                    ui.separator();
+                    ui.add_sized(size, Label::new("IP:"));
+                    ui.add_sized(size, TextEdit::singleline(&mut self.http_api_ip))
+                        .on_hover_text(GUPAX_HTTP_API_IP);
+                    ui.separator();
+                    ui.add_sized(size, Label::new("Port:"));
+                    ui.add_sized(size, TextEdit::singleline(&mut self.http_api_port))
+                        .on_hover_text(GUPAX_HTTP_API_PORT);
+                    ui.separator();
+                    ui.add_sized(size, Label::new("Token:"));
+                    ui.add_sized(
+                        [width * 2.0, size.y],
+                        TextEdit::singleline(&mut self.http_api_token),
+                    )
+                    .on_hover_text(GUPAX_HTTP_API_TOKEN);
+                    ui.separator();
+                    ui.add_sized(size, Checkbox::new(&mut self.http_api_metrics, "Metrics"))
+                        .on_hover_text(GUPAX_HTTP_API_METRICS);
+                });
+            });
+
+            // Start order
+            debug!("Gupaxx Tab | Rendering start order settings");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(RichText::new("Start order").underline().color(LIGHT_GRAY)),
+                )
+                .on_hover_text(GUPAX_WAIT_DEPENDENCIES);
+                ui.separator();
+                let width = size.x / 10.0;
+                let size = vec2(width, height / 3.5);
+                ui.horizontal(|ui| {
+                    ui.add_sized(
+                        size,
+                        Checkbox::new(&mut self.dependencies.wait, "Wait for dependencies"),
+                    )
+                    .on_hover_text(GUPAX_WAIT_DEPENDENCIES);
+                    ui.separator();
+                    ui.add_enabled_ui(self.dependencies.wait, |ui| {
+                        ui.add_sized(size, Label::new("Timeout [0-3600s]:"));
+                        ui.add(Slider::new(&mut self.dependencies.timeout_secs, 0..=3600))
+                            .on_hover_text(GUPAX_DEPENDENCY_TIMEOUT);
+                    });
+                });
+            });
+
+            // Notifications
+            debug!("Gupaxx Tab | Rendering notification settings");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(RichText::new("Notifications").underline().color(LIGHT_GRAY)),
+                )
+                .on_hover_text(GUPAX_NOTIFICATIONS);
+                ui.separator();
+                let width = size.x / 10.0;
+                let size = vec2(width, height / 3.5);
+                let notifications = &mut self.notifications;
+                ui.horizontal(|ui| {
+                    for (enabled, name, hover) in [
+                        (&mut notifications.payout, "Payouts", GUPAX_NOTIFY_PAYOUT),
+                        (&mut notifications.crash, "Crashes", GUPAX_NOTIFY_CRASH),
+                        (&mut notifications.xvb_win, "XvB wins", GUPAX_NOTIFY_XVB_WIN),
+                        (
+                            &mut notifications.xvb_offline,
+                            "XvB offline",
+                            GUPAX_NOTIFY_XVB_OFFLINE,
+                        ),
+                        (
+                            &mut notifications.sync_loss,
+                            "Node sync loss",
+                            GUPAX_NOTIFY_SYNC_LOSS,
+                        ),
+                    ] {
+                        ui.add_sized(size, Checkbox::new(enabled, name))
+                            .on_hover_text(hover);
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add_sized(size, Checkbox::new(&mut notifications.desktop, "Desktop"))
+                        .on_hover_text(GUPAX_NOTIFY_DESKTOP);
+                    ui.separator();
+                    ui.add_sized(size, Checkbox::new(&mut notifications.webhook, "Webhook"))
+                        .on_hover_text(GUPAX_NOTIFY_WEBHOOK);
+                    ui.add_enabled_ui(notifications.webhook, |ui| {
+                        ui.add_sized(
+                            [width * 4.0, size.y],
+                            TextEdit::singleline(&mut notifications.webhook_url)
+                                .hint_text("https://..."),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_WEBHOOK);
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.add_sized(size, Checkbox::new(&mut notifications.mail, "Mail"))
+                        .on_hover_text(GUPAX_NOTIFY_MAIL);
+                    ui.add_enabled_ui(notifications.mail, |ui| {
+                        ui.add_sized(
+                            [width * 1.5, size.y],
+                            TextEdit::singleline(&mut notifications.mail_server)
+                                .hint_text("host:port"),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_MAIL);
+                        ui.separator();
+                        ui.add_sized(
+                            size,
+                            TextEdit::singleline(&mut notifications.mail_username)
+                                .hint_text("Username"),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_MAIL_AUTH);
+                        ui.add_sized(
+                            size,
+                            TextEdit::singleline(&mut notifications.mail_password)
+                                .hint_text("Password")
+                                .password(true),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_MAIL_AUTH);
+                        ui.separator();
+                        ui.add_sized(
+                            [width * 1.5, size.y],
+                            TextEdit::singleline(&mut notifications.mail_from).hint_text("From"),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_MAIL_ADDRESSES);
+                        ui.add_sized(
+                            [width * 1.5, size.y],
+                            TextEdit::singleline(&mut notifications.mail_to).hint_text("To"),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_MAIL_ADDRESSES);
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.add_sized(size, Checkbox::new(&mut notifications.command, "Command"))
+                        .on_hover_text(GUPAX_NOTIFY_COMMAND);
+                    ui.add_enabled_ui(notifications.command, |ui| {
+                        ui.add_sized(
+                            [width * 4.0, size.y],
+                            TextEdit::singleline(&mut notifications.command_line),
+                        )
+                        .on_hover_text(GUPAX_NOTIFY_COMMAND);
+                    });
+                });
+            });
+
+            // Logs
+            debug!("Gupaxx Tab | Rendering log settings");
+            ui.group(|ui| {
+                ui.add_sized(
+                    [ui.available_width(), height / 2.0],
+                    Label::new(RichText::new("Logs").underline().color(LIGHT_GRAY)),
+                )
+                .on_hover_text(GUPAX_LOGS);
+                ui.separator();
+                let width = size.x / 10.0;
+                let size = vec2(width, height / 3.5);
+                ui.horizontal(|ui| {
+                    ui.add_sized(size, Checkbox::new(&mut self.logs.enabled, "Keep logs"))
+                        .on_hover_text(GUPAX_LOGS);
+                    ui.separator();
+                    ui.add_enabled_ui(self.logs.enabled, |ui| {
+                        ui.add_sized(size, Label::new("Size [1-100MB]:"));
+                        ui.add(Slider::new(&mut self.logs.max_size_mb, 1..=100))
+                            .on_hover_text(GUPAX_LOGS_SIZE);
+                        ui.separator();
+                        ui.add_sized(size, Label::new("Files [0-50]:"));
+                        ui.add(Slider::new(&mut self.logs.max_files, 0..=50))
+                            .on_hover_text(GUPAX_LOGS_FILES);
+                    });
+                    ui.separator();
+                    if ui
+                        .add_sized(size, Button::new("Open folder"))
+                        .on_hover_text(GUPAX_LOGS_OPEN)
+                        .clicked()
+                    {
+                        if let Err(e) = crate::miscs::open_folder(logs_path) {
+                            error_state.set(
+                                format!("Could not open the logs folder: {}", e),
+                                ErrorFerris::Error,
+                                ErrorButtons::Okay,
+                            );
+                        }
+                    }
+                    if ui
+                        .add_sized(size, Button::new("Export"))
+                        .on_hover_text(GUPAX_LOGS_EXPORT)
+                        .clicked()
+                    {
+                        Self::spawn_export_logs_thread(logs_path);
+                    }
+                    if ui
+                        .add_sized(size, Button::new("Diagnose"))
+                        .on_hover_text(GUPAX_LOGS_DIAGNOSE)
+                        .clicked()
+                    {
+                        Self::spawn_diagnose_thread(og, state_path, helper);
+                    }
+                });
+            });
         });
     }
 }
@@ -5,9 +5,11 @@ use crate::utils::errors::{ErrorButtons, ErrorFerris};
 use egui::*;
 use log::debug;
 
+pub mod console;
 mod gupax;
 mod node;
 mod p2pool;
+mod restart;
 mod status;
 mod xmrig;
 mod xmrig_proxy;
@@ -43,10 +45,10 @@ impl crate::app::App {
 						let distro = true;
 						#[cfg(not(feature = "distro"))]
 						let distro = false;
-						let node_gui_len = self.node_api.lock().unwrap().output.len();
-						let p2pool_gui_len = self.p2pool_api.lock().unwrap().output.len();
-						let xmrig_gui_len = self.xmrig_api.lock().unwrap().output.len();
-						let xmrig_proxy_gui_len = self.xmrig_proxy_api.lock().unwrap().output.len();
+						let node_gui_len = self.node_api.lock().unwrap().output.bytes();
+						let p2pool_gui_len = self.p2pool_api.lock().unwrap().output.bytes();
+						let xmrig_gui_len = self.xmrig_api.lock().unwrap().output.bytes();
+						let xmrig_proxy_gui_len = self.xmrig_proxy_api.lock().unwrap().output.bytes();
 						let gupax_p2pool_api = self.gupax_p2pool_api.lock().unwrap();
 						let debug_info = format!(
 "Gupax version: {}\n
@@ -156,35 +158,38 @@ path_xmr: {:#?}\n
 
 						if cfg!(debug_assertions) { ui.label(format!("Gupax is running in debug mode - {}", self.now.elapsed().as_secs_f64())); }
 						ui.label(format!("Gupax has been running for {}", self.pub_sys.lock().unwrap().gupax_uptime));
+						ctx.request_repaint_after(std::time::Duration::from_secs(1));
 					});
 				}
 				Tab::Status => {
 					debug!("App | Entering [Status] Tab");
-					crate::disk::state::Status::show(&mut self.state.status, &self.pub_sys, &self.node_api, &self.p2pool_api, &self.xmrig_api,&self.xmrig_proxy_api, &self.xvb_api,&self.p2pool_img, &self.xmrig_img, node_is_alive, p2pool_is_alive, xmrig_is_alive,  xmrig_proxy_is_alive,xvb_is_alive, self.max_threads, &self.gupax_p2pool_api, &self.benchmarks, self.size, ctx, ui);
+					let stats = self.stats.borrow().clone();
+					crate::disk::state::Status::show(&mut self.state.status, &self.pub_sys, &stats, &self.p2pool_img, &self.xmrig_img, node_is_alive, p2pool_is_alive, xmrig_is_alive,  xmrig_proxy_is_alive,xvb_is_alive, self.max_threads, &self.gupax_p2pool_api, &self.history, &self.benchmarks, &self.helper, &self.custom, &mut self.custom_stdin, self.size, ctx, ui);
 				}
 				Tab::Gupax => {
 					debug!("App | Entering [Gupax] Tab");
-					crate::disk::state::Gupax::show(&mut self.state.gupax, &self.og, &self.state_path, &self.update, &self.file_window, &mut self.error_state, &self.restart, self.size,  frame, ctx, ui);
+					crate::disk::state::Gupax::show(&mut self.state.gupax, &self.og, &self.state_path, &self.logs_path, &self.profiles_path, &mut self.profile_name, &self.update, &self.file_window, &self.config_window, &mut self.error_state, &self.restart, &self.helper, self.size,  frame, ctx, ui);
 				}
 				Tab::Node=> {
 					debug!("App | Entering [Node] Tab");
-					crate::disk::state::Node::show(&mut self.state.node, &self.node, &self.node_api, &mut self.node_stdin, self.size,  &self.file_window, ui);
+					crate::disk::state::Node::show(&mut self.state.node, &self.node, &self.node_api, &mut self.node_stdin, &mut self.node_console, self.size,  &self.file_window, ui);
 				}
 				Tab::P2pool => {
 					debug!("App | Entering [P2Pool] Tab");
-					crate::disk::state::P2pool::show(&mut self.state.p2pool, &mut self.node_vec, &self.og, &self.ping, &self.p2pool, &self.p2pool_api, &mut self.p2pool_stdin, self.size, ctx, ui);
+					crate::disk::state::P2pool::show(&mut self.state.p2pool, &mut self.node_vec, &self.og, &self.ping, &self.p2pool, &self.p2pool_api, &mut self.p2pool_stdin, &mut self.p2pool_console, self.size, ctx, ui);
 				}
 				Tab::Xmrig => {
 					debug!("App | Entering [XMRig] Tab");
-					crate::disk::state::Xmrig::show(&mut self.state.xmrig, &mut self.pool_vec, &self.xmrig, &self.xmrig_api, &mut self.xmrig_stdin, self.size, ctx, ui);
+					crate::disk::state::Xmrig::show(&mut self.state.xmrig, &mut self.pool_vec, &self.xmrig, &self.xmrig_api, &mut self.xmrig_stdin, &mut self.xmrig_console, self.size, ctx, ui);
 				}
 				Tab::XmrigProxy => {
 					debug!("App | Entering [XMRig-Proxy] Tab");
-					crate::disk::state::XmrigProxy::show(&mut self.state.xmrig_proxy, &self.xmrig_proxy, &mut self.pool_vec, &self.xmrig_proxy_api, &mut self.xmrig_proxy_stdin, self.size,  ui);
+					crate::disk::state::XmrigProxy::show(&mut self.state.xmrig_proxy, &self.xmrig_proxy, &mut self.pool_vec, &self.xmrig_proxy_api, &mut self.xmrig_proxy_stdin, &mut self.xmrig_proxy_console, self.size,  ui);
 				}
 				Tab::Xvb => {
 					debug!("App | Entering [XvB] Tab");
-					crate::disk::state::Xvb::show(&mut self.state.xvb, self.size, &self.state.p2pool.address, ctx, ui, &self.xvb_api, &self.xmrig_api, &self.xmrig_proxy_api, xvb_is_running);
+					let stats = self.stats.borrow().clone();
+					crate::disk::state::Xvb::show(&mut self.state.xvb, self.size, &self.state.p2pool.address, ctx, ui, &self.xvb_api, &mut self.xvb_console, &stats, &self.gupax_p2pool_api, xvb_is_running);
 				}
 			}
 		});
@@ -1,3 +1,4 @@
+use crate::app::panels::middle::console::Console;
 use crate::{
     GUPAX_SELECT, NODE_API_BIND, NODE_API_PORT, NODE_ARGUMENTS, NODE_DB_DIR, NODE_DB_PATH_EMPTY,
     NODE_DNS_BLOCKLIST, NODE_DNS_CHECKPOINT, NODE_INPUT, NODE_PATH_OK, NODE_PRUNNING, NODE_URL,
@@ -14,17 +15,18 @@ use crate::components::gupax::{FileType, FileWindow};
 use crate::disk::state::{Gupax, Node};
 use crate::helper::node::PubNodeApi;
 use crate::helper::Process;
-use crate::regex::{num_lines, REGEXES};
-use crate::utils::constants::DARK_GRAY;
+use crate::regex::REGEXES;
 use crate::{GREEN, LIGHT_GRAY, P2POOL_IN, P2POOL_LOG, P2POOL_OUT, RED, SPACE};
 
 impl Node {
     #[inline(always)] // called once
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         process: &Arc<Mutex<Process>>,
         api: &Arc<Mutex<PubNodeApi>>,
         buffer: &mut String,
+        console: &mut Console,
         size: Vec2,
         file_window: &Arc<Mutex<FileWindow>>,
         ui: &mut egui::Ui,
@@ -47,30 +49,9 @@ impl Node {
             debug!("Node Tab | Rendering [Console]");
             ui.group(|ui| {
                 let text = &api.lock().unwrap().output;
-                let nb_lines = num_lines(text);
                 let height = size.y / 2.8;
                 let width = (size.x - (space_h / 2.0)).max(0.0);
-                egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
-                    ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
-                    egui::ScrollArea::vertical()
-                        .stick_to_bottom(true)
-                        .max_width(width)
-                        .max_height(height)
-                        .auto_shrink([false; 2])
-                        // .show_viewport(ui, |ui, _| {
-                        .show_rows(
-                            ui,
-                            ui.text_style_height(&TextStyle::Name("MonospaceSmall".into())),
-                            nb_lines,
-                            |ui, row_range| {
-                                for i in row_range {
-                                    if let Some(line) = text.lines().nth(i) {
-                                        ui.label(line);
-                                    }
-                                }
-                            },
-                        );
-                });
+                console.show(ui, text, width, height);
             });
             //---------------------------------------------------------------------------------------------------- [Advanced] Console
             if !self.simple {
@@ -90,7 +71,7 @@ impl Node {
                     let buffer = std::mem::take(buffer); // Take buffer
                     let mut process = process.lock().unwrap(); // Lock
                     if process.is_alive() {
-                        process.input.push(buffer);
+                        process.send_input(buffer);
                     } // Push only if alive
                 }
 
@@ -113,6 +94,7 @@ impl Node {
                         self.arguments.truncate(1024);
                     })
                 });
+                self.restart.show(ui, txt_description_width, text_height);
                 if !self.arguments.is_empty() {
                     ui.disable();
                 }
@@ -1,7 +1,7 @@
+use crate::app::panels::middle::console::Console;
 use crate::disk::node::Node;
 use crate::disk::state::{P2pool, State};
 use crate::helper::p2pool::PubP2poolApi;
-use crate::regex::num_lines;
 // Gupax - GUI Uniting P2Pool And XMRig
 //
 // Copyright (c) 2022-2023 hinto-janai
@@ -19,11 +19,7 @@ use crate::regex::num_lines;
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 use crate::{components::node::*, constants::*, helper::*, utils::regex::Regexes};
-use egui::{
-    vec2, Color32, Label, RichText, TextEdit,
-    TextStyle::{self, *},
-    Vec2,
-};
+use egui::{vec2, Color32, Label, RichText, TextEdit, Vec2};
 use log::*;
 
 use std::sync::{Arc, Mutex};
@@ -42,6 +38,7 @@ impl P2pool {
         process: &Arc<Mutex<Process>>,
         api: &Arc<Mutex<PubP2poolApi>>,
         buffer: &mut String,
+        console: &mut Console,
         size: Vec2,
         _ctx: &egui::Context,
         ui: &mut egui::Ui,
@@ -54,7 +51,6 @@ impl P2pool {
         egui::ScrollArea::vertical().show(ui, |ui| {
             ui.group(|ui| {
                 let text = &api.lock().unwrap().output;
-                let nb_lines = num_lines(text);
                 let (height, width) = if self.simple {
                     ((size.y * 0.38) - SPACE, size.x - SPACE)
                 } else {
@@ -67,27 +63,7 @@ impl P2pool {
                         width - SPACE,
                     )
                 };
-                egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
-                    ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
-                    egui::ScrollArea::vertical()
-                        .stick_to_bottom(true)
-                        .max_width(width)
-                        .max_height(height)
-                        .auto_shrink([false; 2])
-                        // .show_viewport(ui, |ui, _| {
-                        .show_rows(
-                            ui,
-                            ui.text_style_height(&TextStyle::Name("MonospaceSmall".into())),
-                            nb_lines,
-                            |ui, row_range| {
-                                for i in row_range {
-                                    if let Some(line) = text.lines().nth(i) {
-                                        ui.label(line);
-                                    }
-                                }
-                            },
-                        );
-                });
+                console.show(ui, text, width, height);
                 if !self.simple {
                     //---------------------------------------------------------------------------------------------------- [Advanced] Console
                     ui.separator();
@@ -106,7 +82,7 @@ impl P2pool {
                         let buffer = std::mem::take(buffer); // Take buffer
                         let mut process = process.lock().unwrap(); // Lock
                         if process.is_alive() {
-                            process.input.push(buffer);
+                            process.send_input(buffer);
                         } // Push only if alive
                     }
                 }
@@ -130,6 +106,7 @@ impl P2pool {
                         self.arguments.truncate(1024);
                     })
                 });
+                self.restart.show(ui, (width / 10.0) - SPACE, text_edit);
                 if !self.arguments.is_empty() {
                     ui.disable()
                 }
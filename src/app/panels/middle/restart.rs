@@ -0,0 +1,55 @@
+use crate::disk::state::{RestartMode, RestartPolicy};
+use crate::utils::constants::{
+    RESTART_ALWAYS, RESTART_BACKOFF, RESTART_MAX_RETRIES, RESTART_NEVER, RESTART_ON_FAILURE,
+};
+use egui::{Label, SelectableLabel, Slider, Ui};
+use log::debug;
+
+impl RestartPolicy {
+    // Shared by the [Advanced] tabs of P2Pool, XMRig, XMRig-Proxy and Node.
+    pub fn show(&mut self, ui: &mut Ui, txt_description_width: f32, text_height: f32) {
+        debug!("Rendering [Restart policy]");
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.add_sized(
+                    [txt_description_width, text_height],
+                    Label::new("Restart on exit:"),
+                );
+                for (mode, hover) in [
+                    (RestartMode::Never, RESTART_NEVER),
+                    (RestartMode::Always, RESTART_ALWAYS),
+                    (RestartMode::OnFailure, RESTART_ON_FAILURE),
+                ] {
+                    if ui
+                        .add_sized(
+                            [txt_description_width, text_height],
+                            SelectableLabel::new(self.mode == mode, mode.to_string()),
+                        )
+                        .on_hover_text(hover)
+                        .clicked()
+                    {
+                        self.mode = mode;
+                    }
+                }
+            });
+            ui.add_enabled_ui(self.mode != RestartMode::Never, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_sized(
+                        [txt_description_width, text_height],
+                        Label::new("Max retries [0-20]:"),
+                    );
+                    ui.add(Slider::new(&mut self.max_retries, 0..=20))
+                        .on_hover_text(RESTART_MAX_RETRIES);
+                });
+                ui.horizontal(|ui| {
+                    ui.add_sized(
+                        [txt_description_width, text_height],
+                        Label::new("Backoff [1-300s]:"),
+                    );
+                    ui.add(Slider::new(&mut self.backoff_secs, 1..=300))
+                        .on_hover_text(RESTART_BACKOFF);
+                });
+            });
+        });
+    }
+}
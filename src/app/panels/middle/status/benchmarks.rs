@@ -1,5 +1,3 @@
-use std::sync::{Arc, Mutex};
-
 use crate::{app::Benchmark, disk::state::Status, helper::xrig::xmrig::PubXmrigApi};
 use egui::{Hyperlink, ProgressBar, ScrollArea, Spinner, Vec2};
 use egui_extras::{Column, TableBuilder};
@@ -15,7 +13,7 @@ impl Status {
         ui: &mut egui::Ui,
         benchmarks: &[Benchmark],
         xmrig_alive: bool,
-        xmrig_api: &Arc<Mutex<PubXmrigApi>>,
+        api: &PubXmrigApi,
     ) {
         debug!("Status Tab | Rendering [Benchmarks]");
         let text = size.y / 20.0;
@@ -91,7 +89,6 @@ impl Status {
         // User's CPU hashrate comparison (if XMRig is alive).
         ui.scope(|ui| {
             if xmrig_alive {
-                let api = xmrig_api.lock().unwrap();
                 let percent = (api.hashrate_raw / cpu.high) * 100.0;
                 let human = Percent::from(percent);
                 if percent > 100.0 {
@@ -0,0 +1,107 @@
+use std::sync::{Arc, Mutex};
+
+use egui::{Label, RichText, ScrollArea, SelectableLabel, Vec2};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use log::debug;
+
+use crate::{
+    disk::{
+        history::{History, HistorySample},
+        state::Status,
+        status::HistoryView,
+    },
+    utils::constants::*,
+};
+
+impl Status {
+    pub(super) fn history(&mut self, size: Vec2, ui: &mut egui::Ui, history: &Arc<Mutex<History>>) {
+        debug!("Status Tab | Rendering [History]");
+        let width = size.x;
+        let text = size.y / 25.0;
+        // [HistoryView] buttons
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                let width = (width / 2.0) - (SPACE * 2.0);
+                if ui
+                    .add_sized(
+                        [width, text],
+                        SelectableLabel::new(self.history_view == HistoryView::Day, "Day"),
+                    )
+                    .on_hover_text(STATUS_SUBMENU_HISTORY_DAY)
+                    .clicked()
+                {
+                    self.history_view = HistoryView::Day;
+                }
+                ui.separator();
+                if ui
+                    .add_sized(
+                        [width, text],
+                        SelectableLabel::new(self.history_view == HistoryView::Month, "Month"),
+                    )
+                    .on_hover_text(STATUS_SUBMENU_HISTORY_MONTH)
+                    .clicked()
+                {
+                    self.history_view = HistoryView::Month;
+                }
+            })
+        });
+
+        // Copy the samples out, so the [Helper] is not blocked while drawing.
+        let lock = history.lock().unwrap();
+        let (samples, unit, label): (Vec<HistorySample>, f64, &str) = match self.history_view {
+            HistoryView::Day => (lock.minutes.iter().copied().collect(), 3600.0, "Hours ago"),
+            HistoryView::Month => (lock.hours.iter().copied().collect(), 86400.0, "Days ago"),
+        };
+        drop(lock);
+        if samples.is_empty() {
+            ui.add_sized(
+                [width, size.y / 2.0],
+                Label::new(RichText::new(STATUS_SUBMENU_HISTORY_EMPTY).color(LIGHT_GRAY)),
+            );
+            return;
+        }
+
+        // X axis is the time relative to now, so the graphs
+        // read from left (oldest) to right (most recent).
+        let now = HistorySample::now() as f64;
+        let line = |name: &str, f: fn(&HistorySample) -> f64| {
+            let points: PlotPoints = samples
+                .iter()
+                .map(|s| [(s.timestamp as f64 - now) / unit, f(s)])
+                .collect();
+            Line::new(points).name(name)
+        };
+        let height = (size.y - text * 2.0) / 3.0;
+        let link = ui.id().with("history_plots");
+        let plot = |id: &str| {
+            Plot::new(id)
+                .height(height)
+                .legend(Legend::default())
+                .x_axis_label(label)
+                .link_axis(link, true, false)
+                .link_cursor(link, true, false)
+                .allow_scroll(false)
+        };
+        ScrollArea::vertical().show(ui, |ui| {
+            ui.label(RichText::new("Hashrate (H/s)").underline().color(BONE));
+            plot("history_hashrate").show(ui, |plot_ui| {
+                plot_ui.line(line("XMRig", |s| s.xmrig_hashrate as f64));
+                plot_ui.line(line("P2Pool 15m", |s| s.p2pool_hashrate_15m as f64));
+                plot_ui.line(line("P2Pool 1h", |s| s.p2pool_hashrate_1h as f64));
+            });
+            ui.label(RichText::new("Hashrate sent (H/s)").underline().color(BONE));
+            plot("history_sent").show(ui, |plot_ui| {
+                plot_ui.line(line("P2Pool", |s| s.hashrate_p2pool as f64));
+                plot_ui.line(line("XvB", |s| s.hashrate_xvb as f64));
+            });
+            ui.label(RichText::new("Current effort (%)").underline().color(BONE));
+            plot("history_effort").show(ui, |plot_ui| {
+                plot_ui.line(line("Effort", |s| s.current_effort as f64));
+            });
+            ui.label(RichText::new("Shares found").underline().color(BONE));
+            plot("history_shares").show(ui, |plot_ui| {
+                plot_ui.line(line("Shares", |s| s.shares_found as f64));
+            });
+        });
+    }
+}
@@ -19,21 +19,16 @@ use egui::Vec2;
 
 use crate::{
     app::Benchmark,
-    disk::{gupax_p2pool_api::GupaxP2poolApi, state::Status, status::*},
+    disk::{gupax_p2pool_api::GupaxP2poolApi, history::History, state::Status, status::*},
     helper::{
-        node::PubNodeApi,
-        p2pool::{ImgP2pool, PubP2poolApi},
-        xrig::{
-            xmrig::{ImgXmrig, PubXmrigApi},
-            xmrig_proxy::PubXmrigProxyApi,
-        },
-        xvb::PubXvbApi,
+        custom::Custom, p2pool::ImgP2pool, snapshot::StatsSnapshot, xrig::xmrig::ImgXmrig, Helper,
         Sys,
     },
 };
 use std::sync::{Arc, Mutex};
 
 mod benchmarks;
+mod history;
 mod p2pool;
 mod processes;
 
@@ -43,11 +38,7 @@ impl Status {
     pub fn show(
         &mut self,
         sys: &Arc<Mutex<Sys>>,
-        node_api: &Arc<Mutex<PubNodeApi>>,
-        p2pool_api: &Arc<Mutex<PubP2poolApi>>,
-        xmrig_api: &Arc<Mutex<PubXmrigApi>>,
-        xmrig_proxy_api: &Arc<Mutex<PubXmrigProxyApi>>,
-        xvb_api: &Arc<Mutex<PubXvbApi>>,
+        stats: &StatsSnapshot,
         p2pool_img: &Arc<Mutex<ImgP2pool>>,
         xmrig_img: &Arc<Mutex<ImgXmrig>>,
         node_alive: bool,
@@ -57,37 +48,45 @@ impl Status {
         xvb_alive: bool,
         max_threads: usize,
         gupax_p2pool_api: &Arc<Mutex<GupaxP2poolApi>>,
+        history: &Arc<Mutex<History>>,
         benchmarks: &[Benchmark],
+        helper: &Arc<Mutex<Helper>>,
+        custom: &[Custom],
+        custom_stdin: &mut [String],
         size: Vec2,
-        _ctx: &egui::Context,
+        ctx: &egui::Context,
         ui: &mut egui::Ui,
     ) {
+        // [Sys] is not in the snapshot, keep it moving while it is shown.
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
         //---------------------------------------------------------------------------------------------------- [Processes]
         if self.submenu == Submenu::Processes {
             self.processes(
                 sys,
                 size,
                 ui,
+                stats,
                 node_alive,
-                node_api,
                 p2pool_alive,
-                p2pool_api,
                 p2pool_img,
                 xmrig_alive,
-                xmrig_api,
                 xmrig_proxy_alive,
-                xmrig_proxy_api,
                 xmrig_img,
                 xvb_alive,
-                xvb_api,
                 max_threads,
+                helper,
+                custom,
+                custom_stdin,
             );
         //---------------------------------------------------------------------------------------------------- [P2Pool]
         } else if self.submenu == Submenu::P2pool {
-            self.p2pool(size, ui, gupax_p2pool_api, p2pool_alive, p2pool_api);
+            self.p2pool(size, ui, gupax_p2pool_api, p2pool_alive, &stats.p2pool);
         //---------------------------------------------------------------------------------------------------- [Benchmarks]
         } else if self.submenu == Submenu::Benchmarks {
-            self.benchmarks(size, ui, benchmarks, xmrig_alive, xmrig_api)
+            self.benchmarks(size, ui, benchmarks, xmrig_alive, &stats.xmrig)
+        //---------------------------------------------------------------------------------------------------- [History]
+        } else if self.submenu == Submenu::History {
+            self.history(size, ui, history)
         }
     }
 }
@@ -20,7 +20,7 @@ impl Status {
         ui: &mut egui::Ui,
         gupax_p2pool_api: &Arc<Mutex<GupaxP2poolApi>>,
         p2pool_alive: bool,
-        p2pool_api: &Arc<Mutex<PubP2poolApi>>,
+        p2pool_api: &PubP2poolApi,
     ) {
         let api = gupax_p2pool_api.lock().unwrap();
         let height = size.y;
@@ -214,7 +214,7 @@ impl Status {
             let text = height / 25.0;
             let width = (width / 3.0) - (SPACE * 1.666);
             let min_height = ui.available_height() / 1.3;
-            let api = p2pool_api.lock().unwrap();
+            let api = p2pool_api;
             ui.horizontal(|ui| {
                 ui.group(|ui| {
                     ui.vertical(|ui| {
@@ -455,7 +455,6 @@ impl Status {
                 Label::new(api.calculate_tick_bar()),
             )
             .on_hover_text(STATUS_SUBMENU_PROGRESS_BAR);
-            drop(api);
         });
     }
 }
@@ -1,14 +1,17 @@
-use egui::{ScrollArea, Ui, Vec2};
+use egui::{ScrollArea, TextEdit, Ui, Vec2};
 use readable::up::UptimeFull;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::disk::state::Status;
+use crate::helper::custom::Custom;
 use crate::helper::node::PubNodeApi;
 use crate::helper::p2pool::{ImgP2pool, PubP2poolApi};
+use crate::helper::snapshot::StatsSnapshot;
 use crate::helper::xrig::xmrig::{ImgXmrig, PubXmrigApi};
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
 use crate::helper::xvb::{rounds::XvbRound, PubXvbApi};
-use crate::helper::Sys;
+use crate::helper::{Helper, Sys};
 use egui::TextStyle;
 
 use crate::constants::*;
@@ -21,19 +24,18 @@ impl Status {
         sys: &Arc<Mutex<Sys>>,
         size: Vec2,
         ui: &mut egui::Ui,
+        stats: &StatsSnapshot,
         node_alive: bool,
-        node_api: &Arc<Mutex<PubNodeApi>>,
         p2pool_alive: bool,
-        p2pool_api: &Arc<Mutex<PubP2poolApi>>,
         p2pool_img: &Arc<Mutex<ImgP2pool>>,
         xmrig_alive: bool,
-        xmrig_api: &Arc<Mutex<PubXmrigApi>>,
         xmrig_proxy_alive: bool,
-        xmrig_proxy_api: &Arc<Mutex<PubXmrigProxyApi>>,
         xmrig_img: &Arc<Mutex<ImgXmrig>>,
         xvb_alive: bool,
-        xvb_api: &Arc<Mutex<PubXvbApi>>,
         max_threads: usize,
+        helper: &Arc<Mutex<Helper>>,
+        custom: &[Custom],
+        custom_stdin: &mut [String],
     ) {
         // set fixed text size, temporary solution before refactoring text/widget size.
         let width = ((size.x / 5.0) - (SPACE * 1.7500)).max(0.0);
@@ -46,6 +48,8 @@ impl Status {
         // ui.spacing_mut().item_spacing = Vec2::new(2.0, 2.0);
         let min_width = size_text * 14.0;
         let min_size: Vec2 = [min_width, min_height].into();
+        // The snapshot only has when the processes started.
+        let stats = &stats.with_uptimes();
         ui.horizontal(|ui| {
             ScrollArea::horizontal().show(ui, |ui| {
                 ui.set_min_height(min_height * 34.2);
@@ -53,23 +57,29 @@ impl Status {
                 // [Gupax]
                 gupax(ui, min_size, size, sys);
                 // [Node]
-                node(ui, min_size, size, node_alive, node_api);
+                node(ui, min_size, size, node_alive, &stats.node);
                 // [P2Pool]
-                p2pool(ui, min_size, size, p2pool_alive, p2pool_api, p2pool_img);
+                p2pool(ui, min_size, size, p2pool_alive, &stats.p2pool, p2pool_img);
                 // [XMRig]
                 xmrig(
                     ui,
                     min_size,
                     size,
                     xmrig_alive,
-                    xmrig_api,
+                    &stats.xmrig,
                     xmrig_img,
                     max_threads,
                 );
                 //[XMRig-Proxy]
-                xmrig_proxy(ui, min_size, size, xmrig_proxy_alive, xmrig_proxy_api);
+                xmrig_proxy(ui, min_size, size, xmrig_proxy_alive, &stats.xmrig_proxy);
                 // [XvB]
-                xvb(ui, min_size, size, xvb_alive, xvb_api);
+                xvb(ui, min_size, size, xvb_alive, &stats.xvb);
+                // [Custom] processes from [processes.toml]
+                for (i, (custom, buffer)) in custom.iter().zip(custom_stdin.iter_mut()).enumerate()
+                {
+                    let uptime = stats.custom.get(i).map(|api| api.uptime);
+                    custom_process(ui, min_size, size, helper, i, custom, uptime, buffer);
+                }
             })
         });
     }
@@ -127,6 +137,12 @@ fn gupax(ui: &mut Ui, min_size: Vec2, size: Vec2, sys: &Arc<Mutex<Sys>>) {
             )
             .on_hover_text(STATUS_GUPAX_SYSTEM_CPU_MODEL);
             ui.add_sized(size, Label::new(sys.system_cpu_model.to_string()));
+            ui.add_sized(
+                size,
+                Label::new(RichText::new("CPU Temperature").underline().color(BONE)),
+            )
+            .on_hover_text(STATUS_GUPAX_SYSTEM_CPU_TEMPERATURE);
+            ui.add_sized(size, Label::new(sys.system_cpu_temperature.to_string()));
             drop(sys);
         })
     });
@@ -137,7 +153,7 @@ fn p2pool(
     min_size: Vec2,
     size: Vec2,
     p2pool_alive: bool,
-    p2pool_api: &Arc<Mutex<PubP2poolApi>>,
+    api: &PubP2poolApi,
     p2pool_img: &Arc<Mutex<ImgP2pool>>,
 ) {
     ui.group(|ui| {
@@ -159,7 +175,6 @@ fn p2pool(
                     .on_disabled_hover_text("P2Pool is offline");
                     ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
                     let size = [size.x, size.y / 1.4];
-                    let api = p2pool_api.lock().unwrap();
                     ui.add_sized(
                         size,
                         Label::new(RichText::new("Uptime").underline().color(BONE)),
@@ -274,7 +289,6 @@ fn p2pool(
                     .on_hover_text(STATUS_P2POOL_ADDRESS);
                     ui.add_sized(size, Label::new(&img.address));
                     drop(img);
-                    drop(api);
                 });
             })
         })
@@ -286,7 +300,7 @@ fn xmrig_proxy(
     min_size: Vec2,
     size: Vec2,
     xmrig_proxy_alive: bool,
-    xmrig_proxy_api: &Arc<Mutex<PubXmrigProxyApi>>,
+    api: &PubXmrigProxyApi,
 ) {
     ui.group(|ui| {
         ui.vertical(|ui| {
@@ -304,7 +318,6 @@ fn xmrig_proxy(
                 )
                 .on_hover_text("XMRig-Proxy is online")
                 .on_disabled_hover_text("XMRig-Proxy is offline");
-                let api = xmrig_proxy_api.lock().unwrap();
                 ui.add_sized(
                     size,
                     Label::new(RichText::new("Uptime").underline().color(BONE)),
@@ -344,7 +357,6 @@ fn xmrig_proxy(
                 )
                 .on_hover_text(STATUS_XMRIG_PROXY_POOL);
                 ui.add_sized(size, Label::new(api.node.to_string()));
-                drop(api);
             });
         })
     });
@@ -355,7 +367,7 @@ fn xmrig(
     min_size: Vec2,
     size: Vec2,
     xmrig_alive: bool,
-    xmrig_api: &Arc<Mutex<PubXmrigApi>>,
+    api: &PubXmrigApi,
     xmrig_img: &Arc<Mutex<ImgXmrig>>,
     max_threads: usize,
 ) {
@@ -377,7 +389,6 @@ fn xmrig(
                 )
                 .on_hover_text("XMRig is online")
                 .on_disabled_hover_text("XMRig is offline");
-                let api = xmrig_api.lock().unwrap();
                 ui.add_sized(
                     size,
                     Label::new(RichText::new("Uptime").underline().color(BONE)),
@@ -432,16 +443,15 @@ fn xmrig(
                         max_threads
                     )),
                 );
-                drop(api);
             });
         })
         // })
     });
 }
 
-fn xvb(ui: &mut Ui, min_size: Vec2, size: Vec2, xvb_alive: bool, xvb_api: &Arc<Mutex<PubXvbApi>>) {
+fn xvb(ui: &mut Ui, min_size: Vec2, size: Vec2, xvb_alive: bool, xvb_api: &PubXvbApi) {
     //
-    let api = &xvb_api.lock().unwrap().stats_pub;
+    let api = &xvb_api.stats_pub;
     let enabled = xvb_alive;
     ui.group(|ui| {
         ScrollArea::vertical().show(ui, |ui| {
@@ -563,13 +573,7 @@ fn xvb(ui: &mut Ui, min_size: Vec2, size: Vec2, xvb_alive: bool, xvb_api: &Arc<M
     });
 }
 #[allow(clippy::too_many_arguments)]
-fn node(
-    ui: &mut Ui,
-    min_size: Vec2,
-    size: Vec2,
-    node_alive: bool,
-    node_api: &Arc<Mutex<PubNodeApi>>,
-) {
+fn node(ui: &mut Ui, min_size: Vec2, size: Vec2, node_alive: bool, api: &PubNodeApi) {
     ui.group(|ui| {
         ui.vertical(|ui| {
             ui.set_min_height(min_size.y * 34.0);
@@ -586,7 +590,6 @@ fn node(
                 )
                 .on_hover_text("Node is online")
                 .on_disabled_hover_text("Node is offline");
-                let api = node_api.lock().unwrap();
                 ui.add_sized(
                     size,
                     Label::new(RichText::new("Uptime").underline().color(BONE)),
@@ -648,8 +651,144 @@ fn node(
                 )
                 .on_hover_text(STATUS_NODE_STATUS);
                 ui.add_sized(size, Label::new(api.status.to_string()));
-                drop(api);
             });
         })
     });
 }
+
+#[allow(clippy::too_many_arguments)]
+fn custom_process(
+    ui: &mut Ui,
+    min_size: Vec2,
+    size: Vec2,
+    helper: &Arc<Mutex<Helper>>,
+    index: usize,
+    custom: &Custom,
+    uptime: Option<Duration>,
+    buffer: &mut String,
+) {
+    let (state, restarts, alive, waiting, respawning) = {
+        let process = custom.process.lock().unwrap();
+        (
+            process.state,
+            process.restarts,
+            process.is_alive(),
+            process.is_waiting(),
+            process.respawning,
+        )
+    };
+    ui.group(|ui| {
+        ui.vertical(|ui| {
+            ui.set_min_height(min_size.y * 34.0);
+            ui.set_min_size(min_size);
+            debug!("Status Tab | Rendering [{}]", custom.config.name);
+            ui.add_enabled_ui(alive, |ui| {
+                ui.add_sized(
+                    size,
+                    Label::new(
+                        RichText::new(format!("[{}]", custom.config.name))
+                            .color(LIGHT_GRAY)
+                            .text_style(TextStyle::Name("MonospaceLarge".into())),
+                    ),
+                )
+                .on_hover_text(format!("{} is online", custom.config.name))
+                .on_disabled_hover_text(format!("{} is offline", custom.config.name));
+            });
+            ui.add_sized(
+                size,
+                Label::new(RichText::new("State").underline().color(BONE)),
+            )
+            .on_hover_text(STATUS_CUSTOM_STATE);
+            ui.add_sized(size, Label::new(state.to_string()));
+            ui.add_sized(
+                size,
+                Label::new(RichText::new("Uptime").underline().color(BONE)),
+            )
+            .on_hover_text(STATUS_CUSTOM_UPTIME);
+            let uptime = match uptime {
+                Some(uptime) if alive => UptimeFull::from(uptime).to_string(),
+                _ => UNKNOWN_DATA.to_string(),
+            };
+            ui.add_sized(size, Label::new(uptime));
+            ui.add_sized(
+                size,
+                Label::new(RichText::new("Restarts").underline().color(BONE)),
+            )
+            .on_hover_text(STATUS_CUSTOM_RESTARTS);
+            ui.add_sized(
+                size,
+                Label::new(format!(
+                    "{}/{}",
+                    restarts, custom.config.restart.max_retries
+                )),
+            );
+            ui.add_sized(
+                size,
+                Label::new(RichText::new("Path").underline().color(BONE)),
+            )
+            .on_hover_text(STATUS_CUSTOM_PATH);
+            ui.add_sized(size, Label::new(&custom.config.path));
+            // [Start/Stop/Restart], disabled while the process is changing state.
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!waiting, |ui| {
+                    if alive {
+                        if ui.button("⟲").on_hover_text("Restart").clicked() {
+                            Helper::restart_custom(helper, index);
+                        }
+                        if ui.button("⏹").on_hover_text("Stop").clicked() {
+                            Helper::stop_custom(helper, index);
+                        }
+                    } else {
+                        if ui.button("▶").on_hover_text("Start").clicked() {
+                            Helper::start_custom(helper, index);
+                        }
+                        if respawning
+                            && ui.button("⏹").on_hover_text("Cancel the restart").clicked()
+                        {
+                            Helper::stop_custom(helper, index);
+                        }
+                    }
+                });
+            });
+            // Console
+            ui.group(|ui| {
+                let text = &custom.gui_api.lock().unwrap().output;
+                let nb_lines = text.len();
+                egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
+                    ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
+                    ScrollArea::vertical()
+                        .id_salt(format!("custom_console_{}", index))
+                        .stick_to_bottom(true)
+                        .max_width(size.x)
+                        .max_height(size.y * 20.0)
+                        .auto_shrink([false; 2])
+                        .show_rows(
+                            ui,
+                            ui.text_style_height(&TextStyle::Name("MonospaceSmall".into())),
+                            nb_lines,
+                            |ui, row_range| {
+                                for i in row_range {
+                                    if let Some(line) = text.get(text.first_id() + i as u64) {
+                                        ui.label(line);
+                                    }
+                                }
+                            },
+                        );
+                });
+            });
+            // STDIN
+            let response = ui
+                .add_sized(size, TextEdit::singleline(buffer))
+                .on_hover_text(STATUS_CUSTOM_INPUT);
+            // If the user pressed enter, dump buffer contents into the process STDIN
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                response.request_focus();
+                let buffer = std::mem::take(buffer);
+                let mut process = custom.process.lock().unwrap();
+                if process.is_alive() {
+                    process.send_input(buffer);
+                }
+            }
+        })
+    });
+}
@@ -15,17 +15,17 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::app::panels::middle::console::Console;
 use crate::constants::*;
 use crate::disk::pool::Pool;
-use crate::disk::state::Xmrig;
+use crate::disk::state::{MiningWindow, WindowAction, Xmrig};
+use crate::helper::xrig::schedule::parse_time;
 use crate::helper::xrig::xmrig::PubXmrigApi;
 use crate::helper::Process;
-use crate::regex::{num_lines, REGEXES};
+use crate::regex::REGEXES;
 use crate::utils::regex::Regexes;
 use egui::{
-    vec2, Button, Checkbox, ComboBox, Label, RichText, SelectableLabel, Slider, TextEdit,
-    TextStyle::{self, *},
-    Vec2,
+    vec2, Button, Checkbox, ComboBox, Label, RichText, SelectableLabel, Slider, TextEdit, Vec2,
 };
 use log::*;
 
@@ -40,6 +40,7 @@ impl Xmrig {
         process: &Arc<Mutex<Process>>,
         api: &Arc<Mutex<PubXmrigApi>>,
         buffer: &mut String,
+        console: &mut Console,
         size: Vec2,
         _ctx: &egui::Context,
         ui: &mut egui::Ui,
@@ -50,33 +51,12 @@ impl Xmrig {
         egui::ScrollArea::vertical().show(ui, |ui| {
         ui.group(|ui| {
             let text = &api.lock().unwrap().output;
-            let nb_lines = num_lines(text);
             let (height, width) = if self.simple {
                 (size.y / 1.5, size.x - SPACE)
             } else {
                 (size.y / 2.8, size.x - SPACE)
             };
-            egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
-                ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
-                egui::ScrollArea::vertical()
-                    .stick_to_bottom(true)
-                    .max_width(width)
-                    .max_height(height)
-                    .auto_shrink([false; 2])
-                    // .show_viewport(ui, |ui, _| {
-                    .show_rows(
-                        ui,
-                        ui.text_style_height(&TextStyle::Name("MonospaceSmall".into())),
-                        nb_lines,
-                        |ui, row_range| {
-                            for i in row_range {
-                                if let Some(line) = text.lines().nth(i) {
-                                    ui.label(line);
-                                }
-                            }
-                        },
-                    );
-            });
+            console.show(ui, text, width, height);
             //---------------------------------------------------------------------------------------------------- [Advanced] Console
             if !self.simple {
                 ui.separator();
@@ -95,7 +75,7 @@ impl Xmrig {
                     let buffer = std::mem::take(buffer); // Take buffer
                     let mut process = process.lock().unwrap(); // Lock
                     if process.is_alive() {
-                        process.input.push(buffer);
+                        process.send_input(buffer);
                     } // Push only if alive
                 }
             }
@@ -119,6 +99,8 @@ impl Xmrig {
                     self.arguments.truncate(1024);
                 })
             });
+            self.restart
+                .show(ui, (size.x / 10.0) - SPACE, text_edit);
             ui.add_enabled_ui(self.arguments.is_empty(), |ui|{
 
             //---------------------------------------------------------------------------------------------------- Address
@@ -185,6 +167,18 @@ impl Xmrig {
             });
         });
 
+        //---------------------------------------------------------------------------------------------------- Schedule
+        debug!("XMRig Tab | Rendering [Schedule]");
+        ui.group(|ui| {
+            self.schedule(ui, size.x / 10.0, text_edit);
+        });
+
+        //---------------------------------------------------------------------------------------------------- Throttle
+        debug!("XMRig Tab | Rendering [Throttle]");
+        ui.group(|ui| {
+            self.throttle(ui, size.x / 10.0, text_edit);
+        });
+
         //---------------------------------------------------------------------------------------------------- Simple
         if !self.simple {
             debug!("XMRig Tab | Rendering [Pool List] elements");
@@ -493,4 +487,137 @@ impl Xmrig {
         }
     });
     }
+
+    fn schedule(&mut self, ui: &mut egui::Ui, width: f32, text_edit: f32) {
+        ui.horizontal(|ui| {
+            ui.add_sized(
+                [width * 2.4, text_edit],
+                Label::new(
+                    RichText::new("Mining windows")
+                        .underline()
+                        .color(LIGHT_GRAY),
+                ),
+            )
+            .on_hover_text(XMRIG_SCHEDULE);
+            let now = chrono::Local::now().naive_local();
+            let text = match self.active_window(now) {
+                Some(window) => {
+                    format!("Now: {} ({} - {})", window.action, window.start, window.end)
+                }
+                None => "Now: outside of every window".to_string(),
+            };
+            ui.add_sized([width * 3.0, text_edit], Label::new(text));
+            if ui
+                .add_sized([width, text_edit], Button::new("Add"))
+                .on_hover_text("Add a mining window")
+                .clicked()
+            {
+                self.schedule.push(MiningWindow {
+                    threads: self.current_threads,
+                    ..Default::default()
+                });
+            }
+        });
+        let max_threads = self.max_threads.max(1);
+        let mut delete = None;
+        for (i, window) in self.schedule.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                for (day, name) in window
+                    .days
+                    .iter_mut()
+                    .zip(["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"])
+                {
+                    if ui
+                        .add(SelectableLabel::new(*day, name))
+                        .on_hover_text(XMRIG_SCHEDULE_DAYS)
+                        .clicked()
+                    {
+                        *day = !*day;
+                    }
+                }
+                ui.separator();
+                for (label, time) in [("From", &mut window.start), ("to", &mut window.end)] {
+                    let color = if parse_time(time).is_some() {
+                        GREEN
+                    } else {
+                        RED
+                    };
+                    ui.label(RichText::new(label).color(color));
+                    ui.add_sized([width / 1.5, text_edit], TextEdit::singleline(time))
+                        .on_hover_text(XMRIG_SCHEDULE_TIME);
+                    time.truncate(5);
+                }
+                ui.separator();
+                for action in [WindowAction::Mine, WindowAction::Pause] {
+                    if ui
+                        .add(SelectableLabel::new(
+                            window.action == action,
+                            action.to_string(),
+                        ))
+                        .clicked()
+                    {
+                        window.action = action;
+                    }
+                }
+                ui.add_enabled_ui(window.action == WindowAction::Mine, |ui| {
+                    ui.add(Slider::new(&mut window.threads, 1..=max_threads))
+                        .on_hover_text(XMRIG_THREADS);
+                });
+                ui.separator();
+                if ui.button("Delete").clicked() {
+                    delete = Some(i);
+                }
+            });
+        }
+        if let Some(i) = delete {
+            self.schedule.remove(i);
+        }
+    }
+
+    fn throttle(&mut self, ui: &mut egui::Ui, width: f32, text_edit: f32) {
+        let max_threads = self.max_threads.max(1);
+        let throttle = &mut self.throttle;
+        ui.horizontal(|ui| {
+            ui.add_sized(
+                [width * 2.4, text_edit],
+                Checkbox::new(&mut throttle.enabled, "Throttling"),
+            )
+            .on_hover_text(XMRIG_THROTTLE);
+            ui.add_enabled_ui(throttle.enabled, |ui| {
+                ui.separator();
+                ui.label("Max temperature (°C)");
+                ui.add(Slider::new(&mut throttle.max_temperature, 0..=105))
+                    .on_hover_text(XMRIG_THROTTLE_TEMPERATURE);
+                ui.separator();
+                ui.label("Max load (%)");
+                ui.add(Slider::new(&mut throttle.max_load, 0..=100))
+                    .on_hover_text(XMRIG_THROTTLE_LOAD);
+            });
+        });
+        ui.add_enabled_ui(throttle.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.add_sized([width * 2.4, text_edit], Label::new("While throttled"))
+                    .on_hover_text(XMRIG_THROTTLE_ACTION);
+                for action in [WindowAction::Mine, WindowAction::Pause] {
+                    if ui
+                        .add(SelectableLabel::new(
+                            throttle.action == action,
+                            action.to_string(),
+                        ))
+                        .clicked()
+                    {
+                        throttle.action = action;
+                    }
+                }
+                ui.add_enabled_ui(throttle.action == WindowAction::Mine, |ui| {
+                    ui.add(Slider::new(&mut throttle.threads, 1..=max_threads))
+                        .on_hover_text(XMRIG_THREADS);
+                });
+                ui.separator();
+                ui.label("Hysteresis");
+                ui.add(Slider::new(&mut throttle.hysteresis, 1..=20))
+                    .on_hover_text(XMRIG_THROTTLE_HYSTERESIS);
+            });
+        });
+    }
 }
@@ -1,15 +1,15 @@
 use egui::{vec2, Button, Checkbox, ComboBox, Label, RichText, SelectableLabel, TextEdit, Vec2};
 use std::sync::{Arc, Mutex};
 
-use egui::TextStyle::{self, Name};
+use egui::TextStyle::{self};
 use log::{debug, info};
 
+use crate::app::panels::middle::console::Console;
 use crate::disk::pool::Pool;
 use crate::disk::state::XmrigProxy;
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
 use crate::helper::Process;
-use crate::regex::{num_lines, REGEXES};
-use crate::utils::constants::DARK_GRAY;
+use crate::regex::REGEXES;
 use crate::{
     GREEN, LIGHT_GRAY, LIST_ADD, LIST_CLEAR, LIST_DELETE, LIST_SAVE, RED, SPACE, XMRIG_API_IP,
     XMRIG_API_PORT, XMRIG_IP, XMRIG_KEEPALIVE, XMRIG_NAME, XMRIG_PORT, XMRIG_PROXY_ARGUMENTS,
@@ -18,12 +18,14 @@ use crate::{
 
 impl XmrigProxy {
     #[inline(always)] // called once
+    #[allow(clippy::too_many_arguments)]
     pub fn show(
         &mut self,
         process: &Arc<Mutex<Process>>,
         pool_vec: &mut Vec<(String, Pool)>,
         api: &Arc<Mutex<PubXmrigProxyApi>>,
         buffer: &mut String,
+        console: &mut Console,
         size: Vec2,
         ui: &mut egui::Ui,
     ) {
@@ -44,30 +46,9 @@ impl XmrigProxy {
         debug!("Xmrig-Proxy Tab | Rendering [Console]");
         ui.group(|ui| {
             let text = &api.lock().unwrap().output;
-            let nb_lines = num_lines(text);
             let height = size.y / 2.8;
             let width = size.x - (space_h / 2.0);
-            egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
-                ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
-                egui::ScrollArea::vertical()
-                    .stick_to_bottom(true)
-                    .max_width(width)
-                    .max_height(height)
-                    .auto_shrink([false; 2])
-                    // .show_viewport(ui, |ui, _| {
-                    .show_rows(
-                        ui,
-                        ui.text_style_height(&TextStyle::Name("MonospaceSmall".into())),
-                        nb_lines,
-                        |ui, row_range| {
-                            for i in row_range {
-                                if let Some(line) = text.lines().nth(i) {
-                                    ui.label(line);
-                                }
-                            }
-                        },
-                    );
-            });
+            console.show(ui, text, width, height);
         });
         //---------------------------------------------------------------------------------------------------- [Advanced] Console
         if !self.simple {
@@ -87,7 +68,7 @@ impl XmrigProxy {
                 let buffer = std::mem::take(buffer); // Take buffer
                 let mut process = process.lock().unwrap(); // Lock
                 if process.is_alive() {
-                    process.input.push(buffer);
+                    process.send_input(buffer);
                 } // Push only if alive
             }
 
@@ -108,6 +89,8 @@ impl XmrigProxy {
                     self.arguments.truncate(1024);
                 })
             });
+            self.restart
+                .show(ui, (size.x / 10.0) - SPACE, text_edit);
             if !self.arguments.is_empty() {
             	ui.disable();
             }
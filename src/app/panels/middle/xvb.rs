@@ -1,31 +1,33 @@
 use std::sync::{Arc, Mutex};
 
-use egui::TextStyle::{self, Name};
+use egui::TextStyle::{self};
 use egui::{vec2, Image, RichText, TextEdit, Ui, Vec2};
+use egui_extras::{Column, TableBuilder};
 use log::debug;
 use readable::num::Float;
 use readable::up::Uptime;
 
+use crate::app::panels::middle::console::Console;
+use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
 use crate::disk::state::{ManualDonationLevel, ManualDonationMetric, XvbMode};
-use crate::helper::xrig::xmrig::PubXmrigApi;
-use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
+use crate::helper::snapshot::StatsSnapshot;
 use crate::helper::xvb::priv_stats::RuntimeMode;
 use crate::helper::xvb::PubXvbApi;
-use crate::regex::num_lines;
 use crate::utils::constants::{
     GREEN, LIGHT_GRAY, ORANGE, RED, XVB_DONATED_1H_FIELD, XVB_DONATED_24H_FIELD,
     XVB_DONATION_LEVEL_DONOR_HELP, XVB_DONATION_LEVEL_MEGA_DONOR_HELP,
-    XVB_DONATION_LEVEL_VIP_DONOR_HELP, XVB_DONATION_LEVEL_WHALE_DONOR_HELP, XVB_FAILURE_FIELD,
-    XVB_HELP, XVB_HERO_SELECT, XVB_MANUAL_SLIDER_MANUAL_P2POOL_HELP,
-    XVB_MANUAL_SLIDER_MANUAL_XVB_HELP, XVB_MODE_MANUAL_DONATION_LEVEL_HELP,
-    XVB_MODE_MANUAL_P2POOL_HELP, XVB_MODE_MANUAL_XVB_HELP, XVB_ROUND_TYPE_FIELD, XVB_TOKEN_FIELD,
-    XVB_TOKEN_LEN, XVB_URL_RULES, XVB_WINNER_FIELD,
+    XVB_DONATION_LEVEL_VIP_DONOR_HELP, XVB_DONATION_LEVEL_WHALE_DONOR_HELP, XVB_EXTRA_POOL_HELP,
+    XVB_FAILURE_FIELD, XVB_HELP, XVB_HERO_SELECT, XVB_HISTORY_FIELD, XVB_HISTORY_HELP,
+    XVB_MANUAL_SLIDER_MANUAL_P2POOL_HELP, XVB_MANUAL_SLIDER_MANUAL_XVB_HELP,
+    XVB_MODE_MANUAL_DONATION_LEVEL_HELP, XVB_MODE_MANUAL_P2POOL_HELP, XVB_MODE_MANUAL_XVB_HELP,
+    XVB_ROUND_TYPE_FIELD, XVB_STRATUM_HELP, XVB_TOKEN_FIELD, XVB_TOKEN_LEN, XVB_URL_RULES,
+    XVB_WINNER_FIELD,
 };
 use crate::utils::regex::Regexes;
 use crate::XVB_MINING_ON_FIELD;
 use crate::{
     constants::{BYTES_XVB, SPACE},
-    utils::constants::{DARK_GRAY, XVB_URL},
+    utils::constants::XVB_URL,
 };
 
 impl crate::disk::state::Xvb {
@@ -38,8 +40,9 @@ impl crate::disk::state::Xvb {
         _ctx: &egui::Context,
         ui: &mut egui::Ui,
         api: &Arc<Mutex<PubXvbApi>>,
-        gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
-        gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
+        console: &mut Console,
+        stats: &StatsSnapshot,
+        gupax_p2pool_api: &Arc<Mutex<GupaxP2poolApi>>,
         is_alive: bool,
     ) {
         egui::ScrollArea::vertical().show(ui, |ui| {
@@ -66,30 +69,9 @@ impl crate::disk::state::Xvb {
             debug!("XvB Tab | Rendering [Console]");
             ui.group(|ui| {
                 let text = &api.lock().unwrap().output;
-                let nb_lines = num_lines(text);
                 let height = size.y / 2.8;
                 let width = size.x - (space_h / 2.0);
-                egui::Frame::none().fill(DARK_GRAY).show(ui, |ui| {
-                    ui.style_mut().override_text_style = Some(Name("MonospaceSmall".into()));
-                    egui::ScrollArea::vertical()
-                        .stick_to_bottom(true)
-                        .max_width(width)
-                        .max_height(height)
-                        .auto_shrink([false; 2])
-                        // .show_viewport(ui, |ui, _| {
-                        .show_rows(
-                            ui,
-                            ui.text_style_height(&TextStyle::Name("MonospaceSmall".into())),
-                            nb_lines,
-                            |ui, row_range| {
-                                for i in row_range {
-                                    if let Some(line) = text.lines().nth(i) {
-                                        ui.label(line);
-                                    }
-                                }
-                            },
-                        );
-                });
+                console.show(ui, text, width, height);
             });
             // input token
             let len_token = format!("{}", self.token.len());
@@ -178,14 +160,14 @@ impl crate::disk::state::Xvb {
                             };
                             // use proxy HR in priority, or use xmrig or default.
                             let mut hashrate_xmrig = {
-                                if gui_api_xp.lock().unwrap().hashrate_10m > 0.0 {
-                                    gui_api_xp.lock().unwrap().hashrate_10m
-                                } else if gui_api_xmrig.lock().unwrap().hashrate_raw_15m > 0.0 {
-                                    gui_api_xmrig.lock().unwrap().hashrate_raw_15m
-                                } else if gui_api_xmrig.lock().unwrap().hashrate_raw_1m > 0.0 {
-                                    gui_api_xmrig.lock().unwrap().hashrate_raw_1m
-                                } else if gui_api_xmrig.lock().unwrap().hashrate_raw > 0.0 {
-                                    gui_api_xmrig.lock().unwrap().hashrate_raw
+                                if stats.xmrig_proxy.hashrate_10m > 0.0 {
+                                    stats.xmrig_proxy.hashrate_10m
+                                } else if stats.xmrig.hashrate_raw_15m > 0.0 {
+                                    stats.xmrig.hashrate_raw_15m
+                                } else if stats.xmrig.hashrate_raw_1m > 0.0 {
+                                    stats.xmrig.hashrate_raw_1m
+                                } else if stats.xmrig.hashrate_raw > 0.0 {
+                                    stats.xmrig.hashrate_raw
                                 } else {
                                     default_xmrig_hashrate
                                 }
@@ -275,6 +257,31 @@ impl crate::disk::state::Xvb {
                 egui::Slider::new(&mut self.p2pool_buffer, -100..=100)
                 .text("% P2Pool Buffer" )
             ).on_hover_text("Set the % amount of additional HR to send to p2pool. Will reduce (if positive) or augment (if negative) the chances to miss the p2pool window");
+
+            // another pool given a part of the hashrate every round
+            ui.add_space(space_h);
+            ui.horizontal(|ui| {
+                ui.label("Other pool").on_hover_text(XVB_EXTRA_POOL_HELP);
+                ui.add(
+                    TextEdit::singleline(&mut self.extra_pool)
+                        .hint_text("host:port")
+                        .desired_width(width / 4.0),
+                ).on_hover_text(XVB_EXTRA_POOL_HELP);
+                ui.label("User");
+                ui.add(
+                    TextEdit::singleline(&mut self.extra_pool_user)
+                        .desired_width(width / 4.0),
+                ).on_hover_text(XVB_EXTRA_POOL_HELP);
+                ui.checkbox(&mut self.extra_pool_tls, "TLS");
+            });
+            ui.add_sized(
+                [width, text_edit],
+                egui::Slider::new(&mut self.extra_pool_percent, 0..=100)
+                .text("% Other pool")
+            ).on_hover_text(XVB_EXTRA_POOL_HELP);
+
+            ui.add_space(space_h);
+            ui.checkbox(&mut self.use_stratum, "Use stratum data").on_hover_text(XVB_STRATUM_HELP);
         }
 
          ui.add_space(space_h);
@@ -290,7 +297,7 @@ impl crate::disk::state::Xvb {
             ui.add_space(space_h);
             // ui.add_enabled_ui(is_alive, |ui| {
             ui.add_enabled_ui(is_alive, |ui| {
-                let api = &api.lock().unwrap();
+                let api = &stats.xvb;
                 let priv_stats = &api.stats_priv;
                 let current_node = &api.current_node;
                 let width_stat = (ui.available_width() - SPACE * 4.0) / 5.0;
@@ -401,6 +408,65 @@ impl crate::disk::state::Xvb {
                     // currently mining on
                 });
             });
+            // round history, kept when XvB is not running.
+            ui.add_space(space_h);
+            ui.group(|ui| {
+                let xvb_rounds = &gupax_p2pool_api.lock().unwrap().xvb_rounds;
+                let summary = xvb_rounds.summary();
+                ui.vertical_centered(|ui| {
+                    ui.label(XVB_HISTORY_FIELD).on_hover_text(XVB_HISTORY_HELP);
+                    ui.label(format!(
+                        "{} rounds ({} with a share) | {} on XvB | {} won",
+                        summary.rounds,
+                        summary.participated(),
+                        Uptime::from(summary.xvb_seconds),
+                        summary.wins.len()
+                    ));
+                });
+                if xvb_rounds.records.is_empty() {
+                    return;
+                }
+                let text = ui.text_style_height(&TextStyle::Body);
+                TableBuilder::new(ui)
+                    .columns(Column::remainder(), 6)
+                    .max_scroll_height(size.y / 4.0)
+                    .header(text, |mut header| {
+                        for title in ["Date", "Block", XVB_ROUND_TYPE_FIELD, XVB_DONATED_1H_FIELD, "Time on XvB", XVB_WINNER_FIELD] {
+                            header.col(|ui| {
+                                ui.label(title);
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        // newest first
+                        body.rows(text, xvb_rounds.records.len(), |mut row| {
+                            let record = &xvb_rounds.records[xvb_rounds.records.len() - 1 - row.index()];
+                            row.col(|ui| {
+                                ui.label(record.date());
+                            });
+                            row.col(|ui| {
+                                ui.label(record.block_height.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(record.round_str());
+                            });
+                            row.col(|ui| {
+                                ui.label([Float::from_3(record.donor_1h_avg as f64 / 1000.0).as_str(), " kH/s"].concat())
+                                    .on_hover_text(format!("{}: {} kH/s", XVB_DONATED_24H_FIELD, Float::from_3(record.donor_24h_avg as f64 / 1000.0)));
+                            });
+                            row.col(|ui| {
+                                ui.label(Uptime::from(record.xvb_seconds).to_string());
+                            });
+                            row.col(|ui| {
+                                if record.won {
+                                    ui.label(RichText::new("Won").color(GREEN));
+                                } else {
+                                    ui.label("-");
+                                }
+                            });
+                        });
+                    });
+            });
             // Rules link help
             ui.horizontal_centered(|ui| {
                 // can't have horizontal and vertical centering work together so fix by this.
@@ -2,14 +2,30 @@ use clap::crate_authors;
 use clap::crate_description;
 use clap::crate_name;
 use clap::crate_version;
+use clap::Args;
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
 use log::debug;
 use log::info;
 use log::warn;
+use std::path::Path;
+use std::path::PathBuf;
 use std::process::exit;
 
 use crate::app::App;
+use crate::disk::archive::{self, Import, Payouts, PROGRAM};
+use crate::disk::diagnose::Bundle;
+use crate::disk::errors::TomlError;
+use crate::disk::lock::{InstanceLock, LockError};
+use crate::disk::node::Node;
+use crate::disk::pool::Pool;
+use crate::disk::state::ManualDonationLevel;
+use crate::disk::state::State;
+use crate::disk::state::XvbMode;
+use crate::helper::control::{
+    parse_process_name, send_control, ControlRequest, ControlResponse, ProcessAction,
+};
 use crate::miscs::print_disk_file;
 use crate::miscs::print_gupax_p2pool_api;
 use crate::resets::reset;
@@ -17,6 +33,7 @@ use crate::resets::reset_gupax_p2pool_api;
 use crate::resets::reset_nodes;
 use crate::resets::reset_pools;
 use crate::resets::reset_state;
+use crate::GUPAX_VERSION_UNDERSCORE;
 
 #[derive(Parser)]
 #[command(name = crate_name!())]
@@ -24,12 +41,191 @@ use crate::resets::reset_state;
 #[command(version = crate_version!())]
 #[command(about = crate_description!(), long_about = None)]
 #[command(next_line_help = true)]
-#[group(required = false, multiple = false)]
 pub struct Cli {
     #[command(subcommand)]
     pub info: Option<GupaxxData>,
     #[clap(long, short, action)]
     pub logfile: bool,
+    #[clap(
+        long,
+        action,
+        help = "Run without GUI: start the processes enabled for auto-start in the state file and supervise them until SIGTERM. On Unix, XMRig is only started as root or if sudo runs it without a password (NOPASSWD in the sudoers file)"
+    )]
+    pub daemon: bool,
+    #[clap(
+        long,
+        value_name = "NAME",
+        help = "Switch to this profile (see the [Gupaxx] tab) before starting, or in the running Gupaxx"
+    )]
+    pub profile: Option<String>,
+}
+
+// Arguments of [gupaxx-xvb], see [crate::helper::xvb::standalone].
+#[derive(Parser)]
+#[command(name = "gupaxx-xvb")]
+#[command(author = crate_authors!())]
+#[command(version = crate_version!())]
+#[command(
+    about = "Run the XvB algorithm of Gupaxx on an XMRig or XMRig-Proxy and a P2Pool started without it",
+    long_about = None
+)]
+#[command(next_line_help = true)]
+#[command(subcommand_negates_reqs = true)]
+pub struct XvbCli {
+    #[command(subcommand)]
+    pub command: Option<XvbCliCommand>,
+    #[clap(
+        long,
+        value_name = "URL",
+        help = "HTTP API of XMRig, or of XMRig-Proxy with --proxy [default: http://127.0.0.1:18088, http://127.0.0.1:18089 with --proxy]"
+    )]
+    pub api: Option<String>,
+    #[clap(long, help = "The HTTP API is the one of XMRig-Proxy")]
+    pub proxy: bool,
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        default_value = "",
+        hide_default_value = true,
+        help = "Access token of the HTTP API"
+    )]
+    pub api_token: String,
+    #[clap(
+        long,
+        value_name = "NAME",
+        default_value = GUPAX_VERSION_UNDERSCORE,
+        help = "Rig name of XMRig while mining on P2Pool"
+    )]
+    pub rig: String,
+    #[clap(
+        long,
+        required = true,
+        value_name = "DIR",
+        help = "Directory given to P2Pool with --data-api"
+    )]
+    pub data_api: Option<PathBuf>,
+    #[clap(
+        long,
+        required = true,
+        help = "Monero address mining on P2Pool, registered on XvB"
+    )]
+    pub address: Option<String>,
+    #[clap(long, required = true, help = "XvB token of the address")]
+    pub token: Option<String>,
+    #[command(flatten)]
+    pub settings: XvbSettings,
+    #[clap(
+        long,
+        help = "Take the hashrate of the other miners from the workers of the P2Pool stratum instead of estimating it"
+    )]
+    pub stratum: bool,
+    #[clap(
+        long,
+        value_name = "USER",
+        help = "User XMRig or XMRig-Proxy logs in with on P2Pool before the first switch, so its workers are not counted as other miners, can be repeated"
+    )]
+    pub stratum_user: Vec<String>,
+    #[clap(long, short, action)]
+    pub logfile: bool,
+}
+
+#[derive(Subcommand)]
+pub enum XvbCliCommand {
+    #[command(
+        about = "Replay recorded or synthetic rounds through the algorithm and report the rounds reached and the shares kept"
+    )]
+    Simulate(SimulateArgs),
+}
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "Rounds to replay, one JSON object per line with [hashrate] and [difficulty], and optionally [share], [xvb_24h_avg], [xvb_1h_avg], [p2pool_external] and [xvb_external]. Synthetic rounds without it"
+    )]
+    pub samples: Option<PathBuf>,
+    #[clap(long, default_value_t = 7, help = "Days of synthetic rounds")]
+    pub days: u32,
+    #[clap(
+        long,
+        value_name = "H/S",
+        default_value_t = 10000.0,
+        help = "Hashrate of the synthetic rounds"
+    )]
+    pub hashrate: f32,
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 10.0,
+        help = "How much the synthetic hashrate goes up and down during a day"
+    )]
+    pub variation: f32,
+    #[clap(
+        long,
+        default_value_t = 150_000_000,
+        help = "P2Pool difficulty of the synthetic rounds"
+    )]
+    pub difficulty: u64,
+    #[clap(long, help = "Print every round, not only the summary")]
+    pub rounds: bool,
+    #[command(flatten)]
+    pub settings: XvbSettings,
+}
+
+// The settings of the [XvB] tab the algorithm runs with.
+#[derive(Args)]
+pub struct XvbSettings {
+    #[clap(long, help = "P2Pool is on the main sidechain instead of mini")]
+    pub main: bool,
+    #[clap(long, value_enum, default_value = "auto", help = "XvB mode")]
+    pub mode: CliXvbMode,
+    #[clap(
+        long,
+        value_name = "H/S",
+        default_value_t = 0.0,
+        help = "Hashrate sent to XvB with manual-xvb, or kept on P2Pool with manual-p2pool"
+    )]
+    pub amount: f64,
+    #[clap(
+        long,
+        value_enum,
+        default_value = "donor",
+        help = "Donor round to reach with manual-donation-level"
+    )]
+    pub level: CliDonationLevel,
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 25,
+        allow_negative_numbers = true,
+        value_parser = clap::value_parser!(i8).range(-100..=100),
+        help = "Hashrate to keep on P2Pool above what is needed for a share, in percent"
+    )]
+    pub buffer: i8,
+    #[clap(
+        long,
+        value_name = "HOST:PORT",
+        help = "Another pool given --extra-pool-percent of the hashrate every round, after P2Pool and before XvB"
+    )]
+    pub extra_pool: Option<String>,
+    #[clap(
+        long,
+        value_name = "USER",
+        default_value = "",
+        help = "User on the other pool"
+    )]
+    pub extra_pool_user: String,
+    #[clap(long, help = "The other pool uses TLS")]
+    pub extra_pool_tls: bool,
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 0,
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        help = "Hashrate sent to the other pool, in percent, never what P2Pool needs for a share"
+    )]
+    pub extra_pool_percent: u8,
 }
 
 #[derive(Subcommand)]
@@ -50,12 +246,126 @@ pub enum GupaxxData {
     ResetPayouts,
     #[command(about = "Reset all Gupaxx state (your settings)")]
     ResetAll,
+    #[command(
+        about = "Write a tar.gz with the redacted state, versions, system info, recent logs and the stats of a running Gupaxx, to share when reporting a problem"
+    )]
+    Diagnose {
+        #[clap(
+            long,
+            short,
+            help = "Directory to write the bundle in, the current one by default"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(
+        about = "Write a tar.gz with the state, the node and pool lists and optionally the payout history, to import on another rig"
+    )]
+    Export {
+        #[clap(
+            long,
+            short,
+            help = "Directory to write the archive in, the current one by default"
+        )]
+        output: Option<PathBuf>,
+        #[clap(long, help = "Include the P2Pool payout history")]
+        payouts: bool,
+    },
+    #[command(
+        about = "Print what importing an archive made by [export] would change, and apply it with --yes"
+    )]
+    Import {
+        file: PathBuf,
+        #[clap(long, help = "Apply the changes")]
+        yes: bool,
+        #[clap(
+            long,
+            help = "Also replace the binary paths and the notification command, kept as they are otherwise"
+        )]
+        programs: bool,
+    },
+    #[command(
+        about = "Start processes (p2pool, xmrig, xmrig_proxy, xvb, node, custom_N) in the running Gupaxx, or without GUI if none is running"
+    )]
+    Start {
+        #[arg(required = true)]
+        processes: Vec<String>,
+    },
+    #[command(about = "Stop processes in the running Gupaxx")]
+    Stop {
+        #[arg(required = true)]
+        processes: Vec<String>,
+    },
+    #[command(about = "Restart processes in the running Gupaxx")]
+    Restart {
+        #[arg(required = true)]
+        processes: Vec<String>,
+    },
+    #[command(about = "Print the state of the processes in the running Gupaxx")]
+    Status {
+        #[clap(long, help = "Print as JSON")]
+        json: bool,
+    },
+    #[command(about = "Change the XvB settings, in the running Gupaxx if there is one")]
+    Xvb {
+        #[command(subcommand)]
+        command: XvbCommand,
+    },
     #[command(
         about = "Disable all auto-startup settings for this instance (auto-update, auto-ping, etc)",
         name = "no-startup"
     )]
     Nostartup,
 }
+#[derive(Subcommand)]
+pub enum XvbCommand {
+    #[command(about = "Set and save the XvB mode")]
+    Mode { mode: CliXvbMode },
+    #[command(
+        about = "Print the XvB raffles drawn while XvB was running, the round taken part in and the wins"
+    )]
+    History,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliXvbMode {
+    Auto,
+    Hero,
+    ManualXvb,
+    ManualP2pool,
+    ManualDonationLevel,
+}
+
+impl From<CliXvbMode> for XvbMode {
+    fn from(mode: CliXvbMode) -> Self {
+        match mode {
+            CliXvbMode::Auto => Self::Auto,
+            CliXvbMode::Hero => Self::Hero,
+            CliXvbMode::ManualXvb => Self::ManualXvb,
+            CliXvbMode::ManualP2pool => Self::ManualP2pool,
+            CliXvbMode::ManualDonationLevel => Self::ManualDonationLevel,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliDonationLevel {
+    Donor,
+    Vip,
+    Whale,
+    Mega,
+}
+
+impl From<CliDonationLevel> for ManualDonationLevel {
+    fn from(level: CliDonationLevel) -> Self {
+        match level {
+            CliDonationLevel::Donor => Self::Donor,
+            CliDonationLevel::Vip => Self::DonorVIP,
+            CliDonationLevel::Whale => Self::DonorWhale,
+            CliDonationLevel::Mega => Self::DonorMega,
+        }
+    }
+}
+
 // #[cold]
 // #[inline(never)]
 pub fn parse_args<S: Into<String>>(mut app: App, args: Cli, panic: S) -> App {
@@ -127,8 +437,280 @@ pub fn parse_args<S: Into<String>>(mut app: App, args: Cli, panic: S) -> App {
                 &app.pool_path,
                 &app.gupax_p2pool_api_path,
             ),
+            GupaxxData::Diagnose { output } => {
+                let state = match std::fs::read_to_string(&app.state_path)
+                    .map_err(TomlError::Io)
+                    .and_then(|s| State::load(&s))
+                    .map(|(state, _)| state)
+                {
+                    Ok(state) => state,
+                    Err(e) => {
+                        warn!(
+                            "Diagnose | Could not read the state, using the default: {}",
+                            e
+                        );
+                        State::new()
+                    }
+                };
+                let mut bundle = Bundle::new(&state, &app.os_data_path);
+                bundle.add_http_api(&state);
+                match bundle.write(&output.unwrap_or_default()) {
+                    Ok(path) => {
+                        println!("\nDiagnostics bundle ... OK\n{}", path.display());
+                        exit(0)
+                    }
+                    Err(e) => {
+                        eprintln!("\nDiagnostics bundle ... FAIL\n{}", e);
+                        exit(1)
+                    }
+                }
+            }
+            GupaxxData::Export { output, payouts } => {
+                let result = State::get(&app.state_path).and_then(|state| {
+                    archive::export(
+                        &state,
+                        &app.os_data_path,
+                        payouts,
+                        &output.unwrap_or_default(),
+                    )
+                });
+                match result {
+                    Ok(path) => {
+                        println!("\nConfiguration export ... OK\n{}", path.display());
+                        exit(0)
+                    }
+                    Err(e) => {
+                        eprintln!("\nConfiguration export ... FAIL\n{}", e);
+                        exit(1)
+                    }
+                }
+            }
+            GupaxxData::Import {
+                file,
+                yes,
+                programs,
+            } => import(&app, &file, yes, programs),
+            GupaxxData::Start { processes } => {
+                control(&app, args.profile, processes, ProcessAction::Start)
+            }
+            GupaxxData::Stop { processes } => {
+                control(&app, args.profile, processes, ProcessAction::Stop)
+            }
+            GupaxxData::Restart { processes } => {
+                control(&app, args.profile, processes, ProcessAction::Restart)
+            }
+            GupaxxData::Status { json } => {
+                let response = match send_control(&app.os_data_path, &ControlRequest::Status) {
+                    Ok(response) => response,
+                    Err(e) => unreachable_instance(e),
+                };
+                let running = response.is_some();
+                let processes = response.map(|r| r.processes).unwrap_or_default();
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::json!({ "running": running, "processes": processes })
+                    );
+                } else if running {
+                    for p in processes {
+                        println!(
+                            "{:<14} {:<14} {:<8} {}",
+                            p.name, p.display_name, p.state, p.signal
+                        );
+                    }
+                } else {
+                    eprintln!("Gupaxx is not running");
+                }
+                exit(if running { 0 } else { 1 })
+            }
+            GupaxxData::Xvb {
+                command: XvbCommand::Mode { mode },
+            } => {
+                let mode = XvbMode::from(mode);
+                let request = ControlRequest::XvbMode { mode: mode.clone() };
+                match send_control(&app.os_data_path, &request) {
+                    Ok(Some(response)) => exit_with(&response),
+                    // Not running, it will be used on the next start.
+                    Ok(None) => match State::get(&app.state_path).and_then(|mut state| {
+                        state.xvb.set_mode(mode.clone());
+                        state.save(&app.state_path)
+                    }) {
+                        Ok(()) => {
+                            println!("XvB mode set to [{}]", mode);
+                            exit(0)
+                        }
+                        Err(e) => {
+                            eprintln!("Could not save the state: {}", e);
+                            exit(1)
+                        }
+                    },
+                    Err(e) => unreachable_instance(e),
+                }
+            }
+            GupaxxData::Xvb {
+                command: XvbCommand::History,
+            } => {
+                let mut xvb_rounds = app.gupax_p2pool_api.lock().unwrap().xvb_rounds.clone();
+                // Nothing recorded yet without the file.
+                if xvb_rounds.path.exists() {
+                    if let Err(e) = xvb_rounds.read() {
+                        eprintln!("Could not read the XvB round history: {}", e);
+                        exit(1)
+                    }
+                }
+                print!("{}", xvb_rounds);
+                exit(0)
+            }
             GupaxxData::Nostartup => app.no_startup = true,
         }
     }
     app
 }
+
+// Sends [action] on [processes] to the running Gupaxx, after switching it to [profile].
+// Without one, [Start] runs them without GUI like [--daemon] would, the others fail.
+fn control(app: &App, profile: Option<String>, processes: Vec<String>, action: ProcessAction) -> ! {
+    let mut names = Vec::with_capacity(processes.len());
+    for process in &processes {
+        match parse_process_name(process) {
+            Ok(name) => names.push(name),
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(2)
+            }
+        }
+    }
+    if let Some(name) = &profile {
+        let args = vec!["--profile".to_string(), name.clone()];
+        match send_control(&app.os_data_path, &ControlRequest::Launch { args }) {
+            Ok(Some(response)) if !response.ok => exit_with(&response),
+            Ok(Some(response)) => print_response(&response),
+            Ok(None) => (),
+            Err(e) => unreachable_instance(e),
+        }
+    }
+    let mut ok = true;
+    for process in processes {
+        let request = match action {
+            ProcessAction::Start => ControlRequest::Start { process },
+            ProcessAction::Stop => ControlRequest::Stop { process },
+            ProcessAction::Restart => ControlRequest::Restart { process },
+        };
+        match send_control(&app.os_data_path, &request) {
+            Ok(Some(response)) => {
+                print_response(&response);
+                ok &= response.ok;
+            }
+            Ok(None) if action == ProcessAction::Start => {
+                println!("Gupaxx is not running, starting without GUI...");
+                crate::daemon::run_daemon(app.now, profile, Some(names))
+            }
+            Ok(None) => {
+                eprintln!("Gupaxx is not running");
+                exit(1)
+            }
+            Err(e) => unreachable_instance(e),
+        }
+    }
+    exit(if ok { 0 } else { 1 })
+}
+
+// Prints the changes of the archive at [file] and writes them if [apply],
+// the [program] ones only if [programs].
+fn import(app: &App, file: &Path, apply: bool, programs: bool) -> ! {
+    let import = match Import::read(file) {
+        Ok(import) => import,
+        Err(e) => {
+            eprintln!("Could not read [{}]: {}", file.display(), e);
+            exit(1)
+        }
+    };
+    let state = match State::get(&app.state_path) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Could not read the state: {}", e);
+            exit(1)
+        }
+    };
+    let nodes = Node::get(&app.node_path).unwrap_or_default();
+    let pools = Pool::get(&app.pool_path).unwrap_or_default();
+    let payouts = Payouts::read(&app.os_data_path).ok();
+    let changes = import.changes(&state, &nodes, &pools, payouts.as_ref());
+    println!(
+        "Archive of Gupaxx {} made {}",
+        import.manifest.gupaxx, import.manifest.created
+    );
+    if changes.is_empty() {
+        println!("Nothing to change");
+        exit(0)
+    }
+    for change in &changes {
+        println!("  {}", change);
+    }
+    if !programs && changes.iter().any(|c| c.starts_with(PROGRAM)) {
+        println!("The [program] changes are skipped, add --programs to apply them too");
+    }
+    if !apply {
+        println!(
+            "Run again with --yes to apply these {} changes",
+            changes.len()
+        );
+        exit(0)
+    }
+    // A running Gupaxx would write its own state over ours.
+    let lock = match InstanceLock::acquire(&app.os_data_path) {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("{}, close it or import from its [Gupaxx] tab", e);
+            exit(1)
+        }
+    };
+    let result = import.apply(&state, programs, &app.state_path, &app.os_data_path);
+    // [exit] skips destructors.
+    drop(lock);
+    match result {
+        Ok(_) => {
+            println!("\nConfiguration import ... OK");
+            exit(0)
+        }
+        Err(e) => {
+            eprintln!("\nConfiguration import ... FAIL\n{}", e);
+            exit(1)
+        }
+    }
+}
+
+// Another instance holds the lock, hand our arguments over to it.
+pub fn second_instance(os_data_path: &Path, e: LockError) -> ! {
+    warn!("{}, handing the arguments over...", e);
+    let args = std::env::args().skip(1).collect();
+    match send_control(os_data_path, &ControlRequest::Launch { args }) {
+        Ok(Some(response)) => exit_with(&response),
+        _ => {
+            eprintln!(
+                "{} with the data directory [{}] and could not be reached, close it first",
+                e,
+                os_data_path.display()
+            );
+            exit(1)
+        }
+    }
+}
+
+fn print_response(response: &ControlResponse) {
+    if response.ok {
+        println!("{}", response.message);
+    } else {
+        eprintln!("{}", response.message);
+    }
+}
+
+fn exit_with(response: &ControlResponse) -> ! {
+    print_response(response);
+    exit(if response.ok { 0 } else { 1 })
+}
+
+fn unreachable_instance(e: std::io::Error) -> ! {
+    eprintln!("Could not talk to the running Gupaxx: {}", e);
+    exit(1)
+}
@@ -15,10 +15,17 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::disk::archive::{self, Import, Payouts};
+use crate::disk::consts::{NODE_TOML, POOL_TOML};
+use crate::disk::diagnose::Bundle;
+use crate::disk::node::Node;
+use crate::disk::pool::Pool;
+use crate::helper::Helper;
 use crate::{disk::state::*, utils::macros::arc_mut};
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
+    path::Path,
     sync::{Arc, Mutex},
     thread,
 };
@@ -59,6 +66,25 @@ impl FileWindow {
     }
 }
 
+//---------------------------------------------------------------------------------------------------- ConfigWindow
+// Export/import of the configuration archive in the [Gupaxx] tab,
+// the file pickers are started in a new thread like [FileWindow].
+#[derive(Default)]
+pub struct ConfigWindow {
+    pub thread: bool,                          // Is there already a picker thread?
+    pub payouts: bool,                         // Export the payout history too?
+    pub import: Option<(Import, Vec<String>)>, // Archive read and its changes, waiting for [Apply]
+    pub programs: bool,                        // Let the import replace the programs to run?
+    pub apply: bool,                           // [Apply] clicked, the [App] imports
+    pub error: Option<String>,                 // Set by the thread, shown by the tab
+}
+
+impl ConfigWindow {
+    pub fn new() -> Arc<Mutex<Self>> {
+        arc_mut!(Self::default())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum FileType {
     P2pool,
@@ -149,4 +175,137 @@ impl Gupax {
             file_window.lock().unwrap().thread = false;
         });
     }
+
+    #[cold]
+    #[inline(never)]
+    // Copies the logs into the folder the user selects, and opens the copy.
+    pub fn spawn_export_logs_thread(logs_path: &Path) {
+        let logs_path = logs_path.to_path_buf();
+        thread::spawn(move || {
+            let Some(destination) = rfd::FileDialog::new()
+                .set_title("Select a directory to export the logs of Gupaxx to")
+                .pick_folder()
+            else {
+                info!("Gupaxx | No directory selected to export the logs");
+                return;
+            };
+            match crate::disk::logs::export(&logs_path, &destination) {
+                Ok(export) => {
+                    if let Err(e) = crate::miscs::open_folder(&export) {
+                        warn!("Gupaxx | Could not open [{}]: {}", export.display(), e);
+                    }
+                }
+                Err(e) => error!("Gupaxx | Could not export the logs: {}", e),
+            }
+        });
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Writes a diagnostics bundle into the folder the user selects, and opens it.
+    pub fn spawn_diagnose_thread(
+        og: &Arc<Mutex<State>>,
+        state_path: &Path,
+        helper: &Arc<Mutex<Helper>>,
+    ) {
+        let state = og.lock().unwrap().clone();
+        let os_data_path = state_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let helper = Arc::clone(helper);
+        thread::spawn(move || {
+            let Some(destination) = rfd::FileDialog::new()
+                .set_title("Select a directory to write the diagnostics bundle of Gupaxx to")
+                .pick_folder()
+            else {
+                info!("Gupaxx | No directory selected to write the diagnostics bundle");
+                return;
+            };
+            let mut bundle = Bundle::new(&state, &os_data_path);
+            bundle.add_helper(&helper.lock().unwrap());
+            match bundle.write(&destination) {
+                Ok(_) => {
+                    if let Err(e) = crate::miscs::open_folder(&destination) {
+                        warn!("Gupaxx | Could not open [{}]: {}", destination.display(), e);
+                    }
+                }
+                Err(e) => error!("Gupaxx | Could not write the diagnostics bundle: {}", e),
+            }
+        });
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Writes the configuration archive into the folder the user selects, and opens it.
+    // The caller sets [ConfigWindow.thread], the thread clears it.
+    pub fn spawn_export_config_thread(
+        og: &Arc<Mutex<State>>,
+        state_path: &Path,
+        config_window: &Arc<Mutex<ConfigWindow>>,
+        payouts: bool,
+    ) {
+        let state = og.lock().unwrap().clone();
+        let os_data_path = state_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let config_window = Arc::clone(config_window);
+        thread::spawn(move || {
+            let destination = rfd::FileDialog::new()
+                .set_title("Select a directory to export the configuration of Gupaxx to")
+                .pick_folder();
+            let mut window = config_window.lock().unwrap();
+            window.thread = false;
+            let Some(destination) = destination else {
+                info!("Gupaxx | No directory selected to export the configuration");
+                return;
+            };
+            match archive::export(&state, &os_data_path, payouts, &destination) {
+                Ok(_) => {
+                    if let Err(e) = crate::miscs::open_folder(&destination) {
+                        warn!("Gupaxx | Could not open [{}]: {}", destination.display(), e);
+                    }
+                }
+                Err(e) => window.error = Some(format!("Configuration export: {}", e)),
+            }
+        });
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Reads the configuration archive the user selects and what importing it changes,
+    // for the tab to show before it is applied.
+    pub fn spawn_import_config_thread(
+        og: &Arc<Mutex<State>>,
+        state_path: &Path,
+        config_window: &Arc<Mutex<ConfigWindow>>,
+    ) {
+        let state = og.lock().unwrap().clone();
+        let os_data_path = state_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let config_window = Arc::clone(config_window);
+        thread::spawn(move || {
+            let file = rfd::FileDialog::new()
+                .set_title("Select a configuration archive of Gupaxx to import")
+                .add_filter("Gupaxx configuration", &["gz"])
+                .pick_file();
+            let Some(file) = file else {
+                info!("Gupaxx | No configuration archive selected to import");
+                config_window.lock().unwrap().thread = false;
+                return;
+            };
+            let result = Import::read(&file).map(|import| {
+                let nodes = Node::get(&os_data_path.join(NODE_TOML)).unwrap_or_default();
+                let pools = Pool::get(&os_data_path.join(POOL_TOML)).unwrap_or_default();
+                let payouts = Payouts::read(&os_data_path).ok();
+                let changes = import.changes(&state, &nodes, &pools, payouts.as_ref());
+                (import, changes)
+            });
+            let mut window = config_window.lock().unwrap();
+            window.thread = false;
+            match result {
+                Ok(import) => {
+                    window.import = Some(import);
+                    window.programs = false;
+                }
+                Err(e) => {
+                    window.error = Some(format!("Could not read [{}]: {}", file.display(), e))
+                }
+            }
+        });
+    }
 }
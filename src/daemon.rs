@@ -0,0 +1,300 @@
+// Headless mode ([--daemon]).
+//
+// This runs the exact same [Helper] thread and watchdogs as the GUI,
+// but without [App] or any window. The settings are read once from
+// [state.toml], every process enabled for auto-start is started,
+// and then we idle until SIGTERM/SIGINT (Ctrl+C on Windows).
+// On shutdown, the processes are stopped cleanly before exiting.
+
+use crate::cli::second_instance;
+use crate::disk::consts::{NODE_TOML, PROCESSES_TOML, STATE_TOML};
+use crate::disk::get_gupax_data_path;
+use crate::disk::get_gupax_history_path;
+use crate::disk::get_gupax_logs_path;
+use crate::disk::get_gupax_p2pool_path;
+use crate::disk::get_gupax_profiles_path;
+use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use crate::disk::history::History;
+use crate::disk::lock::{InstanceLock, LockError};
+use crate::disk::node::Node;
+use crate::disk::processes::Processes;
+use crate::disk::profiles;
+use crate::disk::state::State;
+use crate::helper::control::{BackupHosts, ProcessAction};
+use crate::helper::custom::Custom;
+use crate::helper::node::PubNodeApi;
+use crate::helper::p2pool::{ImgP2pool, PubP2poolApi};
+use crate::helper::xrig::xmrig::{ImgXmrig, PubXmrigApi};
+use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
+use crate::helper::xvb::PubXvbApi;
+use crate::helper::{Helper, Process, ProcessName, Sys};
+use crate::inits::{init_auto_processes, init_sysinfo};
+use crate::utils::macros::{arc_mut, sleep};
+use crate::utils::sudo::SudoState;
+use log::{error, info, warn};
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+// How long we give the processes to exit after asking them to stop.
+const SHUTDOWN_TIMEOUT_SECS: u64 = 15;
+
+#[cold]
+#[inline(never)]
+// Entry point of [--daemon], never returns.
+// With [profile], that profile is switched to first ([--profile]).
+// With [only], those processes are started instead of the auto-start ones
+// ([gupaxx start] without a running instance).
+pub fn run_daemon(now: Instant, profile: Option<String>, only: Option<Vec<ProcessName>>) -> ! {
+    info!("Daemon | Starting Gupaxx without GUI...");
+
+    // Data paths.
+    let os_data_path = match get_gupax_data_path() {
+        Ok(dir) => dir,
+        Err(e) => {
+            error!("Daemon | get_os_data_path(): {}", e);
+            exit(1);
+        }
+    };
+    let lock = match InstanceLock::acquire(&os_data_path) {
+        Ok(lock) => Some(lock),
+        Err(LockError::Io(e)) => {
+            warn!("Daemon | Could not lock the data directory: {}", e);
+            None
+        }
+        Err(e) => second_instance(&os_data_path, e),
+    };
+    let mut state_path = os_data_path.clone();
+    state_path.push(STATE_TOML);
+    let mut node_path = os_data_path.clone();
+    node_path.push(NODE_TOML);
+    let mut processes_path = os_data_path.clone();
+    processes_path.push(PROCESSES_TOML);
+    let gupax_p2pool_api_path = get_gupax_p2pool_path(&os_data_path);
+    let history_path = get_gupax_history_path(&os_data_path);
+
+    // Disk state. Contrary to the GUI, there is no one to show an error
+    // screen to, so anything unreadable is fatal here.
+    if let Some(name) = profile {
+        info!("Daemon | Switching to profile [{}]...", name);
+        if let Err(e) =
+            profiles::activate(&get_gupax_profiles_path(&os_data_path), &name, &state_path)
+        {
+            error!("Daemon | Profile [{}]: {}", name, e);
+            exit(1);
+        }
+    }
+    info!("Daemon | Reading disk state...");
+    let mut state = match State::get(&state_path) {
+        Ok(state) => state,
+        Err(e) => {
+            error!("Daemon | State file [{}]: {}", state_path.display(), e);
+            exit(1);
+        }
+    };
+    if let Err(e) = state.update_absolute_path() {
+        error!("Daemon | Could not get the absolute paths: {}", e);
+        exit(1);
+    }
+    let node_vec = match Node::get(&node_path) {
+        Ok(vec) => vec,
+        Err(e) => {
+            error!("Daemon | Node list [{}]: {}", node_path.display(), e);
+            exit(1);
+        }
+    };
+    let custom = match Processes::get(&processes_path) {
+        Ok(vec) => Custom::from_configs(vec),
+        Err(e) => {
+            error!(
+                "Daemon | Process list [{}]: {}",
+                processes_path.display(),
+                e
+            );
+            exit(1);
+        }
+    };
+    let max_threads = benri::threads!();
+    if state.xmrig.current_threads > max_threads {
+        warn!(
+            "Daemon | XMRig threads [{}] > max threads [{}], clamping",
+            state.xmrig.current_threads, max_threads
+        );
+        state.xmrig.current_threads = max_threads;
+    }
+
+    // Gupax-P2Pool API, so new payouts are added to the existing history.
+    let gupax_p2pool_api = arc_mut!(GupaxP2poolApi::new());
+    gupax_p2pool_api
+        .lock()
+        .unwrap()
+        .fill_paths(&gupax_p2pool_api_path);
+    if let Err(e) = GupaxP2poolApi::create_all_files(&gupax_p2pool_api_path) {
+        error!("Daemon | GupaxP2poolApi: {}", e);
+        exit(1);
+    }
+    if let Err(e) = gupax_p2pool_api.lock().unwrap().read_all_files_and_update() {
+        error!("Daemon | GupaxP2poolApi: {}", e);
+        exit(1);
+    }
+
+    // Stats history, the GUI will show what was recorded while headless.
+    let history = arc_mut!(History::new());
+    history.lock().unwrap().fill_paths(&history_path);
+    if let Err(e) = History::create_all_files(&history_path)
+        .and_then(|_| history.lock().unwrap().read_all_files_and_update())
+    {
+        warn!("Daemon | History: {}", e);
+    }
+
+    // The [Helper] and everything it needs, the GUI would usually own half of these.
+    let new_process = |name| arc_mut!(Process::new(name, String::new(), PathBuf::new()));
+    let xvb_api = arc_mut!(PubXvbApi::new());
+    let helper = arc_mut!(Helper::new(
+        now,
+        arc_mut!(Sys::new()),
+        new_process(ProcessName::P2pool),
+        new_process(ProcessName::Xmrig),
+        new_process(ProcessName::XmrigProxy),
+        new_process(ProcessName::Xvb),
+        new_process(ProcessName::Node),
+        arc_mut!(PubP2poolApi::new()),
+        arc_mut!(PubXmrigApi::new()),
+        Arc::clone(&xvb_api),
+        arc_mut!(PubXmrigProxyApi::new()),
+        arc_mut!(PubNodeApi::new()),
+        arc_mut!(ImgP2pool::new()),
+        arc_mut!(ImgXmrig::new()),
+        gupax_p2pool_api,
+        history,
+    ));
+    {
+        let mut helper = helper.lock().unwrap();
+        helper.custom = custom;
+        helper.dependencies = state.gupax.dependencies;
+        helper.notifier.set_policy(&state.gupax.notifications);
+        helper.logs_path = get_gupax_logs_path(&os_data_path);
+        helper.logs = state.gupax.logs;
+    }
+
+    // Same as the GUI, the saved XvB mode becomes the runtime mode.
+    xvb_api.lock().unwrap().set_runtime_mode(&state.xvb);
+
+    info!("Daemon | Spawning helper thread...");
+    let (sysinfo, pid) = init_sysinfo();
+    Helper::spawn_helper(&helper, sysinfo, pid, max_threads);
+
+    // There is no ping data without the GUI, so only
+    // the manual node list can be used as backup hosts.
+    let backup_hosts: BackupHosts = Arc::new(move |p2pool| {
+        if p2pool.backup_host && !p2pool.simple {
+            Some(node_vec.iter().map(|(_, node)| node.clone()).collect())
+        } else {
+            None
+        }
+    });
+    let sudo = arc_mut!(SudoState::new());
+    let saved = arc_mut!(state.clone());
+    Helper::spawn_http_api(&helper, &saved, &sudo, Arc::clone(&backup_hosts));
+    Helper::spawn_control_socket(
+        &helper,
+        &saved,
+        &state_path,
+        &os_data_path,
+        &sudo,
+        Arc::clone(&backup_hosts),
+        false,
+    );
+    match only {
+        Some(names) => {
+            for name in names {
+                if let Err(e) = Helper::process_action(
+                    &helper,
+                    &state,
+                    &sudo,
+                    || backup_hosts(&state.p2pool),
+                    name,
+                    ProcessAction::Start,
+                    "Daemon",
+                ) {
+                    error!("Daemon | {}", e);
+                }
+            }
+        }
+        None => init_auto_processes(&helper, &state, || backup_hosts(&state.p2pool), &sudo, None),
+    }
+    info!("/*************************************/ Daemon ... OK /*************************************/");
+
+    wait_for_shutdown_signal();
+    info!("Daemon | Shutdown signal caught, stopping processes...");
+    stop_all(&helper);
+    drop(lock);
+    info!("Daemon | Goodbye!");
+    exit(0);
+}
+
+#[tokio::main(flavor = "current_thread")]
+// Blocks until SIGTERM or SIGINT is received.
+async fn wait_for_shutdown_signal() {
+    #[cfg(target_family = "unix")]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = match signal(SignalKind::terminate()) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Daemon | Could not listen for SIGTERM: {}", e);
+                exit(1);
+            }
+        };
+        tokio::select! {
+            _ = sigterm.recv() => info!("Daemon | SIGTERM received"),
+            _ = tokio::signal::ctrl_c() => info!("Daemon | SIGINT received"),
+        }
+    }
+    #[cfg(target_os = "windows")]
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        error!("Daemon | Could not listen for Ctrl+C: {}", e);
+        exit(1);
+    }
+}
+
+// Sends [Stop] to every alive process, then waits (a bounded amount of time) for them to exit.
+fn stop_all(helper: &Arc<Mutex<Helper>>) {
+    let lock = helper.lock().unwrap();
+    let processes: Vec<_> = [
+        Arc::clone(&lock.xvb),
+        Arc::clone(&lock.xmrig_proxy),
+        Arc::clone(&lock.xmrig),
+        Arc::clone(&lock.p2pool),
+        Arc::clone(&lock.node),
+    ]
+    .into_iter()
+    .chain(lock.custom.iter().map(|c| Arc::clone(&c.process)))
+    .collect();
+    drop(lock);
+    for process in processes.iter() {
+        let name = process.lock().unwrap().name;
+        if !process.lock().unwrap().is_alive() {
+            continue;
+        }
+        match name {
+            ProcessName::Xvb => Helper::stop_xvb(helper),
+            ProcessName::XmrigProxy => Helper::stop_xp(helper),
+            ProcessName::Xmrig => Helper::stop_xmrig(helper),
+            ProcessName::P2pool => Helper::stop_p2pool(helper),
+            ProcessName::Node => Helper::stop_node(helper),
+            ProcessName::Custom(i) => Helper::stop_custom(helper, i),
+        }
+    }
+    for _ in 0..SHUTDOWN_TIMEOUT_SECS {
+        if processes.iter().all(|p| !p.lock().unwrap().is_alive()) {
+            return;
+        }
+        sleep!(1000);
+    }
+    warn!(
+        "Daemon | Processes still alive after {} seconds, exiting anyway",
+        SHUTDOWN_TIMEOUT_SECS
+    );
+}
@@ -0,0 +1,450 @@
+use crate::constants::GUPAX_VERSION;
+use crate::disk::consts::{
+    GUPAX_P2POOL_API_FILE_ARRAY, GUPAX_P2POOL_API_LOG, GUPAX_P2POOL_API_PAYOUT,
+    GUPAX_P2POOL_API_XMR, NODE_TOML, POOL_TOML, STATE_TOML,
+};
+use crate::disk::errors::TomlError;
+use crate::disk::node::Node;
+use crate::disk::pool::Pool;
+use crate::disk::state::State;
+use crate::disk::{create_gupax_p2pool_dir, create_private, get_gupax_p2pool_path};
+use chrono::{Local, SecondsFormat};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+//---------------------------------------------------------------------------------------------------- Configuration archive
+// A [gupaxx-config-<time>.tar.gz] with what is needed to set up Gupaxx on another rig:
+//
+// gupaxx-config-2024-01-01_00-00-00/
+// ├─ manifest.toml    // Format of the archive, version of Gupaxx that wrote it
+// ├─ state.toml
+// ├─ node.toml
+// ├─ pool.toml
+// ├─ p2pool/          // log, payout, xmr, only if the payout history was included
+//
+// Unlike the diagnose bundle nothing is redacted, the archive holds the wallet
+// addresses and tokens. Importing merges [state.toml] with the defaults, so an
+// archive from an older Gupaxx still loads, and lists what changes before writing.
+
+// Bumped when the layout of the archive changes, an archive
+// of a newer format than this is refused.
+pub const ARCHIVE_FORMAT: u32 = 1;
+const MANIFEST: &str = "manifest.toml";
+const P2POOL_DIRECTORY: &str = "p2pool";
+// Shown instead of the values of secrets in the preview.
+const HIDDEN: &str = "<hidden>";
+// Marks the changes of [PROGRAMS] in the preview.
+pub const PROGRAM: &str = "[program] ";
+// Settings naming what Gupaxx runs, kept as they are unless the user allows
+// the archive to replace them.
+const PROGRAMS: [&str; 6] = [
+    "gupax.p2pool_path",
+    "gupax.node_path",
+    "gupax.xmrig_path",
+    "gupax.xmrig_proxy_path",
+    "gupax.notifications.command",
+    "gupax.notifications.command_line",
+];
+// An archive holds a handful of files, anything past this is not one of ours.
+const MAX_ENTRIES: usize = 64;
+// The payout log is the largest, a line per payout.
+const MAX_ENTRY_SIZE: u64 = 32 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub gupaxx: String,
+    pub created: String,
+    pub payouts: bool,
+}
+
+// The Gupax-P2Pool API files, as they are on disk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payouts {
+    pub log: String,
+    pub payout: u64,
+    pub xmr: u64,
+}
+
+impl Payouts {
+    // Missing files read as what [GupaxP2poolApi::create_all_files] would create.
+    pub fn read(os_data_path: &Path) -> Result<Self, TomlError> {
+        let dir = get_gupax_p2pool_path(os_data_path);
+        let [log, payout, xmr] =
+            GUPAX_P2POOL_API_FILE_ARRAY.map(|file| match fs::read_to_string(dir.join(file)) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    Ok(if file == GUPAX_P2POOL_API_LOG {
+                        ""
+                    } else {
+                        "0"
+                    }
+                    .to_string())
+                }
+                result => result,
+            });
+        Self::parse(log?, &payout?, &xmr?)
+    }
+
+    fn parse(log: String, payout: &str, xmr: &str) -> Result<Self, TomlError> {
+        Ok(Self {
+            log,
+            payout: payout
+                .trim()
+                .parse()
+                .map_err(|_| TomlError::Parse(GUPAX_P2POOL_API_PAYOUT))?,
+            xmr: xmr
+                .trim()
+                .parse()
+                .map_err(|_| TomlError::Parse(GUPAX_P2POOL_API_XMR))?,
+        })
+    }
+
+    fn write(&self, os_data_path: &Path) -> Result<(), TomlError> {
+        let dir = get_gupax_p2pool_path(os_data_path);
+        create_gupax_p2pool_dir(&dir)?;
+        fs::write(dir.join(GUPAX_P2POOL_API_LOG), &self.log)?;
+        fs::write(
+            dir.join(GUPAX_P2POOL_API_PAYOUT),
+            format!("{}\n", self.payout),
+        )?;
+        fs::write(dir.join(GUPAX_P2POOL_API_XMR), format!("{}\n", self.xmr))?;
+        Ok(())
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Export
+// Writes [state], the node/pool lists of [os_data_path] and if [payouts]
+// the payout history in [destination], returns the path of the archive.
+pub fn export(
+    state: &State,
+    os_data_path: &Path,
+    payouts: bool,
+    destination: &Path,
+) -> Result<PathBuf, TomlError> {
+    let now = Local::now();
+    let name = format!("gupaxx-config-{}", now.format("%Y-%m-%d_%H-%M-%S"));
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT,
+        gupaxx: GUPAX_VERSION.to_string(),
+        created: now.to_rfc3339_opts(SecondsFormat::Secs, false),
+        payouts,
+    };
+    let mut entries = vec![
+        (
+            MANIFEST.to_string(),
+            toml::ser::to_string(&manifest).map_err(TomlError::Serialize)?,
+        ),
+        (STATE_TOML.to_string(), state.to_string()?),
+    ];
+    // Lists that were never saved are the defaults, which the import creates anyway.
+    for file in [NODE_TOML, POOL_TOML] {
+        match fs::read_to_string(os_data_path.join(file)) {
+            Ok(content) => entries.push((file.to_string(), content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if payouts {
+        let payouts = Payouts::read(os_data_path)?;
+        for (file, content) in [
+            (GUPAX_P2POOL_API_LOG, payouts.log),
+            (GUPAX_P2POOL_API_PAYOUT, format!("{}\n", payouts.payout)),
+            (GUPAX_P2POOL_API_XMR, format!("{}\n", payouts.xmr)),
+        ] {
+            entries.push((format!("{}/{}", P2POOL_DIRECTORY, file), content));
+        }
+    }
+
+    let path = destination.join(format!("{}.tar.gz", name));
+    // The archive itself as well, the headers only matter once extracted.
+    let file = create_private(&path)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = now.timestamp().max(0) as u64;
+    for (file, content) in &entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        // Holds secrets.
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        header.set_cksum();
+        tar.append_data(
+            &mut header,
+            format!("{}/{}", name, file),
+            content.as_bytes(),
+        )?;
+    }
+    tar.into_inner()?.finish()?;
+    info!("Archive | Configuration exported to [{}]", path.display());
+    Ok(path)
+}
+
+//---------------------------------------------------------------------------------------------------- Import
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub manifest: Manifest,
+    pub state: State,
+    pub nodes: Option<Vec<(String, Node)>>,
+    pub pools: Option<Vec<(String, Pool)>>,
+    pub payouts: Option<Payouts>,
+}
+
+impl Import {
+    // Reads and checks the archive at [path], nothing is written yet.
+    pub fn read(path: &Path) -> Result<Self, TomlError> {
+        let mut files = HashMap::new();
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+        for (i, entry) in archive.entries()?.enumerate() {
+            if i == MAX_ENTRIES {
+                return Err(TomlError::Path(format!(
+                    "[{}] has more than {} entries",
+                    path.display(),
+                    MAX_ENTRIES
+                )));
+            }
+            let mut entry = entry?;
+            // Entries are under the directory named after the archive, which may have been renamed.
+            let name = {
+                let path = entry.path()?;
+                let mut components = path.components();
+                components.next();
+                components.as_path().to_string_lossy().replace('\\', "/")
+            };
+            if !entry.header().entry_type().is_file() || !known(&name) {
+                continue;
+            }
+            let mut content = String::new();
+            entry
+                .by_ref()
+                .take(MAX_ENTRY_SIZE + 1)
+                .read_to_string(&mut content)?;
+            if content.len() as u64 > MAX_ENTRY_SIZE {
+                return Err(TomlError::Path(format!(
+                    "[{}] in the archive is larger than {} MiB",
+                    name,
+                    MAX_ENTRY_SIZE / 1024 / 1024
+                )));
+            }
+            files.insert(name, content);
+        }
+
+        let Some(manifest) = files.get(MANIFEST) else {
+            return Err(TomlError::Path(format!(
+                "[{}] is not a Gupaxx configuration archive",
+                path.display()
+            )));
+        };
+        let manifest: Manifest = toml::de::from_str(manifest).map_err(TomlError::Deserialize)?;
+        if manifest.format > ARCHIVE_FORMAT {
+            return Err(TomlError::Path(format!(
+                "The archive was made by Gupaxx {}, update Gupaxx to import it",
+                manifest.gupaxx
+            )));
+        }
+        let Some(state) = files.get(STATE_TOML) else {
+            return Err(TomlError::Parse("archive state"));
+        };
+        let state = State::merge(state)?;
+        let nodes = files
+            .get(NODE_TOML)
+            .map(|s| Node::from_str_to_vec(s))
+            .transpose()?;
+        let pools = files
+            .get(POOL_TOML)
+            .map(|s| Pool::from_str_to_vec(s))
+            .transpose()?;
+        let p2pool = GUPAX_P2POOL_API_FILE_ARRAY
+            .map(|file| files.remove(&format!("{}/{}", P2POOL_DIRECTORY, file)));
+        let payouts = match p2pool {
+            [Some(log), Some(payout), Some(xmr)] => Some(Payouts::parse(log, &payout, &xmr)?),
+            [None, None, None] => None,
+            _ => return Err(TomlError::Parse("archive payout history")),
+        };
+        info!(
+            "Archive | Read [{}] from Gupaxx {} ... OK",
+            path.display(),
+            manifest.gupaxx
+        );
+        Ok(Self {
+            manifest,
+            state,
+            nodes,
+            pools,
+            payouts,
+        })
+    }
+
+    // The state that importing makes out of [current]: the imported one,
+    // staying on the profile in use and unless [programs] on the binaries
+    // and notification command in use.
+    pub fn state(&self, current: &State, programs: bool) -> State {
+        let mut state = self.state.clone();
+        state.version = std::sync::Arc::clone(&current.version);
+        state.gupax.profile.clone_from(&current.gupax.profile);
+        if !programs {
+            let (gupax, old) = (&mut state.gupax, &current.gupax);
+            gupax.p2pool_path.clone_from(&old.p2pool_path);
+            gupax.node_path.clone_from(&old.node_path);
+            gupax.xmrig_path.clone_from(&old.xmrig_path);
+            gupax.xmrig_proxy_path.clone_from(&old.xmrig_proxy_path);
+            gupax.notifications.command = old.notifications.command;
+            gupax
+                .notifications
+                .command_line
+                .clone_from(&old.notifications.command_line);
+        }
+        state
+    }
+
+    // One line per setting, node, pool or history that importing changes,
+    // the programs to run included and marked as such.
+    pub fn changes(
+        &self,
+        state: &State,
+        nodes: &[(String, Node)],
+        pools: &[(String, Pool)],
+        payouts: Option<&Payouts>,
+    ) -> Vec<String> {
+        let mut changes = vec![];
+        match (
+            toml::Value::try_from(state),
+            toml::Value::try_from(self.state(state, true)),
+        ) {
+            (Ok(old), Ok(new)) => {
+                let (old, new) = (flatten(old), flatten(new));
+                for (key, value) in &new {
+                    // Recomputed from the relative paths when saved.
+                    if key.starts_with("gupax.absolute_") {
+                        continue;
+                    }
+                    match old.get(key) {
+                        Some(old) if old == value => (),
+                        old => changes.push(format!(
+                            "{}{}: {} -> {}",
+                            if PROGRAMS.contains(&key.as_str()) {
+                                PROGRAM
+                            } else {
+                                ""
+                            },
+                            key,
+                            show(key, old.map(String::as_str).unwrap_or("none")),
+                            show(key, value)
+                        )),
+                    }
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => changes.push(format!("state: {}", e)),
+        }
+        if let Some(new) = &self.nodes {
+            list_changes(NODE_TOML, nodes, new, &mut changes);
+        }
+        if let Some(new) = &self.pools {
+            list_changes(POOL_TOML, pools, new, &mut changes);
+        }
+        if let Some(new) = &self.payouts {
+            if payouts != Some(new) {
+                changes.push(format!(
+                    "payout history: {} payouts -> {} payouts",
+                    payouts.map_or(0, |p| p.payout),
+                    new.payout
+                ));
+            }
+        }
+        changes
+    }
+
+    // Writes the import over the files of [os_data_path], returns the state in use now.
+    pub fn apply(
+        &self,
+        current: &State,
+        programs: bool,
+        state_path: &Path,
+        os_data_path: &Path,
+    ) -> Result<State, TomlError> {
+        let mut state = self.state(current, programs);
+        state.save(state_path)?;
+        if let Some(nodes) = &self.nodes {
+            Node::save(nodes, &os_data_path.join(NODE_TOML))?;
+        }
+        if let Some(pools) = &self.pools {
+            Pool::save(pools, &os_data_path.join(POOL_TOML))?;
+        }
+        if let Some(payouts) = &self.payouts {
+            payouts.write(os_data_path)?;
+        }
+        info!("Archive | Import ... OK");
+        Ok(state)
+    }
+}
+
+// The files [export] writes, relative to the directory of the archive.
+fn known(name: &str) -> bool {
+    [MANIFEST, STATE_TOML, NODE_TOML, POOL_TOML].contains(&name)
+        || name
+            .strip_prefix(P2POOL_DIRECTORY)
+            .and_then(|file| file.strip_prefix('/'))
+            .is_some_and(|file| GUPAX_P2POOL_API_FILE_ARRAY.contains(&file))
+}
+
+// [section.key] -> value of every setting of a state.
+fn flatten(value: toml::Value) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, value: toml::Value, map: &mut BTreeMap<String, String>) {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, value) in table {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&key, value, map);
+                }
+            }
+            toml::Value::String(s) => {
+                map.insert(prefix.to_string(), format!("\"{}\"", s));
+            }
+            value => {
+                map.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+    let mut map = BTreeMap::new();
+    walk("", value, &mut map);
+    map
+}
+
+fn show<'a>(key: &str, value: &'a str) -> &'a str {
+    let secret = ["token", "password", "webhook_url", "extra_pool_user"]
+        .iter()
+        .any(|suffix| key.ends_with(suffix));
+    if secret && value != "\"\"" {
+        HIDDEN
+    } else {
+        value
+    }
+}
+
+fn list_changes<T: PartialEq>(
+    file: &str,
+    old: &[(String, T)],
+    new: &[(String, T)],
+    changes: &mut Vec<String>,
+) {
+    for (name, value) in new {
+        match old.iter().find(|(n, _)| n == name) {
+            None => changes.push(format!("{}: add [{}]", file, name)),
+            Some((_, old)) if old != value => changes.push(format!("{}: change [{}]", file, name)),
+            _ => (),
+        }
+    }
+    for (name, _) in old {
+        if !new.iter().any(|(n, _)| n == name) {
+            changes.push(format!("{}: remove [{}]", file, name));
+        }
+    }
+}
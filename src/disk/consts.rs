@@ -14,6 +14,8 @@ pub const DIRECTORY: &str = "gupaxx/";
 pub const STATE_TOML: &str = "state.toml";
 pub const NODE_TOML: &str = "node.toml";
 pub const POOL_TOML: &str = "pool.toml";
+pub const PROCESSES_TOML: &str = "processes.toml";
+pub const LOCK_FILE: &str = "gupaxx.lock";
 
 // P2Pool API
 // Lives within the Gupax OS data directory.
@@ -21,6 +23,7 @@ pub const POOL_TOML: &str = "pool.toml";
 // ├─ payout_log  // Raw log lines of payouts received
 // ├─ payout      // Single [u64] representing total payouts
 // ├─ xmr         // Single [u64] representing total XMR mined in atomic units
+// ├─ xvb         // One line per XvB raffle, see [crate::disk::xvb_rounds]
 #[cfg(target_os = "windows")]
 pub const GUPAX_P2POOL_API_DIRECTORY: &str = r"p2pool\";
 #[cfg(target_family = "unix")]
@@ -28,12 +31,47 @@ pub const GUPAX_P2POOL_API_DIRECTORY: &str = "p2pool/";
 pub const GUPAX_P2POOL_API_LOG: &str = "log";
 pub const GUPAX_P2POOL_API_PAYOUT: &str = "payout";
 pub const GUPAX_P2POOL_API_XMR: &str = "xmr";
+pub const GUPAX_P2POOL_API_XVB: &str = "xvb";
 pub const GUPAX_P2POOL_API_FILE_ARRAY: [&str; 3] = [
     GUPAX_P2POOL_API_LOG,
     GUPAX_P2POOL_API_PAYOUT,
     GUPAX_P2POOL_API_XMR,
 ];
 
+// Stats history
+// Lives within the Gupax OS data directory.
+// ~/.local/share/gupaxx/history/
+// ├─ minutes.csv // Averaged stats per minute, last day
+// ├─ hours.csv   // Averaged stats per hour, last month
+#[cfg(target_os = "windows")]
+pub const HISTORY_DIRECTORY: &str = r"history\";
+#[cfg(target_family = "unix")]
+pub const HISTORY_DIRECTORY: &str = "history/";
+pub const HISTORY_MINUTES: &str = "minutes.csv";
+pub const HISTORY_HOURS: &str = "hours.csv";
+
+// Process logs
+// Lives within the Gupax OS data directory.
+// ~/.local/share/gupaxx/logs/
+// ├─ p2pool.log                     // What P2Pool is printing right now
+// ├─ p2pool.2024-01-01_00-00-00.log // Rotated once it reached the maximum size
+// ├─ xmrig.log
+// ...
+#[cfg(target_os = "windows")]
+pub const LOGS_DIRECTORY: &str = r"logs\";
+#[cfg(target_family = "unix")]
+pub const LOGS_DIRECTORY: &str = "logs/";
+
+// State profiles
+// Lives within the Gupax OS data directory.
+// ~/.local/share/gupaxx/profiles/
+// ├─ home.toml // A whole [state.toml], saved under a name
+// ...
+#[cfg(target_os = "windows")]
+pub const PROFILES_DIRECTORY: &str = r"profiles\";
+#[cfg(target_family = "unix")]
+pub const PROFILES_DIRECTORY: &str = "profiles/";
+
 #[cfg(target_os = "windows")]
 pub const DEFAULT_P2POOL_PATH: &str = r"P2Pool\p2pool.exe";
 #[cfg(target_os = "macos")]
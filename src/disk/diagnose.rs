@@ -0,0 +1,486 @@
+use crate::constants::{
+    GUPAX_VERSION, NODE_VERSION, P2POOL_VERSION, XMRIG_PROXY_VERSION, XMRIG_VERSION,
+};
+use crate::disk::consts::{
+    GUPAX_P2POOL_API_FILE_ARRAY, NODE_TOML, POOL_TOML, PROCESSES_TOML, STATE_TOML,
+};
+use crate::disk::processes::Processes;
+use crate::disk::state::State;
+use crate::disk::{get_gupax_logs_path, get_gupax_p2pool_path};
+use crate::helper::Helper;
+use chrono::Local;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+//---------------------------------------------------------------------------------------------------- Diagnose
+// A [gupaxx-diagnose-<time>.tar.gz] with what is needed to look into a problem:
+//
+// gupaxx-diagnose-2024-01-01_00-00-00/
+// ├─ state.toml       // Redacted
+// ├─ node.toml
+// ├─ pool.toml
+// ├─ processes.toml   // Without the [env] values and [args]
+// ├─ p2pool/          // log, payout, xmr
+// ├─ versions.txt     // Gupaxx and the binaries set in the state
+// ├─ system.txt
+// ├─ logs/            // The end of the current log of every process
+// ├─ api/             // [Pub*Api] snapshots and [Sys], if Gupaxx is running
+// ├─ consoles/        // Console output, if made from the GUI
+//
+// Every text put in the bundle goes through the redaction: the wallet addresses
+// are reduced to their head and tail, tokens and passwords are removed.
+
+// How much of the end of each log is kept.
+const LOG_TAIL_BYTES: u64 = 1_000_000;
+// How long a binary gets to print its version.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+const REDACTED: &str = "<redacted>";
+
+pub struct Bundle {
+    name: String,
+    entries: Vec<(String, String)>,
+    // Secret, what replaces it.
+    redact: Vec<(String, String)>,
+}
+
+impl Bundle {
+    // Starts a bundle with the files of the Gupaxx data directory,
+    // the versions and the system info.
+    pub fn new(state: &State, os_data_path: &Path) -> Self {
+        let mut bundle = Self {
+            name: format!(
+                "gupaxx-diagnose-{}",
+                Local::now().format("%Y-%m-%d_%H-%M-%S")
+            ),
+            entries: vec![],
+            redact: redactions(state),
+        };
+        match toml::ser::to_string(&redacted_state(state)) {
+            Ok(toml) => bundle.add(STATE_TOML, toml),
+            Err(e) => bundle.add(STATE_TOML, format!("Could not serialize the state: {}", e)),
+        }
+        for file in [NODE_TOML, POOL_TOML] {
+            bundle.add_file(file, &os_data_path.join(file), None);
+        }
+        let processes = redacted_processes(&os_data_path.join(PROCESSES_TOML));
+        bundle.add(PROCESSES_TOML, processes);
+        let p2pool = get_gupax_p2pool_path(os_data_path);
+        for file in GUPAX_P2POOL_API_FILE_ARRAY {
+            bundle.add_file(&format!("p2pool/{}", file), &p2pool.join(file), None);
+        }
+        bundle.add("versions.txt", versions(state));
+        bundle.add("system.txt", system());
+        bundle.add_logs(&get_gupax_logs_path(os_data_path));
+        bundle
+    }
+
+    pub fn add(&mut self, name: &str, content: String) {
+        let content = self.redact(content);
+        self.entries.push((name.to_string(), content));
+    }
+
+    // A missing file is noted instead, [tail] keeps only the end of it.
+    pub fn add_file(&mut self, name: &str, path: &Path, tail: Option<u64>) {
+        let content = match read_tail(path, tail.unwrap_or(u64::MAX)) {
+            Ok(content) => content,
+            Err(e) => format!("Could not read [{}]: {}\n", path.display(), e),
+        };
+        self.add(name, content);
+    }
+
+    // The current log of every process, not the rotated ones.
+    fn add_logs(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut logs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|file| file.to_str())
+                    .and_then(|file| file.strip_suffix(".log"))
+                    .is_some_and(|name| !name.contains('.'))
+            })
+            .collect();
+        logs.sort();
+        for log in logs {
+            if let Some(file) = log.file_name().and_then(|file| file.to_str()) {
+                let name = format!("logs/{}", file);
+                self.add_file(&name, &log, Some(LOG_TAIL_BYTES));
+            }
+        }
+    }
+
+    // The stats and consoles of the processes of this Gupaxx, used from the GUI.
+    pub fn add_helper(&mut self, helper: &Helper) {
+        let snapshot = helper.snapshot.borrow().with_uptimes();
+        let mut processes = String::new();
+        for (name, alive, restarts) in &snapshot.processes {
+            let _ = writeln!(
+                processes,
+                "{}: alive={}, restarts={}",
+                name.id(),
+                alive,
+                restarts
+            );
+        }
+        self.add("api/processes.txt", processes);
+        self.add_json("api/sys.json", &*helper.pub_sys.lock().unwrap());
+        self.add_json("api/node.json", &snapshot.node);
+        self.add_json("api/p2pool.json", &snapshot.p2pool);
+        self.add_json("api/xmrig.json", &snapshot.xmrig);
+        self.add_json("api/xmrig_proxy.json", &snapshot.xmrig_proxy);
+        self.add_json("api/xvb.json", &snapshot.xvb);
+        let mut consoles = vec![
+            (
+                "node".to_string(),
+                helper.gui_api_node.lock().unwrap().output.to_string(),
+            ),
+            (
+                "p2pool".to_string(),
+                helper.gui_api_p2pool.lock().unwrap().output.to_string(),
+            ),
+            (
+                "xmrig".to_string(),
+                helper.gui_api_xmrig.lock().unwrap().output.to_string(),
+            ),
+            (
+                "xmrig_proxy".to_string(),
+                helper.gui_api_xp.lock().unwrap().output.to_string(),
+            ),
+            (
+                "xvb".to_string(),
+                helper.gui_api_xvb.lock().unwrap().output.to_string(),
+            ),
+        ];
+        for (index, custom) in helper.custom.iter().enumerate() {
+            let name: String = custom
+                .config
+                .name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+            let output = custom.gui_api.lock().unwrap().output.to_string();
+            consoles.push((format!("custom-{}-{}", index + 1, name), output));
+        }
+        for (name, output) in consoles {
+            self.add(&format!("consoles/{}.txt", name), output);
+        }
+    }
+
+    fn add_json<T: serde::Serialize>(&mut self, name: &str, value: &T) {
+        match serde_json::to_string_pretty(value) {
+            Ok(json) => self.add(name, json),
+            Err(e) => self.add(name, format!("Could not serialize: {}", e)),
+        }
+    }
+
+    // Asks the HTTP API of a running Gupaxx for its snapshots, used from the CLI
+    // where the processes are not ours.
+    pub fn add_http_api(&mut self, state: &State) {
+        if !state.gupax.http_api {
+            self.add(
+                "api/README.txt",
+                "The HTTP API is disabled, no snapshot of a running Gupaxx.\n".to_string(),
+            );
+            return;
+        }
+        // Listening on every interface includes the loopback.
+        let ip = match state.gupax.http_api_ip.as_str() {
+            "0.0.0.0" => "127.0.0.1",
+            ip => ip,
+        };
+        let url = format!("http://{}:{}", ip, state.gupax.http_api_port);
+        for (name, body) in fetch_http_api(&url, &state.gupax.http_api_token) {
+            self.add(&format!("api/{}.json", name), body);
+        }
+    }
+
+    fn redact(&self, mut content: String) -> String {
+        for (secret, replacement) in &self.redact {
+            if content.contains(secret.as_str()) {
+                content = content.replace(secret.as_str(), replacement);
+            }
+        }
+        content
+    }
+
+    // Writes [gupaxx-diagnose-<time>.tar.gz] in [destination], returns it.
+    pub fn write(&self, destination: &Path) -> std::io::Result<PathBuf> {
+        let path = destination.join(format!("{}.tar.gz", self.name));
+        let mut tar =
+            tar::Builder::new(GzEncoder::new(File::create(&path)?, Compression::default()));
+        let mtime = Local::now().timestamp().max(0) as u64;
+        for (name, content) in &self.entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            tar.append_data(
+                &mut header,
+                format!("{}/{}", self.name, name),
+                content.as_bytes(),
+            )?;
+        }
+        tar.into_inner()?.finish()?;
+        info!("Diagnose | Bundle written to [{}]", path.display());
+        Ok(path)
+    }
+}
+
+// The secrets of [state] and what replaces them in every text of the bundle.
+fn redactions(state: &State) -> Vec<(String, String)> {
+    let mut redact = vec![];
+    // Replacing a few characters everywhere would make the text unreadable.
+    for address in [
+        &state.p2pool.address,
+        &state.xmrig.address,
+        &state.xmrig_proxy.address,
+    ] {
+        if address.len() >= 4 {
+            redact.push((
+                address.clone(),
+                Helper::head_tail_of_monero_address(address),
+            ));
+        }
+    }
+    let notifications = &state.gupax.notifications;
+    // A recipient can show up alone in the logs.
+    let recipients = notifications.mail_to.split(',').map(str::trim);
+    for secret in [
+        &state.gupax.http_api_token,
+        &notifications.mail_username,
+        &notifications.mail_password,
+        &notifications.mail_from,
+        &notifications.mail_to,
+        &notifications.webhook_url,
+        &notifications.command_line,
+        &state.xmrig.token,
+        &state.xmrig_proxy.token,
+        &state.xvb.token,
+        &state.xvb.extra_pool_user,
+    ]
+    .into_iter()
+    .map(String::as_str)
+    .chain(recipients)
+    {
+        if secret.len() >= 4 {
+            redact.push((secret.to_string(), REDACTED.to_string()));
+        }
+    }
+    // Longest first, so a secret containing another one is replaced whole.
+    redact.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+    redact
+}
+
+pub fn redacted_state(state: &State) -> State {
+    let mut state = state.clone();
+    for address in [
+        &mut state.p2pool.address,
+        &mut state.xmrig.address,
+        &mut state.xmrig_proxy.address,
+    ] {
+        if !address.is_empty() {
+            *address = Helper::head_tail_of_monero_address(address);
+        }
+    }
+    let notifications = &mut state.gupax.notifications;
+    for secret in [
+        &mut state.gupax.http_api_token,
+        &mut notifications.mail_username,
+        &mut notifications.mail_password,
+        &mut notifications.mail_from,
+        &mut notifications.mail_to,
+        &mut notifications.webhook_url,
+        &mut notifications.command_line,
+        &mut state.xmrig.token,
+        &mut state.xmrig_proxy.token,
+        &mut state.xvb.token,
+        &mut state.xvb.extra_pool_user,
+    ] {
+        secret.clear();
+    }
+    // Free-form, where a user would put a key. Whether there were any still tells something.
+    for arguments in [
+        &mut state.p2pool.arguments,
+        &mut state.xmrig.arguments,
+        &mut state.xmrig_proxy.arguments,
+    ] {
+        if !arguments.is_empty() {
+            *arguments = REDACTED.to_string();
+        }
+    }
+    state
+}
+
+// [processes.toml] without the values of [env] and the [args], where the API keys
+// of the custom processes go. Left out if it can not be parsed.
+fn redacted_processes(path: &Path) -> String {
+    let string = match fs::read_to_string(path) {
+        Ok(string) => string,
+        Err(e) => return format!("Could not read [{}]: {}\n", path.display(), e),
+    };
+    let mut process = match Processes::from_str_to_vec(&string) {
+        Ok(process) => process,
+        Err(e) => return format!("Could not parse [{}]: {}\n", path.display(), e),
+    };
+    for config in process.iter_mut() {
+        for value in config.env.values_mut().chain(config.args.iter_mut()) {
+            *value = REDACTED.to_string();
+        }
+    }
+    match toml::ser::to_string(&Processes { process }) {
+        Ok(toml) => toml,
+        Err(e) => format!("Could not serialize [{}]: {}\n", path.display(), e),
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn fetch_http_api(url: &str, token: &str) -> Vec<(&'static str, String)> {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return vec![("error", format!("{{\"error\": \"{}\"}}", e))],
+    };
+    let mut bodies = vec![];
+    for endpoint in [
+        "processes",
+        "sys",
+        "node",
+        "p2pool",
+        "xmrig",
+        "xmrig_proxy",
+        "xvb",
+    ] {
+        let body = match client
+            .get(format!("{}/{}", url, endpoint))
+            .bearer_auth(token)
+            .send()
+            .await
+        {
+            Ok(response) => match response.text().await {
+                Ok(body) => body,
+                Err(e) => e.to_string(),
+            },
+            Err(e) => {
+                warn!("Diagnose | Could not reach the HTTP API: {}", e);
+                format!("Could not reach the HTTP API at [{}]: {}", url, e)
+            }
+        };
+        bodies.push((endpoint, body));
+    }
+    bodies
+}
+
+// Reads [path], keeping the last [max] bytes starting at a full line.
+fn read_tail(path: &Path, max: u64) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut bytes = vec![];
+    if len > max {
+        file.seek(SeekFrom::Start(len - max))?;
+        file.read_to_end(&mut bytes)?;
+        if let Some(newline) = bytes.iter().position(|b| *b == b'\n') {
+            bytes.drain(..=newline);
+        }
+    } else {
+        file.read_to_end(&mut bytes)?;
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn versions(state: &State) -> String {
+    let mut text = format!("Gupaxx: {}\n", GUPAX_VERSION);
+    for (name, bundled, path) in [
+        ("Node", NODE_VERSION, &state.gupax.absolute_node_path),
+        ("P2Pool", P2POOL_VERSION, &state.gupax.absolute_p2pool_path),
+        ("XMRig", XMRIG_VERSION, &state.gupax.absolute_xmrig_path),
+        (
+            "XMRig-Proxy",
+            XMRIG_PROXY_VERSION,
+            &state.gupax.absolute_xp_path,
+        ),
+    ] {
+        let _ = writeln!(
+            text,
+            "{}: {} (bundled {}) [{}]",
+            name,
+            binary_version(path),
+            bundled,
+            path.display()
+        );
+    }
+    text
+}
+
+// The first line printed by [path --version].
+fn binary_version(path: &Path) -> String {
+    if !path.is_file() {
+        return "not found".to_string();
+    }
+    let mut child = match Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return format!("could not be run: {}", e),
+    };
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if start.elapsed() < VERSION_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50))
+            }
+            _ => {
+                warn!("Diagnose | [{}] did not print its version", path.display());
+                let _ = child.kill();
+                let _ = child.wait();
+                return "did not answer".to_string();
+            }
+        }
+    }
+    let mut stdout = String::new();
+    if let Some(mut out) = child.stdout.take() {
+        let _ = out.read_to_string(&mut stdout);
+    }
+    stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn system() -> String {
+    let mut sys = sysinfo::System::new();
+    sys.refresh_cpu_all();
+    sys.refresh_memory();
+    let cpu = sys
+        .cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .unwrap_or_else(|| "???".to_string());
+    format!(
+        "OS: {}\nKernel: {}\nArch: {}\nCPU: {}\nThreads: {}\nMemory: {} MB total, {} MB used\n",
+        sysinfo::System::long_os_version().unwrap_or_else(|| "???".to_string()),
+        sysinfo::System::kernel_version().unwrap_or_else(|| "???".to_string()),
+        std::env::consts::ARCH,
+        cpu,
+        sys.cpus().len(),
+        sys.total_memory() / 1_000_000,
+        sys.used_memory() / 1_000_000,
+    )
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::disk::xvb_rounds::XvbRounds;
 //---------------------------------------------------------------------------------------------------- Gupax-P2Pool API
 #[derive(Clone, Debug)]
 pub struct GupaxP2poolApi {
@@ -13,6 +14,7 @@ pub struct GupaxP2poolApi {
     pub path_log: PathBuf,  // Path to [log]
     pub path_payout: PathBuf, // Path to [payout]
     pub path_xmr: PathBuf,  // Path to [xmr]
+    pub xvb_rounds: XvbRounds, // XvB raffles, see [XvbRounds]
 }
 
 impl Default for GupaxP2poolApi {
@@ -36,6 +38,7 @@ impl GupaxP2poolApi {
             path_xmr: PathBuf::new(),
             path_payout: PathBuf::new(),
             path_log: PathBuf::new(),
+            xvb_rounds: XvbRounds::new(),
         }
     }
 
@@ -46,6 +49,7 @@ impl GupaxP2poolApi {
         path_log.push(GUPAX_P2POOL_API_LOG);
         path_payout.push(GUPAX_P2POOL_API_PAYOUT);
         path_xmr.push(GUPAX_P2POOL_API_XMR);
+        self.xvb_rounds.fill_paths(gupax_p2pool_dir);
         *self = Self {
             path_log,
             path_payout,
@@ -84,6 +88,7 @@ impl GupaxP2poolApi {
                 }
             }
         }
+        XvbRounds::create_file(gupax_p2pool_dir)?;
         Ok(())
     }
 
@@ -120,6 +125,7 @@ impl GupaxP2poolApi {
             ..std::mem::take(self)
         };
         self.update_log_rev();
+        self.xvb_rounds.read()?;
         Ok(())
     }
 
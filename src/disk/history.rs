@@ -0,0 +1,268 @@
+use super::*;
+use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+//---------------------------------------------------------------------------------------------------- History
+// Time-series of the mining stats, shown as graphs in the [Status] tab.
+//
+// Lives within the Gupax OS data directory.
+// ~/.local/share/gupaxx/history/
+// ├─ minutes.csv // One averaged sample per minute, for the last day
+// ├─ hours.csv   // One averaged sample per hour, for the last month
+//
+// The helper thread records a sample every second, they are averaged and appended
+// to [minutes.csv] once a minute, then the minutes are averaged into [hours.csv].
+// Both files are re-written (without the expired samples) once an hour and on startup.
+pub const HISTORY_MINUTES_SECS: u64 = 60 * 60 * 24;
+pub const HISTORY_HOURS_SECS: u64 = 60 * 60 * 24 * 30;
+const HISTORY_CSV_HEADER: &str = "timestamp,xmrig_hashrate,p2pool_hashrate_15m,p2pool_hashrate_1h,shares_found,current_effort,hashrate_p2pool,hashrate_xvb";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HistorySample {
+    pub timestamp: u64,           // UNIX timestamp (seconds)
+    pub xmrig_hashrate: f32,      // H/s
+    pub p2pool_hashrate_15m: f32, // H/s
+    pub p2pool_hashrate_1h: f32,  // H/s
+    pub shares_found: u64,        // Total shares found by P2Pool
+    pub current_effort: f32,      // Percent
+    pub hashrate_p2pool: f32,     // H/s of XMRig sent to P2Pool
+    pub hashrate_xvb: f32,        // H/s of XMRig sent to XvB
+}
+
+impl HistorySample {
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+
+    fn to_csv(self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.timestamp,
+            self.xmrig_hashrate,
+            self.p2pool_hashrate_15m,
+            self.p2pool_hashrate_1h,
+            self.shares_found,
+            self.current_effort,
+            self.hashrate_p2pool,
+            self.hashrate_xvb,
+        )
+    }
+
+    fn from_csv(line: &str) -> Option<Self> {
+        let mut fields = line.split(',');
+        let mut next = || fields.next().map(str::trim);
+        let sample = Self {
+            timestamp: next()?.parse().ok()?,
+            xmrig_hashrate: next()?.parse().ok()?,
+            p2pool_hashrate_15m: next()?.parse().ok()?,
+            p2pool_hashrate_1h: next()?.parse().ok()?,
+            shares_found: next()?.parse().ok()?,
+            current_effort: next()?.parse().ok()?,
+            hashrate_p2pool: next()?.parse().ok()?,
+            hashrate_xvb: next()?.parse().ok()?,
+        };
+        Some(sample)
+    }
+}
+
+// Sums the samples of a minute/hour, to average them once it is over.
+#[derive(Clone, Debug, Default)]
+struct Accumulator {
+    bucket: u64, // Start of the minute/hour being accumulated
+    sum: HistorySample,
+    count: u32,
+}
+
+impl Accumulator {
+    fn push(&mut self, sample: &HistorySample) {
+        self.sum.xmrig_hashrate += sample.xmrig_hashrate;
+        self.sum.p2pool_hashrate_15m += sample.p2pool_hashrate_15m;
+        self.sum.p2pool_hashrate_1h += sample.p2pool_hashrate_1h;
+        self.sum.current_effort += sample.current_effort;
+        self.sum.hashrate_p2pool += sample.hashrate_p2pool;
+        self.sum.hashrate_xvb += sample.hashrate_xvb;
+        // This one is a counter, not a rate.
+        self.sum.shares_found = sample.shares_found;
+        self.count += 1;
+    }
+
+    // Returns the average and resets, [None] if nothing was pushed.
+    fn take(&mut self) -> Option<HistorySample> {
+        if self.count == 0 {
+            return None;
+        }
+        let count = self.count as f32;
+        let sum = std::mem::take(&mut self.sum);
+        self.count = 0;
+        Some(HistorySample {
+            timestamp: self.bucket,
+            xmrig_hashrate: sum.xmrig_hashrate / count,
+            p2pool_hashrate_15m: sum.p2pool_hashrate_15m / count,
+            p2pool_hashrate_1h: sum.p2pool_hashrate_1h / count,
+            shares_found: sum.shares_found,
+            current_effort: sum.current_effort / count,
+            hashrate_p2pool: sum.hashrate_p2pool / count,
+            hashrate_xvb: sum.hashrate_xvb / count,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    pub minutes: VecDeque<HistorySample>, // Last day, oldest first
+    pub hours: VecDeque<HistorySample>,   // Last month, oldest first
+    minute: Accumulator,
+    hour: Accumulator,
+    pub path_minutes: PathBuf,
+    pub path_hours: PathBuf,
+}
+
+impl History {
+    //---------------------------------------------------------------------------------------------------- Init
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fill_paths(&mut self, history_dir: &Path) {
+        let mut path_minutes = history_dir.to_path_buf();
+        let mut path_hours = history_dir.to_path_buf();
+        path_minutes.push(HISTORY_MINUTES);
+        path_hours.push(HISTORY_HOURS);
+        self.path_minutes = path_minutes;
+        self.path_hours = path_hours;
+    }
+
+    pub fn create_all_files(history_dir: &Path) -> Result<(), TomlError> {
+        fs::create_dir_all(history_dir)?;
+        for file in [HISTORY_MINUTES, HISTORY_HOURS] {
+            let mut path = history_dir.to_path_buf();
+            path.push(file);
+            if path.exists() {
+                info!("History | [{}] already exists, skipping...", path.display());
+                continue;
+            }
+            fs::write(&path, format!("{}\n", HISTORY_CSV_HEADER))?;
+            info!("History | [{}] create ... OK", path.display());
+        }
+        Ok(())
+    }
+
+    pub fn read_all_files_and_update(&mut self) -> Result<(), TomlError> {
+        let now = HistorySample::now();
+        self.minutes = Self::read(
+            File::HistoryMinutes,
+            &self.path_minutes,
+            now,
+            HISTORY_MINUTES_SECS,
+        )?;
+        self.hours = Self::read(
+            File::HistoryHours,
+            &self.path_hours,
+            now,
+            HISTORY_HOURS_SECS,
+        )?;
+        // Minutes newer than the last hour were not averaged yet,
+        // (Gupaxx was closed in the middle of an hour) catch up.
+        let last_hour = self.hours.back().map_or(0, |s| s.timestamp + 3600);
+        for sample in self.minutes.iter().filter(|s| s.timestamp >= last_hour) {
+            if let Some(hour) = Self::accumulate(&mut self.hour, sample, 3600) {
+                self.hours.push_back(hour);
+            }
+        }
+        self.write_all_files()?;
+        info!(
+            "History | Read [{}] minutes and [{}] hours ... OK",
+            self.minutes.len(),
+            self.hours.len()
+        );
+        Ok(())
+    }
+
+    // Parses a CSV file, skipping invalid lines and samples older than [max_age].
+    fn read(
+        file: File,
+        path: &PathBuf,
+        now: u64,
+        max_age: u64,
+    ) -> Result<VecDeque<HistorySample>, TomlError> {
+        let string = read_to_string(file, path)?;
+        Ok(string
+            .lines()
+            .skip(1)
+            .filter_map(HistorySample::from_csv)
+            .filter(|s| s.timestamp + max_age > now)
+            .collect())
+    }
+
+    //---------------------------------------------------------------------------------------------------- Live
+    // Called by the helper thread every second.
+    pub fn record(&mut self, sample: HistorySample) -> Result<(), TomlError> {
+        let minute = sample.timestamp - sample.timestamp % 60;
+        if self.minute.bucket != minute {
+            if let Some(average) = self.minute.take() {
+                self.push_minute(average)?;
+            }
+            self.minute.bucket = minute;
+        }
+        self.minute.push(&sample);
+        Ok(())
+    }
+
+    fn push_minute(&mut self, average: HistorySample) -> Result<(), TomlError> {
+        GupaxP2poolApi::disk_append(&average.to_csv(), &self.path_minutes)?;
+        self.minutes.push_back(average);
+        Self::expire(&mut self.minutes, average.timestamp, HISTORY_MINUTES_SECS);
+        // The hour is over, average it and compact the files.
+        if let Some(hour) = Self::accumulate(&mut self.hour, &average, 3600) {
+            self.hours.push_back(hour);
+            Self::expire(&mut self.hours, hour.timestamp, HISTORY_HOURS_SECS);
+            self.write_all_files()?;
+        }
+        Ok(())
+    }
+
+    // Pushes into the [Accumulator], returns the average of the previous bucket if it is over.
+    fn accumulate(
+        acc: &mut Accumulator,
+        sample: &HistorySample,
+        period: u64,
+    ) -> Option<HistorySample> {
+        let bucket = sample.timestamp - sample.timestamp % period;
+        let mut average = None;
+        if acc.bucket != bucket {
+            average = acc.take();
+            acc.bucket = bucket;
+        }
+        acc.push(sample);
+        average
+    }
+
+    fn expire(samples: &mut VecDeque<HistorySample>, now: u64, max_age: u64) {
+        while samples
+            .front()
+            .is_some_and(|s| s.timestamp + max_age <= now)
+        {
+            samples.pop_front();
+        }
+    }
+
+    fn write_all_files(&self) -> Result<(), TomlError> {
+        for (samples, path) in [
+            (&self.minutes, &self.path_minutes),
+            (&self.hours, &self.path_hours),
+        ] {
+            let mut string = String::with_capacity(samples.len() * 64);
+            string.push_str(HISTORY_CSV_HEADER);
+            string.push('\n');
+            for sample in samples {
+                string.push_str(&sample.to_csv());
+                string.push('\n');
+            }
+            fs::write(path, string)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+// Single instance lock.
+//
+// Two instances using the same data directory would fight over [state.toml],
+// the Gupax-P2Pool API files and the processes, so only one may run at a time.
+//
+// [gupaxx.lock] holds the PID of its owner, which keeps an exclusive lock of
+// the OS on it (flock, LockFileEx) while running. The OS drops that lock when
+// the process exits, crashed or killed included, so a lock left behind is
+// simply taken again and two instances can never both get it. The file stays:
+// removing it would let an instance that opened it just before lock a file
+// nobody else can see anymore.
+
+use crate::disk::consts::LOCK_FILE;
+use log::{info, warn};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum LockError {
+    Running(Option<u32>), // PID of the instance holding the lock, if written yet
+    Io(io::Error),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Running(Some(pid)) => write!(f, "Gupaxx is already running (PID {})", pid),
+            Self::Running(None) => write!(f, "Gupaxx is already running"),
+            Self::Io(e) => write!(f, "Lock file error: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for LockError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+}
+
+impl InstanceLock {
+    // Takes the lock of [os_data_path], held until dropped.
+    pub fn acquire(os_data_path: &Path) -> Result<Self, LockError> {
+        let path = os_data_path.join(LOCK_FILE);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        if let Err(e) = try_lock(&file) {
+            if e.kind() == io::ErrorKind::WouldBlock {
+                // The owner may not have written it yet.
+                let pid = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|s| s.trim().parse::<u32>().ok());
+                return Err(LockError::Running(pid));
+            }
+            return Err(e.into());
+        }
+        // Whatever a dead instance left.
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        info!("Lock | [{}] ... OK", path.display());
+        Ok(Self { file })
+    }
+}
+
+// The lock itself goes with the file.
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.set_len(0) {
+            warn!("Lock | Could not clear the PID: {}", e);
+        }
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn try_lock(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: the descriptor is owned by [file] and open.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+// Locks a byte far past the PID, Windows locks are mandatory
+// and the other instances would not be able to read it.
+#[cfg(target_os = "windows")]
+fn try_lock(file: &File) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::ERROR_LOCK_VIOLATION;
+    use windows_sys::Win32::Storage::FileSystem::{
+        LockFileEx, LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY,
+    };
+    use windows_sys::Win32::System::IO::OVERLAPPED;
+    // SAFETY: zeroed is a valid [OVERLAPPED], the offset is set below.
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    overlapped.Anonymous.Anonymous.OffsetHigh = 1;
+    // SAFETY: the handle is owned by [file] and open, [overlapped] outlives the call.
+    let locked = unsafe {
+        LockFileEx(
+            file.as_raw_handle(),
+            LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+            0,
+            1,
+            0,
+            &mut overlapped,
+        )
+    };
+    if locked != 0 {
+        return Ok(());
+    }
+    let e = io::Error::last_os_error();
+    if e.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+        Err(io::ErrorKind::WouldBlock.into())
+    } else {
+        Err(e)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::disk::state::LogPolicy;
+use chrono::Local;
+use log::{info, warn};
+use std::fs::{self, File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+//---------------------------------------------------------------------------------------------------- Logs
+// Everything the processes print, kept on disk since the consoles only
+// hold their last [CONSOLE_MAX_LINES] lines.
+//
+// Lives within the Gupax OS data directory.
+// ~/.local/share/gupaxx/logs/
+// ├─ p2pool.log                         // Being written to
+// ├─ p2pool.2024-01-01_00-00-00.000.log // Rotated, the [max_files] newest are kept
+//
+// The PTY read thread of every process writes each line with a timestamp,
+// and flushes it right away so nothing is lost if Gupaxx is killed.
+
+pub struct LogFile {
+    dir: PathBuf,
+    name: String,
+    policy: LogPolicy,
+    file: Option<LineWriter<File>>,
+    size: u64,
+}
+
+impl LogFile {
+    // Appends to [name.log], a file that can not be opened disables the log,
+    // as does an empty [dir] (the data path is not known).
+    pub fn open(dir: &Path, name: &str, policy: LogPolicy) -> Self {
+        let mut log = Self {
+            dir: dir.to_path_buf(),
+            name: name.to_string(),
+            policy,
+            file: None,
+            size: 0,
+        };
+        if policy.enabled && !dir.as_os_str().is_empty() {
+            if let Err(e) = log.reopen() {
+                warn!("Logs | Could not open the log of [{}]: {}", name, e);
+            }
+        }
+        log
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(format!("{}.log", self.name))
+    }
+
+    fn reopen(&mut self) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path())?;
+        self.size = file.metadata()?.len();
+        self.file = Some(LineWriter::new(file));
+        Ok(())
+    }
+
+    pub fn write_line(&mut self, line: &str) {
+        if self.file.is_none() {
+            return;
+        }
+        if self.size >= self.policy.max_size_mb.max(1) * 1_000_000 {
+            if let Err(e) = self.rotate() {
+                warn!("Logs | Could not rotate the log of [{}]: {}", self.name, e);
+                self.file = None;
+                return;
+            }
+        }
+        let entry = format!(
+            "{} | {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            line
+        );
+        if let Some(file) = &mut self.file {
+            match file.write_all(entry.as_bytes()) {
+                Ok(_) => self.size += entry.len() as u64,
+                Err(e) => {
+                    warn!("Logs | Could not write the log of [{}]: {}", self.name, e);
+                    self.file = None;
+                }
+            }
+        }
+    }
+
+    // Renames the current file with the time, starts a new one and deletes the old ones.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let rotated = loop {
+            let rotated = self.dir.join(format!(
+                "{}.{}.log",
+                self.name,
+                Local::now().format("%Y-%m-%d_%H-%M-%S%.3f")
+            ));
+            // Rotated twice in the same millisecond, the names must stay ordered.
+            if !rotated.exists() {
+                break rotated;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        };
+        fs::rename(self.path(), rotated)?;
+        self.reopen()?;
+        prune(&self.dir, &self.name, self.policy.max_files)
+    }
+}
+
+// The rotated files of [name], oldest first.
+pub fn rotated_files(dir: &Path, name: &str) -> std::io::Result<Vec<PathBuf>> {
+    let current = format!("{}.log", name);
+    let prefix = format!("{}.", name);
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|file| file.to_str())
+                .is_some_and(|file| {
+                    file != current && file.starts_with(&prefix) && file.ends_with(".log")
+                })
+        })
+        .collect();
+    // The time in the names sorts them.
+    files.sort();
+    Ok(files)
+}
+
+// Deletes the oldest rotated files of [name], keeping [max_files].
+pub fn prune(dir: &Path, name: &str, max_files: usize) -> std::io::Result<()> {
+    let files = rotated_files(dir, name)?;
+    for file in files.iter().take(files.len().saturating_sub(max_files)) {
+        info!("Logs | Deleting [{}]", file.display());
+        fs::remove_file(file)?;
+    }
+    Ok(())
+}
+
+// Copies every log into a new [gupaxx-logs-<time>] folder in [destination], returns it.
+pub fn export(dir: &Path, destination: &Path) -> std::io::Result<PathBuf> {
+    let export = destination.join(format!(
+        "gupaxx-logs-{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    fs::create_dir_all(&export)?;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let (true, Some(file)) = (path.is_file(), path.file_name()) {
+            fs::copy(&path, export.join(file))?;
+        }
+    }
+    info!("Logs | Exported to [{}]", export.display());
+    Ok(export)
+}
@@ -0,0 +1,107 @@
+// State schema migrations.
+//
+// [State::merge] fills the missing fields with their defaults and drops the
+// unknown ones, which is enough when fields are added or removed. When the
+// meaning or the type of a field changes, it is not: the old value would be
+// kept as is or fail the whole merge.
+//
+// [state.toml] holds the [schema_version] it was written with, missing before
+// the first one. Every migration takes a state from its schema to the next,
+// on the raw TOML so it can read fields that do not exist anymore. They run
+// in order up to [SCHEMA_VERSION] before the merge. A change of meaning adds
+// a migration at the end of [MIGRATIONS], which bumps [SCHEMA_VERSION].
+
+use crate::constants::GUPAX_VERSION_UNDERSCORE;
+use crate::disk::errors::TomlError;
+use log::{info, warn};
+use toml::{Table, Value};
+
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+// [MIGRATIONS[n]] takes a state of schema [n] to [n + 1].
+const MIGRATIONS: [fn(&mut Table); 1] = [from_unversioned];
+
+// The schema [string] was written with, 0 if before they existed.
+pub fn schema_version(string: &str) -> Result<u32, TomlError> {
+    let table: Table = toml::de::from_str(string).map_err(TomlError::Deserialize)?;
+    Ok(version_of(&table))
+}
+
+fn version_of(table: &Table) -> u32 {
+    table
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(Value::as_integer)
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0)
+}
+
+// Brings [string] to [SCHEMA_VERSION], [None] if it already is.
+// A state of a newer schema is left as is, the merge keeps what it knows.
+pub fn migrate(string: &str) -> Result<Option<String>, TomlError> {
+    let mut table: Table = toml::de::from_str(string).map_err(TomlError::Deserialize)?;
+    let version = version_of(&table);
+    if version > SCHEMA_VERSION {
+        warn!(
+            "State | Schema [{}] is newer than [{}], written by a newer Gupaxx?",
+            version, SCHEMA_VERSION
+        );
+        return Ok(None);
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(None);
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut table);
+        info!("State | Migrate schema [{}] -> [{}] ... OK", from, from + 1);
+    }
+    table.insert(
+        SCHEMA_VERSION_KEY.to_string(),
+        Value::Integer(SCHEMA_VERSION.into()),
+    );
+    toml::ser::to_string(&table)
+        .map(Some)
+        .map_err(TomlError::Serialize)
+}
+
+fn section<'a>(table: &'a mut Table, name: &str) -> Option<&'a mut Table> {
+    table.get_mut(name).and_then(Value::as_table_mut)
+}
+
+//---------------------------------------------------------------------------------------------------- Migrations
+// 0 -> 1: states of Gupax and of Gupaxx up to v1.5.3.
+fn from_unversioned(state: &mut Table) {
+    if let Some(gupax) = section(state, "gupax") {
+        // Updates by Tor were removed in Gupaxx.
+        gupax.remove("update_via_tor");
+    }
+    // The rig name defaulted to the version of Gupax, which a state
+    // copied over from it kept, P2Pool showing it as the worker name.
+    for name in ["xmrig", "xmrig_proxy"] {
+        let Some(section) = section(state, name) else {
+            continue;
+        };
+        for key in ["rig", "selected_rig"] {
+            if let Some(Value::String(rig)) = section.get_mut(key) {
+                if rig.starts_with("Gupax_v") {
+                    *rig = GUPAX_VERSION_UNDERSCORE.to_string();
+                }
+            }
+        }
+    }
+    // A percentage of -100 to 100, a float or a value out of that range
+    // failed the whole merge and reset every setting.
+    if let Some(xvb) = section(state, "xvb") {
+        let buffer = match xvb.get("p2pool_buffer") {
+            Some(Value::Float(f)) => Some(f.round() as i64),
+            Some(Value::Integer(i)) => Some(*i),
+            _ => None,
+        };
+        if let Some(buffer) = buffer {
+            xvb.insert(
+                "p2pool_buffer".to_string(),
+                Value::Integer(buffer.clamp(-100, 100)),
+            );
+        }
+    }
+}
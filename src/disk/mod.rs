@@ -18,9 +18,11 @@
 // This handles reading/writing the disk files:
 //     - [state.toml] -> [App] state
 //     - [nodes.toml] -> [Manual Nodes] list
+//     - [processes.toml] -> Custom processes
 // The TOML format is used. This struct hierarchy
 // directly translates into the TOML parser:
 //   State/
+//   ├─ schema_version // See [migrations]
 //   ├─ Gupax/
 //   │  ├─ ...
 //   ├─ P2pool/
@@ -50,14 +52,23 @@ use std::{
 
 use self::errors::TomlError;
 
+pub mod archive;
 pub mod consts;
+pub mod diagnose;
 pub mod errors;
 pub mod gupax_p2pool_api;
+pub mod history;
+pub mod lock;
+pub mod logs;
+pub mod migrations;
 pub mod node;
 pub mod pool;
+pub mod processes;
+pub mod profiles;
 pub mod state;
 pub mod status;
 pub mod tests;
+pub mod xvb_rounds;
 //---------------------------------------------------------------------------------------------------- General functions for all [File]'s
 // get_file_path()      | Return absolute path to OS data path + filename
 // read_to_string()     | Convert the file at a given path into a [String]
@@ -112,6 +123,24 @@ pub fn get_gupax_p2pool_path(os_data_path: &Path) -> PathBuf {
     gupax_p2pool_dir
 }
 
+pub fn get_gupax_history_path(os_data_path: &Path) -> PathBuf {
+    let mut history_dir = os_data_path.to_path_buf();
+    history_dir.push(HISTORY_DIRECTORY);
+    history_dir
+}
+
+pub fn get_gupax_logs_path(os_data_path: &Path) -> PathBuf {
+    let mut logs_dir = os_data_path.to_path_buf();
+    logs_dir.push(LOGS_DIRECTORY);
+    logs_dir
+}
+
+pub fn get_gupax_profiles_path(os_data_path: &Path) -> PathBuf {
+    let mut profiles_dir = os_data_path.to_path_buf();
+    profiles_dir.push(PROFILES_DIRECTORY);
+    profiles_dir
+}
+
 pub fn create_gupax_dir(path: &PathBuf) -> Result<(), TomlError> {
     // Create Gupax directory
     match fs::create_dir_all(path) {
@@ -162,6 +191,29 @@ pub fn read_to_string(file: File, path: &PathBuf) -> Result<String, TomlError> {
     }
 }
 
+// Creates (or truncates) a file only the user can read, for the ones holding
+// tokens and passwords. Already there with wider permissions, they are narrowed
+// before anything is written.
+pub fn create_private(path: &Path) -> std::io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+    #[cfg(target_family = "unix")]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+// [fs::write()] through [create_private()].
+pub fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    create_private(path)?.write_all(contents.as_bytes())
+}
+
 // Write str to console with [info!] surrounded by "---"
 pub fn print_dash(toml: &str) {
     info!("{}", HORIZONTAL);
@@ -188,12 +240,18 @@ pub fn into_absolute_path(path: String) -> Result<PathBuf, TomlError> {
 #[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum File {
     // State files
-    State, // state.toml   | Gupax state
-    Node,  // node.toml    | P2Pool manual node selector
-    Pool,  // pool.toml    | XMRig manual pool selector
+    State,     // state.toml   | Gupax state
+    Node,      // node.toml    | P2Pool manual node selector
+    Pool,      // pool.toml    | XMRig manual pool selector
+    Processes, // processes.toml | Custom processes
 
     // Gupax-P2Pool API
-    Log,    // log    | Raw log lines of P2Pool payouts received
-    Payout, // payout | Single [u64] representing total payouts
-    Xmr,    // xmr    | Single [u64] representing total XMR mined in atomic units
+    Log,       // log    | Raw log lines of P2Pool payouts received
+    Payout,    // payout | Single [u64] representing total payouts
+    Xmr,       // xmr    | Single [u64] representing total XMR mined in atomic units
+    XvbRounds, // xvb | One line per XvB raffle
+
+    // Stats history
+    HistoryMinutes, // minutes.csv | Averaged stats per minute, last day
+    HistoryHours,   // hours.csv   | Averaged stats per hour, last month
 }
@@ -0,0 +1,94 @@
+use super::*;
+use crate::disk::state::RestartPolicy;
+use std::collections::BTreeMap;
+//---------------------------------------------------------------------------------------------------- [processes.toml]
+// Extra processes supervised next to P2Pool/XMRig (e.g: a Tor daemon, a CUDA plugin host, scripts).
+// They are read once at startup and shown in [Status] -> [Processes].
+pub const PROCESSES_TOML_DEFAULT: &str = r#"# Extra processes supervised by Gupaxx, shown in [Status] -> [Processes].
+# Changes are read when Gupaxx starts.
+#
+# [[process]]
+# name = "Tor"                       # Shown in Gupaxx
+# path = "/usr/bin/tor"              # Relative paths start from the Gupaxx directory
+# args = ["-f", "/etc/tor/torrc"]    # Optional
+# cwd = "/var/lib/tor"               # Optional, the directory of [path] by default
+# env = { LANG = "C" }               # Optional
+# alive_regex = "Bootstrapped 100%"  # Optional, alive as soon as it is started without it
+# auto_start = false                 # Optional, start with Gupaxx
+# restart = { mode = "OnFailure", max_retries = 3, backoff_secs = 5 } # Optional
+"#;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Processes {
+    #[serde(default)]
+    pub process: Vec<ProcessConfig>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProcessConfig {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub alive_regex: Option<String>,
+    #[serde(default)]
+    pub auto_start: bool,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+}
+
+impl Processes {
+    pub fn from_str_to_vec(string: &str) -> Result<Vec<ProcessConfig>, TomlError> {
+        let processes: Self = match toml::de::from_str(string) {
+            Ok(processes) => {
+                info!("Processes | Parse ... OK");
+                processes
+            }
+            Err(err) => {
+                error!("Processes | String parse ... FAIL ... {}", err);
+                return Err(TomlError::Deserialize(err));
+            }
+        };
+        for process in processes.process.iter() {
+            if process.name.is_empty() {
+                error!("Processes | Empty [name] for [{}]", process.path);
+                return Err(TomlError::Parse("[name] can not be empty"));
+            }
+            if let Some(regex) = &process.alive_regex {
+                if let Err(e) = regex::Regex::new(regex) {
+                    error!(
+                        "Processes | [{}] alive_regex ... FAIL ... {}",
+                        process.name, e
+                    );
+                    return Err(TomlError::Parse("[alive_regex] is not a valid regex"));
+                }
+            }
+        }
+        Ok(processes.process)
+    }
+
+    pub fn get(path: &PathBuf) -> Result<Vec<ProcessConfig>, TomlError> {
+        // Read
+        let file = File::Processes;
+        let string = match read_to_string(file, path) {
+            Ok(string) => string,
+            // Create
+            _ => {
+                Self::create_new(path)?;
+                read_to_string(file, path)?
+            }
+        };
+        // Deserialize
+        Self::from_str_to_vec(&string)
+    }
+
+    pub fn create_new(path: &PathBuf) -> Result<(), TomlError> {
+        info!("Processes | Creating new default...");
+        fs::write(path, PROCESSES_TOML_DEFAULT)?;
+        info!("Processes | Write ... OK");
+        Ok(())
+    }
+}
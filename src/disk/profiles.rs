@@ -0,0 +1,98 @@
+// State profiles.
+//
+// A profile is a whole [State] saved under a name in [profiles/<name>.toml],
+// to switch between setups (a rig at home, a laptop with less threads...).
+//
+// [state.toml] stays the state in use, [Gupax.profile] telling which profile
+// it came from. Saving the state also saves that profile, and switching
+// to another profile copies it over [state.toml].
+
+use crate::disk::errors::TomlError;
+use crate::disk::state::State;
+use crate::disk::write_private;
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROFILE_NAME_MAX_LEN: usize = 64;
+
+// Names end up as file names, so anything that could escape
+// the directory or is not allowed on some OS is refused.
+pub fn valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.len() <= PROFILE_NAME_MAX_LEN
+        && name.trim() == name
+        && !name.starts_with('.')
+        && !name.chars().any(|c| {
+            c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+        })
+}
+
+fn path(dir: &Path, name: &str) -> Result<PathBuf, TomlError> {
+    if !valid_name(name) {
+        return Err(TomlError::Path(format!("Invalid profile name [{}]", name)));
+    }
+    Ok(dir.join(format!("{}.toml", name)))
+}
+
+// Names of the profiles in [dir], sorted.
+pub fn list(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            valid_name(&name).then_some(name)
+        })
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+pub fn exists(dir: &Path, name: &str) -> bool {
+    path(dir, name).is_ok_and(|path| path.exists())
+}
+
+// Reads the profile [name], migrating and merging it with
+// the defaults if it was saved by an older version.
+pub fn load(dir: &Path, name: &str) -> Result<State, TomlError> {
+    let path = path(dir, name)?;
+    let string = fs::read_to_string(&path)?;
+    let (mut state, changed) = State::load(&string)?;
+    if changed {
+        warn!("Profile | [{}] Merged with the defaults", name);
+    }
+    state.gupax.profile = name.to_string();
+    Ok(state)
+}
+
+// Saves [state] as the profile [name].
+pub fn save(dir: &Path, name: &str, state: &State) -> Result<(), TomlError> {
+    let path = path(dir, name)?;
+    fs::create_dir_all(dir)?;
+    let mut state = state.clone();
+    state.gupax.profile = name.to_string();
+    // The same secrets as [state.toml].
+    write_private(&path, &state.to_string()?)?;
+    info!("Profile | Save [{}] ... OK", name);
+    Ok(())
+}
+
+pub fn delete(dir: &Path, name: &str) -> Result<(), TomlError> {
+    fs::remove_file(path(dir, name)?)?;
+    info!("Profile | Delete [{}] ... OK", name);
+    Ok(())
+}
+
+// Makes the profile [name] the state in use, for the next read of [state_path].
+pub fn activate(dir: &Path, name: &str, state_path: &Path) -> Result<State, TomlError> {
+    let mut state = load(dir, name)?;
+    state.save(state_path)?;
+    info!("Profile | Activate [{}] ... OK", name);
+    Ok(state)
+}
@@ -2,6 +2,8 @@ use anyhow::Result;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 
 use super::*;
+use crate::disk::diagnose::redacted_state;
+use crate::disk::migrations::{migrate, schema_version, SCHEMA_VERSION};
 use crate::{components::node::RemoteNode, disk::status::*};
 //---------------------------------------------------------------------------------------------------- [State] Impl
 impl Default for State {
@@ -15,6 +17,7 @@ impl State {
         let max_threads = benri::threads!();
         let current_threads = if max_threads == 1 { 1 } else { max_threads / 2 };
         Self {
+            schema_version: SCHEMA_VERSION,
             status: Status::default(),
             gupax: Gupax::default(),
             p2pool: P2pool::default(),
@@ -39,7 +42,7 @@ impl State {
         match toml::de::from_str(string) {
             Ok(state) => {
                 info!("State | Parse ... OK");
-                print_dash(string);
+                Self::print_redacted(&state);
                 Ok(state)
             }
             Err(err) => {
@@ -49,6 +52,15 @@ impl State {
         }
     }
 
+    // Logs [self] without the tokens, the mail login and the addresses,
+    // the logs can end up in the journal.
+    fn print_redacted(&self) {
+        match toml::ser::to_string(&redacted_state(self)) {
+            Ok(string) => print_dash(&string),
+            Err(e) => warn!("State | Couldn't serialize the redacted state: {}", e),
+        }
+    }
+
     // Convert [State] to [String]
     pub fn to_string(&self) -> Result<String, TomlError> {
         match toml::ser::to_string(self) {
@@ -60,11 +72,25 @@ impl State {
         }
     }
 
+    // Deserialize [String] into a proper [Struct], migrating it first if it was
+    // written with an older schema and merging it if deserialization fails.
+    // [true] if the result differs from [string] and should be saved.
+    pub fn load(string: &str) -> Result<(Self, bool), TomlError> {
+        if schema_version(string)? >= SCHEMA_VERSION {
+            if let Ok(state) = Self::from_str(string) {
+                return Ok((state, false));
+            }
+        }
+        warn!("State | Attempting merge...");
+        Ok((Self::merge(string)?, true))
+    }
+
     // Combination of multiple functions:
     //   1. Attempt to read file from path into [String]
     //      |_ Create a default file if not found
-    //   2. Deserialize [String] into a proper [Struct]
-    //      |_ Attempt to merge if deserialization fails
+    //   2. Backup the file if it was written with another schema
+    //   3. Deserialize [String] into a proper [Struct]
+    //      |_ Attempt to migrate and merge if deserialization fails
     pub fn get(path: &PathBuf) -> Result<Self, TomlError> {
         // Read
         let file = File::State;
@@ -79,20 +105,36 @@ impl State {
                 }
             }
         };
+        // Backup, the migrated state can not be read by the older version anymore,
+        // and a newer one loses what this version does not know once saved.
+        let version = schema_version(&string)?;
+        if version != SCHEMA_VERSION {
+            Self::backup(path, version, &string)?;
+        }
         // Deserialize, attempt merge if failed
-        match Self::from_str(&string) {
-            Ok(s) => Ok(s),
-            Err(_) => {
-                warn!("State | Attempting merge...");
-                match Self::merge(&string) {
-                    Ok(mut new) => {
-                        Self::save(&mut new, path)?;
-                        Ok(new)
-                    }
-                    Err(e) => Err(e),
-                }
-            }
+        let (mut new, changed) = Self::load(&string)?;
+        if changed {
+            Self::save(&mut new, path)?;
         }
+        Ok(new)
+    }
+
+    // Copies [string], the content of [path] at schema [version],
+    // to [state.toml.v<version>.bak] unless there already is one.
+    pub fn backup(path: &Path, version: u32, string: &str) -> Result<PathBuf, TomlError> {
+        let mut backup = path.as_os_str().to_os_string();
+        backup.push(format!(".v{}.bak", version));
+        let backup = PathBuf::from(backup);
+        if backup.exists() {
+            info!(
+                "State | Backup [{}] already exists, skipping...",
+                backup.display()
+            );
+        } else {
+            fs::write(&backup, string)?;
+            info!("State | Backup to [{}] ... OK", backup.display());
+        }
+        Ok(backup)
     }
 
     // Completely overwrite current [state.toml]
@@ -107,7 +149,7 @@ impl State {
     }
 
     // Save [State] onto disk file [gupax.toml]
-    pub fn save(&mut self, path: &PathBuf) -> Result<(), TomlError> {
+    pub fn save(&mut self, path: &Path) -> Result<(), TomlError> {
         info!("State | Saving to disk...");
         // Convert path to absolute
         self.gupax.absolute_p2pool_path = into_absolute_path(self.gupax.p2pool_path.clone())?;
@@ -116,7 +158,7 @@ impl State {
         let string = match toml::ser::to_string(&self) {
             Ok(string) => {
                 info!("State | Parse ... OK");
-                print_dash(&string);
+                self.print_redacted();
                 string
             }
             Err(err) => {
@@ -124,7 +166,8 @@ impl State {
                 return Err(TomlError::Serialize(err));
             }
         };
-        match fs::write(path, string) {
+        // Holds the tokens and the mail password.
+        match write_private(path, &string) {
             Ok(_) => {
                 info!("State | Save ... OK");
                 Ok(())
@@ -138,9 +181,13 @@ impl State {
 
     // Take [String] as input, merge it with whatever the current [default] is,
     // leaving behind old keys+values and updating [default] with old valid ones.
+    // [old] is migrated to the current schema first.
     pub fn merge(old: &str) -> Result<Self, TomlError> {
+        let version = schema_version(old)?;
+        let migrated = migrate(old)?;
+        let old = migrated.as_deref().unwrap_or(old);
         let default = toml::ser::to_string(&Self::new()).unwrap();
-        let new: Self = match Figment::from(Toml::string(&default))
+        let mut new: Self = match Figment::from(Toml::string(&default))
             .merge(Toml::string(old))
             .extract()
         {
@@ -153,12 +200,16 @@ impl State {
                 return Err(TomlError::Merge(err));
             }
         };
+        // What is kept of a newer schema is what this version knows, but it
+        // is not marked older so the newer version does not migrate it again.
+        new.schema_version = SCHEMA_VERSION.max(version);
         Ok(new)
     }
 }
 //---------------------------------------------------------------------------------------------------- [State] Struct
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct State {
+    pub schema_version: u32, // See [crate::disk::migrations]
     pub status: Status,
     pub gupax: Gupax,
     pub p2pool: P2pool,
@@ -173,6 +224,7 @@ pub struct State {
 pub struct Status {
     pub submenu: Submenu,
     pub payout_view: PayoutView,
+    pub history_view: HistoryView,
     pub monero_enabled: bool,
     pub manual_hash: bool,
     pub hashrate: f64,
@@ -204,6 +256,15 @@ pub struct Gupax {
     pub tab: Tab,
     pub ratio: Ratio,
     pub bundled: bool,
+    pub http_api: bool,
+    pub http_api_ip: String,
+    pub http_api_port: String,
+    pub http_api_token: String,
+    pub http_api_metrics: bool,
+    pub dependencies: DependencyPolicy,
+    pub notifications: Notifications,
+    pub logs: LogPolicy,
+    pub profile: String, // Name of the profile in use, empty if none
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
@@ -229,6 +290,7 @@ pub struct P2pool {
     pub selected_ip: String,
     pub selected_rpc: String,
     pub selected_zmq: String,
+    pub restart: RestartPolicy,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
@@ -246,6 +308,7 @@ pub struct Node {
     pub dns_blocklist: bool,
     pub disable_dns_checkpoint: bool,
     pub path_db: String,
+    pub restart: RestartPolicy,
 }
 
 impl Default for Node {
@@ -264,6 +327,7 @@ impl Default for Node {
             dns_blocklist: true,
             disable_dns_checkpoint: true,
             path_db: String::new(),
+            restart: RestartPolicy::default(),
         }
     }
 }
@@ -291,6 +355,39 @@ pub struct Xmrig {
     pub selected_ip: String,
     pub selected_port: String,
     pub token: String,
+    pub restart: RestartPolicy,
+    pub schedule: Vec<MiningWindow>,
+    pub throttle: ThrottlePolicy,
+}
+
+// Lowers the threads of XMRig or pauses it while the CPU is too hot or too busy
+// with something else, hot-applied by the watchdog, see [helper::xrig::throttle].
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ThrottlePolicy {
+    pub enabled: bool,
+    pub max_temperature: u8, // °C, 0 ignores the temperature
+    pub max_load: u8,        // % of CPU used by everything but XMRig, 0 ignores the load
+    pub hysteresis: u8,      // How far below the thresholds before restoring, in °C/%
+    pub action: WindowAction,
+    pub threads: usize, // Only for [Mine]
+}
+
+// A weekly time range during which XMRig mines with other threads or is paused,
+// hot-applied by the watchdog, see [helper::xrig::schedule].
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct MiningWindow {
+    pub days: [bool; 7], // Monday to Sunday
+    pub start: String,   // [HH:MM], local time
+    pub end: String,     // [HH:MM], before [start] means it ends the next day
+    pub action: WindowAction,
+    pub threads: usize, // Only for [Mine]
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub enum WindowAction {
+    #[default]
+    Mine,
+    Pause,
 }
 
 // present for future.
@@ -317,6 +414,7 @@ pub struct XmrigProxy {
     pub selected_port: String,
     pub token: String,
     pub redirect_local_xmrig: bool,
+    pub restart: RestartPolicy,
 }
 
 impl Default for XmrigProxy {
@@ -347,10 +445,87 @@ impl Default for XmrigProxy {
             api_port: "18089".to_string(),
             tls: false,
             keepalive: false,
+            restart: RestartPolicy::default(),
+        }
+    }
+}
+
+// What to do when a process exits without being asked to.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    pub max_retries: u8,   // Consecutive restarts before giving up
+    pub backoff_secs: u64, // Delay before the first restart, doubled on each retry
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub enum RestartMode {
+    #[default]
+    Never,
+    Always,
+    OnFailure,
+}
+
+// How long a process waits for the ones it depends on before starting
+// (P2Pool for the Node to be synchronized, XMRig for P2Pool to be synced).
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct DependencyPolicy {
+    pub wait: bool,
+    pub timeout_secs: u64, // Start anyway after this long, 0 waits forever
+}
+
+// How the output of the processes is kept on disk (see [crate::disk::logs]).
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct LogPolicy {
+    pub enabled: bool,
+    pub max_size_mb: u64, // A log file is rotated once it reaches this size
+    pub max_files: usize, // Rotated files kept per process, the older ones are deleted
+}
+
+// Which events are notified, and to which sinks (see [crate::helper::notify]).
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Notifications {
+    // Events
+    pub payout: bool,
+    pub crash: bool,
+    pub xvb_win: bool,
+    pub xvb_offline: bool,
+    pub sync_loss: bool,
+    // Sinks
+    pub desktop: bool,
+    pub webhook: bool,
+    pub webhook_url: String,
+    pub mail: bool,
+    pub mail_server: String, // [host:port] of an SMTP server, TLS on 465 or STARTTLS if offered (cleartext if refused and no username)
+    pub mail_username: String, // Empty to not authenticate
+    pub mail_password: String,
+    pub mail_from: String,
+    pub mail_to: String, // Comma separated
+    pub command: bool,
+    pub command_line: String, // Run with the shell, the event is in the environment
+}
+
+impl Display for WindowAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mine => write!(f, "Mine"),
+            Self::Pause => write!(f, "Pause"),
         }
     }
 }
 
+impl Display for RestartMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Never => "Never",
+            Self::Always => "Always",
+            Self::OnFailure => "On failure",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub struct Xvb {
     pub simple: bool,
@@ -362,6 +537,37 @@ pub struct Xvb {
     pub manual_donation_level: ManualDonationLevel,
     pub manual_donation_metric: ManualDonationMetric,
     pub p2pool_buffer: i8,
+    // Another pool, host:port, given [extra_pool_percent] of the hashrate every round.
+    pub extra_pool: String,
+    pub extra_pool_user: String,
+    pub extra_pool_tls: bool,
+    pub extra_pool_percent: u8,
+    // The external hashrate on P2Pool is read from its stratum instead of estimated.
+    pub use_stratum: bool,
+}
+
+impl Xvb {
+    // [None] unless set with a part of the hashrate.
+    pub fn extra_pool(&self) -> Option<crate::helper::xvb::nodes::Pool> {
+        if self.extra_pool.trim().is_empty() || self.extra_pool_percent == 0 {
+            return None;
+        }
+        Some(crate::helper::xvb::nodes::Pool {
+            url: self.extra_pool.trim().to_string(),
+            user: self.extra_pool_user.trim().to_string(),
+            tls: self.extra_pool_tls,
+            keepalive: true,
+        })
+    }
+    // Switches to [mode] the way the XvB tab would, leaving the simple
+    // tab for the modes it does not have (the manual ones).
+    pub fn set_mode(&mut self, mode: XvbMode) {
+        self.simple_hero_mode = mode == XvbMode::Hero;
+        if !matches!(mode, XvbMode::Auto | XvbMode::Hero) {
+            self.simple = false;
+        }
+        self.mode = mode;
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Default)]
@@ -443,6 +649,7 @@ impl Default for Status {
         Self {
             submenu: Submenu::default(),
             payout_view: PayoutView::default(),
+            history_view: HistoryView::default(),
             monero_enabled: false,
             manual_hash: false,
             hashrate: 1.0,
@@ -480,6 +687,19 @@ impl Default for Gupax {
             bundled: true,
             #[cfg(not(feature = "bundle"))]
             bundled: false,
+            http_api: false,
+            http_api_ip: "127.0.0.1".to_string(),
+            http_api_port: "18090".to_string(),
+            http_api_token: thread_rng()
+                .sample_iter(Alphanumeric)
+                .take(16)
+                .map(char::from)
+                .collect(),
+            http_api_metrics: false,
+            dependencies: DependencyPolicy::default(),
+            notifications: Notifications::default(),
+            logs: LogPolicy::default(),
+            profile: String::new(),
         }
     }
 }
@@ -508,6 +728,7 @@ impl Default for P2pool {
             selected_ip: "localhost".to_string(),
             selected_rpc: "18081".to_string(),
             selected_zmq: "18083".to_string(),
+            restart: RestartPolicy::default(),
         }
     }
 }
@@ -521,6 +742,32 @@ impl Xmrig {
             ..xmrig
         }
     }
+    // Names XMRig shows on the stratum of P2Pool before XvB switches it,
+    // see [Helper::build_xmrig_args_and_mutate_img] and [StratumWorker::external_hashrate].
+    pub fn stratum_logins(&self) -> Vec<String> {
+        stratum_logins(self.simple, &self.simple_rig, &self.address, &self.rig)
+    }
+}
+
+impl XmrigProxy {
+    // Same as [Xmrig::stratum_logins], see [Helper::build_xp_args].
+    pub fn stratum_logins(&self) -> Vec<String> {
+        stratum_logins(self.simple, &self.simple_rig, &self.address, &self.rig)
+    }
+}
+
+// The user, which is the rig name in simple mode, and the rig ID in advanced mode.
+fn stratum_logins(simple: bool, simple_rig: &str, address: &str, rig: &str) -> Vec<String> {
+    let logins = if simple {
+        vec![simple_rig]
+    } else {
+        vec![address, rig]
+    };
+    logins
+        .into_iter()
+        .filter(|login| !login.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 impl Default for Xmrig {
     fn default() -> Self {
@@ -550,6 +797,9 @@ impl Default for Xmrig {
                 .take(16)
                 .map(char::from)
                 .collect(),
+            restart: RestartPolicy::default(),
+            schedule: vec![],
+            throttle: ThrottlePolicy::default(),
         }
     }
 }
@@ -566,6 +816,89 @@ impl Default for Xvb {
             manual_donation_level: Default::default(),
             manual_donation_metric: Default::default(),
             p2pool_buffer: 25,
+            extra_pool: String::new(),
+            extra_pool_user: String::new(),
+            extra_pool_tls: false,
+            extra_pool_percent: 0,
+            use_stratum: false,
+        }
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::default(),
+            max_retries: 5,
+            backoff_secs: 5,
+        }
+    }
+}
+
+impl Default for MiningWindow {
+    fn default() -> Self {
+        Self {
+            days: [true; 7],
+            start: "00:00".to_string(),
+            end: "06:00".to_string(),
+            action: WindowAction::default(),
+            threads: 1,
+        }
+    }
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_temperature: 85,
+            max_load: 50,
+            hysteresis: 5,
+            action: WindowAction::Mine,
+            threads: 1,
+        }
+    }
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_mb: 10,
+            max_files: 5,
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            payout: true,
+            crash: true,
+            xvb_win: true,
+            xvb_offline: true,
+            sync_loss: true,
+            desktop: false,
+            webhook: false,
+            webhook_url: String::new(),
+            mail: false,
+            mail_server: "127.0.0.1:25".to_string(),
+            mail_username: String::new(),
+            mail_password: String::new(),
+            mail_from: String::new(),
+            mail_to: String::new(),
+            command: false,
+            command_line: String::new(),
+        }
+    }
+}
+
+impl Default for DependencyPolicy {
+    // Off, or the states written before it would start to wait once merged.
+    fn default() -> Self {
+        Self {
+            wait: false,
+            timeout_secs: 600,
         }
     }
 }
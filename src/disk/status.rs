@@ -5,6 +5,7 @@ pub enum Submenu {
     Processes,
     P2pool,
     Benchmarks,
+    History,
 }
 
 impl Default for Submenu {
@@ -51,6 +52,21 @@ impl Display for PayoutView {
     }
 }
 
+//---------------------------------------------------------------------------------------------------- [HistoryView] enum for [Status/History] tab
+// Which time range of the stats history the graphs show.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub enum HistoryView {
+    #[default]
+    Day, // Per minute samples, last 24 hours
+    Month, // Per hour samples, last 30 days
+}
+
+impl Display for HistoryView {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 //---------------------------------------------------------------------------------------------------- [Hash] enum for [Status/P2Pool]
 #[derive(Clone, Copy, Eq, PartialEq, Debug, Deserialize, Serialize)]
 #[allow(clippy::enum_variant_names)]
@@ -3,6 +3,7 @@
 mod test {
     use crate::disk::node::Node;
     use crate::disk::pool::Pool;
+    use crate::disk::processes::{Processes, PROCESSES_TOML_DEFAULT};
     use crate::disk::state::State;
     #[test]
     fn serde_default_state() {
@@ -22,10 +23,17 @@ mod test {
         let string = Pool::to_string(&pool).unwrap();
         Pool::from_str_to_vec(&string).unwrap();
     }
+    #[test]
+    fn serde_default_processes() {
+        let processes = Processes::from_str_to_vec(PROCESSES_TOML_DEFAULT).unwrap();
+        assert!(processes.is_empty());
+    }
 
     #[test]
     fn serde_custom_state() {
         let state = r#"
+			schema_version = 1
+
 			[gupax]
 			simple = true
 			auto_update = true
@@ -50,10 +58,20 @@ mod test {
 			tab = "About"
 			ratio = "Width"
 			bundled = false
+			http_api = false
+			http_api_ip = "127.0.0.1"
+			http_api_port = "18090"
+			http_api_token = "ABCDEFGHIJKLMNOP"
+			http_api_metrics = true
+			dependencies = { wait = true, timeout_secs = 600 }
+			notifications = { payout = true, crash = true, xvb_win = true, xvb_offline = false, sync_loss = true, desktop = true, webhook = true, webhook_url = "https://example.com/hook", mail = false, mail_server = "127.0.0.1:25", mail_username = "", mail_password = "", mail_from = "gupaxx@localhost", mail_to = "me@localhost", command = false, command_line = "" }
+			logs = { enabled = true, max_size_mb = 10, max_files = 5 }
+			profile = "home"
 
 			[status]
 			submenu = "P2pool"
 			payout_view = "Oldest"
+			history_view = "Month"
 			monero_enabled = true
 			manual_hash = false
 			hashrate = 1241.23
@@ -81,6 +99,7 @@ mod test {
 			selected_ip = "192.168.1.123"
 			selected_rpc = "18089"
 			selected_zmq = "18083"
+			restart = { mode = "OnFailure", max_retries = 5, backoff_secs = 5 }
 
 			[xmrig]
 			simple = true
@@ -104,6 +123,12 @@ mod test {
 			selected_ip = "192.168.1.122"
 			selected_port = "3333"
             token = "testtoken"
+            restart = { mode = "Always", max_retries = 3, backoff_secs = 10 }
+            schedule = [
+                { days = [true, true, true, true, true, false, false], start = "07:00", end = "10:00", action = "Pause", threads = 1 },
+                { days = [true, true, true, true, true, true, true], start = "22:00", end = "06:00", action = "Mine", threads = 32 },
+            ]
+            throttle = { enabled = true, max_temperature = 80, max_load = 40, hysteresis = 5, action = "Mine", threads = 4 }
 
             [xmrig_proxy]
             simple = true
@@ -127,6 +152,7 @@ mod test {
 			selected_ip = "192.168.1.122"
 			selected_port = "3333"
             redirect_local_xmrig = true
+            restart = { mode = "Never", max_retries = 5, backoff_secs = 5 }
 
             [xvb]
 			simple = true
@@ -140,6 +166,11 @@ mod test {
             hero = false
             node = "Europe"
             p2pool_buffer = 5
+            extra_pool = ""
+            extra_pool_user = ""
+            extra_pool_tls = false
+            extra_pool_percent = 0
+            use_stratum = false
 
             [node]
             simple = false
@@ -155,6 +186,7 @@ mod test {
             dns_blocklist = true
             disable_dns_checkpoint = true
             path_db = ""
+            restart = { mode = "OnFailure", max_retries = 0, backoff_secs = 1 }
 
 			[version]
 			gupax = "v1.3.0"
@@ -210,6 +242,37 @@ mod test {
         Pool::to_string(&pool).unwrap();
     }
 
+    #[test]
+    fn serde_custom_processes() {
+        let processes = r#"
+			[[process]]
+			name = "Tor"
+			path = "/usr/bin/tor"
+			args = ["-f", "/etc/tor/torrc"]
+			env = { LANG = "C" }
+			alive_regex = "Bootstrapped 100%"
+			restart = { mode = "OnFailure", max_retries = 3, backoff_secs = 5 }
+
+			[[process]]
+			name = "Script"
+			path = "scripts/run.sh"
+		"#;
+        let processes = Processes::from_str_to_vec(processes).unwrap();
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[0].env["LANG"], "C");
+        assert_eq!(processes[0].restart.max_retries, 3);
+        assert!(processes[1].args.is_empty());
+        assert!(processes[1].alive_regex.is_none());
+        // Invalid regex.
+        let processes = r#"
+			[[process]]
+			name = "Bad"
+			path = "bad"
+			alive_regex = "("
+		"#;
+        assert!(Processes::from_str_to_vec(processes).is_err());
+    }
+
     // Make sure we keep the user's old values that are still
     // valid but discard the ones that don't exist anymore.
     #[test]
@@ -296,6 +359,86 @@ mod test {
         assert!(merged_state.contains("backup_host = true"));
     }
 
+    #[test]
+    fn state_migrations() {
+        use crate::constants::GUPAX_VERSION_UNDERSCORE;
+        use crate::disk::migrations::{migrate, schema_version, SCHEMA_VERSION};
+        use crate::disk::state::XvbMode;
+
+        let path = std::env::temp_dir().join("gupaxx_test_migrations");
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        let state_path = path.join("state.toml");
+        let backup = path.join("state.toml.v0.bak");
+
+        // Every past version loads, is backed up and saved with the current schema.
+        let fixtures = [
+            include_str!("fixtures/state_gupax_v1.3.5.toml"),
+            include_str!("fixtures/state_gupaxx_v1.5.3.toml"),
+        ];
+        let mut states = vec![];
+        for fixture in fixtures {
+            let _ = std::fs::remove_file(&backup);
+            assert_eq!(schema_version(fixture).unwrap(), 0);
+            std::fs::write(&state_path, fixture).unwrap();
+            let state = State::get(&state_path).unwrap();
+            assert_eq!(state.schema_version, SCHEMA_VERSION);
+            assert_eq!(std::fs::read_to_string(&backup).unwrap(), fixture);
+            let saved = std::fs::read_to_string(&state_path).unwrap();
+            assert_eq!(schema_version(&saved).unwrap(), SCHEMA_VERSION);
+            assert!(!State::load(&saved).unwrap().1);
+            states.push(state);
+        }
+
+        // Gupax: the settings are kept, what was renamed or removed is migrated.
+        let gupax = &states[0];
+        assert!(!gupax.gupax.simple);
+        assert_eq!(gupax.gupax.p2pool_path, "P2Pool/p2pool");
+        assert_eq!(gupax.xmrig.current_threads, 6);
+        assert_eq!(gupax.xmrig.rig, GUPAX_VERSION_UNDERSCORE);
+        assert_eq!(gupax.xmrig.selected_rig, GUPAX_VERSION_UNDERSCORE);
+        assert_eq!(gupax.xvb.p2pool_buffer, 25);
+        let migrated = migrate(fixtures[0]).unwrap().unwrap();
+        assert!(!migrated.contains("update_via_tor"));
+
+        // Gupaxx v1.5.3: the rig chosen by the user stays.
+        let gupaxx = &states[1];
+        assert_eq!(gupaxx.xmrig.rig, "Gupaxx");
+        assert_eq!(gupaxx.xvb.mode, XvbMode::Hero);
+        assert_eq!(gupaxx.xvb.p2pool_buffer, 5);
+        assert_eq!(gupaxx.node.out_peers, 32);
+        // Processes start as they used to, without waiting for their dependencies.
+        assert!(!gupaxx.gupax.dependencies.wait);
+
+        // An existing backup is not replaced.
+        std::fs::write(&state_path, fixtures[0]).unwrap();
+        State::get(&state_path).unwrap();
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), fixtures[1]);
+
+        // A buffer out of the range of the slider no longer fails the whole merge.
+        for (buffer, migrated) in [("250", 100), ("-12.6", -13)] {
+            let old = format!("[xvb]\np2pool_buffer = {}\n", buffer);
+            assert_eq!(State::merge(&old).unwrap().xvb.p2pool_buffer, migrated);
+        }
+        // The current schema is left alone, a newer one is read as far as known.
+        let current = State::new().to_string().unwrap();
+        assert!(migrate(&current).unwrap().is_none());
+        let newer = "schema_version = 99\n[p2pool]\nmini = false\n";
+        assert!(migrate(newer).unwrap().is_none());
+        let state = State::merge(newer).unwrap();
+        assert!(!state.p2pool.mini);
+        assert_eq!(state.schema_version, 99);
+        // Backed up before being saved, without lowering its schema.
+        let backup = path.join("state.toml.v99.bak");
+        std::fs::write(&state_path, newer).unwrap();
+        let state = State::get(&state_path).unwrap();
+        assert_eq!(state.schema_version, 99);
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), newer);
+        let saved = std::fs::read_to_string(&state_path).unwrap();
+        assert_eq!(schema_version(&saved).unwrap(), 99);
+        assert!(!State::load(&saved).unwrap().1);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
     #[test]
     fn create_and_serde_gupax_p2pool_api() {
         use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
@@ -332,6 +475,514 @@ mod test {
             .contains("2022-01-27 01:30:23.1377 | 0.000000000001 XMR | Block 2,642,816"));
     }
 
+    #[test]
+    fn create_and_record_history() {
+        use crate::disk::history::{History, HistorySample};
+
+        // Use a temporary directory, not to mess with the real history.
+        let path = std::env::temp_dir().join("gupaxx_test_history");
+        let _ = std::fs::remove_dir_all(&path);
+        let mut history = History::new();
+        history.fill_paths(&path);
+        History::create_all_files(&path).unwrap();
+        history.read_all_files_and_update().unwrap();
+        assert!(history.minutes.is_empty());
+
+        // Record a sample per second from the start of last hour, until a minute into this one.
+        let now = HistorySample::now();
+        let start = now - now % 3600 - 3600;
+        for (i, timestamp) in (start..=start + 3660).enumerate() {
+            history
+                .record(HistorySample {
+                    timestamp,
+                    xmrig_hashrate: if i % 2 == 0 { 1000.0 } else { 3000.0 },
+                    shares_found: i as u64,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        // 61 minutes, the first 60 averaged into 1 hour.
+        assert_eq!(history.minutes.len(), 61);
+        assert_eq!(history.hours.len(), 1);
+        assert_eq!(history.minutes[0].timestamp, start);
+        assert_eq!(history.minutes[0].xmrig_hashrate, 2000.0);
+        assert_eq!(history.minutes[0].shares_found, 59);
+        assert_eq!(history.hours[0].xmrig_hashrate, 2000.0);
+        assert_eq!(history.hours[0].shares_found, 3599);
+
+        // Read back from disk.
+        let mut read = History::new();
+        read.fill_paths(&path);
+        read.read_all_files_and_update().unwrap();
+        assert_eq!(read.minutes, history.minutes);
+        assert_eq!(read.hours, history.hours);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn create_and_record_xvb_rounds() {
+        use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+        use crate::disk::xvb_rounds::{XvbRoundRecord, XvbRounds};
+        use crate::helper::xvb::rounds::XvbRound;
+
+        // Use a temporary directory, not to mess with the real payouts.
+        let path = std::env::temp_dir().join("gupaxx_test_xvb_rounds");
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        let mut api = GupaxP2poolApi::new();
+        api.fill_paths(&path);
+        GupaxP2poolApi::create_all_files(&path).unwrap();
+        api.read_all_files_and_update().unwrap();
+        assert!(api.xvb_rounds.records.is_empty());
+
+        let won = XvbRoundRecord {
+            timestamp: 1_700_000_000,
+            block_height: 3_000_000,
+            round: Some(XvbRound::DonorVip),
+            donor_1h_avg: 12000.0,
+            donor_24h_avg: 10500.5,
+            xvb_seconds: 3000,
+            won: true,
+        };
+        let without_share = XvbRoundRecord {
+            timestamp: 1_700_003_600,
+            block_height: 3_000_030,
+            xvb_seconds: 600,
+            ..Default::default()
+        };
+        api.xvb_rounds.push(won.clone()).unwrap();
+        api.xvb_rounds.push(without_share.clone()).unwrap();
+        // Invalid lines are skipped.
+        GupaxP2poolApi::disk_append("1,2,NotARound,0,0,0,false", &api.xvb_rounds.path).unwrap();
+
+        // Read back from disk.
+        let mut read = XvbRounds::new();
+        read.fill_paths(&path);
+        read.read().unwrap();
+        assert_eq!(read.records, vec![won, without_share]);
+        let summary = read.summary();
+        assert_eq!(summary.rounds, 2);
+        assert_eq!(summary.participated(), 1);
+        assert_eq!(summary.donor_vip, 1);
+        assert_eq!(summary.xvb_seconds, 3600);
+        assert_eq!(summary.wins, vec![3_000_000]);
+        assert!(read
+            .to_string()
+            .contains("Wins         | 1 (blocks 3000000)"));
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn rotate_and_export_logs() {
+        use crate::disk::logs::{export, rotated_files, LogFile};
+        use crate::disk::state::LogPolicy;
+
+        let path = std::env::temp_dir().join("gupaxx_test_logs");
+        let _ = std::fs::remove_dir_all(&path);
+        let policy = LogPolicy {
+            enabled: true,
+            max_size_mb: 1,
+            max_files: 2,
+        };
+        // Nothing is written while disabled.
+        let mut log = LogFile::open(
+            &path,
+            "p2pool",
+            LogPolicy {
+                enabled: false,
+                ..policy
+            },
+        );
+        log.write_line("disabled");
+        assert!(!path.exists());
+
+        // 4MB of lines, rotated every 1MB, keeping the 2 newest.
+        let mut log = LogFile::open(&path, "p2pool", policy);
+        // 100KB with the time.
+        let line = "x".repeat(99_974);
+        for _ in 0..40 {
+            log.write_line(&line);
+        }
+        let mut other = LogFile::open(&path, "p2pool-other", policy);
+        other.write_line("first line");
+        let rotated = rotated_files(&path, "p2pool").unwrap();
+        assert_eq!(rotated.len(), 2);
+        assert!(rotated[0] < rotated[1]);
+        assert!(rotated_files(&path, "p2pool-other").unwrap().is_empty());
+        let current = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(current.lines().count(), 10);
+        // Every line starts with its time.
+        let other = std::fs::read_to_string(other.path()).unwrap();
+        assert!(other.ends_with(" | first line\n"));
+        assert!(chrono::NaiveDateTime::parse_from_str(
+            other.split(" | ").next().unwrap(),
+            "%Y-%m-%d %H:%M:%S%.3f"
+        )
+        .is_ok());
+
+        // Export copies every file.
+        let exported = export(&path, &path.join("export")).unwrap();
+        assert_eq!(std::fs::read_dir(&exported).unwrap().count(), 4);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn diagnose_bundle() {
+        use crate::disk::diagnose::Bundle;
+        use crate::disk::state::State;
+        use std::io::Read;
+
+        let path = std::env::temp_dir().join("gupaxx_test_diagnose");
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(path.join("logs")).unwrap();
+        let address = "44hintoFpuo3ugKfcqJvh5BmrsTRpnTasJmetKC4VXCt6QDtbHVuixdTtsm6Ptp7Y8haXnJ6j8Gj2dra8CKy5ewz7Vi9CYW";
+        let mut state = State::new();
+        state.p2pool.address = address.to_string();
+        state.xvb.token = "123456789".to_string();
+        state.xvb.extra_pool_user = "pool-login".to_string();
+        let notifications = &mut state.gupax.notifications;
+        notifications.mail_username = "miner-login".to_string();
+        notifications.mail_from = "rig@example.org".to_string();
+        notifications.mail_to = "me@example.org, other@example.org".to_string();
+        notifications.command_line = "notify --key s3cr3t".to_string();
+        state.xmrig.arguments = "--user key-in-args".to_string();
+        std::fs::write(path.join("node.toml"), "[nodes]\n").unwrap();
+        std::fs::write(
+            path.join("processes.toml"),
+            "[[process]]\nname = \"Plugin\"\npath = \"/bin/plugin\"\n\
+             args = [\"--api-key\", \"arg-secret\"]\nenv = { API_KEY = \"env-secret\" }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            path.join("logs/p2pool.log"),
+            format!("Your wallet address = {}\n", address),
+        )
+        .unwrap();
+        // Rotated logs are left out.
+        std::fs::write(path.join("logs/p2pool.2024-01-01_00-00-00.000.log"), "old").unwrap();
+
+        let mut bundle = Bundle::new(&state, &path);
+        bundle.add("consoles/xvb.txt", "token 123456789 rejected\n".to_string());
+        bundle.add(
+            "consoles/p2pool.txt",
+            "mail to other@example.org as miner-login failed\n".to_string(),
+        );
+        let tar_gz = bundle.write(&path).unwrap();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(
+            std::fs::File::open(tar_gz).unwrap(),
+        ));
+        let mut files = std::collections::HashMap::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let (_, name) = name.split_once('/').unwrap();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            files.insert(name.to_string(), content);
+        }
+        for file in [
+            "state.toml",
+            "node.toml",
+            "pool.toml",
+            "p2pool/log",
+            "versions.txt",
+            "system.txt",
+            "logs/p2pool.log",
+            "consoles/xvb.txt",
+        ] {
+            assert!(files.contains_key(file), "missing {}", file);
+        }
+        assert_eq!(files.len(), 12);
+        assert_eq!(files["node.toml"], "[nodes]\n");
+        // A missing file is noted.
+        assert!(files["pool.toml"].starts_with("Could not read"));
+        // Nothing gives away the address or the token.
+        for content in files.values() {
+            assert!(!content.contains(address));
+            assert!(!content.contains("123456789"));
+            for secret in [
+                "miner-login",
+                "example.org",
+                "s3cr3t",
+                "pool-login",
+                "key-in-args",
+                "arg-secret",
+                "env-secret",
+            ] {
+                assert!(!content.contains(secret));
+            }
+        }
+        assert!(files["logs/p2pool.log"].contains("44hintoF...z7Vi9CYW"));
+        assert!(files["consoles/xvb.txt"].contains("<redacted>"));
+        let redacted = State::from_str(&files["state.toml"]).unwrap();
+        assert_eq!(redacted.p2pool.address, "44hintoF...z7Vi9CYW");
+        assert!(redacted.xvb.token.is_empty());
+        assert!(redacted.gupax.notifications.mail_to.is_empty());
+        assert!(redacted.gupax.notifications.command_line.is_empty());
+        assert_eq!(redacted.xmrig.arguments, "<redacted>");
+        assert!(redacted.p2pool.arguments.is_empty());
+        // What the custom processes are is kept.
+        let processes = Processes::from_str_to_vec(&files["processes.toml"]).unwrap();
+        assert_eq!(processes[0].name, "Plugin");
+        assert_eq!(processes[0].args, ["<redacted>", "<redacted>"]);
+        assert_eq!(processes[0].env["API_KEY"], "<redacted>");
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn instance_lock() {
+        use crate::disk::consts::LOCK_FILE;
+        use crate::disk::lock::{InstanceLock, LockError};
+
+        let path = std::env::temp_dir().join("gupaxx_test_lock");
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        let file = path.join(LOCK_FILE);
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            std::process::id().to_string()
+        );
+        // Held, by another open of the file like another instance would.
+        assert!(matches!(
+            InstanceLock::acquire(&path),
+            Err(LockError::Running(Some(pid))) if pid == std::process::id()
+        ));
+        drop(lock);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "");
+        // Left behind by a dead instance, the OS released its lock.
+        std::fs::write(&file, u32::MAX.to_string()).unwrap();
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            std::process::id().to_string()
+        );
+        drop(lock);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+    #[test]
+    fn state_profiles() {
+        use crate::disk::profiles;
+
+        let path = std::env::temp_dir().join("gupaxx_test_profiles");
+        let _ = std::fs::remove_dir_all(&path);
+        let dir = path.join("profiles");
+        let state_path = path.join("state.toml");
+
+        assert!(profiles::valid_name("work laptop (low threads)"));
+        for name in ["", " home", "../state", "a/b", "a\\b", ".hidden", "a:b"] {
+            assert!(!profiles::valid_name(name), "{}", name);
+        }
+        assert!(profiles::list(&dir).is_empty());
+
+        let mut home = State::new();
+        home.xmrig.current_threads = 1;
+        profiles::save(&dir, "home", &home).unwrap();
+        let mut work = State::new();
+        work.p2pool.mini = false;
+        profiles::save(&dir, "Work", &work).unwrap();
+        assert!(profiles::save(&dir, "../escape", &work).is_err());
+        assert_eq!(profiles::list(&dir), ["home", "Work"]);
+        // They hold the same secrets as [state.toml].
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(dir.join("home.toml")), 0o600);
+            std::fs::set_permissions(dir.join("Work.toml"), PermissionsExt::from_mode(0o644))
+                .unwrap();
+            profiles::save(&dir, "Work", &work).unwrap();
+            assert_eq!(mode(dir.join("Work.toml")), 0o600);
+        }
+
+        // Loading tells which profile the state came from.
+        let loaded = profiles::load(&dir, "home").unwrap();
+        assert_eq!(loaded.gupax.profile, "home");
+        assert_eq!(loaded.xmrig.current_threads, 1);
+        assert!(profiles::load(&dir, "none").is_err());
+
+        // Activating makes it the state in use.
+        profiles::activate(&dir, "Work", &state_path).unwrap();
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&state_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let state = State::get(&state_path).unwrap();
+        assert_eq!(state.gupax.profile, "Work");
+        assert!(!state.p2pool.mini);
+
+        profiles::delete(&dir, "home").unwrap();
+        assert_eq!(profiles::list(&dir), ["Work"]);
+        assert!(!profiles::exists(&dir, "home"));
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+    #[test]
+    fn config_archive() {
+        use crate::disk::archive::{self, Import, Payouts};
+        use crate::disk::consts::{NODE_TOML, POOL_TOML};
+        use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+        use crate::disk::{create_gupax_p2pool_dir, get_gupax_p2pool_path};
+
+        let path = std::env::temp_dir().join("gupaxx_test_archive");
+        let _ = std::fs::remove_dir_all(&path);
+        let (from, to) = (path.join("from"), path.join("to"));
+        std::fs::create_dir_all(&from).unwrap();
+        std::fs::create_dir_all(&to).unwrap();
+
+        // The rig exported from.
+        let mut state = State::new();
+        state.p2pool.mini = false;
+        state.xmrig.current_threads = 37;
+        state.gupax.http_api_token = "secret".to_string();
+        state.xvb.extra_pool_user = "pool-secret".to_string();
+        state.gupax.profile = "home".to_string();
+        state.gupax.xmrig_path = "elsewhere/xmrig".to_string();
+        state.gupax.notifications.command_line = "curl example.com".to_string();
+        let nodes = vec![("Local".to_string(), Node::localhost())];
+        Node::save(&nodes, &from.join(NODE_TOML)).unwrap();
+        let pools = vec![("Local".to_string(), Pool::p2pool())];
+        Pool::save(&pools, &from.join(POOL_TOML)).unwrap();
+        let p2pool = get_gupax_p2pool_path(&from);
+        create_gupax_p2pool_dir(&p2pool).unwrap();
+        GupaxP2poolApi::create_all_files(&p2pool).unwrap();
+        std::fs::write(p2pool.join("payout"), "2\n").unwrap();
+        std::fs::write(p2pool.join("xmr"), "500\n").unwrap();
+        let archive = archive::export(&state, &from, true, &path).unwrap();
+        // Holds the tokens and the wallet, not encrypted.
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&archive).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // The new rig, only the defaults.
+        let import = Import::read(&archive).unwrap();
+        assert_eq!(import.manifest.format, archive::ARCHIVE_FORMAT);
+        assert!(import.manifest.payouts);
+        let current = State::new();
+        let changes = import.changes(&current, &[], &[], None);
+        assert!(changes.contains(&"p2pool.mini: true -> false".to_string()));
+        assert!(changes
+            .iter()
+            .any(|c| c.starts_with("xmrig.current_threads:")));
+        // Secrets are not shown.
+        let token = changes
+            .iter()
+            .find(|c| c.starts_with("gupax.http_api_token:"))
+            .unwrap();
+        assert!(token.ends_with("-> <hidden>") && !token.contains("secret"));
+        assert!(changes.contains(&"xvb.extra_pool_user: \"\" -> <hidden>".to_string()));
+        assert!(changes.contains(&"node.toml: add [Local]".to_string()));
+        assert!(changes.contains(&"pool.toml: add [Local]".to_string()));
+        assert!(changes.contains(&"payout history: 0 payouts -> 2 payouts".to_string()));
+        // The profile in use stays, the imported one may not exist here.
+        assert!(!changes.iter().any(|c| c.starts_with("gupax.profile")));
+
+        // What is run is marked, and only replaced if allowed.
+        assert!(changes.contains(&format!(
+            "[program] gupax.xmrig_path: \"{}\" -> \"elsewhere/xmrig\"",
+            current.gupax.xmrig_path
+        )));
+        assert!(changes
+            .iter()
+            .any(|c| c.starts_with("[program] gupax.notifications.command_line:")));
+        let state_path = to.join("state.toml");
+        let kept = import.apply(&current, false, &state_path, &to).unwrap();
+        assert!(!kept.p2pool.mini);
+        assert_eq!(kept.gupax.xmrig_path, current.gupax.xmrig_path);
+        assert!(kept.gupax.notifications.command_line.is_empty());
+        let nodes = Node::get(&to.join(NODE_TOML)).unwrap();
+        let pools = Pool::get(&to.join(POOL_TOML)).unwrap();
+        let payouts = Payouts::read(&to).ok();
+        let left = import.changes(&kept, &nodes, &pools, payouts.as_ref());
+        assert!(left.len() == 2 && left.iter().all(|c| c.starts_with("[program] ")));
+        let applied = import.apply(&kept, true, &state_path, &to).unwrap();
+        assert_eq!(applied.gupax.xmrig_path, "elsewhere/xmrig");
+        assert!(!applied.p2pool.mini);
+        assert_eq!(State::get(&state_path).unwrap().xmrig.current_threads, 37);
+        assert_eq!(Node::get(&to.join(NODE_TOML)).unwrap(), nodes);
+        assert_eq!(Pool::get(&to.join(POOL_TOML)).unwrap(), pools);
+        let payouts = Payouts::read(&to).unwrap();
+        assert_eq!((payouts.payout, payouts.xmr), (2, 500));
+
+        // Importing again changes nothing.
+        let nodes = Node::get(&to.join(NODE_TOML)).unwrap();
+        let pools = Pool::get(&to.join(POOL_TOML)).unwrap();
+        assert!(import
+            .changes(&applied, &nodes, &pools, Some(&payouts))
+            .is_empty());
+
+        // Not an archive of ours.
+        std::fs::write(path.join("bad.tar.gz"), "nope").unwrap();
+        assert!(Import::read(&path.join("bad.tar.gz")).is_err());
+
+        // Entries that are not ours are skipped, too many or too large ones refused.
+        // The manifest and state of the archive, along with [extra] entries.
+        let mut extracted = tar::Archive::new(flate2::read::GzDecoder::new(
+            std::fs::File::open(&archive).unwrap(),
+        ));
+        let mut entries = vec![];
+        for entry in extracted.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().file_name().unwrap().to_owned();
+            if name == "manifest.toml" || name == "state.toml" {
+                let mut content = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut content).unwrap();
+                entries.push((name.to_string_lossy().to_string(), content));
+            }
+        }
+        let read_with = |extra: &[(&str, u64)]| {
+            let file = std::fs::File::create(path.join("extra.tar.gz")).unwrap();
+            let gz = flate2::write::GzEncoder::new(file, flate2::Compression::fast());
+            let mut tar = tar::Builder::new(gz);
+            for (file, content) in &entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_cksum();
+                tar.append_data(&mut header, format!("archive/{}", file), content.as_bytes())
+                    .unwrap();
+            }
+            for (file, size) in extra {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(*size);
+                header.set_cksum();
+                let data = std::io::Read::take(std::io::repeat(b'a'), *size);
+                tar.append_data(&mut header, format!("archive/{}", file), data)
+                    .unwrap();
+            }
+            tar.into_inner().unwrap().finish().unwrap();
+            Import::read(&path.join("extra.tar.gz"))
+        };
+        assert!(read_with(&[("notes.txt", 64 * 1024 * 1024)]).is_ok());
+        let e = read_with(&[(NODE_TOML, 33 * 1024 * 1024)]).unwrap_err();
+        assert!(e.to_string().contains("larger than 32 MiB"));
+        let many: Vec<_> = (0..64).map(|i| (format!("{}.txt", i), 0)).collect();
+        let many: Vec<_> = many.iter().map(|(f, s)| (f.as_str(), *s)).collect();
+        let e = read_with(&many).unwrap_err();
+        assert!(e.to_string().contains("more than 64 entries"));
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+    #[test]
+    fn xvb_set_mode() {
+        use crate::disk::state::{Xvb, XvbMode};
+        let mut xvb = Xvb::default();
+        assert!(xvb.simple);
+        // Hero and Auto exist in the simple tab.
+        xvb.set_mode(XvbMode::Hero);
+        assert!(xvb.simple && xvb.simple_hero_mode);
+        xvb.set_mode(XvbMode::Auto);
+        assert!(xvb.simple && !xvb.simple_hero_mode);
+        // The manual modes only in the advanced one.
+        xvb.set_mode(XvbMode::ManualXvb);
+        assert!(!xvb.simple && !xvb.simple_hero_mode);
+        assert_eq!(xvb.mode, XvbMode::ManualXvb);
+        xvb.set_mode(XvbMode::Hero);
+        assert!(!xvb.simple);
+        assert_eq!(xvb.mode, XvbMode::Hero);
+    }
     #[test]
     fn convert_hash() {
         use crate::disk::status::Hash;
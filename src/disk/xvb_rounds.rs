@@ -0,0 +1,231 @@
+use super::*;
+use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use crate::helper::xvb::rounds::XvbRound;
+use chrono::{DateTime, Local};
+use readable::num::Float;
+use readable::up::Uptime;
+use serde::de::{value, IntoDeserializer};
+//---------------------------------------------------------------------------------------------------- XvB rounds
+// The raffles of XvB drawn while the XvB process was running, what we took part in and
+// if we won. Lives next to the payout log of [GupaxP2poolApi].
+// ~/.local/share/gupaxx/p2pool/
+// ├─ xvb // One line per raffle, the oldest first
+const XVB_ROUNDS_CSV_HEADER: &str =
+    "timestamp,block_height,round,donor_1h_avg,donor_24h_avg,xvb_seconds,won";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XvbRoundRecord {
+    pub timestamp: u64,          // UNIX timestamp (seconds) the raffle was seen
+    pub block_height: u64,       // Block of the raffle
+    pub round: Option<XvbRound>, // Round we took part in, [None] without a share
+    pub donor_1h_avg: f32,       // H/s donated, as XvB measured it before the raffle
+    pub donor_24h_avg: f32,      // H/s
+    pub xvb_seconds: u32,        // Time the algorithm gave to XvB since the previous raffle
+    pub won: bool,
+}
+
+impl XvbRoundRecord {
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.timestamp,
+            self.block_height,
+            // The name of the variant, which [XvbRound] deserializes from.
+            self.round
+                .as_ref()
+                .map_or(String::new(), |r| format!("{:?}", r)),
+            self.donor_1h_avg,
+            self.donor_24h_avg,
+            self.xvb_seconds,
+            self.won,
+        )
+    }
+
+    fn from_csv(line: &str) -> Option<Self> {
+        let mut fields = line.split(',');
+        let mut next = || fields.next().map(str::trim);
+        let record = Self {
+            timestamp: next()?.parse().ok()?,
+            block_height: next()?.parse().ok()?,
+            round: match next()? {
+                "" => None,
+                round => Some(
+                    XvbRound::deserialize(IntoDeserializer::<value::Error>::into_deserializer(
+                        round,
+                    ))
+                    .ok()?,
+                ),
+            },
+            donor_1h_avg: next()?.parse().ok()?,
+            donor_24h_avg: next()?.parse().ok()?,
+            xvb_seconds: next()?.parse().ok()?,
+            won: next()?.parse().ok()?,
+        };
+        Some(record)
+    }
+
+    pub fn date(&self) -> String {
+        DateTime::from_timestamp(self.timestamp as i64, 0)
+            .map(|date| {
+                date.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn round_str(&self) -> String {
+        self.round
+            .as_ref()
+            .map_or("None".to_string(), |r| r.to_string())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct XvbRounds {
+    pub records: Vec<XvbRoundRecord>, // Oldest first
+    pub path: PathBuf,
+}
+
+impl XvbRounds {
+    //---------------------------------------------------------------------------------------------------- Init
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fill_paths(&mut self, gupax_p2pool_dir: &Path) {
+        self.path = gupax_p2pool_dir.join(GUPAX_P2POOL_API_XVB);
+    }
+
+    pub fn create_file(gupax_p2pool_dir: &Path) -> Result<(), TomlError> {
+        let path = gupax_p2pool_dir.join(GUPAX_P2POOL_API_XVB);
+        if path.exists() {
+            info!(
+                "GupaxP2poolApi | [{}] already exists, skipping...",
+                path.display()
+            );
+            return Ok(());
+        }
+        fs::write(&path, format!("{}\n", XVB_ROUNDS_CSV_HEADER))?;
+        info!("GupaxP2poolApi | [{}] create ... OK", path.display());
+        Ok(())
+    }
+
+    // Invalid lines are skipped.
+    pub fn read(&mut self) -> Result<(), TomlError> {
+        self.records = Self::parse(&read_to_string(File::XvbRounds, &self.path)?);
+        Ok(())
+    }
+
+    pub fn parse(string: &str) -> Vec<XvbRoundRecord> {
+        string
+            .lines()
+            .skip(1)
+            .filter_map(XvbRoundRecord::from_csv)
+            .collect()
+    }
+
+    //---------------------------------------------------------------------------------------------------- Live
+    pub fn push(&mut self, record: XvbRoundRecord) -> Result<(), TomlError> {
+        GupaxP2poolApi::disk_append(&record.to_csv(), &self.path)?;
+        self.records.push(record);
+        Ok(())
+    }
+
+    pub fn summary(&self) -> XvbRoundsSummary {
+        let mut summary = XvbRoundsSummary {
+            rounds: self.records.len(),
+            ..Default::default()
+        };
+        for record in &self.records {
+            match &record.round {
+                Some(XvbRound::Vip) => summary.vip += 1,
+                Some(XvbRound::Donor) => summary.donor += 1,
+                Some(XvbRound::DonorVip) => summary.donor_vip += 1,
+                Some(XvbRound::DonorWhale) => summary.donor_whale += 1,
+                Some(XvbRound::DonorMega) => summary.donor_mega += 1,
+                None => (),
+            }
+            if record.won {
+                summary.wins.push(record.block_height);
+            }
+            summary.xvb_seconds += record.xvb_seconds as u64;
+        }
+        summary
+    }
+}
+
+// Prints the records, then the summary.
+impl Display for XvbRounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<16} | {:>8} | {:<11} | {:>14} | {:>14} | {:>12} | Won",
+            "Date", "Block", "Round", "1h avg", "24h avg", "Time on XvB"
+        )?;
+        for record in &self.records {
+            writeln!(
+                f,
+                "{:<16} | {:>8} | {:<11} | {:>9} kH/s | {:>9} kH/s | {:>12} | {}",
+                record.date(),
+                record.block_height,
+                record.round_str(),
+                Float::from_3(record.donor_1h_avg as f64 / 1000.0).as_str(),
+                Float::from_3(record.donor_24h_avg as f64 / 1000.0).as_str(),
+                Uptime::from(record.xvb_seconds).as_str(),
+                if record.won { "yes" } else { "no" },
+            )?;
+        }
+        write!(f, "\n{}", self.summary())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XvbRoundsSummary {
+    pub rounds: usize,
+    pub vip: usize,
+    pub donor: usize,
+    pub donor_vip: usize,
+    pub donor_whale: usize,
+    pub donor_mega: usize,
+    pub xvb_seconds: u64,
+    pub wins: Vec<u64>, // Blocks of the raffles won
+}
+
+impl XvbRoundsSummary {
+    pub fn participated(&self) -> usize {
+        self.vip + self.donor + self.donor_vip + self.donor_whale + self.donor_mega
+    }
+}
+
+impl Display for XvbRoundsSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Rounds       | {} ({} with a share)",
+            self.rounds,
+            self.participated()
+        )?;
+        writeln!(
+            f,
+            "Round types  | VIP {} | Donor {} | VIP Donor {} | Whale Donor {} | Mega Donor {}",
+            self.vip, self.donor, self.donor_vip, self.donor_whale, self.donor_mega
+        )?;
+        writeln!(
+            f,
+            "Time on XvB  | {}",
+            Uptime::from(self.xvb_seconds).as_str()
+        )?;
+        let blocks = self
+            .wins
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.wins.is_empty() {
+            writeln!(f, "Wins         | 0")
+        } else {
+            writeln!(f, "Wins         | {} (blocks {})", self.wins.len(), blocks)
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::fmt;
+//---------------------------------------------------------------------------------------------------- ConsoleBuffer
+// The output of a process shown in its console.
+//
+// Lines are kept in a ring buffer: once [capacity] is reached,
+// the oldest line is dropped for every new one instead of resetting
+// the whole console. Every line gets an id (the count of lines pushed
+// before it), so the GUI can tell which lines are new since its last frame.
+
+// The max amount of lines a console holds.
+pub const CONSOLE_MAX_LINES: usize = 10_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleBuffer {
+    lines: VecDeque<String>,
+    // Text written after the last newline, becomes a line once terminated.
+    partial: String,
+    capacity: usize,
+    // Lines pushed since the creation, the id of the next line.
+    pushed: u64,
+    bytes: usize,
+}
+
+impl Default for ConsoleBuffer {
+    fn default() -> Self {
+        Self::with_capacity(CONSOLE_MAX_LINES)
+    }
+}
+
+impl ConsoleBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            partial: String::new(),
+            capacity: capacity.max(1),
+            pushed: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        let mut text = text;
+        while let Some((line, rest)) = text.split_once('\n') {
+            let line = if self.partial.is_empty() {
+                line.to_string()
+            } else {
+                self.partial.push_str(line);
+                std::mem::take(&mut self.partial)
+            };
+            self.push_line(line);
+            text = rest;
+        }
+        self.partial.push_str(text);
+    }
+
+    fn push_line(&mut self, mut line: String) {
+        // PTYs end lines with [\r\n].
+        if line.ends_with('\r') {
+            line.pop();
+        }
+        if self.lines.len() == self.capacity {
+            if let Some(old) = self.lines.pop_front() {
+                self.bytes -= old.len();
+            }
+        }
+        self.bytes += line.len();
+        self.lines.push_back(line);
+        self.pushed += 1;
+    }
+
+    // Moves the lines of [other] after ours, used when combining the APIs.
+    pub fn append(&mut self, other: &mut Self) {
+        for line in other.lines.drain(..) {
+            if self.partial.is_empty() {
+                self.push_line(line);
+            } else {
+                self.push_str(&line);
+                self.push_str("\n");
+            }
+        }
+        self.push_str(&std::mem::take(&mut other.partial));
+        other.bytes = 0;
+    }
+
+    // Ids keep increasing, so the GUI notices the console being cleared.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.partial.clear();
+        self.bytes = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    // Amount of lines held.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    // Amount of bytes held.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    // Id of the oldest line held.
+    pub fn first_id(&self) -> u64 {
+        self.pushed - self.lines.len() as u64
+    }
+
+    // Id the next line will get.
+    pub fn next_id(&self) -> u64 {
+        self.pushed
+    }
+
+    pub fn get(&self, id: u64) -> Option<&str> {
+        let index = id.checked_sub(self.first_id())?;
+        self.lines
+            .get(usize::try_from(index).ok()?)
+            .map(String::as_str)
+    }
+}
+
+impl fmt::Write for ConsoleBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConsoleBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        f.write_str(&self.partial)
+    }
+}
@@ -0,0 +1,761 @@
+// Local control socket.
+//
+// Lets the CLI ([gupaxx start p2pool], [gupaxx status --json]...) drive a
+// running Gupaxx, GUI or [--daemon], from cron jobs and SSH sessions.
+// A second launch also hands its arguments over here instead
+// of running next to the first one, see [disk/lock.rs].
+//
+// Contrary to the HTTP API, it is always enabled but only reachable
+// by the user owning the data directory:
+//   - Unix: a socket [gupaxx.sock] in the data directory, with mode 0600.
+//   - Windows: a TCP socket on [127.0.0.1], its port and a random token
+//     written to [gupaxx.port] in the data directory. The token must be
+//     the first line sent by the client.
+//
+// The protocol is one JSON [ControlRequest] line answered
+// by one JSON [ControlResponse] line.
+//
+// Processes are started with the last *saved* state, like the HTTP API does,
+// and the same rules as the GUI buttons apply, see [Helper::process_action()].
+
+use crate::cli::Cli;
+use crate::disk::errors::TomlError;
+use crate::disk::get_gupax_profiles_path;
+use crate::disk::node::Node;
+use crate::disk::profiles;
+use crate::disk::state::{P2pool, State, XvbMode};
+use crate::helper::{Helper, Process, ProcessName, ProcessState};
+#[cfg(target_os = "windows")]
+use crate::miscs::constant_time_eq;
+use crate::utils::sudo::SudoState;
+use clap::Parser;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use std::net::{TcpListener as Listener, TcpStream as Stream};
+#[cfg(target_family = "unix")]
+use std::os::unix::net::{UnixListener as Listener, UnixStream as Stream};
+
+#[cfg(target_family = "unix")]
+pub const CONTROL_SOCKET: &str = "gupaxx.sock";
+#[cfg(target_os = "windows")]
+pub const CONTROL_SOCKET: &str = "gupaxx.port";
+
+// How long the client waits for an answer.
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(10);
+// Longest line read from the socket, requests are far shorter.
+const CONTROL_MAX_LINE: u64 = 64 * 1024;
+
+// Gathers the P2Pool backup hosts for the given settings. Called each time
+// P2Pool is (re)started from the outside, so the current ping data is used.
+pub type BackupHosts = Arc<dyn Fn(&P2pool) -> Option<Vec<Node>> + Send + Sync>;
+
+//---------------------------------------------------------------------------------------------------- Protocol
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    Start { process: String },
+    Stop { process: String },
+    Restart { process: String },
+    Status,
+    XvbMode { mode: XvbMode },
+    // Gupaxx launched again, with these arguments.
+    Launch { args: Vec<String> },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<ProcessStatus>,
+}
+
+impl ControlResponse {
+    fn ok(message: String) -> Self {
+        Self {
+            ok: true,
+            message,
+            processes: vec![],
+        }
+    }
+    fn err(message: String) -> Self {
+        Self {
+            ok: false,
+            message,
+            processes: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProcessStatus {
+    pub name: String,         // As accepted by [parse_process_name()]
+    pub display_name: String, // As shown in the GUI
+    pub state: String,
+    pub signal: String,
+}
+
+impl ProcessStatus {
+    pub fn new(name: ProcessName, process: &Process) -> Self {
+        Self {
+            name: name.id(),
+            display_name: name.to_string(),
+            state: process.state.to_string(),
+            signal: process.signal.to_string(),
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Actions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProcessAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionError {
+    Unknown(String),  // No such process
+    Conflict(String), // Not in a state allowing the action
+    Denied(String),   // Not allowed to run it, see [SudoState::check_headless()]
+}
+
+impl std::fmt::Display for ActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Unknown(msg) | Self::Conflict(msg) | Self::Denied(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+// The names processes go by in the HTTP API and the CLI, see [ProcessName::id()].
+// Custom processes from [processes.toml] are [custom_1], [custom_2]...
+pub fn parse_process_name(name: &str) -> Result<ProcessName, ActionError> {
+    match name.to_ascii_lowercase().as_str() {
+        "p2pool" => Ok(ProcessName::P2pool),
+        "xmrig" => Ok(ProcessName::Xmrig),
+        "xmrig_proxy" | "xmrig-proxy" => Ok(ProcessName::XmrigProxy),
+        "xvb" => Ok(ProcessName::Xvb),
+        "node" => Ok(ProcessName::Node),
+        lower => match lower
+            .strip_prefix("custom_")
+            .and_then(|i| i.parse::<usize>().ok())
+        {
+            Some(i) if i > 0 => Ok(ProcessName::Custom(i - 1)),
+            _ => Err(ActionError::Unknown(format!("Unknown process [{}]", name))),
+        },
+    }
+}
+
+impl Helper {
+    // The state of every process, custom ones last.
+    pub fn process_statuses(&self) -> Vec<ProcessStatus> {
+        [
+            (ProcessName::P2pool, &self.p2pool),
+            (ProcessName::Xmrig, &self.xmrig),
+            (ProcessName::XmrigProxy, &self.xmrig_proxy),
+            (ProcessName::Xvb, &self.xvb),
+            (ProcessName::Node, &self.node),
+        ]
+        .into_iter()
+        .chain(
+            self.custom
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (ProcessName::Custom(i), &c.process)),
+        )
+        .map(|(name, p)| ProcessStatus::new(name, &p.lock().unwrap()))
+        .collect()
+    }
+
+    // The [Process] of [name], [None] for a custom process not in [processes.toml].
+    pub fn process(helper: &Arc<Mutex<Self>>, name: ProcessName) -> Option<Arc<Mutex<Process>>> {
+        let helper = helper.lock().unwrap();
+        let process = match name {
+            ProcessName::P2pool => &helper.p2pool,
+            ProcessName::Xmrig => &helper.xmrig,
+            ProcessName::XmrigProxy => &helper.xmrig_proxy,
+            ProcessName::Xvb => &helper.xvb,
+            ProcessName::Node => &helper.node,
+            ProcessName::Custom(i) => &helper.custom.get(i)?.process,
+        };
+        Some(Arc::clone(process))
+    }
+
+    pub fn is_alive(helper: &Arc<Mutex<Self>>, name: ProcessName) -> bool {
+        Self::process(helper, name).is_some_and(|process| process.lock().unwrap().is_alive())
+    }
+
+    // Starts/stops/restarts [name] with [state], with the same rules as the GUI
+    // buttons: nothing while in the middle of something, start only if dead,
+    // stop/restart only if alive. Starting a process [Blocked] on its dependencies
+    // starts it without waiting, stopping one waiting for its [RestartPolicy]
+    // cancels the restart. [backup_hosts] is only called if P2Pool (re)starts.
+    // [origin] prefixes the logs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_action(
+        helper: &Arc<Mutex<Self>>,
+        state: &State,
+        sudo: &Arc<Mutex<SudoState>>,
+        backup_hosts: impl FnOnce() -> Option<Vec<Node>>,
+        name: ProcessName,
+        action: ProcessAction,
+        origin: &str,
+    ) -> Result<String, ActionError> {
+        let Some(process) = Self::process(helper, name) else {
+            return Err(ActionError::Unknown(format!(
+                "Unknown process [{}]",
+                name.id()
+            )));
+        };
+        let process_state = process.lock().unwrap().state;
+        let alive = process.lock().unwrap().is_alive();
+        let conflict = |msg: &str| Err(ActionError::Conflict(format!("{} {}", name, msg)));
+        if process_state == ProcessState::Middle {
+            return conflict("is in the middle of (re)starting/stopping");
+        }
+        match action {
+            ProcessAction::Start => {
+                // Waiting for its dependencies, start it right away.
+                if process_state == ProcessState::Blocked {
+                    info!("{} | Starting {} without waiting...", origin, name);
+                    Self::skip_dependencies(&process);
+                    return Ok(format!("Starting {} without waiting", name));
+                }
+                if alive {
+                    return conflict("is already alive");
+                }
+                info!("{} | Starting {}...", origin, name);
+                match name {
+                    ProcessName::P2pool => Self::start_p2pool(
+                        helper,
+                        &state.p2pool,
+                        &state.gupax.absolute_p2pool_path,
+                        backup_hosts(),
+                    ),
+                    ProcessName::Xmrig => {
+                        SudoState::check_headless(&state.gupax.absolute_xmrig_path)
+                            .map_err(ActionError::Denied)?;
+                        Self::start_xmrig(
+                            helper,
+                            &state.xmrig,
+                            &state.gupax.absolute_xmrig_path,
+                            Arc::clone(sudo),
+                        )
+                    }
+                    ProcessName::XmrigProxy => Self::start_xp(
+                        helper,
+                        &state.xmrig_proxy,
+                        &state.xmrig,
+                        &state.gupax.absolute_xp_path,
+                    ),
+                    ProcessName::Xvb => Self::start_xvb(
+                        helper,
+                        &state.xvb,
+                        &state.p2pool,
+                        &state.xmrig,
+                        &state.xmrig_proxy,
+                    ),
+                    ProcessName::Node => {
+                        Self::start_node(helper, &state.node, &state.gupax.absolute_node_path)
+                    }
+                    ProcessName::Custom(i) => Self::start_custom(helper, i),
+                }
+                Ok(format!("Starting {}", name))
+            }
+            ProcessAction::Stop => {
+                // Dead but waiting to be restarted, this cancels the restart.
+                if !alive && !process.lock().unwrap().respawning {
+                    return conflict("is not alive");
+                }
+                info!("{} | Stopping {}...", origin, name);
+                match name {
+                    ProcessName::P2pool => Self::stop_p2pool(helper),
+                    ProcessName::Xmrig => Self::stop_xmrig(helper),
+                    ProcessName::XmrigProxy => Self::stop_xp(helper),
+                    ProcessName::Xvb => Self::stop_xvb(helper),
+                    ProcessName::Node => Self::stop_node(helper),
+                    ProcessName::Custom(i) => Self::stop_custom(helper, i),
+                }
+                Ok(format!("Stopping {}", name))
+            }
+            ProcessAction::Restart => {
+                if !alive {
+                    return conflict("is not alive");
+                }
+                info!("{} | Restarting {}...", origin, name);
+                match name {
+                    ProcessName::P2pool => Self::restart_p2pool(
+                        helper,
+                        &state.p2pool,
+                        &state.gupax.absolute_p2pool_path,
+                        backup_hosts(),
+                    ),
+                    ProcessName::Xmrig => {
+                        SudoState::check_headless(&state.gupax.absolute_xmrig_path)
+                            .map_err(ActionError::Denied)?;
+                        Self::restart_xmrig(
+                            helper,
+                            &state.xmrig,
+                            &state.gupax.absolute_xmrig_path,
+                            Arc::clone(sudo),
+                        )
+                    }
+                    ProcessName::XmrigProxy => Self::restart_xp(
+                        helper,
+                        &state.xmrig_proxy,
+                        &state.xmrig,
+                        &state.gupax.absolute_xp_path,
+                    ),
+                    ProcessName::Xvb => Self::restart_xvb(
+                        helper,
+                        &state.xvb,
+                        &state.p2pool,
+                        &state.xmrig,
+                        &state.xmrig_proxy,
+                    ),
+                    ProcessName::Node => {
+                        Self::restart_node(helper, &state.node, &state.gupax.absolute_node_path)
+                    }
+                    ProcessName::Custom(i) => Self::restart_custom(helper, i),
+                }
+                Ok(format!("Restarting {}", name))
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Profiles
+impl Helper {
+    // Makes the profile [name] the saved [state] and applies the settings the
+    // [Helper] holds. Returns the state switched from, for [restart_changed()].
+    pub fn switch_profile(
+        helper: &Arc<Mutex<Self>>,
+        state: &Arc<Mutex<State>>,
+        state_path: &Path,
+        profiles_path: &Path,
+        name: &str,
+    ) -> Result<State, TomlError> {
+        let new = profiles::activate(profiles_path, name, state_path)?;
+        let old = Self::replace_state(helper, state, new);
+        info!("Profile | Switched to [{}]", name);
+        Ok(old)
+    }
+
+    // Makes [new] the saved state and applies the settings the helper keeps
+    // its own copy of, returning the state it replaced.
+    pub fn replace_state(
+        helper: &Arc<Mutex<Self>>,
+        state: &Arc<Mutex<State>>,
+        mut new: State,
+    ) -> State {
+        let old = {
+            let mut state = state.lock().unwrap();
+            new.version = Arc::clone(&state.version);
+            std::mem::replace(&mut *state, new.clone())
+        };
+        let mut helper = helper.lock().unwrap();
+        helper.dependencies = new.gupax.dependencies;
+        helper.notifier.set_policy(&new.gupax.notifications);
+        helper.logs = new.gupax.logs;
+        helper
+            .gui_api_xvb
+            .lock()
+            .unwrap()
+            .set_runtime_mode(&new.xvb);
+        old
+    }
+
+    // The processes whose settings differ between [old] and [new].
+    pub fn changed_processes(old: &State, new: &State) -> Vec<ProcessName> {
+        let (og, gupax) = (&old.gupax, &new.gupax);
+        let p2pool = old.p2pool != new.p2pool;
+        let xmrig = old.xmrig != new.xmrig;
+        let xmrig_proxy = old.xmrig_proxy != new.xmrig_proxy;
+        [
+            (
+                ProcessName::Node,
+                old.node != new.node || og.absolute_node_path != gupax.absolute_node_path,
+            ),
+            (
+                ProcessName::P2pool,
+                p2pool || og.absolute_p2pool_path != gupax.absolute_p2pool_path,
+            ),
+            (
+                ProcessName::Xmrig,
+                xmrig || og.absolute_xmrig_path != gupax.absolute_xmrig_path,
+            ),
+            (
+                ProcessName::XmrigProxy,
+                xmrig_proxy || xmrig || og.absolute_xp_path != gupax.absolute_xp_path,
+            ),
+            (
+                ProcessName::Xvb,
+                old.xvb != new.xvb || p2pool || xmrig || xmrig_proxy,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect()
+    }
+
+    // Restarts the alive processes whose settings differ between [old] and [new],
+    // so they run with the arguments of [new]. Returns why the ones that could
+    // not be restarted were not.
+    pub fn restart_changed(
+        helper: &Arc<Mutex<Self>>,
+        old: &State,
+        new: &State,
+        sudo: &Arc<Mutex<SudoState>>,
+        backup_hosts: impl Fn() -> Option<Vec<Node>>,
+        origin: &str,
+    ) -> Vec<String> {
+        let mut failed = vec![];
+        for name in Self::changed_processes(old, new) {
+            if !Self::is_alive(helper, name) {
+                continue;
+            }
+            if let Err(e) = Self::process_action(
+                helper,
+                new,
+                sudo,
+                &backup_hosts,
+                name,
+                ProcessAction::Restart,
+                origin,
+            ) {
+                warn!("{} | Not restarting: {}", origin, e);
+                failed.push(e.to_string());
+            }
+        }
+        failed
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Server
+#[derive(Clone)]
+struct ControlContext {
+    helper: Arc<Mutex<Helper>>,
+    state: Arc<Mutex<State>>,
+    state_path: PathBuf,
+    profiles_path: PathBuf,
+    sudo: Arc<Mutex<SudoState>>,
+    backup_hosts: BackupHosts,
+    gui: bool,
+    #[cfg(target_os = "windows")]
+    token: Arc<str>,
+}
+
+impl Helper {
+    #[cold]
+    #[inline(never)]
+    // Spawns the control socket thread, unless another instance already listens on it.
+    // [state] is the saved state, the XvB mode set through the socket is saved to [state_path].
+    // [gui] is false for [--daemon], which has no window to show to a second launch.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_control_socket(
+        helper: &Arc<Mutex<Self>>,
+        state: &Arc<Mutex<State>>,
+        state_path: &Path,
+        os_data_path: &Path,
+        sudo: &Arc<Mutex<SudoState>>,
+        backup_hosts: BackupHosts,
+        gui: bool,
+    ) {
+        let path = os_data_path.join(CONTROL_SOCKET);
+        let ctx = ControlContext {
+            helper: Arc::clone(helper),
+            state: Arc::clone(state),
+            state_path: state_path.to_path_buf(),
+            profiles_path: get_gupax_profiles_path(os_data_path),
+            sudo: Arc::clone(sudo),
+            backup_hosts,
+            gui,
+            #[cfg(target_os = "windows")]
+            token: random_token().into(),
+        };
+        let listener = match bind(&path, &ctx) {
+            Ok(Some(listener)) => listener,
+            Ok(None) => {
+                warn!(
+                    "Control | Another instance listens on [{}], skipping...",
+                    path.display()
+                );
+                return;
+            }
+            Err(e) => {
+                error!("Control | Could not bind [{}]: {}", path.display(), e);
+                return;
+            }
+        };
+        info!("Control | Listening on [{}] ... OK", path.display());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let ctx = ctx.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = handle(stream, &ctx) {
+                                warn!("Control | Connection error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Control | Could not accept a connection: {}", e),
+                }
+            }
+        });
+    }
+}
+
+// [None] if a running instance already answers on [path].
+#[cfg(target_family = "unix")]
+fn bind(path: &Path, _ctx: &ControlContext) -> io::Result<Option<Listener>> {
+    if path.exists() {
+        if Stream::connect(path).is_ok() {
+            return Ok(None);
+        }
+        // Left behind by an instance that did not exit cleanly.
+        std::fs::remove_file(path)?;
+    }
+    use std::os::unix::fs::PermissionsExt;
+    let listener = Listener::bind(path)?;
+    // Anyone connecting can start and stop processes.
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(Some(listener))
+}
+
+#[cfg(target_os = "windows")]
+fn bind(path: &Path, ctx: &ControlContext) -> io::Result<Option<Listener>> {
+    if read_port_file(path).is_some_and(|(port, _)| Stream::connect(("127.0.0.1", port)).is_ok()) {
+        return Ok(None);
+    }
+    let listener = Listener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    std::fs::write(path, format!("{} {}", port, ctx.token))?;
+    Ok(Some(listener))
+}
+
+#[cfg(target_os = "windows")]
+fn read_port_file(path: &Path) -> Option<(u16, String)> {
+    let file = std::fs::read_to_string(path).ok()?;
+    let (port, token) = file.trim().split_once(' ')?;
+    Some((port.parse().ok()?, token.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn random_token() -> String {
+    use rand::distributions::Alphanumeric;
+    use rand::{thread_rng, Rng};
+    thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+fn handle(stream: Stream, ctx: &ControlContext) -> io::Result<()> {
+    stream.set_read_timeout(Some(CONTROL_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    #[cfg(target_os = "windows")]
+    {
+        read_request_line(&mut reader, &mut line)?;
+        if !constant_time_eq(line.trim_end().as_bytes(), ctx.token.as_bytes()) {
+            return write_response(stream, &ControlResponse::err("Invalid token".to_string()));
+        }
+        line.clear();
+    }
+    read_request_line(&mut reader, &mut line)?;
+    let response = match serde_json::from_str::<ControlRequest>(&line) {
+        Ok(request) => respond(request, ctx),
+        Err(e) => ControlResponse::err(format!("Invalid request: {}", e)),
+    };
+    write_response(stream, &response)
+}
+
+// Fails on a line longer than [CONTROL_MAX_LINE].
+pub(crate) fn read_request_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<()> {
+    let read = reader.take(CONTROL_MAX_LINE).read_line(line)?;
+    if read as u64 == CONTROL_MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "request line too long",
+        ));
+    }
+    Ok(())
+}
+
+fn write_response(mut stream: Stream, response: &ControlResponse) -> io::Result<()> {
+    let mut json = serde_json::to_string(response)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+    stream.flush()
+}
+
+fn respond(request: ControlRequest, ctx: &ControlContext) -> ControlResponse {
+    let (process, action) = match request {
+        ControlRequest::Start { process } => (process, ProcessAction::Start),
+        ControlRequest::Stop { process } => (process, ProcessAction::Stop),
+        ControlRequest::Restart { process } => (process, ProcessAction::Restart),
+        ControlRequest::Status => {
+            return ControlResponse {
+                processes: ctx.helper.lock().unwrap().process_statuses(),
+                ..ControlResponse::ok("Gupaxx is running".to_string())
+            }
+        }
+        ControlRequest::XvbMode { mode } => return set_xvb_mode(mode, ctx),
+        ControlRequest::Launch { args } => return launch(args, ctx),
+    };
+    let name = match parse_process_name(&process) {
+        Ok(name) => name,
+        Err(e) => return ControlResponse::err(e.to_string()),
+    };
+    let state = ctx.state.lock().unwrap().clone();
+    match Helper::process_action(
+        &ctx.helper,
+        &state,
+        &ctx.sudo,
+        || (ctx.backup_hosts)(&state.p2pool),
+        name,
+        action,
+        "Control",
+    ) {
+        Ok(msg) => ControlResponse::ok(msg),
+        Err(e) => ControlResponse::err(e.to_string()),
+    }
+}
+
+// Saves [mode] and applies it right away, like the XvB tab does.
+fn set_xvb_mode(mode: XvbMode, ctx: &ControlContext) -> ControlResponse {
+    info!("Control | Setting XvB mode to [{}]...", mode);
+    let xvb = {
+        let mut state = ctx.state.lock().unwrap();
+        state.xvb.set_mode(mode.clone());
+        if let Err(e) = state.save(&ctx.state_path) {
+            return ControlResponse::err(format!("Could not save the state: {}", e));
+        }
+        state.xvb.clone()
+    };
+    let mut helper = ctx.helper.lock().unwrap();
+    helper.gui_api_xvb.lock().unwrap().set_runtime_mode(&xvb);
+    helper.xvb_mode_request = Some(mode.clone());
+    ControlResponse::ok(format!("XvB mode set to [{}]", mode))
+}
+
+// A second launch: switch to its [--profile] and show the window,
+// nothing else it asks for can be done here.
+fn launch(args: Vec<String>, ctx: &ControlContext) -> ControlResponse {
+    info!("Control | Gupaxx launched again with {:?}", args);
+    let profile = Cli::try_parse_from(std::iter::once("gupaxx".to_string()).chain(args.clone()))
+        .ok()
+        .and_then(|cli| cli.profile);
+    if let Some(name) = profile {
+        return switch_profile(name, ctx);
+    }
+    if !ctx.gui {
+        return ControlResponse::err(
+            "Gupaxx is already running without GUI, see [gupaxx status]".to_string(),
+        );
+    }
+    if args.iter().any(|a| a == "--daemon") {
+        return ControlResponse::err("Gupaxx is already running with its GUI".to_string());
+    }
+    ctx.helper.lock().unwrap().focus_request = true;
+    ControlResponse::ok("Gupaxx is already running, showing its window".to_string())
+}
+
+fn switch_profile(name: String, ctx: &ControlContext) -> ControlResponse {
+    if !profiles::exists(&ctx.profiles_path, &name) {
+        return ControlResponse::err(format!("There is no profile [{}]", name));
+    }
+    // The GUI has its own copy of the state to update, it does the switch.
+    if ctx.gui {
+        let mut helper = ctx.helper.lock().unwrap();
+        helper.profile_request = Some(name.clone());
+        helper.focus_request = true;
+        return ControlResponse::ok(format!("Switching to profile [{}]", name));
+    }
+    match Helper::switch_profile(
+        &ctx.helper,
+        &ctx.state,
+        &ctx.state_path,
+        &ctx.profiles_path,
+        &name,
+    ) {
+        Ok(old) => {
+            let new = ctx.state.lock().unwrap().clone();
+            let failed = Helper::restart_changed(
+                &ctx.helper,
+                &old,
+                &new,
+                &ctx.sudo,
+                || (ctx.backup_hosts)(&new.p2pool),
+                "Control",
+            );
+            if failed.is_empty() {
+                ControlResponse::ok(format!("Switched to profile [{}]", name))
+            } else {
+                ControlResponse::err(format!(
+                    "Switched to profile [{}], but could not restart: {}",
+                    name,
+                    failed.join(", ")
+                ))
+            }
+        }
+        Err(e) => ControlResponse::err(format!("Could not switch to profile [{}]: {}", name, e)),
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Client
+// Sends [request] to the instance running with [os_data_path],
+// [None] if there is none.
+pub fn send_control(
+    os_data_path: &Path,
+    request: &ControlRequest,
+) -> io::Result<Option<ControlResponse>> {
+    let path = os_data_path.join(CONTROL_SOCKET);
+    let stream = match connect(&path) {
+        Ok(stream) => stream,
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Ok(None)
+        }
+        Err(e) => return Err(e),
+    };
+    stream.set_read_timeout(Some(CONTROL_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    #[cfg(target_os = "windows")]
+    if let Some((_, token)) = read_port_file(&path) {
+        writeln!(writer, "{}", token)?;
+    }
+    writeln!(writer, "{}", serde_json::to_string(request)?)?;
+    writer.flush()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(target_family = "unix")]
+fn connect(path: &Path) -> io::Result<Stream> {
+    Stream::connect(path)
+}
+
+#[cfg(target_os = "windows")]
+fn connect(path: &Path) -> io::Result<Stream> {
+    match read_port_file(path) {
+        Some((port, _)) => Stream::connect(("127.0.0.1", port)),
+        None => Err(io::ErrorKind::NotFound.into()),
+    }
+}
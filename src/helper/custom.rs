@@ -0,0 +1,249 @@
+// Custom processes.
+//
+// Anything the user listed in [processes.toml], supervised like the other
+// processes but knowing nothing about them: no API, the output is only
+// matched against [alive_regex] to know when it is ready.
+
+use log::{error, info};
+use portable_pty::CommandBuilder;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::helper::console::ConsoleBuffer;
+use crate::{
+    disk::{into_absolute_path, processes::ProcessConfig, state::RestartPolicy},
+    helper::{
+        supervised::{Handles, PubApi, SupervisedProcess, Watchdog},
+        Helper, Process, ProcessName, ProcessState,
+    },
+    macros::arc_mut,
+};
+
+//---------------------------------------------------------------------------------------------------- [Custom]
+// What the [Helper] and the GUI share about one custom process.
+#[derive(Clone)]
+pub struct Custom {
+    pub config: ProcessConfig,
+    pub process: Arc<Mutex<Process>>,
+    pub gui_api: Arc<Mutex<PubCustomApi>>,
+    pub_api: Arc<Mutex<PubCustomApi>>,
+}
+
+impl Custom {
+    pub fn from_configs(configs: Vec<ProcessConfig>) -> Vec<Self> {
+        configs
+            .into_iter()
+            .enumerate()
+            .map(|(i, config)| Self {
+                config,
+                process: arc_mut!(Process::new(
+                    ProcessName::Custom(i),
+                    String::new(),
+                    PathBuf::new()
+                )),
+                gui_api: arc_mut!(PubCustomApi::new()),
+                pub_api: arc_mut!(PubCustomApi::new()),
+            })
+            .collect()
+    }
+}
+
+impl Helper {
+    #[cold]
+    #[inline(never)]
+    pub fn start_custom(helper: &Arc<Mutex<Self>>, index: usize) {
+        let Some(custom) = CustomProcess::new(&helper.lock().unwrap(), index) else {
+            return;
+        };
+        match into_absolute_path(custom.config.path.clone()) {
+            Ok(path) => Self::start_supervised(helper, custom, &path),
+            Err(e) => error!("{} | Path [{}]: {}", custom.name(), custom.config.path, e),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub fn stop_custom(helper: &Arc<Mutex<Self>>, index: usize) {
+        let process = match helper.lock().unwrap().custom.get(index) {
+            Some(custom) => Arc::clone(&custom.process),
+            None => return,
+        };
+        Self::stop_supervised(&process);
+    }
+
+    #[cold]
+    #[inline(never)]
+    pub fn restart_custom(helper: &Arc<Mutex<Self>>, index: usize) {
+        let Some(custom) = CustomProcess::new(&helper.lock().unwrap(), index) else {
+            return;
+        };
+        match into_absolute_path(custom.config.path.clone()) {
+            Ok(path) => Self::restart_supervised(helper, custom, &path),
+            Err(e) => error!("{} | Path [{}]: {}", custom.name(), custom.config.path, e),
+        }
+    }
+
+    // Starts every custom process with [auto_start].
+    pub fn start_custom_auto(helper: &Arc<Mutex<Self>>) {
+        let auto: Vec<usize> = helper
+            .lock()
+            .unwrap()
+            .custom
+            .iter()
+            .enumerate()
+            .filter(|(_, custom)| custom.config.auto_start)
+            .map(|(i, _)| i)
+            .collect();
+        for i in auto {
+            Self::start_custom(helper, i);
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- [SupervisedProcess]
+#[derive(Clone)]
+pub struct CustomProcess {
+    index: usize,
+    config: ProcessConfig,
+    alive_regex: Option<Regex>,
+}
+
+impl CustomProcess {
+    fn new(helper: &Helper, index: usize) -> Option<Self> {
+        let config = helper.custom.get(index)?.config.clone();
+        // Already checked when [processes.toml] was read.
+        let alive_regex = config
+            .alive_regex
+            .as_deref()
+            .and_then(|regex| Regex::new(regex).ok());
+        Some(Self {
+            index,
+            config,
+            alive_regex,
+        })
+    }
+}
+
+impl SupervisedProcess for CustomProcess {
+    // Not ready until [alive_regex] matches.
+    const SPAWNED_STATE: ProcessState = ProcessState::Syncing;
+    const ANSI_LINES: usize = usize::MAX;
+    type Api = PubCustomApi;
+    type Lines = ();
+
+    fn name(&self) -> ProcessName {
+        ProcessName::Custom(self.index)
+    }
+
+    fn label(&self) -> String {
+        self.config.name.clone()
+    }
+
+    // The number of [custom_N] keeps two names that sanitize the same apart.
+    fn log_name(&self) -> String {
+        let name: String = self
+            .config
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("custom-{}-{}", self.index + 1, name)
+    }
+
+    fn handles(&self, helper: &Helper) -> Handles<PubCustomApi> {
+        let custom = &helper.custom[self.index];
+        (
+            Arc::clone(&custom.process),
+            Arc::clone(&custom.gui_api),
+            Arc::clone(&custom.pub_api),
+        )
+    }
+
+    fn restart_policy(&self) -> &RestartPolicy {
+        &self.config.restart
+    }
+
+    fn args(&mut self, _helper: &Arc<Mutex<Helper>>, _path: &Path) -> Vec<String> {
+        info!("{} | Name: [{}]", self.name(), self.config.name);
+        self.config.args.clone()
+    }
+
+    fn command(&self, args: Vec<String>, path: &Path) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(path);
+        cmd.args(args);
+        match &self.config.cwd {
+            Some(cwd) => cmd.cwd(cwd),
+            None => cmd.cwd(path.parent().unwrap()),
+        }
+        for (key, value) in self.config.env.iter() {
+            cmd.env(key, value);
+        }
+        cmd
+    }
+
+    fn lines(&self) {}
+
+    async fn spawned(&mut self, watchdog: &mut Watchdog<'_, PubCustomApi>) {
+        if self.alive_regex.is_none() {
+            watchdog
+                .process
+                .lock()
+                .unwrap()
+                .set_state(ProcessState::Alive);
+        }
+    }
+
+    fn update_from_output(
+        &mut self,
+        pub_api: &mut PubCustomApi,
+        output_parse: &Arc<Mutex<String>>,
+        output_pub: &Arc<Mutex<String>>,
+        elapsed: Duration,
+        process: &mut Process,
+    ) {
+        let mut output_pub = output_pub.lock().unwrap();
+        if !output_pub.is_empty() {
+            pub_api.output.push_str(&std::mem::take(&mut *output_pub));
+        }
+        pub_api.uptime = elapsed;
+        drop(output_pub);
+
+        let mut output_parse = output_parse.lock().unwrap();
+        if let Some(regex) = &self.alive_regex {
+            if process.state == ProcessState::Syncing && regex.is_match(&output_parse) {
+                info!("{} | [alive_regex] matched, process is alive", self.name());
+                process.set_state(ProcessState::Alive);
+            }
+        }
+        output_parse.clear();
+    }
+
+    // No API to read.
+    async fn tick(&mut self, _watchdog: &mut Watchdog<'_, PubCustomApi>) {}
+}
+
+//---------------------------------------------------------------------------------------------------- Public Custom API
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PubCustomApi {
+    #[serde(skip)]
+    pub output: ConsoleBuffer,
+    pub uptime: Duration,
+}
+
+impl PubCustomApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PubApi for PubCustomApi {
+    fn output(&mut self) -> &mut ConsoleBuffer {
+        &mut self.output
+    }
+}
@@ -0,0 +1,164 @@
+// Watchdog events.
+//
+// Instead of waking up every second to check if something happened,
+// the watchdogs wait on [WatchdogEvents::next()], which returns as soon as:
+//
+// - [Signal] the user clicked [Start/Stop/Restart] (see [Process::send_signal()])
+// - [Input]  the user sent a command to STDIN (see [Process::send_input()])
+// - [Output] the PTY read thread got new lines
+// - [Exit]   the PTY read thread reached the end, the process is gone
+// - [Tick]   the timer for the API requests/file reads expired
+//
+// The timer goes at the pace of the APIs it reads, not of the GUI, which
+// gets the stats through [StatsSnapshot] and the uptimes from [Process::start].
+//
+// The sending halves live in the [Process], so the GUI, the HTTP API
+// and the PTY threads do not need to know about the watchdog itself.
+//
+// Everything else waiting on the processes (the [Helper] publishing the stats,
+// the dependencies and the restart backoff) is woken by [changed()]: every new
+// [ProcessState], every signal and every stats handover of a watchdog.
+
+use crate::helper::Process;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+// How often the watchdogs read their APIs, if nothing else wakes them up.
+pub const WATCHDOG_TICK: Duration = Duration::from_secs(5);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum WatchdogEvent {
+    Tick,
+    Signal,
+    Input,
+    Output,
+    Exit,
+}
+
+//---------------------------------------------------------------------------------------------------- Changes
+static CHANGES: Lazy<watch::Sender<()>> = Lazy::new(|| watch::channel(()).0);
+
+// Something about a process changed, wakes up everyone waiting in [subscribe()].
+pub fn changed() {
+    CHANGES.send_replace(());
+}
+
+// Only the changes from now on are seen. Wakeups can be for any process,
+// the receiver checks again what it waits for.
+pub fn subscribe() -> watch::Receiver<()> {
+    CHANGES.subscribe()
+}
+
+//---------------------------------------------------------------------------------------------------- Sender
+#[derive(Clone, Debug)]
+pub struct EventSender {
+    tx: UnboundedSender<WatchdogEvent>,
+    // Set while an [Output] event is waiting to be received,
+    // so a process printing 1000 lines wakes its watchdog once.
+    output_pending: Arc<AtomicBool>,
+}
+
+impl EventSender {
+    pub fn send(&self, event: WatchdogEvent) {
+        if event == WatchdogEvent::Output && self.output_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        // The watchdog is gone, nothing to wake up.
+        let _ = self.tx.send(event);
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Receiver
+pub struct WatchdogEvents {
+    rx: UnboundedReceiver<WatchdogEvent>,
+    sender: EventSender,
+    output_pending: Arc<AtomicBool>,
+    tick: Interval,
+}
+
+impl WatchdogEvents {
+    // Creates the channel and gives the sending half to [process],
+    // replacing the one of the previous watchdog (if any).
+    pub fn new(process: &Arc<Mutex<Process>>) -> Self {
+        let (tx, rx) = unbounded_channel();
+        let output_pending = Arc::new(AtomicBool::new(false));
+        let sender = EventSender {
+            tx,
+            output_pending: Arc::clone(&output_pending),
+        };
+        process.lock().unwrap().events = Some(sender.clone());
+        // The first tick completes immediately, like the first loop used to.
+        let mut tick = interval(WATCHDOG_TICK);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            rx,
+            sender,
+            output_pending,
+            tick,
+        }
+    }
+
+    // For the PTY read thread.
+    pub fn sender(&self) -> EventSender {
+        self.sender.clone()
+    }
+
+    // Waits for the next event. User actions come first, then the timer.
+    pub async fn next(&mut self) -> WatchdogEvent {
+        let event = tokio::select! {
+            biased;
+            Some(event) = self.rx.recv() => event,
+            _ = self.tick.tick() => WatchdogEvent::Tick,
+        };
+        if event == WatchdogEvent::Output {
+            self.output_pending.store(false, Ordering::Release);
+        }
+        event
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- TESTS
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helper::{ProcessName, ProcessSignal};
+    use crate::macros::arc_mut;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn events_wake_before_tick() {
+        let process = arc_mut!(Process::new(
+            ProcessName::P2pool,
+            String::new(),
+            PathBuf::new()
+        ));
+        let mut events = WatchdogEvents::new(&process);
+        // First tick is immediate.
+        assert_eq!(events.next().await, WatchdogEvent::Tick);
+
+        process.lock().unwrap().send_signal(ProcessSignal::Stop);
+        let event = tokio::time::timeout(WATCHDOG_TICK / 2, events.next()).await;
+        assert_eq!(event.unwrap(), WatchdogEvent::Signal);
+        assert_eq!(process.lock().unwrap().signal, ProcessSignal::Stop);
+
+        // Many lines of output only wake the watchdog once.
+        let sender = events.sender();
+        for _ in 0..100 {
+            sender.send(WatchdogEvent::Output);
+        }
+        process.lock().unwrap().send_input("status".to_string());
+        assert_eq!(events.next().await, WatchdogEvent::Output);
+        assert_eq!(events.next().await, WatchdogEvent::Input);
+        assert_eq!(process.lock().unwrap().input, ["", "status"]);
+
+        // The end of the output wakes it up as well.
+        sender.send(WatchdogEvent::Exit);
+        let event = tokio::time::timeout(WATCHDOG_TICK / 2, events.next()).await;
+        assert_eq!(event.unwrap(), WatchdogEvent::Exit);
+    }
+}
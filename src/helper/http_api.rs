@@ -0,0 +1,222 @@
+// Local HTTP API.
+//
+// An opt-in HTTP server exposing the same data the [Status] tab reads
+// (the [gui_api_*] structs) as JSON, and letting scripts start/stop/restart
+// processes the same way the GUI buttons do.
+//
+// Every request must carry [Authorization: Bearer <token>], the token being
+// [Gupax.http_api_token], it does not start without one. The server is bound once at startup, changing the
+// settings requires a restart of Gupaxx.
+//
+// Processes are started with the last *saved* state, like the auto-start does,
+// with the same rules as the control socket, see [Helper::process_action()].
+//
+// Custom processes from [processes.toml] are named [custom_1], [custom_2]...
+// in the order they are listed. [/processes] lists every process under that
+// name, the one the [/:process] and [/:process/:action] routes take.
+//
+// If [Gupax.http_api_metrics] is enabled, [/metrics] serves the
+// same stats in the Prometheus text format, see [metrics.rs].
+
+use crate::disk::state::State;
+use crate::helper::control::{
+    parse_process_name, ActionError, BackupHosts, ProcessAction, ProcessStatus,
+};
+use crate::helper::metrics::METRICS_CONTENT_TYPE;
+use crate::helper::{Helper, ProcessName};
+use crate::miscs::constant_time_eq;
+use crate::utils::sudo::SudoState;
+use axum::extract::{Path, Request, State as AxumState};
+use axum::http::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    StatusCode,
+};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use log::{error, info};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+//---------------------------------------------------------------------------------------------------- Context
+#[derive(Clone)]
+struct ApiContext {
+    helper: Arc<Mutex<Helper>>,
+    state: Arc<Mutex<State>>,
+    sudo: Arc<Mutex<SudoState>>,
+    backup_hosts: BackupHosts,
+    token: Arc<str>,
+}
+
+type ApiError = (StatusCode, String);
+
+//---------------------------------------------------------------------------------------------------- Spawn
+impl Helper {
+    #[cold]
+    #[inline(never)]
+    // Spawns the HTTP API thread if it is enabled in [state].
+    // [backup_hosts] gathers what P2Pool is started with from this API.
+    pub fn spawn_http_api(
+        helper: &Arc<Mutex<Self>>,
+        state: &Arc<Mutex<State>>,
+        sudo: &Arc<Mutex<SudoState>>,
+        backup_hosts: BackupHosts,
+    ) {
+        let gupax = state.lock().unwrap().gupax.clone();
+        if !gupax.http_api {
+            info!("HTTP API | Disabled, skipping...");
+            return;
+        }
+        // An empty token would let any [Bearer] header in.
+        if gupax.http_api_token.is_empty() {
+            error!("HTTP API | No token set, not starting");
+            return;
+        }
+        let port = match gupax.http_api_port.parse::<u16>() {
+            Ok(port) => port,
+            Err(e) => {
+                error!(
+                    "HTTP API | Invalid port [{}]: {}, not starting",
+                    gupax.http_api_port, e
+                );
+                return;
+            }
+        };
+        let ctx = ApiContext {
+            helper: Arc::clone(helper),
+            state: Arc::clone(state),
+            sudo: Arc::clone(sudo),
+            backup_hosts,
+            token: gupax.http_api_token.into(),
+        };
+        let addr = format!("{}:{}", gupax.http_api_ip, port);
+        let metrics = gupax.http_api_metrics;
+        thread::spawn(move || {
+            Self::serve_http_api(addr, ctx, metrics);
+        });
+    }
+
+    #[tokio::main]
+    async fn serve_http_api(addr: String, ctx: ApiContext, metrics: bool) {
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                error!("HTTP API | Could not bind [{}]: {}", addr, e);
+                return;
+            }
+        };
+        let mut router = Router::new();
+        if metrics {
+            info!("HTTP API | Serving Prometheus metrics on [/metrics]");
+            router = router.route("/metrics", get(metrics_handler));
+        }
+        let router = router
+            .route("/processes", get(processes))
+            .route("/sys", get(sys))
+            .route("/:process", get(process_api))
+            .route("/:process/:action", post(process_action))
+            .layer(middleware::from_fn_with_state(ctx.clone(), auth))
+            .with_state(ctx);
+        info!("HTTP API | Listening on [{}] ... OK", addr);
+        if let Err(e) = axum::serve(listener, router).await {
+            error!("HTTP API | Server error: {}", e);
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Handlers
+async fn auth(AxumState(ctx): AxumState<ApiContext>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|t| constant_time_eq(t.as_bytes(), ctx.token.as_bytes()));
+    if !authorized {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    next.run(request).await
+}
+
+async fn processes(AxumState(ctx): AxumState<ApiContext>) -> Json<Vec<ProcessStatus>> {
+    Json(ctx.helper.lock().unwrap().process_statuses())
+}
+
+async fn sys(AxumState(ctx): AxumState<ApiContext>) -> Response {
+    let sys = Arc::clone(&ctx.helper.lock().unwrap().pub_sys);
+    let sys = sys.lock().unwrap().clone();
+    Json(sys).into_response()
+}
+
+async fn metrics_handler(AxumState(ctx): AxumState<ApiContext>) -> Response {
+    let snapshot = ctx.helper.lock().unwrap().snapshot.borrow().clone();
+    (
+        [(CONTENT_TYPE, METRICS_CONTENT_TYPE)],
+        snapshot.render_metrics(),
+    )
+        .into_response()
+}
+
+async fn process_api(
+    AxumState(ctx): AxumState<ApiContext>,
+    Path(process): Path<String>,
+) -> Result<Response, ApiError> {
+    let name = parse_process_name(&process).map_err(api_error)?;
+    let helper = ctx.helper.lock().unwrap();
+    // Clone the data out so that the [Helper] is not blocked while serializing.
+    let response = match name {
+        ProcessName::P2pool => Json(helper.gui_api_p2pool.lock().unwrap().clone()).into_response(),
+        ProcessName::Xmrig => Json(helper.gui_api_xmrig.lock().unwrap().clone()).into_response(),
+        ProcessName::XmrigProxy => Json(helper.gui_api_xp.lock().unwrap().clone()).into_response(),
+        ProcessName::Xvb => Json(helper.gui_api_xvb.lock().unwrap().clone()).into_response(),
+        ProcessName::Node => Json(helper.gui_api_node.lock().unwrap().clone()).into_response(),
+        ProcessName::Custom(i) => match helper.custom.get(i) {
+            Some(custom) => Json(custom.gui_api.lock().unwrap().clone()).into_response(),
+            None => return Err(unknown_process(&process)),
+        },
+    };
+    Ok(response)
+}
+
+async fn process_action(
+    AxumState(ctx): AxumState<ApiContext>,
+    Path((process, action)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let name = parse_process_name(&process).map_err(api_error)?;
+    let action = match action.as_str() {
+        "start" => ProcessAction::Start,
+        "stop" => ProcessAction::Stop,
+        "restart" => ProcessAction::Restart,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Unknown action [{}], expected start/stop/restart", action),
+            ))
+        }
+    };
+    let state = ctx.state.lock().unwrap().clone();
+    Helper::process_action(
+        &ctx.helper,
+        &state,
+        &ctx.sudo,
+        || (ctx.backup_hosts)(&state.p2pool),
+        name,
+        action,
+        "HTTP API",
+    )
+    .map_err(api_error)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+fn api_error(e: ActionError) -> ApiError {
+    match e {
+        ActionError::Unknown(msg) => (StatusCode::NOT_FOUND, msg),
+        ActionError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+        ActionError::Denied(msg) => (StatusCode::FORBIDDEN, msg),
+    }
+}
+
+fn unknown_process(name: &str) -> ApiError {
+    (StatusCode::NOT_FOUND, format!("Unknown process [{}]", name))
+}
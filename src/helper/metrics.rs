@@ -0,0 +1,335 @@
+// Prometheus metrics.
+//
+// Renders the [StatsSnapshot] in the Prometheus text exposition format,
+// served by the HTTP API on [/metrics] if [Gupax.http_api_metrics] is enabled.
+// Every value is a gauge, the stats of dead processes are not rendered
+// (the GUI keeps the last values around, which would be lying here).
+
+use crate::helper::snapshot::StatsSnapshot;
+use crate::helper::xvb::rounds::XvbRound;
+use crate::helper::ProcessName;
+use std::fmt::Write;
+
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// Rendered from the last [StatsSnapshot] published by the [Helper].
+impl StatsSnapshot {
+    pub fn render_metrics(&self) -> String {
+        let mut m = Metrics::default();
+        // Processes
+        m.gauge(
+            "gupaxx_process_up",
+            "Whether the process is alive",
+            self.processes
+                .iter()
+                .map(|(name, alive, _)| (process_label(*name), *alive as u8 as f64)),
+        );
+        m.gauge(
+            "gupaxx_process_restarts",
+            "Automatic restarts since the last manual start",
+            self.processes
+                .iter()
+                .map(|(name, _, restarts)| (process_label(*name), *restarts as f64)),
+        );
+
+        // Node
+        if self.alive(ProcessName::Node) {
+            let n = &self.node;
+            m.gauge(
+                "gupaxx_node_height",
+                "Current block height",
+                [(String::new(), n.blockheight_u64 as f64)],
+            );
+            m.gauge(
+                "gupaxx_node_difficulty",
+                "Current Monero network difficulty",
+                [(String::new(), n.difficulty_u64 as f64)],
+            );
+            m.gauge(
+                "gupaxx_node_connections",
+                "Peer connections",
+                [
+                    (label("direction", "in"), n.incoming_connections as f64),
+                    (label("direction", "out"), n.outgoing_connections as f64),
+                ],
+            );
+            m.gauge(
+                "gupaxx_node_synchronized",
+                "Whether the node is synchronized",
+                [(String::new(), n.synchronized as u8 as f64)],
+            );
+        }
+
+        // P2Pool
+        if self.alive(ProcessName::P2pool) {
+            let p = &self.p2pool;
+            m.gauge(
+                "gupaxx_p2pool_hashrate",
+                "Hashrate of the miners connected to P2Pool in H/s",
+                [
+                    (
+                        label("window", "15m"),
+                        p.user_p2pool_hashrate_15m_u64 as f64,
+                    ),
+                    (label("window", "1h"), p.user_p2pool_hashrate_u64 as f64),
+                    (
+                        label("window", "24h"),
+                        p.user_p2pool_hashrate_24h_u64 as f64,
+                    ),
+                ],
+            );
+            m.gauge(
+                "gupaxx_p2pool_shares_found",
+                "Shares found",
+                [(String::new(), p.shares_found.unwrap_or_default() as f64)],
+            );
+            m.gauge(
+                "gupaxx_p2pool_effort_percent",
+                "Effort to find a share",
+                [
+                    (label("kind", "current"), p.current_effort_f32 as f64),
+                    (label("kind", "average"), p.average_effort_f32 as f64),
+                ],
+            );
+            m.gauge(
+                "gupaxx_p2pool_connections",
+                "Stratum connections",
+                [(String::new(), p.connections_u32 as f64)],
+            );
+            m.gauge(
+                "gupaxx_p2pool_difficulty",
+                "Network difficulty",
+                [
+                    (label("network", "p2pool"), p.p2pool_difficulty_u64 as f64),
+                    (label("network", "monero"), p.monero_difficulty_u64 as f64),
+                ],
+            );
+            m.gauge(
+                "gupaxx_p2pool_network_hashrate",
+                "Network hashrate in H/s",
+                [
+                    (label("network", "p2pool"), p.p2pool_hashrate_u64 as f64),
+                    (label("network", "monero"), p.monero_hashrate_u64 as f64),
+                ],
+            );
+            m.gauge(
+                "gupaxx_p2pool_payouts",
+                "Payouts received since P2Pool started",
+                [(String::new(), p.payouts as f64)],
+            );
+            m.gauge(
+                "gupaxx_p2pool_xmr",
+                "XMR received since P2Pool started",
+                [(String::new(), p.xmr)],
+            );
+            m.gauge(
+                "gupaxx_p2pool_sidechain_shares",
+                "Shares in the PPLNS window",
+                [(String::new(), p.sidechain_shares as f64)],
+            );
+            m.gauge(
+                "gupaxx_p2pool_sidechain_hashrate",
+                "Estimated hashrate from the shares in the PPLNS window in H/s",
+                [(String::new(), p.sidechain_ehr as f64)],
+            );
+        }
+
+        // XMRig
+        if self.alive(ProcessName::Xmrig) {
+            let x = &self.xmrig;
+            m.gauge(
+                "gupaxx_xmrig_hashrate",
+                "Hashrate in H/s",
+                [
+                    (label("window", "10s"), x.hashrate_raw as f64),
+                    (label("window", "1m"), x.hashrate_raw_1m as f64),
+                    (label("window", "15m"), x.hashrate_raw_15m as f64),
+                ],
+            );
+            m.gauge(
+                "gupaxx_xmrig_shares",
+                "Shares sent to the pool",
+                [
+                    (label("result", "accepted"), x.accepted_raw as f64),
+                    (label("result", "rejected"), x.rejected_raw as f64),
+                ],
+            );
+        }
+
+        // XMRig-Proxy
+        if self.alive(ProcessName::XmrigProxy) {
+            let x = &self.xmrig_proxy;
+            m.gauge(
+                "gupaxx_xmrig_proxy_hashrate",
+                "Hashrate of the miners connected to XMRig-Proxy in H/s",
+                [
+                    (label("window", "1m"), x.hashrate_1m as f64),
+                    (label("window", "10m"), x.hashrate_10m as f64),
+                    (label("window", "1h"), x.hashrate_1h as f64),
+                    (label("window", "12h"), x.hashrate_12h as f64),
+                    (label("window", "24h"), x.hashrate_24h as f64),
+                ],
+            );
+            m.gauge(
+                "gupaxx_xmrig_proxy_shares",
+                "Shares sent to the pool",
+                [
+                    (label("result", "accepted"), x.accepted as f64),
+                    (label("result", "rejected"), x.rejected as f64),
+                ],
+            );
+        }
+
+        // XvB
+        if self.alive(ProcessName::Xvb) {
+            let s = &self.xvb.stats_priv;
+            // The XvB API returns kH/s.
+            m.gauge(
+                "gupaxx_xvb_donor_hashrate",
+                "Average hashrate donated, as seen by XvB in H/s",
+                [
+                    (label("window", "1h"), s.donor_1hr_avg as f64 * 1000.0),
+                    (label("window", "24h"), s.donor_24hr_avg as f64 * 1000.0),
+                ],
+            );
+            m.gauge(
+                "gupaxx_xvb_round",
+                "The round the donor hashrate currently qualifies for",
+                [
+                    XvbRound::Vip,
+                    XvbRound::Donor,
+                    XvbRound::DonorVip,
+                    XvbRound::DonorWhale,
+                    XvbRound::DonorMega,
+                ]
+                .into_iter()
+                .map(|r| {
+                    let value = (s.round_participate.as_ref() == Some(&r)) as u8 as f64;
+                    (label("round", &r.to_string()), value)
+                }),
+            );
+            m.gauge(
+                "gupaxx_xvb_winner",
+                "Whether the current raffle was won",
+                [(String::new(), s.win_current as u8 as f64)],
+            );
+            m.gauge(
+                "gupaxx_xvb_fails",
+                "Failed private stats requests in a row",
+                [(String::new(), s.fails as f64)],
+            );
+            m.gauge(
+                "gupaxx_xvb_time_switch_node_seconds",
+                "Time before switching the hashrate to the other pool",
+                [(String::new(), s.time_switch_node as f64)],
+            );
+        }
+        m.0
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Format
+#[derive(Default)]
+struct Metrics(String);
+
+impl Metrics {
+    // Writes a gauge with its [HELP] and [TYPE] lines, [samples] are (labels, value).
+    fn gauge(&mut self, name: &str, help: &str, samples: impl IntoIterator<Item = (String, f64)>) {
+        let _ = writeln!(self.0, "# HELP {} {}", name, help);
+        let _ = writeln!(self.0, "# TYPE {} gauge", name);
+        for (labels, value) in samples {
+            let _ = writeln!(self.0, "{}{} {}", name, labels, number(value));
+        }
+    }
+}
+
+// Rust prints [inf] and [NaN], the text format wants [+Inf], [-Inf] and [NaN].
+fn number(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if value.is_nan() {
+        "NaN".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn label(key: &str, value: &str) -> String {
+    let value = value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n");
+    format!("{{{}=\"{}\"}}", key, value)
+}
+
+fn process_label(name: ProcessName) -> String {
+    label("process", &name.id())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render_metrics() {
+        let mut snapshot = StatsSnapshot {
+            processes: vec![
+                (ProcessName::P2pool, true, 2),
+                (ProcessName::Xmrig, false, 0),
+            ],
+            ..Default::default()
+        };
+        snapshot.p2pool.user_p2pool_hashrate_15m_u64 = 12345;
+        snapshot.p2pool.shares_found = Some(3);
+        let text = snapshot.render_metrics();
+        assert!(text.contains("# TYPE gupaxx_process_up gauge\n"));
+        assert!(text.contains("gupaxx_process_up{process=\"p2pool\"} 1\n"));
+        assert!(text.contains("gupaxx_process_up{process=\"xmrig\"} 0\n"));
+        assert!(text.contains("gupaxx_process_restarts{process=\"p2pool\"} 2\n"));
+        assert!(text.contains("gupaxx_p2pool_hashrate{window=\"15m\"} 12345\n"));
+        assert!(text.contains("gupaxx_p2pool_shares_found 3\n"));
+        // Dead processes are not rendered.
+        assert!(!text.contains("gupaxx_xmrig_hashrate"));
+        assert_eq!(label("k", "a\"b"), "{k=\"a\\\"b\"}");
+        // Non-finite values, like an effort computed without any hashrate yet.
+        snapshot.p2pool.current_effort_f32 = f32::INFINITY;
+        snapshot.p2pool.average_effort_f32 = f32::NAN;
+        let text = snapshot.render_metrics();
+        assert!(text.contains("gupaxx_p2pool_effort_percent{kind=\"current\"} +Inf\n"));
+        assert!(text.contains("gupaxx_p2pool_effort_percent{kind=\"average\"} NaN\n"));
+        assert_eq!(number(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(number(0.5), "0.5");
+    }
+
+    #[test]
+    fn render_metrics_unique() {
+        let mut snapshot = StatsSnapshot {
+            processes: vec![
+                (ProcessName::Node, false, 0),
+                (ProcessName::P2pool, true, 0),
+                (ProcessName::Xmrig, false, 0),
+                (ProcessName::XmrigProxy, true, 1),
+                (ProcessName::Xvb, false, 0),
+                (ProcessName::Custom(0), true, 0),
+            ],
+            ..Default::default()
+        };
+        snapshot.xmrig_proxy.hashrate_1m = 5000.0;
+        let text = snapshot.render_metrics();
+        assert!(text.contains("gupaxx_process_up{process=\"xmrig_proxy\"} 1\n"));
+        assert!(text.contains("gupaxx_process_restarts{process=\"xmrig_proxy\"} 1\n"));
+        assert!(text.contains("gupaxx_xmrig_proxy_hashrate{window=\"1m\"} 5000\n"));
+        // Prometheus rejects a scrape with the same series twice.
+        let mut series = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| line.rsplit_once(' ').unwrap().0)
+            .collect::<Vec<_>>();
+        let len = series.len();
+        series.sort_unstable();
+        series.dedup();
+        assert_eq!(series.len(), len);
+    }
+}
@@ -19,8 +19,8 @@
 // that runs alongside the main [App] GUI thread. It exists for the entire duration
 // of Gupax so that things can be handled without locking up the GUI thread.
 //
-// This thread is a continual 1 second loop, collecting available jobs on the
-// way down and (if possible) asynchronously executing them at the very end.
+// This thread wakes up every second to refresh [Sys] and the history, and on every
+// change of a process (see [events::changed()]) to publish the stats that changed.
 //
 // The main GUI thread will interface with this thread by mutating the Arc<Mutex>'s
 // found here, e.g: User clicks [Stop P2Pool] -> Arc<Mutex<ProcessSignal> is set
@@ -34,6 +34,13 @@
 // piping their stdout/stderr/stdin, accessing their APIs (HTTP + disk files), etc.
 
 //---------------------------------------------------------------------------------------------------- Import
+use crate::components::update::XMRIG_BINARY;
+use crate::disk::history::History;
+use crate::disk::state::{DependencyPolicy, LogPolicy, RestartMode, RestartPolicy, XvbMode};
+use crate::helper::console::ConsoleBuffer;
+use crate::helper::events::{EventSender, WatchdogEvent};
+use crate::helper::notify::Notifier;
+use crate::helper::snapshot::StatsSnapshot;
 use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
 use crate::helper::{
     p2pool::{ImgP2pool, PubP2poolApi},
@@ -44,6 +51,7 @@ use log::*;
 use node::PubNodeApi;
 use portable_pty::Child;
 use readable::up::Uptime;
+use serde::Serialize;
 use std::fmt::Write;
 use std::path::Path;
 use std::{
@@ -52,25 +60,36 @@ use std::{
     thread,
     time::*,
 };
+use tokio::sync::watch;
 
+use self::custom::Custom;
 use self::xvb::{nodes::XvbNode, PubXvbApi};
+pub mod console;
+pub mod control;
+pub mod custom;
+pub mod events;
+pub mod http_api;
+pub mod metrics;
 pub mod node;
+pub mod notify;
 pub mod p2pool;
+pub mod snapshot;
+pub mod supervised;
 pub mod tests;
 pub mod xrig;
 pub mod xvb;
 
 //---------------------------------------------------------------------------------------------------- Constants
-// The max amount of bytes of process output we are willing to
-// hold in memory before it's too much and we need to reset.
-const MAX_GUI_OUTPUT_BYTES: usize = 500_000;
-// Just a little leeway so a reset will go off before the [String] allocates more memory.
-const GUI_OUTPUT_LEEWAY: usize = MAX_GUI_OUTPUT_BYTES - 1000;
-
 // Some constants for generating hashrate/difficulty.
 const MONERO_BLOCK_TIME_IN_SECONDS: u64 = 120;
 const P2POOL_BLOCK_TIME_IN_SECONDS: u64 = 10;
 
+// How long a process must have been alive before
+// dying for its restart counter to be reset.
+const RESTART_STABLE_SECS: u64 = 300;
+// The exponential backoff between restarts is capped to this.
+const RESTART_MAX_BACKOFF_SECS: u64 = 600;
+
 //---------------------------------------------------------------------------------------------------- [Helper] Struct
 // A meta struct holding all the data that gets processed in this thread
 pub struct Helper {
@@ -95,18 +114,29 @@ pub struct Helper {
     pub_api_node: Arc<Mutex<PubNodeApi>>,     // Node API state (for Helper/Node thread)
     pub_api_xvb: Arc<Mutex<PubXvbApi>>,       // XvB API state (for Helper/XvB thread)
     pub gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>, //
+    pub history: Arc<Mutex<History>>,         // Stats history, recorded every second
+    pub custom: Vec<Custom>,                  // Processes from [processes.toml]
+    pub dependencies: DependencyPolicy,       // How long processes wait for the ones they depend on
+    pub notifier: Notifier,                   // Sends the events set in [Gupax.notifications]
+    pub logs_path: PathBuf,                   // Where the output of the processes is kept
+    pub logs: LogPolicy,                      // When the logs are rotated/deleted
+    pub snapshot: watch::Sender<Arc<StatsSnapshot>>, // Last stats published, for readers that should not lock
+    pub xvb_mode_request: Option<XvbMode>, // XvB mode set from the control socket, for the GUI to pick up
+    pub focus_request: bool,               // Gupaxx was launched again, the GUI should show itself
+    pub profile_request: Option<String>,   // Profile to switch to, for the GUI to do it
 }
 
 // The communication between the data here and the GUI thread goes as follows:
 // [GUI] <---> [Helper] <---> [Watchdog] <---> [Private Data only available here]
 //
-// Both [GUI] and [Helper] own their separate [Pub*Api] structs.
-// Since P2Pool & XMRig will be updating their information out of sync,
-// it's the helpers job to lock everything, and move the watchdog [Pub*Api]s
-// on a 1-second interval into the [GUI]'s [Pub*Api] struct, atomically.
+// Both [GUI] and [Watchdog] own their separate [Pub*Api] structs.
+// Each watchdog moves its [Pub*Api] into the [GUI]'s one itself, after every
+// event it handled (see [events]), locking only those two.
+// Each handover wakes up the [Helper], which takes a [StatsSnapshot] of them and publishes
+// it if it changed, which wakes up the GUI. It also feeds the history and the metrics.
 
 //----------------------------------------------------------------------------------------------------
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Sys {
     pub gupax_uptime: String,
     pub gupax_cpu_usage: String,
@@ -114,6 +144,10 @@ pub struct Sys {
     pub system_cpu_model: String,
     pub system_memory: String,
     pub system_cpu_usage: String,
+    pub system_cpu_temperature: String,
+    // The raw values the XMRig throttling policy is checked against.
+    pub cpu_temperature: Option<f32>, // Hottest CPU sensor in °C, if any
+    pub non_mining_load: f32,         // System CPU usage minus XMRig's, in %
 }
 
 impl Sys {
@@ -125,6 +159,9 @@ impl Sys {
             system_cpu_usage: "???%".to_string(),
             system_memory: "???GB / ???GB".to_string(),
             system_cpu_model: "???".to_string(),
+            system_cpu_temperature: "???°C".to_string(),
+            cpu_temperature: None,
+            non_mining_load: 0.0,
         }
     }
 }
@@ -172,6 +209,15 @@ pub struct Process {
 
     // Start time of process.
     start: std::time::Instant,
+
+    // Consecutive automatic restarts done following the [RestartPolicy], until the next manual start.
+    pub restarts: u8,
+    // Waiting for the [RestartPolicy] backoff to start again, [Stop] cancels it.
+    pub respawning: bool,
+    // Only set by [check_died()], meaning the process exited without being asked to.
+    exit_code: Option<u32>,
+    // Wakes up the watchdog, set by the watchdog when it starts.
+    pub events: Option<EventSender>,
 }
 
 //---------------------------------------------------------------------------------------------------- [Process] Impl
@@ -187,6 +233,37 @@ impl Process {
             output_parse: arc_mut!(String::with_capacity(500)),
             output_pub: arc_mut!(String::with_capacity(500)),
             input: vec![String::new()],
+            restarts: 0,
+            respawning: false,
+            exit_code: None,
+            events: None,
+        }
+    }
+
+    // Every change of [state] goes through here, so the ones waiting on it are woken up.
+    pub fn set_state(&mut self, state: ProcessState) {
+        if self.state != state {
+            self.state = state;
+            events::changed();
+        }
+    }
+
+    // Sets the [signal] and wakes up the watchdog so it is handled right away.
+    pub fn send_signal(&mut self, signal: ProcessSignal) {
+        self.signal = signal;
+        self.notify(WatchdogEvent::Signal);
+        events::changed();
+    }
+
+    // Queues a line for STDIN and wakes up the watchdog.
+    pub fn send_input(&mut self, input: String) {
+        self.input.push(input);
+        self.notify(WatchdogEvent::Input);
+    }
+
+    fn notify(&self, event: WatchdogEvent) {
+        if let Some(events) = &self.events {
+            events.send(event);
         }
     }
 
@@ -198,12 +275,137 @@ impl Process {
             || self.state == ProcessState::Syncing
             || self.state == ProcessState::NotMining
             || self.state == ProcessState::OfflineNodesAll
+            || self.state == ProcessState::Blocked
+    }
+
+    #[inline]
+    // On its way to [Alive], what the processes depending on it wait for.
+    pub fn is_starting(&self) -> bool {
+        self.state == ProcessState::Middle
+            || self.state == ProcessState::Waiting
+            || self.state == ProcessState::Syncing
+            || self.state == ProcessState::Blocked
     }
 
     #[inline]
     pub fn is_waiting(&self) -> bool {
         self.state == ProcessState::Middle || self.state == ProcessState::Waiting
     }
+
+    // Called once the watchdog returned. Following the [RestartPolicy], returns
+    // how long to wait before starting the process again, [None] if it should stay dead.
+    fn respawn_delay(
+        &mut self,
+        policy: &RestartPolicy,
+        gui_api_output_raw: &mut ConsoleBuffer,
+    ) -> Option<Duration> {
+        // Stopped/restarted by the user.
+        let exit_code = self.exit_code.take()?;
+        let failed = self.state == ProcessState::Failed;
+        match policy.mode {
+            RestartMode::Never => return None,
+            RestartMode::OnFailure if !failed => return None,
+            _ => (),
+        }
+        // It was running fine for a while, this is not a crash loop.
+        if self.start.elapsed().as_secs() >= RESTART_STABLE_SECS {
+            self.restarts = 0;
+        }
+        let reason = if failed {
+            format!("Exit code [{}]", exit_code)
+        } else {
+            "Exited on its own".to_string()
+        };
+        if self.restarts >= policy.max_retries {
+            warn!(
+                "{} | Giving up after [{}] restarts, reason: [{}]",
+                self.name, self.restarts, reason
+            );
+            if let Err(e) = writeln!(
+                gui_api_output_raw,
+                "{}\n{} gave up restarting after [{}] attempts | Reason: [{}]\n{}\n\n\n\n",
+                self.name, HORI_CONSOLE, self.restarts, reason, HORI_CONSOLE
+            ) {
+                error!(
+                    "{} Watchdog | GUI restart status write failed: {}",
+                    self.name, e
+                );
+            }
+            return None;
+        }
+        self.restarts += 1;
+        self.respawning = true;
+        events::changed();
+        let delay = policy
+            .backoff_secs
+            .saturating_mul(1 << (self.restarts - 1).min(16))
+            .min(RESTART_MAX_BACKOFF_SECS);
+        info!(
+            "{} | Restarting in [{}s], attempt [{}/{}], reason: [{}]",
+            self.name, delay, self.restarts, policy.max_retries, reason
+        );
+        if let Err(e) = writeln!(
+            gui_api_output_raw,
+            "{} restarting in [{}s] | Attempt: [{}/{}] | Reason: [{}]\n\n\n\n",
+            self.name, delay, self.restarts, policy.max_retries, reason
+        ) {
+            error!(
+                "{} Watchdog | GUI restart status write failed: {}",
+                self.name, e
+            );
+        }
+        // The GUI output is reset when the new watchdog starts,
+        // so this one goes where the output of the new process will go.
+        if let Err(e) = writeln!(
+            self.output_pub.lock().unwrap(),
+            "{}\n{} restarted | Attempt: [{}/{}] | Reason: [{}]\n{}\n",
+            self.name,
+            HORI_CONSOLE,
+            self.restarts,
+            policy.max_retries,
+            reason,
+            HORI_CONSOLE
+        ) {
+            error!(
+                "{} Watchdog | Restart status write failed: {}",
+                self.name, e
+            );
+        }
+        Some(Duration::from_secs(delay))
+    }
+
+    // Waits for the backoff [delay], returns [false] if the process was
+    // started by someone else or stopped by the user in the meantime.
+    #[tokio::main(flavor = "current_thread")]
+    async fn wait_respawn(process: &Arc<Mutex<Self>>, delay: Duration) -> bool {
+        let mut changes = events::subscribe();
+        let deadline = tokio::time::Instant::now() + delay;
+        loop {
+            {
+                let mut lock = process.lock().unwrap();
+                // [start_supervised()] clears it.
+                if !lock.respawning {
+                    info!(
+                        "{} | Started during the restart backoff, skipping",
+                        lock.name
+                    );
+                    return false;
+                }
+                if lock.signal == ProcessSignal::Stop {
+                    info!("{} | Stopped during the restart backoff", lock.name);
+                    lock.respawning = false;
+                    lock.signal = ProcessSignal::None;
+                    return false;
+                }
+            }
+            if tokio::time::timeout_at(deadline, changes.changed())
+                .await
+                .is_err()
+            {
+                return true;
+            }
+        }
+    }
 }
 
 //---------------------------------------------------------------------------------------------------- [Process*] Enum
@@ -219,6 +421,9 @@ pub enum ProcessState {
     // XvB: Xmrig or P2pool are not alive
     Syncing,
 
+    // Started, but waiting for a process it depends on to be ready, YELLOW!
+    Blocked,
+
     // Only for XMRig and XvB, ORANGE.
     // XvB: token or address are invalid even if syntax correct
     NotMining,
@@ -254,6 +459,7 @@ pub enum ProcessName {
     XmrigProxy,
     Xvb,
     Node,
+    Custom(usize), // Index in [processes.toml]
 }
 
 impl std::fmt::Display for ProcessState {
@@ -274,6 +480,22 @@ impl std::fmt::Display for ProcessName {
             ProcessName::XmrigProxy => write!(f, "XMRig-Proxy"),
             ProcessName::Xvb => write!(f, "XvB"),
             ProcessName::Node => write!(f, "Node"),
+            ProcessName::Custom(i) => write!(f, "Custom #{}", i + 1),
+        }
+    }
+}
+
+impl ProcessName {
+    // The name the process goes by in the CLI, the HTTP API and the metrics,
+    // see [control::parse_process_name()].
+    pub fn id(&self) -> String {
+        match *self {
+            ProcessName::P2pool => "p2pool".to_string(),
+            ProcessName::Xmrig => "xmrig".to_string(),
+            ProcessName::XmrigProxy => "xmrig_proxy".to_string(),
+            ProcessName::Xvb => "xvb".to_string(),
+            ProcessName::Node => "node".to_string(),
+            ProcessName::Custom(i) => format!("custom_{}", i + 1),
         }
     }
 }
@@ -298,6 +520,7 @@ impl Helper {
         img_p2pool: Arc<Mutex<ImgP2pool>>,
         img_xmrig: Arc<Mutex<ImgXmrig>>,
         gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
+        history: Arc<Mutex<History>>,
     ) -> Self {
         Self {
             instant,
@@ -322,27 +545,16 @@ impl Helper {
             img_p2pool,
             img_xmrig,
             gupax_p2pool_api,
-        }
-    }
-
-    // Reset output if larger than max bytes.
-    // This will also append a message showing it was reset.
-    fn check_reset_gui_output(output: &mut String, name: ProcessName) {
-        let len = output.len();
-        if len > GUI_OUTPUT_LEEWAY {
-            info!(
-                "{} Watchdog | Output is nearing {} bytes, resetting!",
-                name, MAX_GUI_OUTPUT_BYTES
-            );
-            let text = format!("{}\n{} GUI log is exceeding the maximum: {} bytes!\nI've reset the logs for you!\n{}\n\n\n\n", HORI_CONSOLE, name, MAX_GUI_OUTPUT_BYTES, HORI_CONSOLE);
-            output.clear();
-            output.push_str(&text);
-            debug!("{} Watchdog | Resetting GUI output ... OK", name);
-        } else {
-            debug!(
-                "{} Watchdog | GUI output reset not needed! Current byte length ... {}",
-                name, len
-            );
+            history,
+            custom: vec![],
+            dependencies: DependencyPolicy::default(),
+            notifier: Notifier::spawn(),
+            logs_path: PathBuf::new(),
+            logs: LogPolicy::default(),
+            snapshot: watch::channel(Arc::new(StatsSnapshot::default())).0,
+            xvb_mode_request: None,
+            focus_request: false,
+            profile_request: None,
         }
     }
 
@@ -363,6 +575,7 @@ impl Helper {
     #[inline(always)] // called once
     fn update_pub_sys_from_sysinfo(
         sysinfo: &sysinfo::System,
+        components: &sysinfo::Components,
         pub_sys: &mut Sys,
         pid: &sysinfo::Pid,
         helper: &Helper,
@@ -383,12 +596,21 @@ impl Helper {
             let total = (sysinfo.total_memory() as f64) / 1_000_000_000.0;
             format!("{:.3} GB / {:.3} GB", used, total)
         };
-        let system_cpu_usage = {
-            let mut total: f32 = 0.0;
-            for cpu in sysinfo.cpus() {
-                total += cpu.cpu_usage();
-            }
-            format!("{:.2}%", total / (max_threads as f32))
+        let mut total: f32 = 0.0;
+        for cpu in sysinfo.cpus() {
+            total += cpu.cpu_usage();
+        }
+        let system_cpu_usage = format!("{:.2}%", total / (max_threads as f32));
+        // XMRig runs under [sudo] on Unix, so it is found by its name rather than its PID.
+        let xmrig_usage: f32 = sysinfo
+            .processes_by_exact_name(XMRIG_BINARY.as_ref())
+            .map(|process| process.cpu_usage())
+            .sum();
+        let non_mining_load = ((total - xmrig_usage) / (max_threads as f32)).max(0.0);
+        let cpu_temperature = cpu_temperature(components);
+        let system_cpu_temperature = match cpu_temperature {
+            Some(temperature) => format!("{:.1}°C", temperature),
+            None => "???°C".to_string(),
         };
         *pub_sys = Sys {
             gupax_uptime,
@@ -397,224 +619,136 @@ impl Helper {
             system_cpu_usage,
             system_memory,
             system_cpu_model,
+            system_cpu_temperature,
+            cpu_temperature,
+            non_mining_load,
         };
     }
 
     #[cold]
     #[inline(never)]
     // The "helper" thread. Syncs data between threads here and the GUI.
-    #[allow(clippy::await_holding_lock)]
     pub fn spawn_helper(
         helper: &Arc<Mutex<Self>>,
-        mut sysinfo: sysinfo::System,
+        sysinfo: sysinfo::System,
         pid: sysinfo::Pid,
         max_threads: usize,
     ) {
-        // The ordering of these locks is _very_ important. They MUST be in sync with how the main GUI thread locks stuff
-        // or a deadlock will occur given enough time. They will eventually both want to lock the [Arc<Mutex>] the other
-        // thread is already locking. Yes, I figured this out the hard way, hence the vast amount of debug!() messages.
-        // Example of different order (BAD!):
-        //
-        // GUI Main       -> locks [p2pool] first
-        // Helper         -> locks [gui_api_p2pool] first
-        // GUI Status Tab -> tries to lock [gui_api_p2pool] -> CAN'T
-        // Helper         -> tries to lock [p2pool] -> CAN'T
-        //
-        // These two threads are now in a deadlock because both
-        // are trying to access locks the other one already has.
-        //
-        // The locking order here must be in the same chronological
-        // order as the main GUI thread (top to bottom).
-
         let helper = Arc::clone(helper);
-        let lock = helper.lock().unwrap();
-        let node = Arc::clone(&lock.node);
-        let p2pool = Arc::clone(&lock.p2pool);
-        let xmrig = Arc::clone(&lock.xmrig);
-        let xmrig_proxy = Arc::clone(&lock.xmrig_proxy);
-        let xvb = Arc::clone(&lock.xvb);
-        let pub_sys = Arc::clone(&lock.pub_sys);
-        let gui_api_node = Arc::clone(&lock.gui_api_node);
-        let gui_api_p2pool = Arc::clone(&lock.gui_api_p2pool);
-        let gui_api_xmrig = Arc::clone(&lock.gui_api_xmrig);
-        let gui_api_xp = Arc::clone(&lock.gui_api_xp);
-        let gui_api_xvb = Arc::clone(&lock.gui_api_xvb);
-        let pub_api_node = Arc::clone(&lock.pub_api_node);
-        let pub_api_p2pool = Arc::clone(&lock.pub_api_p2pool);
-        let pub_api_xmrig = Arc::clone(&lock.pub_api_xmrig);
-        let pub_api_xp = Arc::clone(&lock.pub_api_xp);
-        let pub_api_xvb = Arc::clone(&lock.pub_api_xvb);
-        drop(lock);
+        thread::spawn(move || {
+            info!("Helper | Hello from helper thread! Entering loop where I will spend the rest of my days...");
+            Self::helper_loop(helper, sysinfo, pid, max_threads);
+        });
+    }
+
+    #[tokio::main(flavor = "current_thread")]
+    async fn helper_loop(
+        helper: Arc<Mutex<Self>>,
+        mut sysinfo: sysinfo::System,
+        pid: sysinfo::Pid,
+        max_threads: usize,
+    ) {
+        // Nothing here locks more than one [Arc<Mutex>] at a time (other than the [Helper] itself),
+        // the watchdogs hand over their stats to the GUI themselves. This used to lock all 17 of
+        // them in the same order as the GUI, or the two threads would deadlock each other.
+        let (pub_sys, history) = {
+            let lock = helper.lock().unwrap();
+            (Arc::clone(&lock.pub_sys), Arc::clone(&lock.history))
+        };
 
         let sysinfo_cpu = sysinfo::CpuRefreshKind::everything();
         let sysinfo_processes = sysinfo::ProcessRefreshKind::new().with_cpu();
+        let mut components = sysinfo::Components::new_with_refreshed_list();
+
+        // [Sys] and the history go by the second, the stats by the events of the processes.
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut changes = events::subscribe();
+        loop {
+            let refresh = tokio::select! {
+                _ = tick.tick() => true,
+                _ = changes.changed() => false,
+            };
+            debug!(
+                "Helper | ----------- Woken up, refresh: [{}] -----------",
+                refresh
+            );
 
-        thread::spawn(move || {
-            info!("Helper | Hello from helper thread! Entering loop where I will spend the rest of my days...");
-            // Begin loop
-            loop {
-                // 1. Loop init timestamp
-                let start = Instant::now();
-                debug!("Helper | ----------- Start of loop -----------");
-
-                // Ignore the invasive [debug!()] messages on the right side of the code.
-                // The reason why they are there are so that it's extremely easy to track
-                // down the culprit of an [Arc<Mutex>] deadlock. I know, they're ugly.
-
-                // 2. Lock... EVERYTHING!
-                let mut lock = helper.lock().unwrap();
-                debug!("Helper | Locked (1/17) ... [helper]");
-                let node = node.lock().unwrap();
-                debug!("Helper | Locked (2/17) ... [node]");
-                let p2pool = p2pool.lock().unwrap();
-                debug!("Helper | Locked (3/17) ... [p2pool]");
-                let xmrig = xmrig.lock().unwrap();
-                debug!("Helper | Locked (4/17) ... [xmrig]");
-                let xmrig_proxy = xmrig_proxy.lock().unwrap();
-                debug!("Helper | Locked (5/17) ... [xmrig_proxy]");
-                let xvb = xvb.lock().unwrap();
-                debug!("Helper | Locked (6/17) ... [xvb]");
-                let mut lock_pub_sys = pub_sys.lock().unwrap();
-                debug!("Helper | Locked (8/17) ... [pub_sys]");
-                let mut gui_api_node = gui_api_node.lock().unwrap();
-                debug!("Helper | Locked (7/17) ... [gui_api_node]");
-                let mut gui_api_p2pool = gui_api_p2pool.lock().unwrap();
-                debug!("Helper | Locked (9/17) ... [gui_api_p2pool]");
-                let mut gui_api_xmrig = gui_api_xmrig.lock().unwrap();
-                debug!("Helper | Locked (10/17) ... [gui_api_xmrig]");
-                let mut gui_api_xp = gui_api_xp.lock().unwrap();
-                debug!("Helper | Locked (11/17) ... [gui_api_xp]");
-                let mut gui_api_xvb = gui_api_xvb.lock().unwrap();
-                debug!("Helper | Locked (12/17) ... [gui_api_xvb]");
-                let mut pub_api_node = pub_api_node.lock().unwrap();
-                debug!("Helper | Locked (13/17) ... [pub_api_node]");
-                let mut pub_api_p2pool = pub_api_p2pool.lock().unwrap();
-                debug!("Helper | Locked (14/17) ... [pub_api_p2pool]");
-                let mut pub_api_xmrig = pub_api_xmrig.lock().unwrap();
-                debug!("Helper | Locked (15/17) ... [pub_api_xmrig]");
-                let mut pub_api_xp = pub_api_xp.lock().unwrap();
-                debug!("Helper | Locked (16/17) ... [pub_api_xp]");
-                let mut pub_api_xvb = pub_api_xvb.lock().unwrap();
-                debug!("Helper | Locked (17/17) ... [pub_api_xvb]");
-                // Calculate Gupax's uptime always.
-                lock.uptime = HumanTime::into_human(lock.instant.elapsed());
-                // If [Node] is alive...
-                if node.is_alive() {
-                    debug!("Helper | Node is alive! Running [combine_gui_pub_api()]");
-                    PubNodeApi::combine_gui_pub_api(&mut gui_api_node, &mut pub_api_node);
-                } else {
-                    debug!("Helper | Node is dead! Skipping...");
-                }
-                // If [P2Pool] is alive...
-                if p2pool.is_alive() {
-                    debug!("Helper | P2Pool is alive! Running [combine_gui_pub_api()]");
-                    PubP2poolApi::combine_gui_pub_api(&mut gui_api_p2pool, &mut pub_api_p2pool);
-                } else {
-                    debug!("Helper | P2Pool is dead! Skipping...");
-                }
-                // If [XMRig] is alive...
-                if xmrig.is_alive() {
-                    debug!("Helper | XMRig is alive! Running [combine_gui_pub_api()]");
-                    PubXmrigApi::combine_gui_pub_api(&mut gui_api_xmrig, &mut pub_api_xmrig);
-                } else {
-                    debug!("Helper | XMRig is dead! Skipping...");
-                }
-                // If [XMRig-Proxy] is alive...
-                if xmrig_proxy.is_alive() {
-                    debug!("Helper | XMRig-Proxy is alive! Running [combine_gui_pub_api()]");
-                    PubXmrigProxyApi::combine_gui_pub_api(&mut gui_api_xp, &mut pub_api_xp);
-                } else {
-                    debug!("Helper | XMRig-Proxy is dead! Skipping...");
-                }
-                // If [XvB] is alive...
-                if xvb.is_alive() {
-                    debug!("Helper | XvB is alive! Running [combine_gui_pub_api()]");
-                    PubXvbApi::combine_gui_pub_api(&mut gui_api_xvb, &mut pub_api_xvb);
-                } else {
-                    debug!("Helper | XvB is dead! Skipping...");
-                }
-
-                // 2. Selectively refresh [sysinfo] for only what we need (better performance).
+            if refresh {
+                // Selectively refresh [sysinfo] for only what we need (better performance).
                 sysinfo.refresh_cpu_specifics(sysinfo_cpu);
-                debug!("Helper | Sysinfo refresh (1/3) ... [cpu]");
+                debug!("Helper | Sysinfo refresh (1/4) ... [cpu]");
                 sysinfo.refresh_processes_specifics(
                     sysinfo::ProcessesToUpdate::All,
                     false,
                     sysinfo_processes,
                 );
-                debug!("Helper | Sysinfo refresh (2/3) ... [processes]");
+                debug!("Helper | Sysinfo refresh (2/4) ... [processes]");
                 sysinfo.refresh_memory();
-                debug!("Helper | Sysinfo refresh (3/3) ... [memory]");
+                debug!("Helper | Sysinfo refresh (3/4) ... [memory]");
+                components.refresh();
+                debug!("Helper | Sysinfo refresh (4/4) ... [components]");
+            }
+
+            // Calculate Gupax's uptime along with [Sys], then take the snapshot.
+            let mut lock = helper.lock().unwrap();
+            if refresh {
+                lock.uptime = HumanTime::into_human(lock.instant.elapsed());
                 debug!("Helper | Sysinfo OK, running [update_pub_sys_from_sysinfo()]");
                 Self::update_pub_sys_from_sysinfo(
                     &sysinfo,
-                    &mut lock_pub_sys,
+                    &components,
+                    &mut pub_sys.lock().unwrap(),
                     &pid,
                     &lock,
                     max_threads,
                 );
-
-                // 3. Drop... (almost) EVERYTHING... IN REVERSE!
-                drop(lock_pub_sys);
-                debug!("Helper | Unlocking (1/17) ... [pub_sys]");
-                drop(xvb);
-                debug!("Helper | Unlocking (2/17) ... [xvb]");
-                drop(xmrig_proxy);
-                debug!("Helper | Unlocking (3/17) ... [xmrig_proxy]");
-                drop(xmrig);
-                debug!("Helper | Unlocking (4/17) ... [xmrig]");
-                drop(p2pool);
-                debug!("Helper | Unlocking (5/17) ... [p2pool]");
-                drop(node);
-                debug!("Helper | Unlocking (6/17) ... [node]");
-                drop(pub_api_xvb);
-                debug!("Helper | Unlocking (7/17) ... [pub_api_xvb]");
-                drop(pub_api_xp);
-                debug!("Helper | Unlocking (8/17) ... [pub_api_xp]");
-                drop(pub_api_xmrig);
-                debug!("Helper | Unlocking (9/17) ... [pub_api_xmrig]");
-                drop(pub_api_p2pool);
-                debug!("Helper | Unlocking (10/17) ... [pub_api_p2pool]");
-                drop(pub_api_node);
-                debug!("Helper | Unlocking (11/17) ... [pub_api_node]");
-                drop(gui_api_xvb);
-                debug!("Helper | Unlocking (12/17) ... [gui_api_xvb]");
-                drop(gui_api_xp);
-                debug!("Helper | Unlocking (13/17) ... [gui_api_xp]");
-                drop(gui_api_xmrig);
-                debug!("Helper | Unlocking (14/17) ... [gui_api_xmrig]");
-                drop(gui_api_p2pool);
-                debug!("Helper | Unlocking (15/17) ... [gui_api_p2pool]");
-                drop(gui_api_node);
-                debug!("Helper | Unlocking (16/17) ... [gui_api_node]");
-                drop(lock);
-                debug!("Helper | Unlocking (17/17) ... [helper]");
-
-                // 4. Calculate if we should sleep or not.
-                // If we should sleep, how long?
-                let elapsed = start.elapsed().as_millis();
-                if elapsed < 1000 {
-                    // Casting from u128 to u64 should be safe here, because [elapsed]
-                    // is less than 1000, meaning it can fit into a u64 easy.
-                    let sleep = (1000 - elapsed) as u64;
-                    debug!("Helper | END OF LOOP - Sleeping for [{}]ms...", sleep);
-                    sleep!(sleep);
-                } else {
-                    debug!("Helper | END OF LOOP - Not sleeping!");
+            }
+            let snapshot = StatsSnapshot::from_helper(&lock);
+            let sender = lock.snapshot.clone();
+            drop(lock);
+
+            // Record the stats history, nothing to record if nothing is mining.
+            if refresh {
+                if let Some(sample) = snapshot.history_sample() {
+                    if let Err(e) = history.lock().unwrap().record(sample) {
+                        warn!("Helper | Could not write the stats history: {}", e);
+                    }
                 }
-
-                // 5. End loop
             }
-        });
+            // Publish if anything changed, this wakes up the GUI.
+            sender.send_if_modified(|old| {
+                if **old == snapshot {
+                    return false;
+                }
+                *old = Arc::new(snapshot);
+                true
+            });
+        }
     }
 }
 
+// The hottest of the CPU sensors (hwmon on Linux), [None] if none can be read.
+fn cpu_temperature(components: &sysinfo::Components) -> Option<f32> {
+    const CPU_LABELS: [&str; 6] = ["cpu", "package", "core", "tctl", "tdie", "k10temp"];
+    components
+        .list()
+        .iter()
+        .filter(|component| {
+            let label = component.label().to_lowercase();
+            CPU_LABELS.iter().any(|cpu| label.contains(cpu))
+        })
+        .map(|component| component.temperature())
+        .filter(|temperature| temperature.is_finite() && *temperature > 0.0)
+        .reduce(f32::max)
+}
+
 // common functions inside watchdog thread
 fn check_died(
     child_pty: &Arc<Mutex<Box<dyn Child + Sync + Send>>>,
     process: &mut Process,
     start: &Instant,
-    gui_api_output_raw: &mut String,
+    gui_api_output_raw: &mut ConsoleBuffer,
 ) -> bool {
     // Check if the process secretly died without us knowing :)
     if let Ok(Some(code)) = child_pty.lock().unwrap().try_wait() {
@@ -624,11 +758,11 @@ fn check_died(
         );
         let exit_status = match code.success() {
             true => {
-                process.state = ProcessState::Dead;
+                process.set_state(ProcessState::Dead);
                 "Successful"
             }
             false => {
-                process.state = ProcessState::Failed;
+                process.set_state(ProcessState::Failed);
                 "Failed"
             }
         };
@@ -648,6 +782,7 @@ fn check_died(
             );
         }
         process.signal = ProcessSignal::None;
+        process.exit_code = Some(code.exit_code());
         debug!(
             "{} Watchdog | Secret dead process reap OK, breaking",
             process.name
@@ -687,7 +822,7 @@ fn signal_end(
     process: &mut Process,
     child_pty: &Arc<Mutex<Box<dyn Child + Sync + Send>>>,
     start: &Instant,
-    gui_api_output_raw: &mut String,
+    gui_api_output_raw: &mut ConsoleBuffer,
 ) -> bool {
     let mut child_pty_lock = child_pty.lock().unwrap();
     if process.signal == ProcessSignal::Stop {
@@ -700,15 +835,15 @@ fn signal_end(
         let exit_status = match child_pty_lock.wait() {
             Ok(e) => {
                 if e.success() {
-                    process.state = ProcessState::Dead;
+                    process.set_state(ProcessState::Dead);
                     "Successful"
                 } else {
-                    process.state = ProcessState::Failed;
+                    process.set_state(ProcessState::Failed);
                     "Failed"
                 }
             }
             _ => {
-                process.state = ProcessState::Failed;
+                process.set_state(ProcessState::Failed);
                 "Unknown Error"
             }
         };
@@ -767,24 +902,9 @@ fn signal_end(
                 name, e
             );
         }
-        process.state = ProcessState::Waiting;
+        process.set_state(ProcessState::Waiting);
         debug!("{} Watchdog | Restart SIGNAL done, breaking", process.name,);
         return true;
     }
     false
 }
-async fn sleep_end_loop(now: Instant, name: ProcessName) {
-    // Sleep (only if 999ms hasn't passed)
-    let elapsed = now.elapsed().as_millis();
-    // Since logic goes off if less than 1000, casting should be safe
-    if elapsed < 1000 {
-        let sleep = (1000 - elapsed) as u64;
-        debug!(
-            "{} Watchdog | END OF LOOP - Sleeping for [{}]ms...",
-            name, sleep
-        );
-        tokio::time::sleep(Duration::from_millis(sleep)).await;
-    } else {
-        debug!("{} Watchdog | END OF LOOP - Not sleeping!", name);
-    }
-}
@@ -1,5 +1,4 @@
-use enclose::enc;
-use log::{debug, error, info, warn};
+use log::{debug, warn};
 use readable::byte::Byte;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -8,53 +7,22 @@ use std::os::windows::fs::MetadataExt;
 use std::{
     path::Path,
     sync::{Arc, Mutex},
-    thread,
-    time::{Duration, Instant},
+    time::Duration,
 };
-use tokio::spawn;
 
+use crate::helper::console::ConsoleBuffer;
 use crate::{
-    disk::state::Node,
+    disk::state::{Node, RestartPolicy},
     helper::{
-        check_died, check_user_input, signal_end, sleep_end_loop, ProcessName, ProcessSignal,
-        ProcessState,
+        notify::{Notifier, NotifyEvent},
+        supervised::{Handles, PubApi, SupervisedProcess, Watchdog},
+        ProcessName, ProcessState,
     },
-    macros::{arc_mut, sleep},
 };
-use std::fmt::Write;
 
 use super::{Helper, HumanNumber, HumanTime, Process};
 
 impl Helper {
-    #[cold]
-    #[inline(never)]
-    fn read_pty_node(
-        output_parse: Arc<Mutex<String>>,
-        output_pub: Arc<Mutex<String>>,
-        reader: Box<dyn std::io::Read + Send>,
-    ) {
-        use std::io::BufRead;
-        let mut stdout = std::io::BufReader::new(reader).lines();
-
-        // Run a ANSI escape sequence filter.
-        while let Some(Ok(line)) = stdout.next() {
-            let line = strip_ansi_escapes::strip_str(line);
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("Node PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("Node PTY Pub | Output error: {}", e);
-            }
-        }
-        while let Some(Ok(line)) = stdout.next() {
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("P2Pool PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("P2Pool PTY Pub | Output error: {}", e);
-            }
-        }
-    }
     pub fn build_node_args(state: &crate::disk::state::Node) -> Vec<String> {
         let mut args = Vec::with_capacity(500);
 
@@ -121,9 +89,7 @@ impl Helper {
     #[inline(never)]
     // Just sets some signals for the watchdog thread to pick up on.
     pub fn stop_node(helper: &Arc<Mutex<Self>>) {
-        info!("Node | Attempting to stop...");
-        helper.lock().unwrap().node.lock().unwrap().signal = ProcessSignal::Stop;
-        helper.lock().unwrap().node.lock().unwrap().state = ProcessState::Middle;
+        Self::stop_supervised(&helper.lock().unwrap().node);
         let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_node);
         let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_node);
         *pub_api.lock().unwrap() = PubNodeApi::new();
@@ -132,176 +98,121 @@ impl Helper {
     #[cold]
     #[inline(never)]
     // The "restart frontend" to a "frontend" function.
-    // Basically calls to kill the current p2pool, waits a little, then starts the below function in a a new thread, then exit.
     pub fn restart_node(helper: &Arc<Mutex<Self>>, state: &Node, path: &Path) {
-        info!("Node | Attempting to restart...");
-        helper.lock().unwrap().node.lock().unwrap().signal = ProcessSignal::Restart;
-        helper.lock().unwrap().node.lock().unwrap().state = ProcessState::Middle;
-
-        let helper = Arc::clone(helper);
-        let state = state.clone();
-        let path = path.to_path_buf();
-        // This thread lives to wait, start p2pool then die.
-        thread::spawn(move || {
-            while helper.lock().unwrap().node.lock().unwrap().state != ProcessState::Waiting {
-                warn!("Node | Want to restart but process is still alive, waiting...");
-                sleep!(1000);
-            }
-            // Ok, process is not alive, start the new one!
-            info!("Node | Old process seems dead, starting new one!");
-            Self::start_node(&helper, &state, &path);
-        });
-        info!("Node | Restart ... OK");
+        let node = NodeProcess::new(&helper.lock().unwrap(), state);
+        Self::restart_supervised(helper, node, path);
     }
     #[cold]
     #[inline(never)]
-    // The "frontend" function that parses the arguments, and spawns either the [Simple] or [Advanced] Node watchdog thread.
+    // The "frontend" function that parses the arguments, and spawns the Node watchdog thread.
     pub fn start_node(helper: &Arc<Mutex<Self>>, state: &Node, path: &Path) {
-        helper.lock().unwrap().node.lock().unwrap().state = ProcessState::Middle;
+        let node = NodeProcess::new(&helper.lock().unwrap(), state);
+        Self::start_supervised(helper, node, path);
+    }
+}
+//---------------------------------------------------------------------------------------------------- [SupervisedProcess]
+#[derive(Clone)]
+pub struct NodeProcess {
+    state: Node,
+    client: Client,
+    notifier: Notifier,
+}
 
-        let args = Self::build_node_args(state);
+impl NodeProcess {
+    pub fn new(helper: &Helper, state: &Node) -> Self {
+        Self {
+            state: state.clone(),
+            client: Client::new(),
+            notifier: helper.notifier.clone(),
+        }
+    }
+}
 
-        // Print arguments & user settings to console
-        crate::disk::print_dash(&format!("Node | Launch arguments: {:#?}", args));
+impl SupervisedProcess for NodeProcess {
+    const SPAWNED_STATE: ProcessState = ProcessState::Syncing;
+    const ANSI_LINES: usize = usize::MAX;
+    type Api = PubNodeApi;
+    type Lines = ();
 
-        // Spawn watchdog thread
-        let process = Arc::clone(&helper.lock().unwrap().node);
-        let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_node);
-        let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_node);
-        let path = path.to_path_buf();
-        let state = state.clone();
-        thread::spawn(move || {
-            Self::spawn_node_watchdog(&process, &gui_api, &pub_api, args, path, state);
-        });
+    fn name(&self) -> ProcessName {
+        ProcessName::Node
+    }
+
+    fn handles(&self, helper: &Helper) -> Handles<PubNodeApi> {
+        (
+            Arc::clone(&helper.node),
+            Arc::clone(&helper.gui_api_node),
+            Arc::clone(&helper.pub_api_node),
+        )
+    }
+
+    fn restart_policy(&self) -> &RestartPolicy {
+        &self.state.restart
     }
-    #[tokio::main]
-    #[allow(clippy::await_holding_lock)]
-    #[allow(clippy::too_many_arguments)]
-    async fn spawn_node_watchdog(
-        process: &Arc<Mutex<Process>>,
-        gui_api: &Arc<Mutex<PubNodeApi>>,
-        pub_api: &Arc<Mutex<PubNodeApi>>,
-        args: Vec<String>,
-        path: std::path::PathBuf,
-        state: Node,
+
+    fn args(&mut self, _helper: &Arc<Mutex<Helper>>, _path: &Path) -> Vec<String> {
+        Helper::build_node_args(&self.state)
+    }
+
+    fn lines(&self) {}
+
+    // No need to parse the output since monerod has a sufficient API
+    fn update_from_output(
+        &mut self,
+        pub_api: &mut PubNodeApi,
+        _output_parse: &Arc<Mutex<String>>,
+        output_pub: &Arc<Mutex<String>>,
+        elapsed: std::time::Duration,
+        _process: &mut Process,
     ) {
-        process.lock().unwrap().start = Instant::now();
-        // spawn pty
-        debug!("Node | Creating PTY...");
-        let pty = portable_pty::native_pty_system();
-        let pair = pty
-            .openpty(portable_pty::PtySize {
-                rows: 100,
-                cols: 1000,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .unwrap();
-        // 4. Spawn PTY read thread
-        debug!("Node | Spawning PTY read thread...");
-        let reader = pair.master.try_clone_reader().unwrap(); // Get STDOUT/STDERR before moving the PTY
-        let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
-        spawn(enc!((output_parse, output_pub) async move {
-            Self::read_pty_node(output_parse, output_pub, reader);
-        }));
-        // 1b. Create command
-        debug!("Node | Creating command...");
-        let mut cmd = portable_pty::cmdbuilder::CommandBuilder::new(path.clone());
-        cmd.args(args);
-        cmd.cwd(path.as_path().parent().unwrap());
-        // 1c. Create child
-        debug!("Node | Creating child...");
-        let child_pty = arc_mut!(pair.slave.spawn_command(cmd).unwrap());
-        drop(pair.slave);
-        let mut stdin = pair.master.take_writer().unwrap();
-        // set state
-        let client = Client::new();
-        process.lock().unwrap().state = ProcessState::Syncing;
-        process.lock().unwrap().signal = ProcessSignal::None;
-        // reset stats
-        *pub_api.lock().unwrap() = PubNodeApi::new();
-        *gui_api.lock().unwrap() = PubNodeApi::new();
-        // loop
-        let start = process.lock().unwrap().start;
-        info!("Node | Entering watchdog mode... woof!");
-        loop {
-            let now = Instant::now();
-            debug!("Node Watchdog | ----------- Start of loop -----------");
-            {
-                // scope to drop locked mutex before the sleep
-                // check state
-                if check_died(
-                    &child_pty,
-                    &mut process.lock().unwrap(),
-                    &start,
-                    &mut gui_api.lock().unwrap().output,
-                ) {
-                    break;
-                }
-                // check signal
-                if signal_end(
-                    &mut process.lock().unwrap(),
-                    &child_pty,
-                    &start,
-                    &mut gui_api.lock().unwrap().output,
-                ) {
-                    break;
-                }
-                // check user input
-                check_user_input(process, &mut stdin);
-                // get data output/api
+        PubNodeApi::update_from_output(pub_api, output_pub, elapsed);
+    }
 
-                // Check if logs need resetting
-                debug!("Node Watchdog | Attempting GUI log reset check");
-                {
-                    Self::check_reset_gui_output(
-                        &mut gui_api.lock().unwrap().output,
-                        ProcessName::Node,
-                    );
-                }
-                // No need to check output since monerod has a sufficient API
-                // Always update from output
-                debug!("Node Watchdog | Starting [update_from_output()]");
-                PubNodeApi::update_from_output(pub_api, &output_pub, start.elapsed());
-                // update data from api
-                debug!("Node Watchdog | Attempting HTTP API request...");
-                match PrivNodeApi::request_api(&client, &state).await {
-                    Ok(priv_api) => {
-                        debug!(
-                            "Node Watchdog | HTTP API request OK, attempting [update_from_priv()]"
-                        );
-                        if priv_api.result.synchronized && priv_api.result.status == "OK" {
-                            process.lock().unwrap().state = ProcessState::Alive
-                        }
-                        PubNodeApi::update_from_priv(pub_api, priv_api);
-                    }
-                    Err(err) => {
-                        // if node is just starting, do not throw an error
-                        if start.elapsed() > Duration::from_secs(10) {
-                            warn!(
-                                "Node Watchdog | Could not send HTTP API request to node\n{}",
-                                err
-                            );
-                        }
+    async fn tick(&mut self, watchdog: &mut Watchdog<'_, PubNodeApi>) {
+        // update data from api
+        debug!("Node Watchdog | Attempting HTTP API request...");
+        match PrivNodeApi::request_api(&self.client, &self.state).await {
+            Ok(priv_api) => {
+                debug!("Node Watchdog | HTTP API request OK, attempting [update_from_priv()]");
+                let synchronized = priv_api.result.synchronized && priv_api.result.status == "OK";
+                let mut process = watchdog.process.lock().unwrap();
+                match process.state {
+                    ProcessState::Syncing if synchronized => process.set_state(ProcessState::Alive),
+                    // Back to [Syncing], so what depends on the node knows it is not ready.
+                    ProcessState::Alive if !synchronized => {
+                        warn!("Node Watchdog | Node is not synchronized anymore");
+                        process.set_state(ProcessState::Syncing);
+                        self.notifier.notify(NotifyEvent::SyncLoss {
+                            height: priv_api.result.height,
+                        });
                     }
+                    _ => (),
+                }
+                drop(process);
+                PubNodeApi::update_from_priv(watchdog.pub_api, priv_api);
+            }
+            Err(err) => {
+                // if node is just starting, do not throw an error
+                if watchdog.start.elapsed() > Duration::from_secs(10) {
+                    warn!(
+                        "Node Watchdog | Could not send HTTP API request to node\n{}",
+                        err
+                    );
                 }
             }
-            // do not use more than 1 second for the loop
-            sleep_end_loop(now, ProcessName::Node).await;
         }
-
-        // 5. If loop broke, we must be done here.
-        info!("XMRig-Proxy Watchdog | Watchdog thread exiting... Goodbye!");
-        // sleep
     }
 }
-#[derive(Clone)]
+
+#[derive(Clone, PartialEq, Serialize)]
 pub struct PubNodeApi {
-    pub output: String,
+    #[serde(skip)]
+    pub output: ConsoleBuffer,
     pub uptime: HumanTime,
     pub blockheight: HumanNumber,
     pub difficulty: HumanNumber,
+    pub blockheight_u64: u64,
+    pub difficulty_u64: u64,
     pub database_size: String,
     pub free_space: String,
     pub nettype: String,
@@ -319,10 +230,12 @@ impl Default for PubNodeApi {
 impl PubNodeApi {
     pub fn new() -> Self {
         Self {
-            output: String::new(),
+            output: ConsoleBuffer::new(),
             uptime: HumanTime::new(),
             blockheight: HumanNumber::unknown(),
             difficulty: HumanNumber::unknown(),
+            blockheight_u64: 0,
+            difficulty_u64: 0,
             database_size: HumanNumber::unknown().to_string(),
             free_space: HumanNumber::unknown().to_string(),
             nettype: String::from("???"),
@@ -332,22 +245,13 @@ impl PubNodeApi {
             synchronized: false,
         }
     }
-    pub fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
-        let output = std::mem::take(&mut gui_api.output);
-        let buf = std::mem::take(&mut pub_api.output);
-        *gui_api = Self {
-            output,
-            ..pub_api.clone()
-        };
-        if !buf.is_empty() {
-            gui_api.output.push_str(&buf);
-        }
-    }
     fn update_from_priv(public: &Arc<Mutex<Self>>, private: PrivNodeApi) {
         let mut public = public.lock().unwrap();
         *public = Self {
             blockheight: HumanNumber::from_u64(private.result.height),
             difficulty: HumanNumber::from_u64(private.result.difficulty),
+            blockheight_u64: private.result.height,
+            difficulty_u64: private.result.difficulty,
             database_size: Byte::from(private.result.database_size).to_string(),
             free_space: Byte::from(private.result.free_space).to_string(),
             nettype: private.result.nettype,
@@ -359,21 +263,22 @@ impl PubNodeApi {
         }
     }
     pub fn update_from_output(
-        public: &Arc<Mutex<Self>>,
+        public: &mut Self,
         output_pub: &Arc<Mutex<String>>,
         elapsed: std::time::Duration,
     ) {
         // 1. Take the process's current output buffer and combine it with Pub (if not empty)
         let mut output_pub = output_pub.lock().unwrap();
-
-        {
-            let mut public = public.lock().unwrap();
-            if !output_pub.is_empty() {
-                public.output.push_str(&std::mem::take(&mut *output_pub));
-            }
-            // Update uptime
-            public.uptime = HumanTime::into_human(elapsed);
+        if !output_pub.is_empty() {
+            public.output.push_str(&std::mem::take(&mut *output_pub));
         }
+        // Update uptime
+        public.uptime = HumanTime::into_human(elapsed);
+    }
+}
+impl PubApi for PubNodeApi {
+    fn output(&mut self) -> &mut ConsoleBuffer {
+        &mut self.output
     }
 }
 #[derive(Deserialize, Serialize)]
@@ -0,0 +1,529 @@
+// Notifications.
+//
+// The watchdogs call [Notifier::notify()] when something worth knowing happens
+// while nobody is watching the console:
+//
+// - [Payout]     P2Pool printed a new payout
+// - [Crash]      a process exited on its own with a failure
+// - [XvbWin]     the address won the current XvB round
+// - [XvbOffline] every XvB node is offline
+// - [SyncLoss]   the node is not synchronized anymore
+//
+// [notify()] only filters the event with [Notifications] and queues it, the
+// sinks are called one after the other by the notifier thread so a slow mail
+// server never holds a watchdog. A sink failing is logged and does not stop
+// the others.
+
+use crate::disk::state::Notifications;
+use crate::miscs::client;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use log::{debug, info, warn};
+use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+// How long a sink has to deliver one event.
+const SINK_TIMEOUT: Duration = Duration::from_secs(30);
+// SMTP over TLS, without STARTTLS.
+const SMTPS_PORT: &str = "465";
+
+//---------------------------------------------------------------------------------------------------- NotifyEvent
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum NotifyEvent {
+    Payout {
+        date: String,
+        atomic_units: u64,
+        xmr: String,
+        block: String,
+    },
+    Crash {
+        process: String,
+        exit_code: Option<u32>,
+    },
+    XvbWin,
+    XvbOffline,
+    SyncLoss {
+        height: u64,
+    },
+}
+
+impl NotifyEvent {
+    pub fn enabled(&self, notifications: &Notifications) -> bool {
+        match self {
+            Self::Payout { .. } => notifications.payout,
+            Self::Crash { .. } => notifications.crash,
+            Self::XvbWin => notifications.xvb_win,
+            Self::XvbOffline => notifications.xvb_offline,
+            Self::SyncLoss { .. } => notifications.sync_loss,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Payout { .. } => "payout",
+            Self::Crash { .. } => "crash",
+            Self::XvbWin => "xvb_win",
+            Self::XvbOffline => "xvb_offline",
+            Self::SyncLoss { .. } => "sync_loss",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Payout { .. } => "Gupaxx | New payout",
+            Self::Crash { .. } => "Gupaxx | Process failed",
+            Self::XvbWin => "Gupaxx | XvB round won",
+            Self::XvbOffline => "Gupaxx | XvB nodes offline",
+            Self::SyncLoss { .. } => "Gupaxx | Node out of sync",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::Payout { xmr, block, .. } => {
+                format!("P2Pool paid {} XMR in block {}", xmr, block)
+            }
+            Self::Crash { process, exit_code } => match exit_code {
+                Some(code) => format!("{} exited with code {}", process, code),
+                None => format!("{} exited with a failure", process),
+            },
+            Self::XvbWin => "Your address is the winner of the current XvB round".to_string(),
+            Self::XvbOffline => {
+                "Every XvB node is offline, mining on the local P2Pool instead".to_string()
+            }
+            Self::SyncLoss { height } => {
+                format!("The node is not synchronized anymore (height {})", height)
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Notifier
+// Shared by the [Helper] and every watchdog, the policy is replaced on [Save].
+#[derive(Clone, Debug)]
+pub struct Notifier {
+    policy: Arc<Mutex<Notifications>>,
+    tx: Option<UnboundedSender<NotifyEvent>>,
+}
+
+impl Default for Notifier {
+    // Drops every event, for what runs without a notifier thread.
+    fn default() -> Self {
+        Self {
+            policy: Arc::new(Mutex::new(Notifications::default())),
+            tx: None,
+        }
+    }
+}
+
+impl Notifier {
+    #[cold]
+    #[inline(never)]
+    // Spawns the notifier thread, living as long as a [Notifier] does.
+    pub fn spawn() -> Self {
+        let policy = Arc::new(Mutex::new(Notifications::default()));
+        let (tx, rx) = unbounded_channel();
+        let thread_policy = Arc::clone(&policy);
+        std::thread::spawn(move || Self::run(thread_policy, rx));
+        Self {
+            policy,
+            tx: Some(tx),
+        }
+    }
+
+    pub fn set_policy(&self, notifications: &Notifications) {
+        *self.policy.lock().unwrap() = notifications.clone();
+    }
+
+    pub fn notify(&self, event: NotifyEvent) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        if !event.enabled(&self.policy.lock().unwrap()) {
+            debug!("Notify | [{}] is disabled, skipping", event.kind());
+            return;
+        }
+        info!("Notify | {}", event.message());
+        // The notifier thread is gone, nothing to send to.
+        let _ = tx.send(event);
+    }
+
+    #[tokio::main(flavor = "current_thread")]
+    async fn run(policy: Arc<Mutex<Notifications>>, mut rx: UnboundedReceiver<NotifyEvent>) {
+        let client = client();
+        while let Some(event) = rx.recv().await {
+            let sinks = Sink::from_policy(&policy.lock().unwrap());
+            for sink in sinks {
+                match tokio::time::timeout(SINK_TIMEOUT, sink.send(&client, &event)).await {
+                    Ok(Ok(())) => debug!("Notify | [{}] sent to {}", event.kind(), sink),
+                    Ok(Err(e)) => warn!("Notify | [{}] to {} failed: {}", event.kind(), sink, e),
+                    Err(_) => warn!("Notify | [{}] to {} timed out", event.kind(), sink),
+                }
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Sink
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sink {
+    Desktop,
+    Webhook(String),
+    Mail(Mail),
+    Command(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mail {
+    pub server: String,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+// What the webhook receives: the event with its data, and the text the other sinks show.
+#[derive(Serialize)]
+struct WebhookBody<'a> {
+    #[serde(flatten)]
+    event: &'a NotifyEvent,
+    title: &'a str,
+    message: String,
+}
+
+impl std::fmt::Display for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Desktop => write!(f, "desktop"),
+            // The rest of the URL is usually a secret.
+            Self::Webhook(url) => write!(
+                f,
+                "webhook [{}]",
+                reqwest::Url::parse(url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(String::from))
+                    .unwrap_or_default()
+            ),
+            Self::Mail(mail) => write!(f, "mail [{}]", mail.server),
+            Self::Command(_) => write!(f, "command"),
+        }
+    }
+}
+
+impl Sink {
+    // The enabled sinks, skipping the ones not configured.
+    pub fn from_policy(notifications: &Notifications) -> Vec<Self> {
+        let mut sinks = vec![];
+        if notifications.desktop {
+            sinks.push(Self::Desktop);
+        }
+        if notifications.webhook && !notifications.webhook_url.is_empty() {
+            sinks.push(Self::Webhook(notifications.webhook_url.clone()));
+        }
+        let to: Vec<String> = notifications
+            .mail_to
+            .split(',')
+            .map(str::trim)
+            .filter(|to| !to.is_empty())
+            .map(String::from)
+            .collect();
+        if notifications.mail && !notifications.mail_server.is_empty() && !to.is_empty() {
+            sinks.push(Self::Mail(Mail {
+                server: notifications.mail_server.clone(),
+                username: notifications.mail_username.clone(),
+                password: notifications.mail_password.clone(),
+                from: notifications.mail_from.clone(),
+                to,
+            }));
+        }
+        if notifications.command && !notifications.command_line.trim().is_empty() {
+            sinks.push(Self::Command(notifications.command_line.clone()));
+        }
+        sinks
+    }
+
+    pub async fn send(&self, client: &Client, event: &NotifyEvent) -> Result<()> {
+        match self {
+            Self::Desktop => Self::desktop(event).await,
+            Self::Webhook(url) => {
+                let body = WebhookBody {
+                    event,
+                    title: event.title(),
+                    message: event.message(),
+                };
+                // The errors of reqwest print the URL, which is logged.
+                client
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .body(serde_json::to_string(&body)?)
+                    .send()
+                    .await
+                    .map_err(|e| match e {
+                        reqwest_middleware::Error::Reqwest(e) => anyhow!(e.without_url()),
+                        e => anyhow!(e.to_string().replace(url, "<webhook>")),
+                    })?
+                    .error_for_status()
+                    .map_err(|e| e.without_url())?;
+                Ok(())
+            }
+            Self::Mail(mail) => mail.send(event).await,
+            Self::Command(line) => {
+                let status = Self::shell(line)
+                    .env("GUPAXX_EVENT", event.kind())
+                    .env("GUPAXX_TITLE", event.title())
+                    .env("GUPAXX_MESSAGE", event.message())
+                    .env("GUPAXX_JSON", serde_json::to_string(event)?)
+                    .stdin(std::process::Stdio::null())
+                    .status()
+                    .await?;
+                if !status.success() {
+                    return Err(anyhow!("exited with {}", status));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn shell(line: &str) -> tokio::process::Command {
+        #[cfg(target_os = "windows")]
+        {
+            let mut cmd = tokio::process::Command::new("cmd");
+            cmd.args(["/C", line]);
+            cmd
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let mut cmd = tokio::process::Command::new("sh");
+            cmd.args(["-c", line]);
+            cmd
+        }
+    }
+
+    // Uses what the OS already ships instead of linking a notification library.
+    async fn desktop(event: &NotifyEvent) -> Result<()> {
+        let (title, message) = (event.title(), event.message());
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("notify-send");
+            cmd.args(["--app-name=Gupaxx", title, &message]);
+            cmd
+        };
+        #[cfg(target_os = "macos")]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("osascript");
+            // AppleScript strings, only the quotes and backslashes need escaping.
+            let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+            cmd.args([
+                "-e",
+                &format!(
+                    "display notification \"{}\" with title \"{}\"",
+                    escape(&message),
+                    escape(title)
+                ),
+            ]);
+            cmd
+        };
+        #[cfg(target_os = "windows")]
+        let mut cmd = {
+            let mut cmd = tokio::process::Command::new("powershell");
+            // PowerShell single-quoted strings, only the quotes need escaping.
+            let escape = |s: &str| s.replace('\'', "''");
+            cmd.args([
+                "-NoProfile",
+                "-Command",
+                &format!(
+                    "Add-Type -AssemblyName System.Windows.Forms; \
+                     $n = New-Object System.Windows.Forms.NotifyIcon; \
+                     $n.Icon = [System.Drawing.SystemIcons]::Information; \
+                     $n.Visible = $true; \
+                     $n.ShowBalloonTip(10000, '{}', '{}', 'Info'); \
+                     Start-Sleep -Seconds 10; $n.Dispose()",
+                    escape(title),
+                    escape(&message)
+                ),
+            ]);
+            cmd
+        };
+        cmd.stdin(std::process::Stdio::null());
+        // The balloon stays 10s before being disposed of, the next
+        // sinks and events do not wait for it.
+        if cfg!(target_os = "windows") {
+            cmd.spawn()?;
+            return Ok(());
+        }
+        let status = cmd.status().await?;
+        if !status.success() {
+            return Err(anyhow!("exited with {}", status));
+        }
+        Ok(())
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Mail
+// What the SMTP session runs over, TCP or TLS.
+trait SmtpIo: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> SmtpIo for T {}
+type Smtp = BufReader<Box<dyn SmtpIo>>;
+
+// The TLS handshake following [STARTTLS] failed.
+#[derive(Debug)]
+struct StartTlsError(anyhow::Error);
+
+impl std::fmt::Display for StartTlsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "STARTTLS failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for StartTlsError {}
+
+impl Mail {
+    // A minimal SMTP client: TLS from the start on port 465, STARTTLS when the
+    // server offers it on the others. AUTH PLAIN if a username is set, never
+    // without TLS.
+    //
+    // Without a username nothing secret is sent, so if the STARTTLS handshake
+    // fails (a local server with a self-signed certificate) the mail is sent
+    // again without it.
+    async fn send(&self, event: &NotifyEvent) -> Result<()> {
+        match self.session(event, true).await {
+            Err(e) if self.username.is_empty() && e.is::<StartTlsError>() => {
+                warn!(
+                    "Notify | Mail [{}]: {}, sending without TLS",
+                    self.server, e
+                );
+                self.session(event, false).await
+            }
+            result => result,
+        }
+    }
+
+    async fn session(&self, event: &NotifyEvent, starttls: bool) -> Result<()> {
+        let (host, port) = self
+            .server
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("the server must be host:port"))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let tcp = TcpStream::connect(&self.server).await?;
+        let mut tls = port == SMTPS_PORT;
+        let mut smtp: Smtp = if tls {
+            BufReader::new(Box::new(Self::tls(tcp, host).await?))
+        } else {
+            BufReader::new(Box::new(tcp))
+        };
+
+        Self::reply(&mut smtp, 220).await?;
+        let extensions = Self::command(&mut smtp, "EHLO gupaxx", 250).await?;
+        if !tls
+            && starttls
+            && extensions
+                .iter()
+                .any(|extension| extension.eq_ignore_ascii_case("STARTTLS"))
+        {
+            Self::command(&mut smtp, "STARTTLS", 220).await?;
+            let io = Self::tls(smtp.into_inner(), host)
+                .await
+                .map_err(StartTlsError)?;
+            smtp = BufReader::new(Box::new(io));
+            Self::command(&mut smtp, "EHLO gupaxx", 250).await?;
+            tls = true;
+        }
+        if !self.username.is_empty() {
+            if !tls {
+                return Err(anyhow!(
+                    "the server does not offer TLS, not sending the password in cleartext"
+                ));
+            }
+            let auth = BASE64.encode(format!("\0{}\0{}", self.username, self.password));
+            Self::command(&mut smtp, &format!("AUTH PLAIN {}", auth), 235).await?;
+        }
+        let from = format!("MAIL FROM:<{}>", self.from);
+        Self::command(&mut smtp, &from, 250).await?;
+        for to in &self.to {
+            Self::command(&mut smtp, &format!("RCPT TO:<{}>", to), 250).await?;
+        }
+        Self::command(&mut smtp, "DATA", 354).await?;
+        smtp.write_all(self.data(event).as_bytes()).await?;
+        Self::command(&mut smtp, ".", 250).await?;
+        // The mail is accepted, a failing [QUIT] does not matter.
+        let _ = Self::command(&mut smtp, "QUIT", 221).await;
+        Ok(())
+    }
+
+    // Checks the certificate of [host] against the roots shipped with Gupaxx.
+    async fn tls<IO: AsyncRead + AsyncWrite + Unpin>(
+        io: IO,
+        host: &str,
+    ) -> Result<tokio_rustls::client::TlsStream<IO>> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let name = ServerName::try_from(host.to_string())?;
+        Ok(TlsConnector::from(Arc::new(config))
+            .connect(name, io)
+            .await?)
+    }
+
+    // The headers and body, dot-stuffed, without the final [.].
+    pub fn data(&self, event: &NotifyEvent) -> String {
+        let mut data = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            self.to.join(", "),
+            event.title(),
+            chrono::Local::now().to_rfc2822(),
+        );
+        for line in event.message().lines() {
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        data
+    }
+
+    async fn command(smtp: &mut Smtp, command: &str, expected: u16) -> Result<Vec<String>> {
+        smtp.write_all(format!("{}\r\n", command).as_bytes())
+            .await?;
+        smtp.flush().await?;
+        Self::reply(smtp, expected).await
+    }
+
+    // Reads a reply, multiline ones being [250-...] until [250 ...].
+    // Returns the text of each line, the EHLO extensions.
+    async fn reply(smtp: &mut Smtp, expected: u16) -> Result<Vec<String>> {
+        let mut lines = vec![];
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if smtp.read_line(&mut line).await? == 0 {
+                return Err(anyhow!("connection closed by the server"));
+            }
+            let code: u16 = line
+                .get(..3)
+                .and_then(|code| code.parse().ok())
+                .ok_or_else(|| anyhow!("invalid reply: {}", line.trim_end()))?;
+            if code != expected {
+                return Err(anyhow!("expected {}, got: {}", expected, line.trim_end()));
+            }
+            lines.push(line.get(4..).unwrap_or_default().trim_end().to_string());
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(lines);
+            }
+        }
+    }
+}
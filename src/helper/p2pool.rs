@@ -1,13 +1,13 @@
 use super::Helper;
 use super::Process;
 use crate::components::node::RemoteNode;
-use crate::disk::state::P2pool;
-use crate::helper::check_died;
-use crate::helper::check_user_input;
-use crate::helper::signal_end;
-use crate::helper::sleep_end_loop;
+use crate::disk::state::{P2pool, RestartPolicy};
+use crate::helper::console::ConsoleBuffer;
+use crate::helper::notify::{Notifier, NotifyEvent};
+use crate::helper::supervised::{
+    is_localhost, Handles, PtyLines, PubApi, SupervisedProcess, Watchdog,
+};
 use crate::helper::ProcessName;
-use crate::helper::ProcessSignal;
 use crate::helper::ProcessState;
 use crate::regex::contains_end_status;
 use crate::regex::contains_statuscommand;
@@ -21,170 +21,37 @@ use crate::{
     disk::{gupax_p2pool_api::GupaxP2poolApi, node::Node},
     helper::{MONERO_BLOCK_TIME_IN_SECONDS, P2POOL_BLOCK_TIME_IN_SECONDS},
     human::*,
-    macros::*,
     xmr::*,
 };
 use log::*;
+use portable_pty::CommandBuilder;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::{
-    fmt::Write,
     path::PathBuf,
     sync::{Arc, Mutex},
-    thread,
     time::*,
 };
 impl Helper {
-    #[cold]
-    #[inline(never)]
-    fn read_pty_p2pool(
-        output_parse: Arc<Mutex<String>>,
-        output_pub: Arc<Mutex<String>>,
-        reader: Box<dyn std::io::Read + Send>,
-        gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
-        gui_api: Arc<Mutex<PubP2poolApi>>,
-    ) {
-        use std::io::BufRead;
-        let mut stdout = std::io::BufReader::new(reader).lines();
-
-        // Run a ANSI escape sequence filter for the first few lines.
-        let mut i = 0;
-        let mut status_output = false;
-        while let Some(Ok(line)) = stdout.next() {
-            let line = strip_ansi_escapes::strip_str(line);
-
-            // status could be present before 20 lines with a low verbosity value
-            if contains_statuscommand(&line) {
-                status_output = true;
-                continue;
-            }
-            if status_output {
-                if contains_end_status(&line) {
-                    // end of status
-                    status_output = false;
-                    continue;
-                }
-            } else {
-                if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                    error!("P2Pool PTY Parse | Output error: {}", e);
-                }
-                if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                    error!("P2Pool PTY Pub | Output error: {}", e);
-                }
-                if i > 20 {
-                    break;
-                } else {
-                    i += 1;
-                }
-            }
-        }
-        while let Some(Ok(line)) = stdout.next() {
-            // if command status is sent by gupaxx process and not the user, forward it only to update_from_status method.
-            // 25 lines after the command are the result of status, with last line finishing by update.
-            if contains_statuscommand(&line) {
-                status_output = true;
-                continue;
-            }
-            if status_output {
-                if contains_yourhashrate(&line) {
-                    if let Some(ehr) = estimated_hr(&line) {
-                        debug!(
-                            "P2pool | PTY getting current estimated HR data from status: {} KH/s",
-                            ehr
-                        );
-                        // multiply by a thousand because value is given as kH/s instead H/s
-                        gui_api.lock().unwrap().sidechain_ehr = ehr;
-                        debug!(
-                            "P2pool | PTY getting current estimated HR data from status: {} H/s",
-                            gui_api.lock().unwrap().sidechain_ehr
-                        );
-                    } else {
-                        error!("P2pool | PTY Getting data from status: Lines contains Your shares but no value found: {}", line);
-                    }
-                }
-                if contains_yourshare(&line) {
-                    // update sidechain shares
-                    if let Some(shares) = nb_current_shares(&line) {
-                        debug!(
-                            "P2pool | PTY getting current shares data from status: {} share",
-                            shares
-                        );
-                        gui_api.lock().unwrap().sidechain_shares = shares;
-                    } else {
-                        error!("P2pool | PTY Getting data from status: Lines contains Your shares but no value found: {}", line);
-                    }
-                }
-                if contains_end_status(&line) {
-                    // end of status
-                    status_output = false;
-                }
-                continue;
-            }
-            //			println!("{}", line); // For debugging.
-            if P2POOL_REGEX.payout.is_match(&line) {
-                debug!("P2Pool PTY | Found payout, attempting write: {}", line);
-                let (date, atomic_unit, block) = PayoutOrd::parse_raw_payout_line(&line);
-                let formatted_log_line = GupaxP2poolApi::format_payout(&date, &atomic_unit, &block);
-                GupaxP2poolApi::add_payout(
-                    &mut gupax_p2pool_api.lock().unwrap(),
-                    &formatted_log_line,
-                    date,
-                    atomic_unit,
-                    block,
-                );
-                if let Err(e) = GupaxP2poolApi::write_to_all_files(
-                    &gupax_p2pool_api.lock().unwrap(),
-                    &formatted_log_line,
-                ) {
-                    error!("P2Pool PTY GupaxP2poolApi | Write error: {}", e);
-                }
-            }
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("P2Pool PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("P2Pool PTY Pub | Output error: {}", e);
-            }
-        }
-    }
     //---------------------------------------------------------------------------------------------------- P2Pool specific
     #[cold]
     #[inline(never)]
     // Just sets some signals for the watchdog thread to pick up on.
     pub fn stop_p2pool(helper: &Arc<Mutex<Self>>) {
-        info!("P2Pool | Attempting to stop...");
-        helper.lock().unwrap().p2pool.lock().unwrap().signal = ProcessSignal::Stop;
-        helper.lock().unwrap().p2pool.lock().unwrap().state = ProcessState::Middle;
+        Self::stop_supervised(&helper.lock().unwrap().p2pool);
     }
 
     #[cold]
     #[inline(never)]
     // The "restart frontend" to a "frontend" function.
-    // Basically calls to kill the current p2pool, waits a little, then starts the below function in a a new thread, then exit.
     pub fn restart_p2pool(
         helper: &Arc<Mutex<Self>>,
         state: &P2pool,
         path: &Path,
         backup_hosts: Option<Vec<Node>>,
     ) {
-        info!("P2Pool | Attempting to restart...");
-        helper.lock().unwrap().p2pool.lock().unwrap().signal = ProcessSignal::Restart;
-        helper.lock().unwrap().p2pool.lock().unwrap().state = ProcessState::Middle;
-
-        let helper = Arc::clone(helper);
-        let state = state.clone();
-        let path = path.to_path_buf();
-        // This thread lives to wait, start p2pool then die.
-        thread::spawn(move || {
-            while helper.lock().unwrap().p2pool.lock().unwrap().state != ProcessState::Waiting {
-                warn!("P2Pool | Want to restart but process is still alive, waiting...");
-                sleep!(1000);
-            }
-            // Ok, process is not alive, start the new one!
-            info!("P2Pool | Old process seems dead, starting new one!");
-            Self::start_p2pool(&helper, &state, &path, backup_hosts);
-        });
-        info!("P2Pool | Restart ... OK");
+        let p2pool = P2poolProcess::new(&helper.lock().unwrap(), state, backup_hosts);
+        Self::restart_supervised(helper, p2pool, path);
     }
 
     #[cold]
@@ -196,39 +63,8 @@ impl Helper {
         path: &Path,
         backup_hosts: Option<Vec<Node>>,
     ) {
-        helper.lock().unwrap().p2pool.lock().unwrap().state = ProcessState::Middle;
-
-        let (args, api_path_local, api_path_network, api_path_pool) =
-            Self::build_p2pool_args_and_mutate_img(helper, state, path, backup_hosts);
-
-        // Print arguments & user settings to console
-        crate::disk::print_dash(&format!(
-			"P2Pool | Launch arguments: {:#?} | Local API Path: {:#?} | Network API Path: {:#?} | Pool API Path: {:#?}",
-			 args,
-			 api_path_local,
-			 api_path_network,
-			 api_path_pool,
-		));
-
-        // Spawn watchdog thread
-        let process = Arc::clone(&helper.lock().unwrap().p2pool);
-        let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_p2pool);
-        let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_p2pool);
-        let gupax_p2pool_api = Arc::clone(&helper.lock().unwrap().gupax_p2pool_api);
-        let path = path.to_path_buf();
-        thread::spawn(move || {
-            Self::spawn_p2pool_watchdog(
-                process,
-                gui_api,
-                pub_api,
-                args,
-                path,
-                api_path_local,
-                api_path_network,
-                api_path_pool,
-                gupax_p2pool_api,
-            );
-        });
+        let p2pool = P2poolProcess::new(&helper.lock().unwrap(), state, backup_hosts);
+        Self::start_supervised(helper, p2pool, path);
     }
     // Takes in a 95-char Monero address, returns the first and last
     // 8 characters separated with dots like so: [4abcdefg...abcdefgh]
@@ -433,78 +269,116 @@ impl Helper {
         api_path_pool.push(P2POOL_API_PATH_POOL);
         (args, api_path_local, api_path_network, api_path_pool)
     }
+}
+//---------------------------------------------------------------------------------------------------- [SupervisedProcess]
+#[derive(Clone)]
+pub struct P2poolProcess {
+    state: P2pool,
+    backup_hosts: Option<Vec<Node>>,
+    gui_api: Arc<Mutex<PubP2poolApi>>,
+    gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
+    notifier: Notifier,
+    api_path_local: PathBuf,
+    api_path_network: PathBuf,
+    api_path_pool: PathBuf,
+    first_loop: bool,
+    last_p2pool_request: Instant,
+    last_status_request: Instant,
+}
 
-    #[cold]
-    #[inline(never)]
-    // The P2Pool watchdog. Spawns 1 OS thread for reading a PTY (STDOUT+STDERR), and combines the [Child] with a PTY so STDIN actually works.
-    // or if P2Pool simple is false and extern is true, only prints data from stratum api.
-    #[allow(clippy::too_many_arguments)]
-    #[allow(clippy::await_holding_lock)]
-    #[tokio::main]
-    async fn spawn_p2pool_watchdog(
-        process: Arc<Mutex<Process>>,
-        gui_api: Arc<Mutex<PubP2poolApi>>,
-        pub_api: Arc<Mutex<PubP2poolApi>>,
-        args: Vec<String>,
-        path: std::path::PathBuf,
-        api_path_local: std::path::PathBuf,
-        api_path_network: std::path::PathBuf,
-        api_path_pool: std::path::PathBuf,
-        gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
-    ) {
-        // 1a. Create PTY
-        debug!("P2Pool | Creating PTY...");
-        let pty = portable_pty::native_pty_system();
-        let pair = pty
-            .openpty(portable_pty::PtySize {
-                rows: 100,
-                cols: 1000,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .unwrap();
-        // 1b. Create command
-        debug!("P2Pool | Creating command...");
-        let mut cmd = portable_pty::CommandBuilder::new(path.as_path());
-        cmd.args(args);
-        cmd.env("NO_COLOR", "true");
-        cmd.cwd(path.as_path().parent().unwrap());
-        // 1c. Create child
-        debug!("P2Pool | Creating child...");
-        let child_pty = arc_mut!(pair.slave.spawn_command(cmd).unwrap());
-        drop(pair.slave);
+impl P2poolProcess {
+    pub fn new(helper: &Helper, state: &P2pool, backup_hosts: Option<Vec<Node>>) -> Self {
+        Self {
+            state: state.clone(),
+            backup_hosts,
+            gui_api: Arc::clone(&helper.gui_api_p2pool),
+            gupax_p2pool_api: Arc::clone(&helper.gupax_p2pool_api),
+            notifier: helper.notifier.clone(),
+            api_path_local: PathBuf::new(),
+            api_path_network: PathBuf::new(),
+            api_path_pool: PathBuf::new(),
+            first_loop: true,
+            last_p2pool_request: Instant::now(),
+            last_status_request: Instant::now(),
+        }
+    }
+}
+
+impl SupervisedProcess for P2poolProcess {
+    const SPAWNED_STATE: ProcessState = ProcessState::Syncing;
+    const ANSI_LINES: usize = 22;
+    type Api = PubP2poolApi;
+    type Lines = P2poolLines;
+
+    fn name(&self) -> ProcessName {
+        ProcessName::P2pool
+    }
 
-        // 2. Set process state
-        debug!("P2Pool | Setting process state...");
-        let mut lock = process.lock().unwrap();
-        lock.state = ProcessState::Syncing;
-        lock.signal = ProcessSignal::None;
-        lock.start = Instant::now();
-        let reader = pair.master.try_clone_reader().unwrap(); // Get STDOUT/STDERR before moving the PTY
-        let mut stdin = pair.master.take_writer().unwrap();
-        drop(lock);
+    fn handles(&self, helper: &Helper) -> Handles<PubP2poolApi> {
+        (
+            Arc::clone(&helper.p2pool),
+            Arc::clone(&helper.gui_api_p2pool),
+            Arc::clone(&helper.pub_api_p2pool),
+        )
+    }
+
+    fn restart_policy(&self) -> &RestartPolicy {
+        &self.state.restart
+    }
 
-        // 3. Spawn PTY read thread
-        debug!("P2Pool | Spawning PTY read thread...");
-        let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
-        let gupax_p2pool_api = Arc::clone(&gupax_p2pool_api);
-        let p2pool_api_c = Arc::clone(&gui_api);
-        tokio::spawn(async move {
-            Self::read_pty_p2pool(
-                output_parse,
-                output_pub,
-                reader,
-                gupax_p2pool_api,
-                p2pool_api_c,
+    // The local Node must be synchronized.
+    fn dependencies(&self, helper: &Helper) -> Vec<Arc<Mutex<Process>>> {
+        let local_node = if self.state.simple {
+            self.state.local_node
+        } else {
+            self.state.arguments.is_empty() && is_localhost(&self.state.ip)
+        };
+        if local_node {
+            vec![Arc::clone(&helper.node)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn args(&mut self, helper: &Arc<Mutex<Helper>>, path: &Path) -> Vec<String> {
+        let (args, api_path_local, api_path_network, api_path_pool) =
+            Helper::build_p2pool_args_and_mutate_img(
+                helper,
+                &self.state,
+                path,
+                self.backup_hosts.clone(),
             );
-        });
-        let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
+        info!(
+            "P2Pool | Local API Path: {:#?} | Network API Path: {:#?} | Pool API Path: {:#?}",
+            api_path_local, api_path_network, api_path_pool,
+        );
+        self.api_path_local = api_path_local;
+        self.api_path_network = api_path_network;
+        self.api_path_pool = api_path_pool;
+        args
+    }
+
+    fn command(&self, args: Vec<String>, path: &Path) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(path);
+        cmd.args(args);
+        cmd.env("NO_COLOR", "true");
+        cmd.cwd(path.parent().unwrap());
+        cmd
+    }
 
+    fn lines(&self) -> P2poolLines {
+        P2poolLines {
+            status_output: false,
+            gui_api: Arc::clone(&self.gui_api),
+            gupax_p2pool_api: Arc::clone(&self.gupax_p2pool_api),
+            notifier: self.notifier.clone(),
+        }
+    }
+
+    async fn spawned(&mut self, _watchdog: &mut Watchdog<'_, PubP2poolApi>) {
         debug!("P2Pool | Cleaning old [local] API files...");
         // Attempt to remove stale API file
-        match std::fs::remove_file(&api_path_local) {
+        match std::fs::remove_file(&self.api_path_local) {
             Ok(_) => info!("P2Pool | Attempting to remove stale API file ... OK"),
             Err(e) => warn!(
                 "P2Pool | Attempting to remove stale API file ... FAIL ... {}",
@@ -512,10 +386,9 @@ impl Helper {
             ),
         }
         // Attempt to create a default empty one.
-        use std::io::Write;
-        if std::fs::File::create(&api_path_local).is_ok() {
+        if std::fs::File::create(&self.api_path_local).is_ok() {
             let text = r#"{"hashrate_15m":0,"hashrate_1h":0,"hashrate_24h":0,"shares_found":0,"average_effort":0.0,"current_effort":0.0,"connections":0}"#;
-            match std::fs::write(&api_path_local, text) {
+            match std::fs::write(&self.api_path_local, text) {
                 Ok(_) => info!("P2Pool | Creating default empty API file ... OK"),
                 Err(e) => warn!(
                     "P2Pool | Creating default empty API file ... FAIL ... {}",
@@ -523,130 +396,147 @@ impl Helper {
                 ),
             }
         }
-        let start = process.lock().unwrap().start;
-
-        // Reset stats before loop
-        *pub_api.lock().unwrap() = PubP2poolApi::new();
-        *gui_api.lock().unwrap() = PubP2poolApi::new();
+        self.first_loop = true;
+        self.last_p2pool_request = Instant::now();
+        self.last_status_request = Instant::now();
+    }
 
-        // 4. Loop as watchdog
-        let mut first_loop = true;
-        let mut last_p2pool_request = tokio::time::Instant::now();
-        let mut last_status_request = tokio::time::Instant::now();
+    fn update_from_output(
+        &mut self,
+        pub_api: &mut PubP2poolApi,
+        output_parse: &Arc<Mutex<String>>,
+        output_pub: &Arc<Mutex<String>>,
+        elapsed: Duration,
+        process: &mut Process,
+    ) {
+        PubP2poolApi::update_from_output(pub_api, output_parse, output_pub, elapsed, process);
+    }
 
-        info!("P2Pool | Entering watchdog mode... woof!");
-        loop {
-            // Set timer
-            let now = Instant::now();
-            debug!("P2Pool Watchdog | ----------- Start of loop -----------");
-            {
-                gui_api.lock().unwrap().tick = (last_p2pool_request.elapsed().as_secs() % 60) as u8;
-                // Check if the process is secretly died without us knowing :)
-                if check_died(
-                    &child_pty,
-                    &mut process.lock().unwrap(),
-                    &start,
-                    &mut gui_api.lock().unwrap().output,
-                ) {
-                    break;
+    async fn tick(&mut self, watchdog: &mut Watchdog<'_, PubP2poolApi>) {
+        watchdog.gui_api.lock().unwrap().tick =
+            (self.last_p2pool_request.elapsed().as_secs() % 60) as u8;
+        let alive = watchdog.process.lock().unwrap().state == ProcessState::Alive;
+        {
+            let mut pub_api = watchdog.pub_api.lock().unwrap();
+            // Read [local] API
+            debug!("P2Pool Watchdog | Attempting [local] API file read");
+            if let Ok(string) = Helper::path_to_string(&self.api_path_local, ProcessName::P2pool) {
+                // Deserialize
+                if let Ok(local_api) = PrivP2poolLocalApi::from_str(&string) {
+                    // Update the structs.
+                    PubP2poolApi::update_from_local(&mut pub_api, local_api);
                 }
-
-                // Check SIGNAL
-                if signal_end(
-                    &mut process.lock().unwrap(),
-                    &child_pty,
-                    &start,
-                    &mut gui_api.lock().unwrap().output,
+            }
+            // If more than 1 minute has passed, read the other API files.
+            let last_p2pool_request_expired =
+                self.last_p2pool_request.elapsed() >= Duration::from_secs(60);
+            // need to reload fast to get the first right values after syncing.
+            // check if value is 100k or under and request immediately if that's the case. fixed in release of p2pool including commit https://github.com/SChernykh/p2pool/commit/64a199be6dec7924b41f857a401086f25e1ec9be
+            if (last_p2pool_request_expired || pub_api.p2pool_difficulty_u64 <= 100000) && alive {
+                debug!("P2Pool Watchdog | Attempting [network] & [pool] API file read");
+                if let (Ok(network_api), Ok(pool_api)) = (
+                    Helper::path_to_string(&self.api_path_network, ProcessName::P2pool),
+                    Helper::path_to_string(&self.api_path_pool, ProcessName::P2pool),
                 ) {
-                    break;
-                }
-                // Check vector of user input
-                check_user_input(&process, &mut stdin);
-                // Check if logs need resetting
-                debug!("P2Pool Watchdog | Attempting GUI log reset check");
-                let mut lock = gui_api.lock().unwrap();
-                Self::check_reset_gui_output(&mut lock.output, ProcessName::P2pool);
-                drop(lock);
-
-                // Always update from output
-                debug!("P2Pool Watchdog | Starting [update_from_output()]");
-                let mut process_lock = process.lock().unwrap();
-                let mut pub_api_lock = pub_api.lock().unwrap();
-                PubP2poolApi::update_from_output(
-                    &mut pub_api_lock,
-                    &output_parse,
-                    &output_pub,
-                    start.elapsed(),
-                    &mut process_lock,
-                );
-
-                // Read [local] API
-                debug!("P2Pool Watchdog | Attempting [local] API file read");
-                if let Ok(string) = Self::path_to_string(&api_path_local, ProcessName::P2pool) {
-                    // Deserialize
-                    if let Ok(local_api) = PrivP2poolLocalApi::from_str(&string) {
-                        // Update the structs.
-                        PubP2poolApi::update_from_local(&mut pub_api_lock, local_api);
-                    }
-                }
-                // If more than 1 minute has passed, read the other API files.
-                let last_p2pool_request_expired =
-                    last_p2pool_request.elapsed() >= Duration::from_secs(60);
-                // need to reload fast to get the first right values after syncing.
-                // check if value is 100k or under and request immediately if that's the case. fixed in release of p2pool including commit https://github.com/SChernykh/p2pool/commit/64a199be6dec7924b41f857a401086f25e1ec9be
-                if (last_p2pool_request_expired || pub_api_lock.p2pool_difficulty_u64 <= 100000)
-                    && process_lock.state == ProcessState::Alive
-                {
-                    debug!("P2Pool Watchdog | Attempting [network] & [pool] API file read");
                     if let (Ok(network_api), Ok(pool_api)) = (
-                        Self::path_to_string(&api_path_network, ProcessName::P2pool),
-                        Self::path_to_string(&api_path_pool, ProcessName::P2pool),
+                        PrivP2poolNetworkApi::from_str(&network_api),
+                        PrivP2poolPoolApi::from_str(&pool_api),
                     ) {
-                        if let (Ok(network_api), Ok(pool_api)) = (
-                            PrivP2poolNetworkApi::from_str(&network_api),
-                            PrivP2poolPoolApi::from_str(&pool_api),
-                        ) {
-                            PubP2poolApi::update_from_network_pool(
-                                &mut pub_api_lock,
-                                network_api,
-                                pool_api,
-                            );
-                            last_p2pool_request = tokio::time::Instant::now();
-                        }
+                        PubP2poolApi::update_from_network_pool(&mut pub_api, network_api, pool_api);
+                        self.last_p2pool_request = Instant::now();
                     }
                 }
+            }
+        } // end of scope to drop lock
 
-                let last_status_request_expired =
-                    last_status_request.elapsed() >= Duration::from_secs(60);
-                if (last_status_request_expired || first_loop)
-                    && process_lock.state == ProcessState::Alive
-                {
-                    debug!("P2Pool Watchdog | Reading status output of p2pool node");
-                    #[cfg(target_os = "windows")]
-                    if let Err(e) = write!(stdin, "statusfromgupaxx\r\n") {
-                        error!("P2Pool Watchdog | STDIN error: {}", e);
-                    }
-                    #[cfg(target_family = "unix")]
-                    if let Err(e) = writeln!(stdin, "statusfromgupaxx") {
-                        error!("P2Pool Watchdog | STDIN error: {}", e);
-                    }
-                    // Flush.
-                    if let Err(e) = stdin.flush() {
-                        error!("P2Pool Watchdog | STDIN flush error: {}", e);
-                    }
-                    last_status_request = tokio::time::Instant::now();
-                }
+        let last_status_request_expired =
+            self.last_status_request.elapsed() >= Duration::from_secs(60);
+        if (last_status_request_expired || self.first_loop) && alive {
+            debug!("P2Pool Watchdog | Reading status output of p2pool node");
+            watchdog.write_stdin(ProcessName::P2pool, "statusfromgupaxx");
+            self.last_status_request = Instant::now();
+        }
+        self.first_loop = false;
+    }
+}
+
+// Keeps the answers to the status command sent by the watchdog out of the output,
+// reading the sidechain stats from them instead, and writes the payouts to disk.
+pub struct P2poolLines {
+    status_output: bool,
+    gui_api: Arc<Mutex<PubP2poolApi>>,
+    gupax_p2pool_api: Arc<Mutex<GupaxP2poolApi>>,
+    notifier: Notifier,
+}
 
-                // Sleep (only if 900ms hasn't passed)
-                if first_loop {
-                    first_loop = false;
+impl PtyLines for P2poolLines {
+    fn line(&mut self, line: &str) -> bool {
+        // if command status is sent by gupaxx process and not the user, forward it only to update_from_status method.
+        // 25 lines after the command are the result of status, with last line finishing by update.
+        if contains_statuscommand(line) {
+            self.status_output = true;
+            return false;
+        }
+        if self.status_output {
+            if contains_yourhashrate(line) {
+                if let Some(ehr) = estimated_hr(line) {
+                    debug!(
+                        "P2pool | PTY getting current estimated HR data from status: {} KH/s",
+                        ehr
+                    );
+                    // multiply by a thousand because value is given as kH/s instead H/s
+                    self.gui_api.lock().unwrap().sidechain_ehr = ehr;
+                    debug!(
+                        "P2pool | PTY getting current estimated HR data from status: {} H/s",
+                        self.gui_api.lock().unwrap().sidechain_ehr
+                    );
+                } else {
+                    error!("P2pool | PTY Getting data from status: Lines contains Your shares but no value found: {}", line);
+                }
+            }
+            if contains_yourshare(line) {
+                // update sidechain shares
+                if let Some(shares) = nb_current_shares(line) {
+                    debug!(
+                        "P2pool | PTY getting current shares data from status: {} share",
+                        shares
+                    );
+                    self.gui_api.lock().unwrap().sidechain_shares = shares;
+                } else {
+                    error!("P2pool | PTY Getting data from status: Lines contains Your shares but no value found: {}", line);
                 }
-            } // end of scope to drop lock
-            sleep_end_loop(now, ProcessName::P2pool).await;
+            }
+            if contains_end_status(line) {
+                // end of status
+                self.status_output = false;
+            }
+            return false;
         }
-
-        // 5. If loop broke, we must be done here.
-        info!("P2Pool Watchdog | Watchdog thread exiting... Goodbye!");
+        if P2POOL_REGEX.payout.is_match(line) {
+            debug!("P2Pool PTY | Found payout, attempting write: {}", line);
+            let (date, atomic_unit, block) = PayoutOrd::parse_raw_payout_line(line);
+            let formatted_log_line = GupaxP2poolApi::format_payout(&date, &atomic_unit, &block);
+            self.notifier.notify(NotifyEvent::Payout {
+                date: date.clone(),
+                atomic_units: atomic_unit.to_u64(),
+                xmr: format!("{}", atomic_unit),
+                block: block.to_string(),
+            });
+            GupaxP2poolApi::add_payout(
+                &mut self.gupax_p2pool_api.lock().unwrap(),
+                &formatted_log_line,
+                date,
+                atomic_unit,
+                block,
+            );
+            if let Err(e) = GupaxP2poolApi::write_to_all_files(
+                &self.gupax_p2pool_api.lock().unwrap(),
+                &formatted_log_line,
+            ) {
+                error!("P2Pool PTY GupaxP2poolApi | Write error: {}", e);
+            }
+        }
+        true
     }
 }
 //---------------------------------------------------------------------------------------------------- [ImgP2pool]
@@ -687,11 +577,12 @@ impl ImgP2pool {
 
 //---------------------------------------------------------------------------------------------------- Public P2Pool API
 // Helper/GUI threads both have a copy of this, Helper updates
-// the GUI's version on a 1-second interval from the private data.
-#[derive(Debug, Clone, PartialEq)]
+// the GUI's version after every event it handled, from the private data.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PubP2poolApi {
     // Output
-    pub output: String,
+    #[serde(skip)]
+    pub output: ConsoleBuffer,
     // Uptime
     pub uptime: HumanTime,
     // These are manually parsed from the STDOUT.
@@ -715,6 +606,13 @@ pub struct PubP2poolApi {
     // there's not a good way to access it without doing weird
     // [Arc<Mutex>] shenanigans, so some raw ints are stored here.
     pub user_p2pool_hashrate_u64: u64,
+    pub user_p2pool_hashrate_15m_u64: u64,
+    pub user_p2pool_hashrate_24h_u64: u64,
+    pub current_effort_f32: f32,
+    pub average_effort_f32: f32,
+    pub connections_u32: u32,
+    // [None] if P2Pool does not list them.
+    pub stratum_workers: Option<Vec<StratumWorker>>,
     pub p2pool_difficulty_u64: u64,
     pub monero_difficulty_u64: u64,
     pub p2pool_hashrate_u64: u64,
@@ -754,7 +652,7 @@ impl Default for PubP2poolApi {
 impl PubP2poolApi {
     pub fn new() -> Self {
         Self {
-            output: String::new(),
+            output: ConsoleBuffer::new(),
             uptime: HumanTime::new(),
             payouts: 0,
             payouts_hour: 0.0,
@@ -773,6 +671,12 @@ impl PubP2poolApi {
             connections: HumanNumber::unknown(),
             tick: 0,
             user_p2pool_hashrate_u64: 0,
+            user_p2pool_hashrate_15m_u64: 0,
+            user_p2pool_hashrate_24h_u64: 0,
+            current_effort_f32: 0.0,
+            average_effort_f32: 0.0,
+            connections_u32: 0,
+            stratum_workers: None,
             p2pool_difficulty_u64: 0,
             monero_difficulty_u64: 0,
             p2pool_hashrate_u64: 0,
@@ -803,10 +707,7 @@ impl PubP2poolApi {
     // This is used in the "helper" thread.
     pub(super) fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
         let mut output = std::mem::take(&mut gui_api.output);
-        let buf = std::mem::take(&mut pub_api.output);
-        if !buf.is_empty() {
-            output.push_str(&buf);
-        }
+        output.append(&mut pub_api.output);
         *gui_api = Self {
             output,
             tick: std::mem::take(&mut gui_api.tick),
@@ -869,12 +770,12 @@ impl PubP2poolApi {
             // one for the sidechain, one for main/mini.
             if P2POOL_REGEX.next_height_1.is_match(&output_parse) {
                 if synchronized_captures > 1 {
-                    process.state = ProcessState::Alive;
+                    process.set_state(ProcessState::Alive);
                 }
             } else if synchronized_captures > 0 {
                 // if there is no `next block = 1`, fallback to
                 // just finding 1 instance of "SYNCHRONIZED".
-                process.state = ProcessState::Alive;
+                process.set_state(ProcessState::Alive);
             }
         }
 
@@ -943,6 +844,17 @@ impl PubP2poolApi {
             current_effort: HumanNumber::to_percent(local.current_effort),
             connections: HumanNumber::from_u32(local.connections),
             user_p2pool_hashrate_u64: local.hashrate_1h,
+            user_p2pool_hashrate_15m_u64: local.hashrate_15m,
+            user_p2pool_hashrate_24h_u64: local.hashrate_24h,
+            current_effort_f32: local.current_effort,
+            average_effort_f32: local.average_effort,
+            connections_u32: local.connections,
+            stratum_workers: local.workers.map(|workers| {
+                workers
+                    .iter()
+                    .filter_map(|w| StratumWorker::from_str(w))
+                    .collect()
+            }),
             ..std::mem::take(&mut *public)
         };
     }
@@ -1109,10 +1021,21 @@ impl PubP2poolApi {
     }
 }
 
+impl PubApi for PubP2poolApi {
+    fn output(&mut self) -> &mut ConsoleBuffer {
+        &mut self.output
+    }
+
+    // The tick and the sidechain stats are written to [gui_api] directly.
+    fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
+        PubP2poolApi::combine_gui_pub_api(gui_api, pub_api)
+    }
+}
+
 //---------------------------------------------------------------------------------------------------- Private P2Pool "Local" Api
 // This matches directly to P2Pool's [local/stratum] JSON API file (excluding a few stats).
 // P2Pool seems to initialize all stats at 0 (or 0.0), so no [Option] wrapper seems needed.
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub(super) struct PrivP2poolLocalApi {
     pub hashrate_15m: u64,
     pub hashrate_1h: u64,
@@ -1121,6 +1044,9 @@ pub(super) struct PrivP2poolLocalApi {
     pub average_effort: f32,
     pub current_effort: f32,
     pub connections: u32, // This is a `uint32_t` in `p2pool`
+    // Missing before P2Pool v3, see [StratumWorker].
+    #[serde(default, skip_serializing)]
+    pub workers: Option<Vec<String>>,
 }
 
 impl Default for PrivP2poolLocalApi {
@@ -1139,6 +1065,7 @@ impl PrivP2poolLocalApi {
             average_effort: 0.0,
             current_effort: 0.0,
             connections: 0,
+            workers: None,
         }
     }
 
@@ -1154,6 +1081,39 @@ impl PrivP2poolLocalApi {
     }
 }
 
+//---------------------------------------------------------------------------------------------------- Stratum workers
+// A miner connected to the stratum of P2Pool, from the [workers] list of
+// [local/stratum]: "ip:port,seconds connected,difficulty,hashrate,name".
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StratumWorker {
+    pub address: String,
+    pub hashrate: u64,
+    pub name: String,
+}
+
+impl StratumWorker {
+    pub fn from_str(worker: &str) -> Option<Self> {
+        let fields = worker.splitn(5, ',').collect::<Vec<_>>();
+        Some(Self {
+            address: fields.first()?.to_string(),
+            hashrate: fields.get(3)?.parse().ok()?,
+            name: fields.get(4).unwrap_or(&"").to_string(),
+        })
+    }
+    // Hashrate of the miners XvB does not control. The one it controls logs in
+    // with the user it gives to P2Pool, see [XvbNode::user], or with [logins]
+    // until the algorithm switches it the first time.
+    pub fn external_hashrate(workers: &[Self], logins: &[String]) -> f32 {
+        workers
+            .iter()
+            .filter(|worker| {
+                worker.name != GUPAX_VERSION_UNDERSCORE && !logins.contains(&worker.name)
+            })
+            .map(|worker| worker.hashrate as f32)
+            .sum()
+    }
+}
+
 //---------------------------------------------------------------------------------------------------- Private P2Pool "Network" API
 // This matches P2Pool's [network/stats] JSON API file.
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,220 @@
+// Stats snapshot.
+//
+// A copy of the stats the watchdogs handed over to the GUI, published by the [Helper]
+// through a [watch] channel whenever it changed. Readers (GUI, history, metrics)
+// get the last one without locking anything, so nothing has to agree on an order
+// to lock all the [Arc<Mutex>] at once anymore.
+//
+// The consoles are left out, only how many lines they got is kept so that new
+// output still counts as a change. The GUI locks the [Pub*Api] for those alone.
+//
+// The uptimes are left out as well, or every snapshot would differ while a process
+// is alive. Only when the processes started is kept, see [with_uptimes()].
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::disk::history::HistorySample;
+use crate::helper::console::ConsoleBuffer;
+use crate::helper::custom::PubCustomApi;
+use crate::helper::node::PubNodeApi;
+use crate::helper::p2pool::PubP2poolApi;
+use crate::helper::xrig::xmrig::PubXmrigApi;
+use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
+use crate::helper::xvb::nodes::XvbNode;
+use crate::helper::xvb::PubXvbApi;
+use crate::helper::{Helper, Process, ProcessName};
+use crate::human::HumanTime;
+
+#[derive(Clone, PartialEq)]
+pub struct StatsSnapshot {
+    pub processes: Vec<(ProcessName, bool, u8)>, // Name, alive, restarts
+    pub console_lines: Vec<u64>,                 // Lines pushed to each console so far
+    pub starts: Vec<(ProcessName, Instant)>,     // When the alive processes started
+    pub p2pool: PubP2poolApi,
+    pub xmrig: PubXmrigApi,
+    pub xmrig_proxy: PubXmrigProxyApi,
+    pub node: PubNodeApi,
+    pub xvb: PubXvbApi,
+    pub custom: Vec<PubCustomApi>, // In the order of [Helper.custom]
+}
+
+impl Default for StatsSnapshot {
+    fn default() -> Self {
+        Self {
+            processes: vec![],
+            console_lines: vec![],
+            starts: vec![],
+            p2pool: PubP2poolApi::new(),
+            xmrig: PubXmrigApi::new(),
+            xmrig_proxy: PubXmrigProxyApi::new(),
+            node: PubNodeApi::new(),
+            xvb: PubXvbApi::new(),
+            custom: vec![],
+        }
+    }
+}
+
+impl StatsSnapshot {
+    // Locks one [Arc<Mutex>] at a time, never two together.
+    pub fn from_helper(helper: &Helper) -> Self {
+        // Labelled here rather than by [Process.name], so that
+        // each slot of the [Helper] is reported under its own name.
+        let slots = [
+            (ProcessName::Node, &helper.node),
+            (ProcessName::P2pool, &helper.p2pool),
+            (ProcessName::Xmrig, &helper.xmrig),
+            (ProcessName::XmrigProxy, &helper.xmrig_proxy),
+            (ProcessName::Xvb, &helper.xvb),
+        ];
+        let processes = slots
+            .iter()
+            .map(|(name, p)| {
+                let p = p.lock().unwrap();
+                (*name, p.is_alive(), p.restarts)
+            })
+            .collect();
+        let starts = slots
+            .into_iter()
+            .chain(
+                helper
+                    .custom
+                    .iter()
+                    .enumerate()
+                    .map(|(i, custom)| (ProcessName::Custom(i), &custom.process)),
+            )
+            .filter_map(|(name, process)| start(process).map(|start| (name, start)))
+            .collect();
+        let mut lines = vec![];
+        let mut snapshot = Self {
+            processes,
+            node: without_output(&helper.gui_api_node, |api| &mut api.output, &mut lines),
+            p2pool: without_output(&helper.gui_api_p2pool, |api| &mut api.output, &mut lines),
+            xmrig: without_output(&helper.gui_api_xmrig, |api| &mut api.output, &mut lines),
+            xmrig_proxy: without_output(&helper.gui_api_xp, |api| &mut api.output, &mut lines),
+            xvb: without_output(&helper.gui_api_xvb, |api| &mut api.output, &mut lines),
+            custom: helper
+                .custom
+                .iter()
+                .map(|c| without_output(&c.gui_api, |api| &mut api.output, &mut lines))
+                .collect(),
+            console_lines: lines,
+            starts,
+        };
+        // The watchdogs update them on every event.
+        snapshot.node.uptime = HumanTime::new();
+        snapshot.p2pool.uptime = HumanTime::new();
+        snapshot.xmrig.uptime = Duration::ZERO;
+        snapshot.xmrig_proxy.uptime = Duration::ZERO;
+        for api in snapshot.custom.iter_mut() {
+            api.uptime = Duration::ZERO;
+        }
+        snapshot
+    }
+
+    // How long [name] has been alive, [None] if it is not.
+    pub fn uptime(&self, name: ProcessName) -> Option<Duration> {
+        self.starts
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, start)| start.elapsed())
+    }
+
+    // A copy with the uptimes as of now, for the readers showing them.
+    pub fn with_uptimes(&self) -> Self {
+        let mut snapshot = self.clone();
+        if let Some(uptime) = self.uptime(ProcessName::Node) {
+            snapshot.node.uptime = HumanTime::into_human(uptime);
+        }
+        if let Some(uptime) = self.uptime(ProcessName::P2pool) {
+            snapshot.p2pool.uptime = HumanTime::into_human(uptime);
+        }
+        if let Some(uptime) = self.uptime(ProcessName::Xmrig) {
+            snapshot.xmrig.uptime = uptime;
+        }
+        if let Some(uptime) = self.uptime(ProcessName::XmrigProxy) {
+            snapshot.xmrig_proxy.uptime = uptime;
+        }
+        for (i, api) in snapshot.custom.iter_mut().enumerate() {
+            if let Some(uptime) = self.uptime(ProcessName::Custom(i)) {
+                api.uptime = uptime;
+            }
+        }
+        snapshot
+    }
+
+    pub fn alive(&self, name: ProcessName) -> bool {
+        self.processes
+            .iter()
+            .any(|(n, alive, _)| *n == name && *alive)
+    }
+
+    // Builds a sample of the current stats for the history,
+    // [None] if neither P2Pool nor a miner is alive.
+    pub fn history_sample(&self) -> Option<HistorySample> {
+        let p2pool_alive = self.alive(ProcessName::P2pool);
+        let xmrig_alive = self.alive(ProcessName::Xmrig);
+        let xp_alive = self.alive(ProcessName::XmrigProxy);
+        if !p2pool_alive && !xmrig_alive && !xp_alive {
+            return None;
+        }
+        // XMRig-Proxy is the one receiving the hashrate of all the miners if it is used.
+        let hashrate = match (xp_alive, xmrig_alive) {
+            (true, _) => self.xmrig_proxy.hashrate_1m,
+            (false, true) => self.xmrig.hashrate_raw,
+            (false, false) => 0.0,
+        };
+        // Where the hashrate is sent, the XvB nodes or P2Pool (local or through the proxy).
+        let to_xvb = self.alive(ProcessName::Xvb)
+            && matches!(
+                self.xvb.current_node,
+                Some(XvbNode::Europe) | Some(XvbNode::NorthAmerica)
+            );
+        let (hashrate_p2pool, hashrate_xvb) = match (to_xvb, p2pool_alive) {
+            (true, _) => (0.0, hashrate),
+            (false, true) => (hashrate, 0.0),
+            (false, false) => (0.0, 0.0),
+        };
+        let (p2pool_hashrate_15m, p2pool_hashrate_1h, shares_found, current_effort) =
+            if p2pool_alive {
+                (
+                    self.p2pool.user_p2pool_hashrate_15m_u64 as f32,
+                    self.p2pool.user_p2pool_hashrate_u64 as f32,
+                    self.p2pool.shares_found.unwrap_or_default(),
+                    self.p2pool.current_effort_f32,
+                )
+            } else {
+                (0.0, 0.0, 0, 0.0)
+            };
+        Some(HistorySample {
+            timestamp: HistorySample::now(),
+            xmrig_hashrate: hashrate,
+            p2pool_hashrate_15m,
+            p2pool_hashrate_1h,
+            shares_found,
+            current_effort,
+            hashrate_p2pool,
+            hashrate_xvb,
+        })
+    }
+}
+
+// Clones [api] with an empty console, the console can be big.
+fn without_output<T: Clone>(
+    api: &Mutex<T>,
+    output: fn(&mut T) -> &mut ConsoleBuffer,
+    lines: &mut Vec<u64>,
+) -> T {
+    let mut api = api.lock().unwrap();
+    let console = std::mem::take(output(&mut api));
+    lines.push(console.next_id());
+    let clone = api.clone();
+    *output(&mut api) = console;
+    clone
+}
+
+// The start of [process], [None] if not alive.
+fn start(process: &Mutex<Process>) -> Option<Instant> {
+    let process = process.lock().unwrap();
+    process.is_alive().then_some(process.start)
+}
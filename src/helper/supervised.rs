@@ -0,0 +1,599 @@
+// Supervised processes.
+//
+// P2Pool, XMRig, XMRig-Proxy and the Node all run the same way: a child in a PTY,
+// a thread reading its output, and a watchdog waking up on [events] to handle the
+// signals, the STDIN, the output and the APIs. What differs between them is described
+// by [SupervisedProcess], everything else lives here once.
+//
+// Adding a binary is implementing the trait and calling [Helper::start_supervised()].
+// Before spawning, the driver waits for the [dependencies()] to be ready, following the
+// [DependencyPolicy] of the [Helper]: the process is [Blocked] meanwhile, woken up by the
+// changes of the processes (see [events::changed()]).
+// XvB is not a child process, it keeps its own watchdog.
+
+use crate::disk::logs::LogFile;
+use crate::disk::state::{DependencyPolicy, RestartPolicy};
+use crate::helper::console::ConsoleBuffer;
+use crate::helper::events::{self, EventSender, WatchdogEvent, WatchdogEvents};
+use crate::helper::notify::NotifyEvent;
+use crate::helper::{
+    check_died, check_user_input, signal_end, Helper, Process, ProcessName, ProcessSignal,
+    ProcessState,
+};
+use crate::macros::{arc_mut, sleep};
+use log::*;
+use portable_pty::{Child, CommandBuilder};
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+//---------------------------------------------------------------------------------------------------- PubApi
+// The stats shared by the watchdog ([pub_api_*]) and the GUI ([gui_api_*]).
+pub trait PubApi: Clone + Default + Send + 'static {
+    fn output(&mut self) -> &mut ConsoleBuffer;
+
+    // The issue with just doing [gui_api = pub_api] is that values get overwritten.
+    // This doesn't matter for any of the values EXCEPT for the output, so we must
+    // manually append it instead of overwriting.
+    fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
+        let mut output = std::mem::take(gui_api.output());
+        output.append(pub_api.output());
+        *gui_api = pub_api.clone();
+        *gui_api.output() = output;
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- PtyLines
+// Runs in the PTY read thread on every line, before it is written to the output.
+pub trait PtyLines: Send + 'static {
+    // [false] keeps the line out of the output.
+    fn line(&mut self, line: &str) -> bool;
+}
+
+impl PtyLines for () {
+    fn line(&mut self, _line: &str) -> bool {
+        true
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Watchdog
+// What the hooks of [SupervisedProcess] get to work with.
+pub struct Watchdog<'a, A> {
+    pub process: &'a Arc<Mutex<Process>>,
+    pub gui_api: &'a Arc<Mutex<A>>,
+    pub pub_api: &'a Arc<Mutex<A>>,
+    pub stdin: &'a mut Box<dyn std::io::Write + Send>,
+    pub start: Instant,
+}
+
+impl<A> Watchdog<'_, A> {
+    // Writes a command to STDIN, the same way the user would.
+    pub fn write_stdin(&mut self, name: ProcessName, line: &str) {
+        #[cfg(target_os = "windows")]
+        if let Err(e) = write!(self.stdin, "{}\r\n", line) {
+            error!("{} Watchdog | STDIN error: {}", name, e);
+        }
+        #[cfg(target_family = "unix")]
+        if let Err(e) = writeln!(self.stdin, "{}", line) {
+            error!("{} Watchdog | STDIN error: {}", name, e);
+        }
+        if let Err(e) = self.stdin.flush() {
+            error!("{} Watchdog | STDIN flush error: {}", name, e);
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- SupervisedProcess
+// The [Process], [gui_api] and [pub_api] of a process.
+pub type Handles<A> = (Arc<Mutex<Process>>, Arc<Mutex<A>>, Arc<Mutex<A>>);
+
+// The hooks are only awaited on the watchdog's own runtime, their futures do not need to be [Send].
+#[allow(async_fn_in_trait)]
+pub trait SupervisedProcess: Clone + Send + 'static {
+    // The state right after spawning, until the output or the API says otherwise.
+    const SPAWNED_STATE: ProcessState;
+    // How many lines the ANSI escape sequence filter runs on. Colors are disabled
+    // with the arguments, but some are still printed while starting.
+    const ANSI_LINES: usize;
+
+    type Api: PubApi;
+    type Lines: PtyLines;
+
+    fn name(&self) -> ProcessName;
+
+    // What the user calls it, in the notifications.
+    fn label(&self) -> String {
+        self.name().to_string()
+    }
+
+    // The name of its files in the logs directory.
+    fn log_name(&self) -> String {
+        self.name().to_string().to_lowercase()
+    }
+
+    fn handles(&self, helper: &Helper) -> Handles<Self::Api>;
+
+    fn restart_policy(&self) -> &RestartPolicy;
+
+    // The processes that must be ready ([Alive]) before this one is spawned.
+    fn dependencies(&self, _helper: &Helper) -> Vec<Arc<Mutex<Process>>> {
+        vec![]
+    }
+
+    // Builds the command arguments, and the static "image" of the data the process started with.
+    fn args(&mut self, helper: &Arc<Mutex<Helper>>, path: &Path) -> Vec<String>;
+
+    fn command(&self, args: Vec<String>, path: &Path) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(path);
+        cmd.args(args);
+        cmd.cwd(path.parent().unwrap());
+        cmd
+    }
+
+    // Handed over to the PTY read thread.
+    fn lines(&self) -> Self::Lines;
+
+    // Once the child is spawned and the stats reset, before waiting for the first event.
+    async fn spawned(&mut self, _watchdog: &mut Watchdog<'_, Self::Api>) {}
+
+    // Kills the child on [Stop/Restart], returns [true] if it did.
+    fn signal_end(
+        &self,
+        process: &mut Process,
+        child_pty: &Arc<Mutex<Box<dyn Child + Sync + Send>>>,
+        start: &Instant,
+        gui_api_output_raw: &mut ConsoleBuffer,
+    ) -> bool {
+        signal_end(process, child_pty, start, gui_api_output_raw)
+    }
+
+    // After every event, moves the new output into [pub_api] and parses it.
+    fn update_from_output(
+        &mut self,
+        pub_api: &mut Self::Api,
+        output_parse: &Arc<Mutex<String>>,
+        output_pub: &Arc<Mutex<String>>,
+        elapsed: std::time::Duration,
+        process: &mut Process,
+    );
+
+    // Every [WATCHDOG_TICK], reads the APIs.
+    async fn tick(&mut self, watchdog: &mut Watchdog<'_, Self::Api>);
+
+    // Right before being started again by the [RestartPolicy].
+    fn respawning(&self) {}
+}
+
+// If [ip] is this computer, used to know if a process depends on another one.
+pub fn is_localhost(ip: &str) -> bool {
+    ip.is_empty() || ip == "localhost" || ip == "127.0.0.1"
+}
+
+//---------------------------------------------------------------------------------------------------- Driver
+impl Helper {
+    #[cold]
+    #[inline(never)]
+    // The "frontend" function that builds the arguments and spawns the watchdog thread.
+    pub fn start_supervised<P: SupervisedProcess>(
+        helper: &Arc<Mutex<Self>>,
+        supervised: P,
+        path: &Path,
+    ) {
+        Self::launch_supervised(helper, supervised, path, false);
+    }
+
+    #[cold]
+    #[inline(never)]
+    // [respawn] is set when started again by the [RestartPolicy], the user starting
+    // it (or a pending restart) counts the restarts from zero again.
+    fn launch_supervised<P: SupervisedProcess>(
+        helper: &Arc<Mutex<Self>>,
+        mut supervised: P,
+        path: &Path,
+        respawn: bool,
+    ) {
+        let name = supervised.name();
+        let (process, gui_api, pub_api) = supervised.handles(&helper.lock().unwrap());
+        {
+            let mut process = process.lock().unwrap();
+            if !respawn {
+                process.restarts = 0;
+            }
+            process.respawning = false;
+            process.set_state(ProcessState::Middle);
+            // Only the signals sent from now on are for this start.
+            process.signal = ProcessSignal::None;
+        }
+
+        // Kept untouched for the [RestartPolicy], the arguments are built on every start.
+        let respawn = supervised.clone();
+        let args = supervised.args(helper, path);
+        // Print arguments & user settings to console
+        crate::disk::print_dash(&format!("{} | Launch arguments: {:#?}", name, args));
+        info!("{} | Using path: [{}]", name, path.display());
+
+        // Spawn watchdog thread
+        let path = path.to_path_buf();
+        let (notifier, log) = {
+            let helper = helper.lock().unwrap();
+            let log = LogFile::open(&helper.logs_path, &supervised.log_name(), helper.logs);
+            (helper.notifier.clone(), log)
+        };
+        let helper = Arc::clone(helper);
+        thread::spawn(move || {
+            if !Self::wait_dependencies(&helper, &supervised, &process) {
+                return;
+            }
+            Self::spawn_supervised_watchdog(
+                &mut supervised,
+                &process,
+                &gui_api,
+                &pub_api,
+                args,
+                &path,
+                log,
+            );
+            {
+                let process = process.lock().unwrap();
+                if process.state == ProcessState::Failed {
+                    notifier.notify(NotifyEvent::Crash {
+                        process: supervised.label(),
+                        exit_code: process.exit_code,
+                    });
+                }
+            }
+            let delay = process.lock().unwrap().respawn_delay(
+                supervised.restart_policy(),
+                gui_api.lock().unwrap().output(),
+            );
+            if delay.is_some_and(|delay| Process::wait_respawn(&process, delay)) {
+                respawn.respawning();
+                Self::launch_supervised(&helper, respawn, &path, true);
+            }
+        });
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Blocks until every dependency is ready, the [DependencyPolicy] timeout is reached
+    // or the user asked to start anyway. Returns [false] if it was stopped/restarted meanwhile.
+    fn wait_dependencies<P: SupervisedProcess>(
+        helper: &Arc<Mutex<Self>>,
+        supervised: &P,
+        process: &Arc<Mutex<Process>>,
+    ) -> bool {
+        let (policy, dependencies) = {
+            let helper = helper.lock().unwrap();
+            (helper.dependencies, supervised.dependencies(&helper))
+        };
+        if !policy.wait || dependencies.is_empty() {
+            return true;
+        }
+        Self::wait_ready(supervised.name(), policy, &dependencies, process)
+    }
+
+    // Checks again on every change of a process (see [events::changed()]).
+    #[tokio::main(flavor = "current_thread")]
+    pub(super) async fn wait_ready(
+        name: ProcessName,
+        policy: DependencyPolicy,
+        dependencies: &[Arc<Mutex<Process>>],
+        process: &Arc<Mutex<Process>>,
+    ) -> bool {
+        let mut changes = events::subscribe();
+        let start = Instant::now();
+        let deadline = (policy.timeout_secs != 0)
+            .then(|| tokio::time::Instant::now() + Duration::from_secs(policy.timeout_secs));
+        loop {
+            // A dependency that is not started at all is not waited for.
+            let Some(dependency) = dependencies.iter().find_map(|dependency| {
+                let dependency = dependency.lock().unwrap();
+                dependency.is_starting().then_some(dependency.name)
+            }) else {
+                if start.elapsed().as_secs() > 0 {
+                    info!("{} | Dependencies are ready, starting...", name);
+                }
+                return true;
+            };
+            {
+                let mut process = process.lock().unwrap();
+                match process.signal {
+                    ProcessSignal::Stop => {
+                        info!("{} | Stopped while waiting for [{}]", name, dependency);
+                        process.set_state(ProcessState::Dead);
+                        process.signal = ProcessSignal::None;
+                        return false;
+                    }
+                    // [restart_supervised()] starts it again once [Waiting].
+                    ProcessSignal::Restart => {
+                        process.set_state(ProcessState::Waiting);
+                        process.signal = ProcessSignal::None;
+                        return false;
+                    }
+                    ProcessSignal::Start => {
+                        info!("{} | Starting without waiting for [{}]", name, dependency);
+                        process.signal = ProcessSignal::None;
+                        return true;
+                    }
+                    _ => (),
+                }
+                if process.state != ProcessState::Blocked {
+                    info!("{} | Waiting for [{}] to be ready...", name, dependency);
+                    process.set_state(ProcessState::Blocked);
+                }
+            }
+            match deadline {
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline, changes.changed())
+                        .await
+                        .is_err()
+                    {
+                        warn!(
+                            "{} | [{}] is still not ready after {} seconds, starting anyway...",
+                            name, dependency, policy.timeout_secs
+                        );
+                        return true;
+                    }
+                }
+                None => {
+                    let _ = changes.changed().await;
+                }
+            }
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Starts a [Blocked] process right away, without waiting for its dependencies.
+    pub fn skip_dependencies(process: &Arc<Mutex<Process>>) {
+        let mut process = process.lock().unwrap();
+        if process.state == ProcessState::Blocked {
+            info!("{} | Skipping dependencies...", process.name);
+            process.send_signal(ProcessSignal::Start);
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Just sets some signals for the watchdog thread to pick up on.
+    // Dead and waiting for the [RestartPolicy], it cancels the restart.
+    pub fn stop_supervised(process: &Arc<Mutex<Process>>) {
+        let mut process = process.lock().unwrap();
+        if process.respawning {
+            info!("{} | Cancelling the restart...", process.name);
+            process.send_signal(ProcessSignal::Stop);
+            return;
+        }
+        info!("{} | Attempting to stop...", process.name);
+        // Middle first, the watchdog handles the signal right away.
+        process.set_state(ProcessState::Middle);
+        process.send_signal(ProcessSignal::Stop);
+    }
+
+    #[cold]
+    #[inline(never)]
+    // The "restart frontend" to a "frontend" function.
+    // Basically calls to kill the current process, waits a little, then starts [start_supervised()] in a new thread, then exit.
+    pub fn restart_supervised<P: SupervisedProcess>(
+        helper: &Arc<Mutex<Self>>,
+        supervised: P,
+        path: &Path,
+    ) {
+        let name = supervised.name();
+        info!("{} | Attempting to restart...", name);
+        let (process, _, _) = supervised.handles(&helper.lock().unwrap());
+        // Middle first, the watchdog handles the signal right away.
+        {
+            let mut process = process.lock().unwrap();
+            process.set_state(ProcessState::Middle);
+            process.send_signal(ProcessSignal::Restart);
+        }
+
+        let helper = Arc::clone(helper);
+        let path = path.to_path_buf();
+        // This thread lives to wait, start the process then die.
+        thread::spawn(move || {
+            while process.lock().unwrap().state != ProcessState::Waiting {
+                warn!(
+                    "{} | Want to restart but process is still alive, waiting...",
+                    name
+                );
+                sleep!(1000);
+            }
+            // Ok, process is not alive, start the new one!
+            info!("{} | Old process seems dead, starting new one!", name);
+            Self::start_supervised(&helper, supervised, &path);
+        });
+        info!("{} | Restart ... OK", name);
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Reads STDOUT+STDERR of the PTY until the child is gone.
+    #[allow(clippy::too_many_arguments)]
+    fn read_pty_supervised(
+        name: ProcessName,
+        output_parse: Arc<Mutex<String>>,
+        output_pub: Arc<Mutex<String>>,
+        reader: Box<dyn std::io::Read + Send>,
+        mut lines: impl PtyLines,
+        ansi_lines: usize,
+        events: EventSender,
+        mut log: LogFile,
+    ) {
+        use std::io::BufRead;
+        let stdout = std::io::BufReader::new(reader).lines();
+
+        for (i, line) in stdout.map_while(Result::ok).enumerate() {
+            // Run a ANSI escape sequence filter for the first few lines.
+            let line = if i < ansi_lines {
+                strip_ansi_escapes::strip_str(line)
+            } else {
+                line
+            };
+            // Every line, even the ones kept out of the output.
+            log.write_line(&line);
+            if !lines.line(&line) {
+                continue;
+            }
+            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
+                error!("{} PTY Parse | Output error: {}", name, e);
+            }
+            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
+                error!("{} PTY Pub | Output error: {}", name, e);
+            }
+            events.send(WatchdogEvent::Output);
+        }
+        // Without waiting for the timer to notice.
+        events.send(WatchdogEvent::Exit);
+    }
+
+    #[cold]
+    #[inline(never)]
+    // The watchdog. Spawns 1 OS thread for reading a PTY (STDOUT+STDERR), and combines the [Child] with a PTY so STDIN actually works.
+    // This isn't actually async, a tokio runtime is unfortunately needed because [Hyper] is an async library (HTTP API calls)
+    #[tokio::main]
+    #[allow(clippy::await_holding_lock)]
+    async fn spawn_supervised_watchdog<P: SupervisedProcess>(
+        supervised: &mut P,
+        process: &Arc<Mutex<Process>>,
+        gui_api: &Arc<Mutex<P::Api>>,
+        pub_api: &Arc<Mutex<P::Api>>,
+        args: Vec<String>,
+        path: &Path,
+        log: LogFile,
+    ) {
+        let name = supervised.name();
+        // 1a. Create PTY
+        debug!("{} | Creating PTY...", name);
+        let pty = portable_pty::native_pty_system();
+        let pair = pty
+            .openpty(portable_pty::PtySize {
+                rows: 100,
+                cols: 1000,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .unwrap();
+        // 1b. Create command
+        debug!("{} | Creating command...", name);
+        let cmd = supervised.command(args, path);
+        // 1c. Create child
+        debug!("{} | Creating child...", name);
+        let child_pty = arc_mut!(pair.slave.spawn_command(cmd).unwrap());
+        drop(pair.slave);
+        let reader = pair.master.try_clone_reader().unwrap(); // Get STDOUT/STDERR before moving the PTY
+        let mut stdin = pair.master.take_writer().unwrap();
+
+        // 2. Set process state
+        debug!("{} | Setting process state...", name);
+        let mut lock = process.lock().unwrap();
+        lock.set_state(P::SPAWNED_STATE);
+        lock.signal = ProcessSignal::None;
+        lock.start = Instant::now();
+        let start = lock.start;
+        let output_parse = Arc::clone(&lock.output_parse);
+        let output_pub = Arc::clone(&lock.output_pub);
+        drop(lock);
+
+        // 3. Spawn PTY read thread
+        debug!("{} | Spawning PTY read thread...", name);
+        let mut events = WatchdogEvents::new(process);
+        let sender = events.sender();
+        let lines = supervised.lines();
+        tokio::spawn({
+            let output_parse = Arc::clone(&output_parse);
+            let output_pub = Arc::clone(&output_pub);
+            async move {
+                Self::read_pty_supervised(
+                    name,
+                    output_parse,
+                    output_pub,
+                    reader,
+                    lines,
+                    P::ANSI_LINES,
+                    sender,
+                    log,
+                );
+            }
+        });
+
+        // Reset stats before loop
+        *pub_api.lock().unwrap() = P::Api::default();
+        *gui_api.lock().unwrap() = P::Api::default();
+        supervised
+            .spawned(&mut Watchdog {
+                process,
+                gui_api,
+                pub_api,
+                stdin: &mut stdin,
+                start,
+            })
+            .await;
+
+        // 4. Loop as watchdog
+        info!("{} | Entering watchdog mode... woof!", name);
+        loop {
+            // Hand over to the GUI what the last event changed.
+            P::Api::combine_gui_pub_api(&mut gui_api.lock().unwrap(), &mut pub_api.lock().unwrap());
+            events::changed();
+            // Wait for something to happen
+            let event = events.next().await;
+            debug!("{} Watchdog | ----------- {:?} -----------", name, event);
+
+            // Check if the process secretly died without us knowing :)
+            if check_died(
+                &child_pty,
+                &mut process.lock().unwrap(),
+                &start,
+                gui_api.lock().unwrap().output(),
+            ) {
+                break;
+            }
+            // Stop on [Stop/Restart] SIGNAL
+            if supervised.signal_end(
+                &mut process.lock().unwrap(),
+                &child_pty,
+                &start,
+                gui_api.lock().unwrap().output(),
+            ) {
+                break;
+            }
+            // Check vector of user input
+            check_user_input(process, &mut stdin);
+
+            // Always update from output
+            debug!("{} Watchdog | Starting [update_from_output()]", name);
+            let mut process_lock = process.lock().unwrap();
+            let mut pub_api_lock = pub_api.lock().unwrap();
+            supervised.update_from_output(
+                &mut pub_api_lock,
+                &output_parse,
+                &output_pub,
+                start.elapsed(),
+                &mut process_lock,
+            );
+            drop(pub_api_lock);
+            drop(process_lock);
+
+            // The rest is only for the timer.
+            if event != WatchdogEvent::Tick {
+                continue;
+            }
+            supervised
+                .tick(&mut Watchdog {
+                    process,
+                    gui_api,
+                    pub_api,
+                    stdin: &mut stdin,
+                    start,
+                })
+                .await;
+        }
+
+        // 5. If loop broke, we must be done here.
+        info!("{} Watchdog | Watchdog thread exiting... Goodbye!", name);
+    }
+}
@@ -2,14 +2,23 @@
 mod test {
 
     use crate::helper::xrig::xmrig_proxy::PubXmrigProxyApi;
-    use crate::helper::xvb::algorithm::Algorithm;
+    use crate::helper::xvb::algorithm::{Inputs, Stats};
     use crate::helper::{
         p2pool::{PrivP2poolLocalApi, PrivP2poolNetworkApi},
         xvb::{priv_stats::RuntimeDonationLevel, priv_stats::RuntimeMode},
-        Helper, Process, ProcessName, ProcessState,
+        Process, ProcessName, ProcessState,
     };
     use crate::miscs::client;
 
+    #[test]
+    fn constant_time_eq() {
+        use crate::miscs::constant_time_eq;
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
     #[test]
     fn get_current_shares() {
         let stdout = "
@@ -64,15 +73,32 @@ Uptime         = 0h 2m 4s
         assert_eq!(shares, 0);
     }
     #[test]
-    fn reset_gui_output() {
-        let max = crate::helper::GUI_OUTPUT_LEEWAY;
-        let mut string = String::with_capacity(max);
-        for _ in 0..=max {
-            string.push('0');
-        }
-        Helper::check_reset_gui_output(&mut string, ProcessName::P2pool);
-        // Some text gets added, so just check for less than 500 bytes.
-        assert!(string.len() < 500);
+    fn console_ring_buffer() {
+        use crate::helper::console::ConsoleBuffer;
+        use std::fmt::Write;
+        let mut console = ConsoleBuffer::with_capacity(3);
+        writeln!(console, "line 0").unwrap();
+        console.push_str("line 1\r\nline");
+        // Not terminated yet.
+        assert_eq!(console.len(), 2);
+        console.push_str(" 2\nline 3\n");
+        // The oldest line is dropped, ids keep going.
+        assert_eq!(console.len(), 3);
+        assert_eq!(console.first_id(), 1);
+        assert_eq!(console.get(0), None);
+        assert_eq!(console.get(1), Some("line 1"));
+        assert_eq!(console.get(2), Some("line 2"));
+        assert_eq!(console.bytes(), 18);
+        // The GUI side receives the lines of the watchdog side.
+        let mut other = ConsoleBuffer::with_capacity(3);
+        other.push_str("line 4\nline 5\n");
+        console.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(console.to_string(), "line 3\nline 4\nline 5\n");
+        assert_eq!(console.next_id(), 6);
+        console.clear();
+        assert!(console.is_empty());
+        assert_eq!(console.first_id(), 6);
     }
 
     #[test]
@@ -256,6 +282,10 @@ Uptime         = 0h 2m 4s
             average_effort: 100.000,
             current_effort: 200.000,
             connections: 1234,
+            workers: Some(vec![
+                "127.0.0.1:5000,120,10000,3000,rig1".to_string(),
+                "garbage".to_string(),
+            ]),
         };
         let network = PrivP2poolNetworkApi {
             difficulty: 300_000_000_000,
@@ -274,6 +304,7 @@ Uptime         = 0h 2m 4s
         let mut p = public.lock().unwrap();
         PubP2poolApi::update_from_local(&mut p, local);
         println!("AFTER LOCAL: {:#?}", p);
+        assert_eq!(p.stratum_workers.as_ref().map(Vec::len), Some(1));
         assert_eq!(p.hashrate_15m.to_string(), "10,000");
         assert_eq!(p.hashrate_1h.to_string(), "20,000");
         assert_eq!(p.hashrate_24h.to_string(), "30,000");
@@ -349,6 +380,277 @@ Uptime         = 0h 2m 4s
         assert!(process.lock().unwrap().state == ProcessState::Alive);
     }
 
+    #[test]
+    fn xmrig_schedule() {
+        use crate::disk::state::{MiningWindow, WindowAction, Xmrig};
+        use crate::helper::xrig::schedule::parse_time;
+        use chrono::NaiveDate;
+
+        assert_eq!(parse_time("00:00"), Some(0));
+        assert_eq!(parse_time("23:59"), Some(1439));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("7:00"), None);
+        assert_eq!(parse_time("07:60"), None);
+
+        let xmrig = Xmrig {
+            current_threads: 4,
+            max_threads: 16,
+            schedule: vec![
+                // Peak hours on weekdays.
+                MiningWindow {
+                    days: [true, true, true, true, true, false, false],
+                    start: "07:00".to_string(),
+                    end: "10:00".to_string(),
+                    action: WindowAction::Pause,
+                    threads: 1,
+                },
+                // Nights, ending the next day.
+                MiningWindow {
+                    days: [true; 7],
+                    start: "22:00".to_string(),
+                    end: "06:00".to_string(),
+                    action: WindowAction::Mine,
+                    threads: 32,
+                },
+            ],
+            ..Default::default()
+        };
+        // 2024-01-01 is a Monday.
+        let at = |day, hour, minute| {
+            NaiveDate::from_ymd_opt(2024, 1, day)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+        };
+        // Paused during peak hours on weekdays only.
+        assert!(xmrig.scheduled_target(at(1, 7, 0)).paused());
+        assert!(xmrig.scheduled_target(at(5, 9, 59)).paused());
+        assert_eq!(xmrig.scheduled_target(at(1, 10, 0)).threads, 4);
+        assert_eq!(xmrig.scheduled_target(at(6, 8, 0)).threads, 4);
+        // Full threads at night, capped to [max_threads], until the next morning.
+        assert_eq!(xmrig.scheduled_target(at(1, 23, 0)).threads, 16);
+        assert_eq!(xmrig.scheduled_target(at(2, 5, 59)).threads, 16);
+        assert_eq!(xmrig.scheduled_target(at(2, 6, 0)).threads, 4);
+        // Outside of every window.
+        assert_eq!(xmrig.scheduled_target(at(3, 15, 0)).threads, 4);
+    }
+
+    #[test]
+    fn xmrig_throttle() {
+        use crate::constants::{XMRIG_RESTORE_DELAY, XMRIG_THROTTLE_DELAY};
+        use crate::disk::state::{ThrottlePolicy, WindowAction};
+        use crate::helper::xrig::schedule::ScheduleTarget;
+        use crate::helper::xrig::throttle::Throttle;
+        use std::time::{Duration, Instant};
+
+        let mut policy = ThrottlePolicy {
+            enabled: true,
+            max_temperature: 80,
+            max_load: 50,
+            hysteresis: 5,
+            action: WindowAction::Mine,
+            threads: 2,
+        };
+        // Thresholds, with the hysteresis to come back.
+        assert!(policy.over(Some(81.0), 0.0));
+        assert!(policy.over(None, 51.0));
+        assert!(!policy.over(None, 50.0));
+        assert!(!policy.under(Some(78.0), 0.0));
+        assert!(policy.under(Some(75.0), 45.0));
+        assert!(policy.under(None, 10.0));
+        // Never more threads than the target.
+        let target = ScheduleTarget { threads: 8 };
+        assert_eq!(policy.limit(target).threads, 2);
+        assert_eq!(policy.limit(ScheduleTarget { threads: 1 }).threads, 1);
+        policy.action = WindowAction::Pause;
+        assert!(policy.limit(target).paused());
+
+        // A spike shorter than the delay does not throttle.
+        let mut throttle = Throttle::default();
+        let start = Instant::now();
+        assert!(!throttle.update(&policy, Some(90.0), 0.0, start));
+        assert!(!throttle.update(&policy, Some(70.0), 0.0, start + Duration::from_secs(5)));
+        assert!(!throttle.update(&policy, Some(90.0), 0.0, start + XMRIG_THROTTLE_DELAY));
+        assert!(!throttle.throttled());
+        // Throttled once crossed for the whole delay.
+        let start = start + XMRIG_THROTTLE_DELAY;
+        assert!(throttle.update(&policy, Some(90.0), 0.0, start + XMRIG_THROTTLE_DELAY));
+        assert!(throttle.throttled());
+        // Between the threshold and the hysteresis is not enough to restore.
+        let start = start + XMRIG_THROTTLE_DELAY;
+        assert!(!throttle.update(&policy, Some(78.0), 0.0, start + XMRIG_RESTORE_DELAY));
+        assert!(!throttle.update(&policy, Some(70.0), 0.0, start + XMRIG_RESTORE_DELAY));
+        let start = start + XMRIG_RESTORE_DELAY;
+        assert!(throttle.update(&policy, Some(70.0), 0.0, start + XMRIG_RESTORE_DELAY));
+        assert!(!throttle.throttled());
+    }
+
+    #[test]
+    fn notify_sinks() {
+        use crate::disk::state::Notifications;
+        use crate::helper::notify::{NotifyEvent, Sink};
+
+        // Only the enabled events are sent.
+        let mut notifications = Notifications {
+            payout: false,
+            ..Default::default()
+        };
+        let payout = NotifyEvent::Payout {
+            date: "2024-01-01 00:00:00.0000".to_string(),
+            atomic_units: 1_000_000_000,
+            xmr: "0.001000000000".to_string(),
+            block: "3000000".to_string(),
+        };
+        assert!(!payout.enabled(&notifications));
+        assert!(NotifyEvent::XvbWin.enabled(&notifications));
+        assert_eq!(
+            serde_json::to_value(&payout).unwrap(),
+            serde_json::json!({
+                "event": "payout",
+                "data": {
+                    "date": "2024-01-01 00:00:00.0000",
+                    "atomic_units": 1_000_000_000,
+                    "xmr": "0.001000000000",
+                    "block": "3000000",
+                },
+            })
+        );
+
+        // Only the enabled and configured sinks are used.
+        assert!(Sink::from_policy(&notifications).is_empty());
+        notifications.desktop = true;
+        notifications.webhook = true;
+        notifications.mail = true;
+        notifications.mail_to = " a@localhost, ,b@localhost ".to_string();
+        notifications.command = true;
+        let sinks = Sink::from_policy(&notifications);
+        assert_eq!(sinks.len(), 2);
+        let Sink::Mail(mut mail) = sinks[1].clone() else {
+            panic!("expected a mail sink, got {}", sinks[1]);
+        };
+        assert_eq!(mail.to, ["a@localhost", "b@localhost"]);
+
+        // Only the host of the webhook is logged.
+        assert_eq!(
+            Sink::Webhook("https://discord.com/api/webhooks/1/secret".to_string()).to_string(),
+            "webhook [discord.com]"
+        );
+
+        // A whole SMTP exchange, against a server recording the commands.
+        mail.from = "gupaxx@localhost".to_string();
+        let (server, commands) = smtp_server(false, 1);
+        mail.server = server;
+        send(&Sink::Mail(mail.clone()), &NotifyEvent::XvbWin).unwrap();
+        assert_eq!(
+            commands.join().unwrap(),
+            [
+                "EHLO gupaxx",
+                "MAIL FROM:<gupaxx@localhost>",
+                "RCPT TO:<a@localhost>",
+                "RCPT TO:<b@localhost>",
+                "DATA",
+                ".",
+                "QUIT",
+            ]
+        );
+        // The server offers no TLS, the password is not sent.
+        let (server, commands) = smtp_server(false, 1);
+        mail.server = server;
+        mail.username = "user".to_string();
+        mail.password = "pass".to_string();
+        assert!(send(&Sink::Mail(mail.clone()), &NotifyEvent::XvbWin).is_err());
+        assert_eq!(commands.join().unwrap(), ["EHLO gupaxx"]);
+        // Nor when its certificate is refused.
+        let (server, commands) = smtp_server(true, 1);
+        mail.server = server;
+        assert!(send(&Sink::Mail(mail.clone()), &NotifyEvent::XvbWin).is_err());
+        assert_eq!(commands.join().unwrap(), ["EHLO gupaxx", "STARTTLS"]);
+        // Without credentials, the mail is sent again without TLS.
+        let (server, commands) = smtp_server(true, 2);
+        mail.server = server;
+        mail.username.clear();
+        mail.password.clear();
+        send(&Sink::Mail(mail), &NotifyEvent::XvbWin).unwrap();
+        assert_eq!(
+            commands.join().unwrap(),
+            [
+                "EHLO gupaxx",
+                "STARTTLS",
+                "EHLO gupaxx",
+                "MAIL FROM:<gupaxx@localhost>",
+                "RCPT TO:<a@localhost>",
+                "RCPT TO:<b@localhost>",
+                "DATA",
+                ".",
+                "QUIT",
+            ]
+        );
+    }
+    // An SMTP server without TLS, returns its address and the commands it received
+    // over [connections]. If [starttls] is offered, the connection is closed after
+    // accepting it, as a handshake the client refuses would.
+    fn smtp_server(
+        starttls: bool,
+        connections: usize,
+    ) -> (String, thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let mut commands = vec![];
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().unwrap();
+                let mut writer = stream.try_clone().unwrap();
+                let mut reader = BufReader::new(stream);
+                writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+                let mut data = false;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                        break;
+                    }
+                    let line = line.trim_end().to_string();
+                    let reply: &[u8] = if data {
+                        if line != "." {
+                            continue;
+                        }
+                        data = false;
+                        b"250 OK\r\n"
+                    } else if line.starts_with("EHLO") && starttls {
+                        b"250-localhost\r\n250-STARTTLS\r\n250 AUTH PLAIN\r\n"
+                    } else if line.starts_with("EHLO") {
+                        b"250-localhost\r\n250 AUTH PLAIN\r\n"
+                    } else if line == "STARTTLS" {
+                        commands.push(line);
+                        writer.write_all(b"220 Ready\r\n").unwrap();
+                        break;
+                    } else if line.starts_with("AUTH") {
+                        b"235 OK\r\n"
+                    } else if line == "DATA" {
+                        data = true;
+                        b"354 Go ahead\r\n"
+                    } else if line == "QUIT" {
+                        b"221 Bye\r\n"
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    commands.push(line);
+                    writer.write_all(reply).unwrap();
+                }
+            }
+            commands
+        });
+        (address, server)
+    }
+    #[tokio::main]
+    async fn send(
+        sink: &crate::helper::notify::Sink,
+        event: &crate::helper::notify::NotifyEvent,
+    ) -> anyhow::Result<()> {
+        sink.send(&client(), event).await
+    }
+
     #[test]
     fn serde_priv_p2pool_local_api() {
         let data = r#"{
@@ -561,245 +863,1038 @@ Uptime         = 0h 2m 4s
         XvbPubStats::request_api(client).await.unwrap()
     }
 
+    // Inputs of the algorithm, as read from a default state.
+    fn algorithm_inputs() -> Inputs {
+        Inputs {
+            share: 1,
+            mini: P2pool::default().mini,
+            p2pool_buffer: 5,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_manual_xvb_mode() {
-        let client = client();
+        let stats = Stats::new(Inputs {
+            hashrate: 10000.0,
+            runtime_mode: RuntimeMode::ManualXvb,
+            runtime_amount: 1000.0,
+            ..algorithm_inputs()
+        });
+
+        assert_eq!(stats.target_donation_hashrate, 1000.0);
+    }
+
+    #[test]
+    fn test_manual_p2pool_mode() {
+        let stats = Stats::new(Inputs {
+            hashrate: 10000.0,
+            runtime_mode: RuntimeMode::ManualP2pool,
+            runtime_amount: 1000.0,
+            ..algorithm_inputs()
+        });
+
+        assert_eq!(stats.target_donation_hashrate, 9000.0);
+    }
+
+    #[test]
+    fn test_manual_donor_level_mode_donor() {
+        let stats = Stats::new(Inputs {
+            hashrate: 10000.0,
+            runtime_mode: RuntimeMode::ManualDonationLevel,
+            runtime_amount: 1000.0,
+            runtime_donation_level: RuntimeDonationLevel::Donor,
+            ..algorithm_inputs()
+        });
+
+        assert_eq!(stats.target_donation_hashrate, 1000.0);
+    }
+
+    #[test]
+    fn test_auto_mode() {
+        let stats = Stats::new(Inputs {
+            hashrate: 20000.0,
+            runtime_mode: RuntimeMode::Auto,
+            p2pool_difficulty: 9_000_000,
+            ..algorithm_inputs()
+        });
+
+        assert_eq!(stats.target_donation_hashrate, 10000.0);
+
+        let stats = Stats::new(Inputs {
+            hashrate: 10000.0,
+            runtime_mode: RuntimeMode::Auto,
+            p2pool_difficulty: 95_000_000,
+            ..algorithm_inputs()
+        });
+
+        assert_eq!(stats.target_donation_hashrate, 1000.0);
+    }
+
+    #[test]
+    fn test_hero_mode() {
+        let inputs = Inputs {
+            hashrate: 20000.0,
+            runtime_mode: RuntimeMode::Hero,
+            p2pool_difficulty: 95_000_000,
+            ..algorithm_inputs()
+        };
+        let stats = Stats::new(inputs.clone());
+
+        assert_eq!(stats.target_donation_hashrate, 15382.1);
+
+        let stats = Stats::new(Inputs {
+            p2pool_total_hashrate: 25000.0,
+            ..inputs
+        });
+
+        assert_eq!(stats.target_donation_hashrate, 20000.0);
+    }
+
+    #[test]
+    fn algorithm_inputs_from_apis() {
+        use crate::disk::state::Xvb;
+
         let pub_api = Arc::new(Mutex::new(PubXvbApi::new()));
         let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
         let gui_api_xmrig = Arc::new(Mutex::new(PubXmrigApi::new()));
         let gui_api_xp = Arc::new(Mutex::new(PubXmrigProxyApi::new()));
         let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
-        let token_xmrig = "12345678";
         let state_p2pool = P2pool::default();
-        let time_donated = Arc::new(Mutex::new(u32::default()));
-        let rig = "test_rig";
-        let xp_alive = false;
-        let share = 1;
-        let p2pool_buffer = 5;
 
-        gui_api_xmrig.lock().unwrap().hashrate_raw_15m = 10000.0;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::ManualXvb;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_manual_amount = 1000.0;
+        gui_api_xmrig.lock().unwrap().hashrate_raw_1m = 8000.0;
+        gui_api_xp.lock().unwrap().hashrate_1m = 30000.0;
+        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::Hero;
+        gui_api_p2pool.lock().unwrap().sidechain_ehr = 25000.0;
+        gui_api_p2pool.lock().unwrap().p2pool_difficulty_u64 = 95_000_000;
+        pub_api.lock().unwrap().stats_priv.donor_24hr_avg = 1.5;
+        let read = |xp_alive| {
+            Inputs::from_apis(
+                &pub_api,
+                &gui_api_xvb,
+                &gui_api_xmrig,
+                &gui_api_xp,
+                &gui_api_p2pool,
+                &state_p2pool,
+                1,
+                xp_alive,
+                &Xvb {
+                    p2pool_buffer: 5,
+                    extra_pool: "pool.example:3333".to_string(),
+                    extra_pool_percent: 10,
+                    ..Default::default()
+                },
+                &[],
+            )
+        };
+        let inputs = read(false);
+        // 15m average is not there yet.
+        assert_eq!(inputs.hashrate, 8000.0);
+        assert_eq!(inputs.runtime_mode, RuntimeMode::Hero);
+        assert_eq!(inputs.p2pool_total_hashrate, 25000.0);
+        assert_eq!(inputs.p2pool_difficulty, 95_000_000);
+        assert_eq!(inputs.xvb_24h_avg, 1500.0);
+        assert_eq!(inputs.xvb_avg_last_hour_hashrate, 0.0);
+        assert_eq!(inputs.api_url, crate::XMRIG_CONFIG_URL);
+        assert_eq!(inputs.p2pool_buffer, 5);
+        assert_eq!(
+            inputs.extra_pool.map(|pool| pool.url).as_deref(),
+            Some("pool.example:3333")
+        );
+        assert_eq!(inputs.extra_pool_percent, 10);
+        let inputs = read(true);
+        assert_eq!(inputs.hashrate, 30000.0);
+        assert_eq!(inputs.api_url, crate::XMRIG_PROXY_CONFIG_URL);
+    }
+
+    #[test]
+    fn standalone_inputs() {
+        use crate::helper::xvb::standalone::{summary_hashrate, P2poolData};
+
+        // XMRig before its 15m average, XMRig-Proxy in kH/s.
+        let xmrig = serde_json::json!({"hashrate": {"total": [1200.5, 1100.0, null]}});
+        assert_eq!(summary_hashrate(&xmrig, false), 1100.0);
+        let proxy = serde_json::json!({"hashrate": {"total": [12.0, 0.0, 0.0, 0.0, 0.0, 0.0]}});
+        assert_eq!(summary_hashrate(&proxy, true), 12000.0);
+        assert_eq!(summary_hashrate(&serde_json::json!({}), false), 0.0);
 
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
+        let pool = r#"{"pool_statistics": {"hashRate": 10000000, "miners": 1000}}"#;
+        assert_eq!(
+            P2poolData::from_str(r#"{"block_reward_share_percent": 0.25}"#, pool, &[]),
+            Some(P2poolData {
+                share: 1,
+                hashrate: 25000.0,
+                difficulty: 100_000_000,
+                stratum: None,
+            })
+        );
+        let workers = r#"{"block_reward_share_percent": 0.25, "workers": ["127.0.0.1:5000,120,10000,3000,rig1"]}"#;
+        assert_eq!(
+            P2poolData::from_str(workers, pool, &[]).unwrap().stratum,
+            Some(3000.0)
         );
+        assert_eq!(
+            P2poolData::from_str(workers, pool, &["rig1".to_string()])
+                .unwrap()
+                .stratum,
+            Some(0.0)
+        );
+        // Older P2Pool, no share known.
+        assert_eq!(
+            P2poolData::from_str(r#"{"hashrate_15m": 1}"#, pool, &[])
+                .unwrap()
+                .share,
+            0
+        );
+        // Not synchronized yet.
+        let syncing = r#"{"pool_statistics": {"hashRate": 0, "miners": 0}}"#;
+        assert_eq!(P2poolData::from_str("{}", syncing, &[]), None);
+    }
 
-        assert_eq!(algo.stats.target_donation_hashrate, 1000.0);
+    #[test]
+    fn stratum_workers() {
+        use crate::helper::p2pool::StratumWorker;
+        use crate::GUPAX_VERSION_UNDERSCORE;
+
+        let worker = StratumWorker::from_str("127.0.0.1:5000,120,10000,3000,rig1").unwrap();
+        assert_eq!(worker.address, "127.0.0.1:5000");
+        assert_eq!(worker.hashrate, 3000);
+        assert_eq!(worker.name, "rig1");
+        assert_eq!(StratumWorker::from_str("127.0.0.1:5000,120"), None);
+        assert_eq!(StratumWorker::from_str("127.0.0.1:5000,120,10000,a,"), None);
+
+        // The miner controlled by XvB is not external.
+        let gupaxx = StratumWorker::from_str(&format!(
+            "127.0.0.1:5001,60,10000,5000,{GUPAX_VERSION_UNDERSCORE}"
+        ))
+        .unwrap();
+        let workers = [worker, gupaxx];
+        assert_eq!(StratumWorker::external_hashrate(&workers, &[]), 3000.0);
+        // Nor before its first switch, logged in with the rig name of simple mode
+        // or the user of advanced mode.
+        let mut xmrig = crate::disk::state::Xmrig {
+            simple_rig: "rig1".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(xmrig.stratum_logins(), ["rig1"]);
+        assert_eq!(
+            StratumWorker::external_hashrate(&workers, &xmrig.stratum_logins()),
+            0.0
+        );
+        xmrig.simple = false;
+        xmrig.address = "rig1".to_string();
+        xmrig.rig = String::new();
+        assert_eq!(xmrig.stratum_logins(), ["rig1"]);
+        xmrig.address = "4Aaddress".to_string();
+        assert_eq!(
+            StratumWorker::external_hashrate(&workers, &xmrig.stratum_logins()),
+            3000.0
+        );
+
+        let inputs = Inputs {
+            hashrate: 5000.0,
+            p2pool_avg_last_hour_hashrate: 5000.0,
+            p2pool_stratum_hashrate: Some(3000.0),
+            ..algorithm_inputs()
+        };
+        assert_eq!(
+            Stats::new(Inputs {
+                p2pool_total_hashrate: 8500.0,
+                ..inputs.clone()
+            })
+            .stratum_mismatch(),
+            None
+        );
+        assert_eq!(
+            Stats::new(Inputs {
+                p2pool_total_hashrate: 20000.0,
+                ..inputs.clone()
+            })
+            .stratum_mismatch(),
+            Some((8000.0, 20000.0))
+        );
+        // Sidechain not estimated yet.
+        assert_eq!(Stats::new(inputs).stratum_mismatch(), None);
     }
 
     #[test]
-    fn test_manual_p2pool_mode() {
-        let client = client();
-        let pub_api = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xmrig = Arc::new(Mutex::new(PubXmrigApi::new()));
-        let gui_api_xp = Arc::new(Mutex::new(PubXmrigProxyApi::new()));
-        let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
-        let token_xmrig = "12345678";
-        let state_p2pool = P2pool::default();
-        let time_donated = Arc::new(Mutex::new(u32::default()));
-        let rig = "test_rig";
-        let xp_alive = false;
-        let share = 1;
-        let p2pool_buffer = 5;
+    fn round_tracker() {
+        use crate::helper::xvb::priv_stats::XvbPrivStats;
+        use crate::helper::xvb::public_stats::XvbPubStats;
+        use crate::helper::xvb::rounds::{RoundTracker, XvbRound};
+
+        let address = "4".repeat(95);
+        let stats_priv = XvbPrivStats {
+            donor_1hr_avg: 12.0,
+            donor_24hr_avg: 11.0,
+            round_participate: Some(XvbRound::DonorVip),
+            ..Default::default()
+        };
+        let raffle = |block_height, winner: &str| XvbPubStats {
+            block_height,
+            winner: winner.to_string(),
+            ..Default::default()
+        };
+        let mut tracker = RoundTracker::default();
+        // Public stats not received yet, then the raffle drawn before the start.
+        assert_eq!(
+            tracker.update(&raffle(0, ""), &stats_priv, &address, 1),
+            None
+        );
+        assert_eq!(
+            tracker.update(&raffle(100, ""), &stats_priv, &address, 2),
+            None
+        );
+        tracker.donated(600);
+        tracker.donated(300);
+        assert_eq!(
+            tracker.update(&raffle(100, ""), &stats_priv, &address, 3),
+            None
+        );
+
+        let winner = crate::helper::Helper::head_tail_of_monero_address(&address);
+        let record = tracker
+            .update(&raffle(130, &winner), &stats_priv, &address, 4)
+            .unwrap();
+        assert_eq!(record.timestamp, 4);
+        assert_eq!(record.block_height, 130);
+        assert_eq!(record.round, Some(XvbRound::DonorVip));
+        assert_eq!(record.donor_1h_avg, 12000.0);
+        assert_eq!(record.donor_24h_avg, 11000.0);
+        assert_eq!(record.xvb_seconds, 900);
+        assert!(record.won);
+
+        // The time donated starts over with the next round.
+        let record = tracker
+            .update(&raffle(160, "other"), &XvbPrivStats::default(), &address, 5)
+            .unwrap();
+        assert_eq!(record.xvb_seconds, 0);
+        assert_eq!(record.round, None);
+        assert!(!record.won);
+    }
+
+    #[test]
+    fn algorithm_decision() {
+        use crate::helper::xvb::algorithm::{Decision, Destination, Reason, Slot};
+        use crate::XVB_TIME_ALGO;
+
+        let inputs = Inputs {
+            hashrate: 10000.0,
+            p2pool_total_hashrate: 10000.0,
+            p2pool_difficulty: 150_000_000,
+            ..algorithm_inputs()
+        };
+        assert_eq!(
+            Stats::new(Inputs {
+                share: 0,
+                ..inputs.clone()
+            })
+            .decide(),
+            Decision {
+                reason: Reason::NoShare,
+                slots: vec![Slot {
+                    destination: Destination::P2pool,
+                    seconds: XVB_TIME_ALGO,
+                }],
+            }
+        );
+        // Nothing on XvB yet in auto mode.
+        assert_eq!(
+            Stats::new(inputs.clone()).decide(),
+            Decision {
+                reason: Reason::Xvb24h,
+                slots: vec![Slot {
+                    destination: Destination::Xvb,
+                    seconds: XVB_TIME_ALGO,
+                }],
+            }
+        );
+        assert_eq!(
+            Stats::new(inputs.clone()).decide().message(),
+            "24H avg XvB target not achieved. Sending all hashrate to XvB!"
+        );
+        let decision = Stats::new(Inputs {
+            runtime_mode: RuntimeMode::ManualXvb,
+            runtime_amount: 1000.0,
+            ..inputs
+        })
+        .decide();
+        assert_eq!(decision.reason, Reason::Cycle);
+        assert_eq!(decision.slots.len(), 2);
+        assert_eq!(decision.slots[0].destination, Destination::P2pool);
+        assert!(decision.xvb_time() > 0 && decision.xvb_time() < XVB_TIME_ALGO);
+        assert_eq!(
+            decision.seconds(&Destination::P2pool) + decision.xvb_time(),
+            XVB_TIME_ALGO
+        );
+    }
+
+    #[test]
+    fn algorithm_schedule() {
+        use crate::helper::xvb::algorithm::{Destination, Reason};
+        use crate::helper::xvb::nodes::Pool;
+        use crate::XVB_TIME_ALGO;
+
+        let pool = Pool {
+            url: "pool.example:3333".to_string(),
+            ..Default::default()
+        };
+        // Nothing else on P2Pool, 7291H/s needed for a share.
+        let inputs = Inputs {
+            hashrate: 10000.0,
+            p2pool_difficulty: 150_000_000,
+            runtime_mode: RuntimeMode::ManualXvb,
+            runtime_amount: 1000.0,
+            extra_pool: Some(pool.clone()),
+            extra_pool_percent: 10,
+            ..algorithm_inputs()
+        };
+        let share_time = Stats::minimum_hashrate_share(150_000_000, inputs.mini, 0.0, 5)
+            / inputs.hashrate
+            * XVB_TIME_ALGO as f32;
+
+        // P2Pool first, XvB last, the whole round.
+        let decision = Stats::new(inputs.clone()).decide();
+        assert_eq!(decision.reason, Reason::Cycle);
+        let destinations = decision
+            .slots
+            .iter()
+            .map(|slot| slot.destination.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            destinations,
+            [
+                Destination::P2pool,
+                Destination::Pool(pool.clone()),
+                Destination::Xvb
+            ]
+        );
+        assert_eq!(
+            decision.slots.iter().map(|slot| slot.seconds).sum::<u32>(),
+            XVB_TIME_ALGO
+        );
+        assert!((59..=60).contains(&decision.seconds_pool()));
+        assert!((59..=60).contains(&decision.xvb_time()));
+        assert_eq!(
+            decision.to_string(),
+            format!(
+                "P2Pool {}s, pool.example:3333 {}s, XvB {}s",
+                decision.seconds(&Destination::P2pool),
+                decision.seconds_pool(),
+                decision.xvb_time()
+            )
+        );
 
-        gui_api_xmrig.lock().unwrap().hashrate_raw_15m = 10000.0;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::ManualP2pool;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_manual_amount = 1000.0;
+        // Never what P2Pool needs for a share.
+        let decision = Stats::new(Inputs {
+            runtime_mode: RuntimeMode::Hero,
+            extra_pool_percent: 50,
+            ..inputs.clone()
+        })
+        .decide();
+        assert!(decision.seconds_pool() > 0);
+        assert!(decision.seconds(&Destination::P2pool) as f32 >= share_time.floor());
 
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
+        // Under the 24h target, the other pool keeps its part.
+        let decision = Stats::new(Inputs {
+            runtime_mode: RuntimeMode::Auto,
+            ..inputs.clone()
+        })
+        .decide();
+        assert_eq!(decision.reason, Reason::Xvb24h);
+        assert!(decision.seconds_pool() > 0);
+        assert_eq!(
+            decision.message(),
+            format!(
+                "24H avg XvB target not achieved. Sending {} seconds to the extra pool and the rest to XvB!",
+                decision.seconds_pool()
+            )
         );
 
-        assert_eq!(algo.stats.target_donation_hashrate, 9000.0);
+        // Without a share or the other pool, as before.
+        let decision = Stats::new(Inputs {
+            share: 0,
+            ..inputs.clone()
+        })
+        .decide();
+        assert_eq!(decision.seconds(&Destination::P2pool), XVB_TIME_ALGO);
+        let decision = Stats::new(Inputs {
+            extra_pool: None,
+            ..inputs
+        })
+        .decide();
+        assert_eq!(decision.seconds_pool(), 0);
+        assert_eq!(
+            decision.seconds(&Destination::P2pool) + decision.xvb_time(),
+            XVB_TIME_ALGO
+        );
     }
 
     #[test]
-    fn test_manual_donor_level_mode_donor() {
-        let client = client();
-        let pub_api = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xmrig = Arc::new(Mutex::new(PubXmrigApi::new()));
-        let gui_api_xp = Arc::new(Mutex::new(PubXmrigProxyApi::new()));
-        let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
-        let token_xmrig = "12345678";
-        let state_p2pool = P2pool::default();
-        let time_donated = Arc::new(Mutex::new(u32::default()));
-        let rig = "test_rig";
-        let xp_alive = false;
-        let share = 1;
-        let p2pool_buffer = 5;
-
-        gui_api_xmrig.lock().unwrap().hashrate_raw_15m = 10000.0;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::ManualDonationLevel;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_manual_amount = 1000.0;
-        gui_api_xvb
-            .lock()
-            .unwrap()
-            .stats_priv
-            .runtime_manual_donation_level = RuntimeDonationLevel::Donor;
-
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
-        );
-
-        assert_eq!(algo.stats.target_donation_hashrate, 1000.0);
+    fn simulator() {
+        use crate::helper::xvb::rounds::XvbRound;
+        use crate::helper::xvb::simulator::{simulate, Sample};
+
+        let samples = Sample::synthetic(2, 10000.0, 10.0, 150_000_000);
+        assert_eq!(samples.len(), 288);
+        assert_eq!(samples[0].hashrate, 10000.0);
+        let report = simulate(algorithm_inputs(), &samples);
+        assert_eq!(report.rounds.len(), 288);
+        let rate = report.share_keep_rate();
+        assert!(rate > 0.5 && rate < 1.0);
+        // A tier only with a share.
+        for round in &report.rounds {
+            assert_eq!(round.share_kept, round.tier.is_some());
+        }
+        let tiers = report.tiers();
+        assert_eq!(tiers.iter().map(|(_, n)| n).sum::<usize>(), 288);
+        assert!(tiers
+            .iter()
+            .any(|(tier, n)| *tier == Some(XvbRound::Donor) && *n > 0));
+
+        // Never enough for a share on the main chain.
+        let samples = Sample::synthetic(1, 3000.0, 0.0, 1_000_000_000);
+        let report = simulate(
+            Inputs {
+                mini: false,
+                ..algorithm_inputs()
+            },
+            &samples,
+        );
+        assert_eq!(report.share_keep_rate(), 0.0);
+        assert_eq!(report.xvb_time_rate(), 0.0);
+
+        // Recorded rounds, what is missing is simulated.
+        let samples = Sample::read(
+            r#"{"hashrate": 12000, "difficulty": 150000000, "share": 1, "xvb_24h_avg": 2000}
+
+{"hashrate": 12000, "difficulty": 150000000}"#,
+        )
+        .unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].xvb_24h_avg, Some(2000.0));
+        assert_eq!(samples[1].share, None);
+        assert!(Sample::read("{").is_err());
     }
 
     #[test]
-    fn test_auto_mode() {
-        let client = client();
-        let pub_api = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xmrig = Arc::new(Mutex::new(PubXmrigApi::new()));
-        let gui_api_xp = Arc::new(Mutex::new(PubXmrigProxyApi::new()));
-        let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
-        let token_xmrig = "12345678";
-        let state_p2pool = P2pool::default();
-        let time_donated = Arc::new(Mutex::new(u32::default()));
-        let rig = "test_rig";
-        let xp_alive = false;
-        let share = 1;
-        let p2pool_buffer = 5;
-
-        gui_api_p2pool.lock().unwrap().p2pool_difficulty_u64 = 9_000_000;
-        gui_api_xmrig.lock().unwrap().hashrate_raw_15m = 20000.0;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::Auto;
-
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
-        );
-
-        assert_eq!(algo.stats.target_donation_hashrate, 10000.0);
+    fn control_requests() {
+        use crate::disk::state::XvbMode;
+        use crate::helper::control::{
+            parse_process_name, read_request_line, ControlRequest, ControlResponse, ProcessStatus,
+        };
 
-        gui_api_p2pool.lock().unwrap().p2pool_difficulty_u64 = 95_000_000;
-        gui_api_xmrig.lock().unwrap().hashrate_raw_15m = 10000.0;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::Auto;
+        // Lines are capped.
+        let mut line = String::new();
+        read_request_line(&mut "{\"command\":\"status\"}\n".as_bytes(), &mut line).unwrap();
+        assert_eq!(line, "{\"command\":\"status\"}\n");
+        let long = "a".repeat(1024 * 1024);
+        assert!(read_request_line(&mut long.as_bytes(), &mut String::new()).is_err());
+
+        // Names, as typed in the CLI.
+        assert_eq!(parse_process_name("p2pool"), Ok(ProcessName::P2pool));
+        assert_eq!(parse_process_name("XMRig"), Ok(ProcessName::Xmrig));
+        assert_eq!(
+            parse_process_name("xmrig-proxy"),
+            Ok(ProcessName::XmrigProxy)
+        );
+        assert_eq!(
+            parse_process_name("xmrig_proxy"),
+            Ok(ProcessName::XmrigProxy)
+        );
+        assert_eq!(parse_process_name("custom_2"), Ok(ProcessName::Custom(1)));
+        assert!(parse_process_name("custom_0").is_err());
+        assert!(parse_process_name("monerod").is_err());
 
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
+        // One JSON object per line.
+        let request = ControlRequest::XvbMode {
+            mode: XvbMode::ManualP2pool,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(json, r#"{"command":"xvb_mode","mode":"ManualP2pool"}"#);
+        assert_eq!(
+            serde_json::from_str::<ControlRequest>(&json).unwrap(),
+            request
+        );
+        let json = serde_json::to_string(&ControlRequest::Start {
+            process: "xvb".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"command":"start","process":"xvb"}"#);
+        assert_eq!(
+            serde_json::from_str::<ControlRequest>(r#"{"command":"status"}"#).unwrap(),
+            ControlRequest::Status
         );
 
-        assert_eq!(algo.stats.target_donation_hashrate, 1000.0);
+        // Processes are only sent with [Status].
+        let response = ControlResponse {
+            ok: true,
+            message: "Starting P2Pool".to_string(),
+            processes: vec![],
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(!json.contains("processes"));
+        let status = ControlResponse {
+            processes: vec![ProcessStatus::new(
+                ProcessName::XmrigProxy,
+                &Process::new(ProcessName::Xmrig, String::new(), std::path::PathBuf::new()),
+            )],
+            ..response
+        };
+        let json = serde_json::to_string(&status).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ControlResponse>(&json).unwrap(),
+            status
+        );
+        // Named after the slot, as the CLI and the HTTP API take it.
+        assert_eq!(status.processes[0].name, "xmrig_proxy");
+        assert_eq!(status.processes[0].display_name, "XMRig-Proxy");
+        assert_eq!(
+            parse_process_name(&status.processes[0].name),
+            Ok(ProcessName::XmrigProxy)
+        );
+        assert_eq!(ProcessName::Custom(0).id(), "custom_1");
+        assert_eq!(
+            parse_process_name(&ProcessName::Custom(0).id()),
+            Ok(ProcessName::Custom(0))
+        );
     }
 
     #[test]
-    fn test_hero_mode() {
-        let client = client();
-        let pub_api = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xvb = Arc::new(Mutex::new(PubXvbApi::new()));
-        let gui_api_xmrig = Arc::new(Mutex::new(PubXmrigApi::new()));
-        let gui_api_xp = Arc::new(Mutex::new(PubXmrigProxyApi::new()));
-        let gui_api_p2pool = Arc::new(Mutex::new(PubP2poolApi::new()));
-        let token_xmrig = "12345678";
-        let state_p2pool = P2pool::default();
-        let time_donated = Arc::new(Mutex::new(u32::default()));
-        let rig = "test_rig";
-        let xp_alive = false;
-        let share = 1;
-        let p2pool_buffer = 5;
+    fn xmrig_headless_sudo() {
+        use crate::utils::sudo::SudoState;
+        let path = std::path::Path::new("/nonexistent/xmrig");
+        // Root runs XMRig directly, [sudo] is not even asked.
+        let probe = |_: &std::path::Path| -> bool { panic!("sudo probed as root") };
+        assert!(SudoState::check_headless_with(path, false, probe).is_ok());
+        // Otherwise only if [sudo] runs it without a password.
+        assert!(SudoState::check_headless_with(path, true, |probed| probed == path).is_ok());
+        let e = SudoState::check_headless_with(path, true, |_| false).unwrap_err();
+        assert!(e.contains("NOPASSWD"));
+        assert!(e.contains("/nonexistent/xmrig"));
+    }
 
-        gui_api_p2pool.lock().unwrap().p2pool_difficulty_u64 = 95_000_000;
-        gui_api_xmrig.lock().unwrap().hashrate_raw_15m = 20000.0;
-        gui_api_xvb.lock().unwrap().stats_priv.runtime_mode = RuntimeMode::Hero;
+    #[test]
+    fn respawn_delay() {
+        use crate::disk::state::{RestartMode, RestartPolicy};
+        use crate::helper::console::ConsoleBuffer;
+        use std::path::PathBuf;
+        use std::time::Duration;
+        let policy = |mode, max_retries, backoff_secs| RestartPolicy {
+            mode,
+            max_retries,
+            backoff_secs,
+        };
+        let mut output = ConsoleBuffer::new();
+        let mut process = Process::new(ProcessName::P2pool, String::new(), PathBuf::new());
+        // What [check_died()] leaves behind.
+        let mut exit = |process: &mut Process, code: u32, policy: &RestartPolicy| {
+            process.state = if code == 0 {
+                ProcessState::Dead
+            } else {
+                ProcessState::Failed
+            };
+            process.exit_code = Some(code);
+            process.respawning = false;
+            let delay = process.respawn_delay(policy, &mut output);
+            delay.map(|delay| delay.as_secs())
+        };
 
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
+        // Never, Always and OnFailure.
+        let never = policy(RestartMode::Never, 5, 10);
+        assert_eq!(exit(&mut process, 1, &never), None);
+        let on_failure = policy(RestartMode::OnFailure, 5, 10);
+        assert_eq!(exit(&mut process, 0, &on_failure), None);
+        assert_eq!(exit(&mut process, 1, &on_failure), Some(10));
+        assert!(process.respawning);
+        assert_eq!(process.restarts, 1);
+        let always = policy(RestartMode::Always, 5, 10);
+        assert_eq!(exit(&mut process, 0, &always), Some(20));
+        assert_eq!(process.restarts, 2);
+
+        // Stopped by the user, not restarted and the count is kept.
+        process.exit_code = None;
+        process.respawning = false;
+        assert_eq!(
+            process.respawn_delay(&always, &mut ConsoleBuffer::new()),
+            None
         );
+        assert!(!process.respawning);
+        assert_eq!(process.restarts, 2);
 
-        assert_eq!(algo.stats.target_donation_hashrate, 15382.1);
+        // The backoff doubles on each restart, up to 600 seconds.
+        process.restarts = 0;
+        let backoff = policy(RestartMode::Always, 10, 100);
+        let delays: Vec<_> = (0..5)
+            .map(|_| exit(&mut process, 1, &backoff).unwrap())
+            .collect();
+        assert_eq!(delays, [100, 200, 400, 600, 600]);
 
-        gui_api_p2pool.lock().unwrap().sidechain_ehr = 25000.0;
+        // [max_retries] restarts, then it gives up and keeps the count.
+        process.restarts = 0;
+        let retries = policy(RestartMode::Always, 2, 1);
+        assert_eq!(exit(&mut process, 1, &retries), Some(1));
+        assert_eq!(exit(&mut process, 1, &retries), Some(2));
+        assert_eq!(exit(&mut process, 1, &retries), None);
+        assert!(!process.respawning);
+        assert_eq!(process.restarts, 2);
+
+        // Alive long enough before dying, it counts from zero again.
+        process.start -= Duration::from_secs(crate::helper::RESTART_STABLE_SECS);
+        assert_eq!(exit(&mut process, 1, &retries), Some(1));
+        assert_eq!(process.restarts, 1);
+        assert!(output
+            .to_string()
+            .contains("gave up restarting after [2] attempts"));
+    }
+
+    #[test]
+    fn wait_respawn() {
+        use crate::helper::ProcessSignal;
+        use crate::macros::arc_mut;
+        use std::path::PathBuf;
+        use std::time::{Duration, Instant};
+        let process = arc_mut!(Process::new(
+            ProcessName::P2pool,
+            String::new(),
+            PathBuf::new()
+        ));
+        let delay = Duration::from_millis(100);
+
+        // Nothing happened during the backoff.
+        process.lock().unwrap().respawning = true;
+        assert!(Process::wait_respawn(&process, delay));
+
+        // Stopped by the user during the backoff, it is woken up right away.
+        let long = Duration::from_secs(30);
+        let now = Instant::now();
+        let stop = std::thread::spawn({
+            let process = std::sync::Arc::clone(&process);
+            move || {
+                std::thread::sleep(delay);
+                crate::helper::Helper::stop_supervised(&process);
+            }
+        });
+        assert!(!Process::wait_respawn(&process, long));
+        stop.join().unwrap();
+        assert!(now.elapsed() < long);
+        {
+            let process = process.lock().unwrap();
+            assert!(!process.respawning);
+            assert_eq!(process.signal, ProcessSignal::None);
+            assert_eq!(process.state, ProcessState::Dead);
+        }
+
+        // Started by the user during the backoff.
+        process.lock().unwrap().respawning = true;
+        let start = std::thread::spawn({
+            let process = std::sync::Arc::clone(&process);
+            move || {
+                std::thread::sleep(delay);
+                let mut process = process.lock().unwrap();
+                process.respawning = false;
+                process.set_state(ProcessState::Middle);
+            }
+        });
+        assert!(!Process::wait_respawn(&process, long));
+        start.join().unwrap();
+        assert!(now.elapsed() < long);
+    }
+
+    #[test]
+    fn wait_dependencies() {
+        use crate::disk::state::DependencyPolicy;
+        use crate::helper::{Helper, ProcessSignal};
+        use crate::macros::arc_mut;
+        use std::path::PathBuf;
+        use std::sync::{Arc, Mutex};
+        use std::time::{Duration, Instant};
+        let new = |name| arc_mut!(Process::new(name, String::new(), PathBuf::new()));
+        let forever = DependencyPolicy {
+            wait: true,
+            timeout_secs: 0,
+        };
+        let node = new(ProcessName::Node);
+        let p2pool = new(ProcessName::P2pool);
+        let dependencies = [Arc::clone(&node)];
+        // Runs [action] on [p2pool] once it is [Blocked].
+        let when_blocked = |action: fn(&Arc<Mutex<Process>>)| {
+            let p2pool = Arc::clone(&p2pool);
+            std::thread::spawn(move || {
+                while p2pool.lock().unwrap().state != ProcessState::Blocked {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                action(&p2pool);
+            })
+        };
+
+        // A dependency that is not started is not waited for.
+        assert!(Helper::wait_ready(
+            ProcessName::P2pool,
+            forever,
+            &dependencies,
+            &p2pool
+        ));
+        assert_eq!(p2pool.lock().unwrap().state, ProcessState::Dead);
+
+        // Blocked until the dependency is ready, woken up by its new state.
+        node.lock().unwrap().state = ProcessState::Syncing;
+        let ready = std::thread::spawn({
+            let node = Arc::clone(&node);
+            let p2pool = Arc::clone(&p2pool);
+            move || {
+                while p2pool.lock().unwrap().state != ProcessState::Blocked {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                node.lock().unwrap().set_state(ProcessState::Alive);
+            }
+        });
+        assert!(Helper::wait_ready(
+            ProcessName::P2pool,
+            forever,
+            &dependencies,
+            &p2pool
+        ));
+        ready.join().unwrap();
+
+        // Started anyway once the timeout is reached.
+        node.lock().unwrap().state = ProcessState::Syncing;
+        p2pool.lock().unwrap().state = ProcessState::Middle;
+        let now = Instant::now();
+        let timeout = DependencyPolicy {
+            wait: true,
+            timeout_secs: 1,
+        };
+        assert!(Helper::wait_ready(
+            ProcessName::P2pool,
+            timeout,
+            &dependencies,
+            &p2pool
+        ));
+        assert!(now.elapsed() >= Duration::from_secs(1));
+        assert_eq!(p2pool.lock().unwrap().state, ProcessState::Blocked);
+
+        // [Start] while blocked starts it without waiting.
+        let start = when_blocked(Helper::skip_dependencies);
+        assert!(Helper::wait_ready(
+            ProcessName::P2pool,
+            forever,
+            &dependencies,
+            &p2pool
+        ));
+        start.join().unwrap();
+        assert_eq!(p2pool.lock().unwrap().signal, ProcessSignal::None);
 
-        let algo = Algorithm::new(
-            &client,
-            &pub_api,
-            &gui_api_xvb,
-            &gui_api_xmrig,
-            &gui_api_xp,
-            &gui_api_p2pool,
-            token_xmrig,
-            &state_p2pool,
-            share,
-            &time_donated,
-            rig,
-            xp_alive,
-            p2pool_buffer,
-        );
-
-        assert_eq!(algo.stats.target_donation_hashrate, 20000.0);
+        // [Stop] while blocked, it is not started.
+        let stop = when_blocked(Helper::stop_supervised);
+        assert!(!Helper::wait_ready(
+            ProcessName::P2pool,
+            forever,
+            &dependencies,
+            &p2pool
+        ));
+        stop.join().unwrap();
+        assert_eq!(p2pool.lock().unwrap().state, ProcessState::Dead);
+        assert_eq!(p2pool.lock().unwrap().signal, ProcessSignal::None);
+
+        // [Restart] while blocked, left [Waiting] for [restart_supervised()].
+        p2pool.lock().unwrap().state = ProcessState::Middle;
+        let restart = when_blocked(|p2pool| {
+            let mut p2pool = p2pool.lock().unwrap();
+            p2pool.set_state(ProcessState::Middle);
+            p2pool.send_signal(ProcessSignal::Restart);
+        });
+        assert!(!Helper::wait_ready(
+            ProcessName::P2pool,
+            forever,
+            &dependencies,
+            &p2pool
+        ));
+        restart.join().unwrap();
+        assert_eq!(p2pool.lock().unwrap().state, ProcessState::Waiting);
+    }
+
+    // A [Helper] with dead processes and empty APIs.
+    fn new_helper() -> crate::helper::Helper {
+        use crate::disk::{gupax_p2pool_api::GupaxP2poolApi, history::History};
+        use crate::helper::{
+            node::PubNodeApi, p2pool::ImgP2pool, xrig::xmrig::ImgXmrig, xrig::xmrig::PubXmrigApi,
+            xvb::PubXvbApi, Helper, PubP2poolApi, Sys,
+        };
+        use crate::macros::arc_mut;
+        use std::path::PathBuf;
+        let process = |name| arc_mut!(Process::new(name, String::new(), PathBuf::new()));
+        Helper::new(
+            std::time::Instant::now(),
+            arc_mut!(Sys::new()),
+            process(ProcessName::P2pool),
+            process(ProcessName::Xmrig),
+            process(ProcessName::XmrigProxy),
+            process(ProcessName::Xvb),
+            process(ProcessName::Node),
+            arc_mut!(PubP2poolApi::new()),
+            arc_mut!(PubXmrigApi::new()),
+            arc_mut!(PubXvbApi::new()),
+            arc_mut!(PubXmrigProxyApi::new()),
+            arc_mut!(PubNodeApi::new()),
+            arc_mut!(ImgP2pool::new()),
+            arc_mut!(ImgXmrig::new()),
+            arc_mut!(GupaxP2poolApi::new()),
+            arc_mut!(History::new()),
+        )
+    }
+
+    #[test]
+    fn stats_snapshot() {
+        use crate::helper::snapshot::StatsSnapshot;
+        let helper = new_helper();
+        let (p2pool, gui_api_p2pool) = (&helper.p2pool, &helper.gui_api_p2pool);
+        let first = StatsSnapshot::from_helper(&helper);
+        assert!(first == StatsSnapshot::from_helper(&helper));
+
+        // The console stays with the GUI, only its new lines count as a change.
+        gui_api_p2pool.lock().unwrap().output.push_str("line\n");
+        let second = StatsSnapshot::from_helper(&helper);
+        assert!(first != second);
+        assert!(second.p2pool.output.is_empty());
+        assert_eq!(gui_api_p2pool.lock().unwrap().output.len(), 1);
+
+        // The uptime of an alive process is read from its start, by the readers.
+        {
+            let mut p2pool = p2pool.lock().unwrap();
+            p2pool.state = ProcessState::Alive;
+            p2pool.start -= std::time::Duration::from_millis(2500);
+        }
+        let third = StatsSnapshot::from_helper(&helper);
+        assert_eq!(third.with_uptimes().p2pool.uptime.to_string(), "2 seconds");
+        assert!(third.uptime(ProcessName::Xmrig).is_none());
+
+        // The watchdog updating the uptime is not a change.
+        gui_api_p2pool.lock().unwrap().uptime =
+            crate::human::HumanTime::into_human(std::time::Duration::from_secs(3));
+        assert!(third == StatsSnapshot::from_helper(&helper));
+    }
+
+    #[test]
+    fn history_sample_proxy() {
+        use crate::helper::snapshot::StatsSnapshot;
+        let helper = new_helper();
+        assert!(StatsSnapshot::from_helper(&helper)
+            .history_sample()
+            .is_none());
+        helper.gui_api_xmrig.lock().unwrap().hashrate_raw = 1000.0;
+        helper.gui_api_xp.lock().unwrap().hashrate_1m = 5000.0;
+        helper.xmrig.lock().unwrap().state = ProcessState::Alive;
+        let sample = StatsSnapshot::from_helper(&helper)
+            .history_sample()
+            .unwrap();
+        assert_eq!(sample.xmrig_hashrate, 1000.0);
+
+        // XMRig-Proxy receives the hashrate of all the miners, XMRig included.
+        helper.xmrig_proxy.lock().unwrap().state = ProcessState::Alive;
+        let snapshot = StatsSnapshot::from_helper(&helper);
+        assert!(snapshot.alive(ProcessName::XmrigProxy));
+        assert_eq!(
+            snapshot
+                .processes
+                .iter()
+                .filter(|(name, _, _)| *name == ProcessName::Xmrig)
+                .count(),
+            1
+        );
+        assert_eq!(snapshot.history_sample().unwrap().xmrig_hashrate, 5000.0);
+    }
+
+    #[test]
+    fn restart_changed() {
+        use crate::disk::state::State;
+        use crate::helper::Helper;
+        use crate::macros::arc_mut;
+        use crate::utils::sudo::SudoState;
+        let old = State::new();
+        let mut new = old.clone();
+        new.xmrig.simple_rig = "rig".to_string();
+        assert_eq!(
+            Helper::changed_processes(&old, &new),
+            [
+                ProcessName::Xmrig,
+                ProcessName::XmrigProxy,
+                ProcessName::Xvb
+            ]
+        );
+        assert!(Helper::changed_processes(&old, &old).is_empty());
+
+        // Dead processes have nothing to restart, the others say why they were not.
+        let helper = arc_mut!(new_helper());
+        let sudo = arc_mut!(SudoState::new());
+        assert!(Helper::restart_changed(&helper, &old, &new, &sudo, || None, "Test").is_empty());
+        helper.lock().unwrap().xvb.lock().unwrap().state = ProcessState::Middle;
+        let failed = Helper::restart_changed(&helper, &old, &new, &sudo, || None, "Test");
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("in the middle"));
+    }
+
+    #[test]
+    fn process_statuses() {
+        use crate::helper::control::parse_process_name;
+        let statuses = new_helper().process_statuses();
+        let names = statuses.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["p2pool", "xmrig", "xmrig_proxy", "xvb", "node"]);
+        // What [/processes] lists is what the routes take.
+        for status in &statuses {
+            let name = parse_process_name(&status.name).unwrap();
+            assert_eq!(name.id(), status.name);
+            assert_eq!(name.to_string(), status.display_name);
+        }
+    }
+
+    #[test]
+    fn xmrig_config_writes() {
+        use crate::helper::xrig::{update_xmrig_pool, update_xmrig_threads};
+        use crate::helper::xvb::nodes::Pool;
+        use axum::{extract::State, routing::get, Json, Router};
+        use serde_json::{json, Value};
+        use std::sync::{Arc, Mutex};
+        type Config = State<Arc<Mutex<Value>>>;
+        #[tokio::main(flavor = "current_thread")]
+        async fn run() -> Value {
+            let config = Arc::new(Mutex::new(json!({
+                "cpu": {"enabled": true},
+                "pools": [{"url": "p2pool", "user": "", "rig-id": "", "tls": false, "keepalive": false}],
+            })));
+            let get_config = |State(config): Config| async move {
+                let config = config.lock().unwrap().clone();
+                // A slow XMRig, the other writer would read the same config meanwhile.
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                Json(config)
+            };
+            let put_config = |State(config): Config, Json(new): Json<Value>| async move {
+                *config.lock().unwrap() = new;
+            };
+            let router = Router::new()
+                .route("/config", get(get_config).put(put_config))
+                .with_state(Arc::clone(&config));
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let uri = format!("http://{}/config", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, router).await });
+            let client = client();
+            let pool = Pool {
+                url: "xvb".to_string(),
+                ..Default::default()
+            };
+            let (threads, pool) = tokio::join!(
+                update_xmrig_threads(&client, &uri, "token", 0),
+                update_xmrig_pool(&client, &uri, "token", &pool, "rig"),
+            );
+            threads.unwrap();
+            pool.unwrap();
+            let config = config.lock().unwrap().clone();
+            config
+        }
+        // Neither change puts back what the other did.
+        let config = run();
+        assert_eq!(config["cpu"]["enabled"], false);
+        assert_eq!(config["pools"][0]["url"], "xvb");
+        assert_eq!(config["pools"][0]["rig-id"], "rig");
     }
 }
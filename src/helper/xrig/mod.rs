@@ -1,3 +1,4 @@
+use crate::helper::xvb::nodes::Pool;
 use crate::helper::XvbNode;
 use anyhow::anyhow;
 use anyhow::Result;
@@ -8,47 +9,31 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
 
+pub mod schedule;
+pub mod throttle;
 pub mod xmrig;
 pub mod xmrig_proxy;
 
-// update config of xmrig or xmrig-proxy
-pub async fn update_xmrig_config(
+// Every change to the config of xmrig or xmrig-proxy is a get-modify-put of the whole
+// config, from the XvB thread, the schedule/throttle task of XMRig and the watchdogs.
+// They are done one at a time, so one does not put back what another just changed.
+static CONFIG_WRITE: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// Reads the config at [api_uri], lets [modify] change it and puts it back,
+// the config being read again right before each change.
+async fn modify_xmrig_config(
     client: &Client,
     api_uri: &str,
     token: &str,
-    node: &XvbNode,
-    address: &str,
-    rig: &str,
+    modify: impl FnOnce(&mut Value) -> Result<()>,
 ) -> Result<()> {
+    let _write = CONFIG_WRITE.lock().await;
     // get config
     let request = client
         .get(api_uri)
         .header(AUTHORIZATION, ["Bearer ", token].concat());
     let mut config = request.send().await?.json::<Value>().await?;
-    // modify node configuration
-    let uri = [node.url(), ":".to_string(), node.port()].concat();
-    info!(
-        "replace xmrig from api url {api_uri} config with node {}",
-        uri
-    );
-    *config
-        .pointer_mut("/pools/0/url")
-        .ok_or_else(|| anyhow!("pools/0/url does not exist in xmrig config"))? = uri.into();
-    *config
-        .pointer_mut("/pools/0/user")
-        .ok_or_else(|| anyhow!("pools/0/user does not exist in xmrig config"))? = node
-        .user(&address.chars().take(8).collect::<String>())
-        .into();
-    *config
-        .pointer_mut("/pools/0/rig-id")
-        .ok_or_else(|| anyhow!("pools/0/rig-id does not exist in xmrig config"))? = rig.into();
-    *config
-        .pointer_mut("/pools/0/tls")
-        .ok_or_else(|| anyhow!("pools/0/tls does not exist in xmrig config"))? = node.tls().into();
-    *config
-        .pointer_mut("/pools/0/keepalive")
-        .ok_or_else(|| anyhow!("pools/0/keepalive does not exist in xmrig config"))? =
-        node.keepalive().into();
+    modify(&mut config)?;
     // send new config
     client
         .put(api_uri)
@@ -57,9 +42,86 @@ pub async fn update_xmrig_config(
         .timeout(std::time::Duration::from_secs(5))
         .body(config.to_string())
         .send()
-        .await?;
+        .await?
+        .error_for_status()?;
     anyhow::Ok(())
 }
+
+// update config of xmrig or xmrig-proxy
+pub async fn update_xmrig_config(
+    client: &Client,
+    api_uri: &str,
+    token: &str,
+    node: &XvbNode,
+    address: &str,
+    rig: &str,
+) -> Result<()> {
+    update_xmrig_pool(client, api_uri, token, &node.pool(address), rig).await
+}
+// Same as [update_xmrig_config] for any pool, not only the nodes known to the algorithm.
+pub async fn update_xmrig_pool(
+    client: &Client,
+    api_uri: &str,
+    token: &str,
+    pool: &Pool,
+    rig: &str,
+) -> Result<()> {
+    modify_xmrig_config(client, api_uri, token, |config| {
+        // modify node configuration
+        info!(
+            "replace xmrig from api url {api_uri} config with node {}",
+            pool.url
+        );
+        *config
+            .pointer_mut("/pools/0/url")
+            .ok_or_else(|| anyhow!("pools/0/url does not exist in xmrig config"))? =
+            pool.url.clone().into();
+        *config
+            .pointer_mut("/pools/0/user")
+            .ok_or_else(|| anyhow!("pools/0/user does not exist in xmrig config"))? =
+            pool.user.clone().into();
+        *config
+            .pointer_mut("/pools/0/rig-id")
+            .ok_or_else(|| anyhow!("pools/0/rig-id does not exist in xmrig config"))? = rig.into();
+        *config
+            .pointer_mut("/pools/0/tls")
+            .ok_or_else(|| anyhow!("pools/0/tls does not exist in xmrig config"))? =
+            pool.tls.into();
+        *config
+            .pointer_mut("/pools/0/keepalive")
+            .ok_or_else(|| anyhow!("pools/0/keepalive does not exist in xmrig config"))? =
+            pool.keepalive.into();
+        anyhow::Ok(())
+    })
+    .await
+}
+// Hot-applies a thread count to the CPU backend of xmrig, 0 pauses it.
+// [--threads] is turned by xmrig into the [*] profile, used here for every algorithm.
+pub async fn update_xmrig_threads(
+    client: &Client,
+    api_uri: &str,
+    token: &str,
+    threads: usize,
+) -> Result<()> {
+    modify_xmrig_config(client, api_uri, token, |config| {
+        // modify cpu configuration
+        let cpu = config
+            .pointer_mut("/cpu")
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow!("cpu does not exist in xmrig config"))?;
+        cpu.insert("enabled".to_string(), (threads != 0).into());
+        if threads != 0 {
+            cpu.insert(
+                "*".to_string(),
+                serde_json::json!({"intensity": 1, "threads": threads, "affinity": -1}),
+            );
+            cpu.insert("rx".to_string(), "*".into());
+        }
+        anyhow::Ok(())
+    })
+    .await
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 struct Hashrate {
     total: [Option<f32>; 3],
@@ -0,0 +1,85 @@
+// XMRig mining windows.
+//
+// [Xmrig.schedule] lists weekly time ranges during which XMRig mines with
+// another thread count or is paused. Every tick, the XMRig watchdog looks for
+// the first window containing the current local time, and hot-applies its
+// target through the config API if it changed, so XMRig is never restarted.
+// Outside of every window, XMRig mines with [Xmrig.current_threads].
+
+use crate::disk::state::{MiningWindow, WindowAction, Xmrig};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+//---------------------------------------------------------------------------------------------------- ScheduleTarget
+// What XMRig should be doing, [threads] being 0 when paused.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct ScheduleTarget {
+    pub threads: usize,
+}
+
+impl ScheduleTarget {
+    pub fn paused(&self) -> bool {
+        self.threads == 0
+    }
+}
+
+impl std::fmt::Display for ScheduleTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.paused() {
+            write!(f, "paused")
+        } else {
+            write!(f, "mining with {} threads", self.threads)
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Schedule
+// Parses [HH:MM] into minutes since midnight.
+pub fn parse_time(time: &str) -> Option<u32> {
+    let (hour, minute) = time.split_once(':')?;
+    if hour.len() != 2 || minute.len() != 2 {
+        return None;
+    }
+    let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+impl MiningWindow {
+    // [weekday] is 0 for Monday. A window with invalid times is never active.
+    pub fn contains(&self, weekday: usize, minute: u32) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let yesterday = (weekday + 6) % 7;
+        match start.cmp(&end) {
+            std::cmp::Ordering::Less => self.days[weekday] && start <= minute && minute < end,
+            // Ends the next day, the days are the ones it starts on.
+            std::cmp::Ordering::Greater => {
+                (self.days[weekday] && minute >= start) || (self.days[yesterday] && minute < end)
+            }
+            // The whole day.
+            std::cmp::Ordering::Equal => self.days[weekday],
+        }
+    }
+}
+
+impl Xmrig {
+    // The first window containing [now], if any.
+    pub fn active_window(&self, now: NaiveDateTime) -> Option<&MiningWindow> {
+        let weekday = now.weekday().num_days_from_monday() as usize;
+        let minute = now.hour() * 60 + now.minute();
+        self.schedule
+            .iter()
+            .find(|window| window.contains(weekday, minute))
+    }
+
+    pub fn scheduled_target(&self, now: NaiveDateTime) -> ScheduleTarget {
+        let threads = match self.active_window(now) {
+            Some(window) => match window.action {
+                WindowAction::Mine => window.threads.clamp(1, self.max_threads.max(1)),
+                WindowAction::Pause => 0,
+            },
+            None => self.current_threads,
+        };
+        ScheduleTarget { threads }
+    }
+}
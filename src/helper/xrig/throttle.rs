@@ -0,0 +1,90 @@
+// XMRig throttling.
+//
+// [Xmrig.throttle] lowers the threads of XMRig, or pauses it, while the CPU
+// temperature or the load of everything but XMRig is over a threshold. The
+// helper thread samples both into [Sys] every second and the XMRig watchdog
+// feeds them to [Throttle], hot-applying the result with the mining windows.
+//
+// A threshold must be crossed for [XMRIG_THROTTLE_DELAY] to throttle, and the
+// values must stay [hysteresis] under it for [XMRIG_RESTORE_DELAY] to restore,
+// so a short spike (compiling, opening a browser) does not make XMRig flap.
+
+use crate::constants::{XMRIG_RESTORE_DELAY, XMRIG_THROTTLE_DELAY};
+use crate::disk::state::{ThrottlePolicy, WindowAction};
+use crate::helper::xrig::schedule::ScheduleTarget;
+use std::time::Instant;
+
+//---------------------------------------------------------------------------------------------------- Throttle
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Throttle {
+    throttled: bool,
+    // Since when the values ask for the other state.
+    since: Option<Instant>,
+}
+
+impl Throttle {
+    pub fn throttled(&self) -> bool {
+        self.throttled
+    }
+
+    // Updates with the last sample, returns [true] if it just switched.
+    pub fn update(
+        &mut self,
+        policy: &ThrottlePolicy,
+        temperature: Option<f32>,
+        load: f32,
+        now: Instant,
+    ) -> bool {
+        let wants_switch = if self.throttled {
+            policy.under(temperature, load)
+        } else {
+            policy.over(temperature, load)
+        };
+        if !wants_switch {
+            self.since = None;
+            return false;
+        }
+        let delay = if self.throttled {
+            XMRIG_RESTORE_DELAY
+        } else {
+            XMRIG_THROTTLE_DELAY
+        };
+        let since = *self.since.get_or_insert(now);
+        if now.duration_since(since) < delay {
+            return false;
+        }
+        self.throttled = !self.throttled;
+        self.since = None;
+        true
+    }
+}
+
+impl ThrottlePolicy {
+    // A threshold is crossed.
+    pub fn over(&self, temperature: Option<f32>, load: f32) -> bool {
+        let hot = self.max_temperature != 0
+            && temperature.is_some_and(|temperature| temperature > self.max_temperature as f32);
+        let busy = self.max_load != 0 && load > self.max_load as f32;
+        hot || busy
+    }
+
+    // Every value is far enough under its threshold.
+    pub fn under(&self, temperature: Option<f32>, load: f32) -> bool {
+        let cool = self.max_temperature == 0
+            || temperature.map_or(true, |temperature| {
+                temperature <= self.max_temperature.saturating_sub(self.hysteresis) as f32
+            });
+        let idle =
+            self.max_load == 0 || load <= self.max_load.saturating_sub(self.hysteresis) as f32;
+        cool && idle
+    }
+
+    // What XMRig does while throttled, never more than [target].
+    pub fn limit(&self, target: ScheduleTarget) -> ScheduleTarget {
+        let threads = match self.action {
+            WindowAction::Mine => target.threads.min(self.threads.max(1)),
+            WindowAction::Pause => 0,
+        };
+        ScheduleTarget { threads }
+    }
+}
@@ -1,15 +1,21 @@
 use crate::constants::*;
-use crate::helper::xrig::update_xmrig_config;
-use crate::helper::{arc_mut, check_died, check_user_input, sleep, sleep_end_loop, Process};
+use crate::disk::state::RestartPolicy;
+use crate::helper::console::ConsoleBuffer;
+use crate::helper::supervised::{
+    is_localhost, Handles, PtyLines, PubApi, SupervisedProcess, Watchdog,
+};
+use crate::helper::xrig::schedule::ScheduleTarget;
+use crate::helper::xrig::throttle::Throttle;
+use crate::helper::xrig::{update_xmrig_config, update_xmrig_threads};
+use crate::helper::{sleep, Process};
 use crate::helper::{Helper, ProcessName, ProcessSignal, ProcessState};
-use crate::helper::{PubXvbApi, XvbNode};
+use crate::helper::{PubXvbApi, Sys, XvbNode};
 use crate::miscs::{client, output_console};
 use crate::regex::{contains_error, contains_usepool, detect_new_node_xmrig, XMRIG_REGEX};
 use crate::utils::human::HumanNumber;
 use crate::utils::sudo::SudoState;
-use enclose::enclose;
 use log::*;
-use portable_pty::Child;
+use portable_pty::{Child, CommandBuilder};
 use readable::num::Unsigned;
 use readable::up::Uptime;
 use reqwest::header::AUTHORIZATION;
@@ -18,90 +24,14 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::{
     fmt::Write,
-    path::PathBuf,
     process::Stdio,
     sync::{Arc, Mutex},
-    thread,
     time::*,
 };
-use tokio::spawn;
 
 use super::Hashrate;
 
 impl Helper {
-    #[cold]
-    #[inline(never)]
-    pub async fn read_pty_xmrig(
-        output_parse: Arc<Mutex<String>>,
-        output_pub: Arc<Mutex<String>>,
-        reader: Box<dyn std::io::Read + Send>,
-        process_xvb: Arc<Mutex<Process>>,
-        process_xp: Arc<Mutex<Process>>,
-        pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
-    ) {
-        use std::io::BufRead;
-        let mut stdout = std::io::BufReader::new(reader).lines();
-
-        // Run a ANSI escape sequence filter for the first few lines.
-        let mut i = 0;
-        while let Some(Ok(line)) = stdout.next() {
-            let line = strip_ansi_escapes::strip_str(line);
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("XMRig PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("XMRig PTY Pub | Output error: {}", e);
-            }
-            if i > 13 {
-                break;
-            } else {
-                i += 1;
-            }
-        }
-
-        while let Some(Ok(line)) = stdout.next() {
-            // need to verify if node still working
-            // for that need to catch "connect error"
-            // only check if xvb process is used and xmrig-proxy is not.
-            if process_xvb.lock().unwrap().is_alive() && !process_xp.lock().unwrap().is_alive() {
-                if contains_error(&line) {
-                    let current_node = pub_api_xvb.lock().unwrap().current_node;
-                    if let Some(current_node) = current_node {
-                        // updating current node to None, will stop sending signal of FailedNode until new node is set
-                        // send signal to update node.
-                        warn!("XMRig PTY Parse | node is offline, sending signal to update nodes.");
-                        // update nodes only if we were not mining on p2pool.
-                        // if xmrig stop, xvb will react in any case.
-                        if current_node != XvbNode::P2pool {
-                            process_xvb.lock().unwrap().signal =
-                                ProcessSignal::UpdateNodes(current_node);
-                        }
-                        pub_api_xvb.lock().unwrap().current_node = None;
-                    }
-                }
-                if contains_usepool(&line) {
-                    info!("XMRig PTY Parse | new pool detected");
-                    // need to update current node because it was updated.
-                    // if custom node made by user, it is not supported because algo is deciding which node to use.
-                    let node = detect_new_node_xmrig(&line);
-                    if node.is_none() {
-                        error!("XMRig PTY Parse | node is not understood, switching to backup.");
-                        // update with default will choose which XvB to prefer. Will update XvB to use p2pool.
-                        process_xvb.lock().unwrap().signal =
-                            ProcessSignal::UpdateNodes(XvbNode::default());
-                    }
-                    pub_api_xvb.lock().unwrap().current_node = node;
-                }
-            }
-            //			println!("{}", line); // For debugging.
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("XMRig PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("XMRig PTY Pub | Output error: {}", e);
-            }
-        }
-    }
     //---------------------------------------------------------------------------------------------------- XMRig specific, most functions are very similar to P2Pool's
     #[cold]
     #[inline(never)]
@@ -131,9 +61,7 @@ impl Helper {
     #[inline(never)]
     // Just sets some signals for the watchdog thread to pick up on.
     pub fn stop_xmrig(helper: &Arc<Mutex<Self>>) {
-        info!("XMRig | Attempting to stop...");
-        helper.lock().unwrap().xmrig.lock().unwrap().signal = ProcessSignal::Stop;
-        helper.lock().unwrap().xmrig.lock().unwrap().state = ProcessState::Middle;
+        Self::stop_supervised(&helper.lock().unwrap().xmrig);
         let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_xmrig);
         let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_xmrig);
         *pub_api.lock().unwrap() = PubXmrigApi::new();
@@ -143,31 +71,14 @@ impl Helper {
     #[cold]
     #[inline(never)]
     // The "restart frontend" to a "frontend" function.
-    // Basically calls to kill the current xmrig, waits a little, then starts the below function in a a new thread, then exit.
     pub fn restart_xmrig(
         helper: &Arc<Mutex<Self>>,
         state: &crate::disk::state::Xmrig,
         path: &Path,
         sudo: Arc<Mutex<SudoState>>,
     ) {
-        info!("XMRig | Attempting to restart...");
-        helper.lock().unwrap().xmrig.lock().unwrap().signal = ProcessSignal::Restart;
-        helper.lock().unwrap().xmrig.lock().unwrap().state = ProcessState::Middle;
-
-        let helper = Arc::clone(helper);
-        let state = state.clone();
-        let path = path.to_path_buf();
-        // This thread lives to wait, start xmrig then die.
-        thread::spawn(move || {
-            while helper.lock().unwrap().xmrig.lock().unwrap().state != ProcessState::Waiting {
-                warn!("XMRig | Want to restart but process is still alive, waiting...");
-                sleep!(1000);
-            }
-            // Ok, process is not alive, start the new one!
-            info!("XMRig | Old process seems dead, starting new one!");
-            Self::start_xmrig(&helper, &state, &path, sudo);
-        });
-        info!("XMRig | Restart ... OK");
+        let xmrig = XmrigProcess::new(&helper.lock().unwrap(), state, sudo);
+        Self::restart_supervised(helper, xmrig, path);
     }
 
     #[cold]
@@ -178,39 +89,8 @@ impl Helper {
         path: &Path,
         sudo: Arc<Mutex<SudoState>>,
     ) {
-        helper.lock().unwrap().xmrig.lock().unwrap().state = ProcessState::Middle;
-
-        let (args, api_ip_port) = Self::build_xmrig_args_and_mutate_img(helper, state, path);
-        // Print arguments & user settings to console
-        crate::disk::print_dash(&format!("XMRig | Launch arguments: {:#?}", args));
-        info!("XMRig | Using path: [{}]", path.display());
-
-        // Spawn watchdog thread
-        let process = Arc::clone(&helper.lock().unwrap().xmrig);
-        let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_xmrig);
-        let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_xmrig);
-        let process_xvb = Arc::clone(&helper.lock().unwrap().xvb);
-        let process_xp = Arc::clone(&helper.lock().unwrap().xmrig_proxy);
-        let process_p2pool = Arc::clone(&helper.lock().unwrap().p2pool);
-        let path = path.to_path_buf();
-        let token = state.token.clone();
-        let pub_api_xvb = Arc::clone(&helper.lock().unwrap().pub_api_xvb);
-        thread::spawn(move || {
-            Self::spawn_xmrig_watchdog(
-                process,
-                gui_api,
-                pub_api,
-                args,
-                path,
-                sudo,
-                api_ip_port,
-                &token,
-                process_xvb,
-                process_xp,
-                process_p2pool,
-                &pub_api_xvb,
-            );
-        });
+        let xmrig = XmrigProcess::new(&helper.lock().unwrap(), state, sudo);
+        Self::start_supervised(helper, xmrig, path);
     }
 
     #[cold]
@@ -231,7 +111,8 @@ impl Helper {
         // the XMRig path is just an argument to sudo, so add it.
         // Before that though, add the ["--prompt"] flag and set it
         // to emptiness so that it doesn't show up in the output.
-        if cfg!(unix) {
+        // As root, XMRig is executed directly.
+        if cfg!(unix) && SudoState::needed() {
             args.push(r#"--prompt="#.to_string());
             args.push("--".to_string());
             args.push(path.display().to_string());
@@ -350,256 +231,18 @@ impl Helper {
         (args, format!("{}:{}", api_ip, api_port))
     }
 
-    // We actually spawn [sudo] on Unix, with XMRig being the argument.
-    #[cfg(target_family = "unix")]
-    fn create_xmrig_cmd_unix(args: Vec<String>, path: PathBuf) -> portable_pty::CommandBuilder {
-        let mut cmd = portable_pty::cmdbuilder::CommandBuilder::new("sudo");
-        cmd.args(args);
-        cmd.cwd(path.as_path().parent().unwrap());
-        cmd
-    }
-
-    // Gupax should be admin on Windows, so just spawn XMRig normally.
-    #[cfg(target_os = "windows")]
-    fn create_xmrig_cmd_windows(args: Vec<String>, path: PathBuf) -> portable_pty::CommandBuilder {
-        let mut cmd = portable_pty::cmdbuilder::CommandBuilder::new(path.clone());
-        cmd.args(args);
-        cmd.cwd(path.as_path().parent().unwrap());
-        cmd
-    }
-
-    #[cold]
-    #[inline(never)]
-    // The XMRig watchdog. Spawns 1 OS thread for reading a PTY (STDOUT+STDERR), and combines the [Child] with a PTY so STDIN actually works.
-    // This isn't actually async, a tokio runtime is unfortunately needed because [Hyper] is an async library (HTTP API calls)
-    #[tokio::main]
-    #[allow(clippy::await_holding_lock)]
-    #[allow(clippy::too_many_arguments)]
-    async fn spawn_xmrig_watchdog(
-        process: Arc<Mutex<Process>>,
-        gui_api: Arc<Mutex<PubXmrigApi>>,
-        pub_api: Arc<Mutex<PubXmrigApi>>,
-        args: Vec<String>,
-        path: std::path::PathBuf,
-        sudo: Arc<Mutex<SudoState>>,
-        mut api_ip_port: String,
-        token: &str,
-        process_xvb: Arc<Mutex<Process>>,
-        process_xp: Arc<Mutex<Process>>,
-        process_p2pool: Arc<Mutex<Process>>,
-        pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
-    ) {
-        // 1a. Create PTY
-        debug!("XMRig | Creating PTY...");
-        let pty = portable_pty::native_pty_system();
-        let pair = pty
-            .openpty(portable_pty::PtySize {
-                rows: 100,
-                cols: 1000,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .unwrap();
-        // 4. Spawn PTY read thread
-        debug!("XMRig | Spawning PTY read thread...");
-        let reader = pair.master.try_clone_reader().unwrap(); // Get STDOUT/STDERR before moving the PTY
-        let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
-        spawn(enclose!((pub_api_xvb, process_xp) async move {
-            Self::read_pty_xmrig(output_parse, output_pub, reader, process_xvb, process_xp, &pub_api_xvb).await;
-        }));
-        // 1b. Create command
-        debug!("XMRig | Creating command...");
-        #[cfg(target_os = "windows")]
-        let cmd = Self::create_xmrig_cmd_windows(args, path);
-        #[cfg(target_family = "unix")]
-        let cmd = Self::create_xmrig_cmd_unix(args, path);
-        // 1c. Create child
-        debug!("XMRig | Creating child...");
-        let child_pty = arc_mut!(pair.slave.spawn_command(cmd).unwrap());
-        drop(pair.slave);
-
-        let mut stdin = pair.master.take_writer().unwrap();
-
-        // 2. Input [sudo] pass, wipe, then drop.
-        if cfg!(unix) {
-            debug!("XMRig | Inputting [sudo] and wiping...");
-            // a) Sleep to wait for [sudo]'s non-echo prompt (on Unix).
-            // this prevents users pass from showing up in the STDOUT.
-            sleep!(3000);
-            if let Err(e) = writeln!(stdin, "{}", sudo.lock().unwrap().pass) {
-                error!("XMRig | Sudo STDIN error: {}", e);
-            };
-            SudoState::wipe(&sudo);
-
-            // b) Reset GUI STDOUT just in case.
-            debug!("XMRig | Clearing GUI output...");
-            gui_api.lock().unwrap().output.clear();
-        }
-
-        // 3. Set process state
-        debug!("XMRig | Setting process state...");
-        let mut lock = process.lock().unwrap();
-        lock.state = ProcessState::NotMining;
-        lock.signal = ProcessSignal::None;
-        lock.start = Instant::now();
-        drop(lock);
-
-        // // 4. Spawn PTY read thread
-        // debug!("XMRig | Spawning PTY read thread...");
-        // let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        // let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
-        // spawn(enclose!((pub_api_xvb) async move {
-        //     Self::read_pty_xmrig(output_parse, output_pub, reader, process_xvb, &pub_api_xvb).await;
-        // }));
-        let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
-
-        let client = client();
-        let start = process.lock().unwrap().start;
-        let api_uri = {
-            if !api_ip_port.ends_with('/') {
-                api_ip_port.push('/');
-            }
-            "http://".to_owned() + &api_ip_port + XMRIG_API_SUMMARY_URI
-        };
-        info!("XMRig | Final API URI: {}", api_uri);
-
-        // Reset stats before loop
-        *pub_api.lock().unwrap() = PubXmrigApi::new();
-        *gui_api.lock().unwrap() = PubXmrigApi::new();
-        // node used for process Status tab
-        pub_api.lock().unwrap().node = NO_POOL.to_string();
-        // 5. Loop as watchdog
-        info!("XMRig | Entering watchdog mode... woof!");
-        // needs xmrig to be in belownormal priority or else Gupaxx will be in trouble if it does not have enough cpu time.
-        #[cfg(target_os = "windows")]
-        {
-            use std::os::windows::process::CommandExt;
-            std::process::Command::new("cmd")
-                .creation_flags(0x08000000)
-                .args(["/c", "wmic"])
-                .args([
-                    "process",
-                    "where",
-                    "name='xmrig.exe'",
-                    "CALL",
-                    "setpriority",
-                    "below normal",
-                ])
-                .spawn()
-                .expect("failure to execute command wmic");
-        }
-        loop {
-            // Set timer
-            let now = Instant::now();
-            debug!("XMRig Watchdog | ----------- Start of loop -----------");
-
-            // Check if the process secretly died without us knowing :)
-            if check_died(
-                &child_pty,
-                &mut process.lock().unwrap(),
-                &start,
-                &mut gui_api.lock().unwrap().output,
-            ) {
-                break;
-            }
-            // Stop on [Stop/Restart] SIGNAL
-            if Self::xmrig_signal_end(
-                &mut process.lock().unwrap(),
-                &child_pty,
-                &start,
-                &mut gui_api.lock().unwrap().output,
-                &sudo,
-            ) {
-                break;
-            }
-            // Check vector of user input
-            check_user_input(&process, &mut stdin);
-            // Check if logs need resetting
-            debug!("XMRig Watchdog | Attempting GUI log reset check");
-            {
-                let mut lock = gui_api.lock().unwrap();
-                Self::check_reset_gui_output(&mut lock.output, ProcessName::Xmrig);
-            }
-            // Always update from output
-            debug!("XMRig Watchdog | Starting [update_from_output()]");
-            let mut process_lock = process.lock().unwrap();
-            let mut pub_api_lock = pub_api.lock().unwrap();
-            PubXmrigApi::update_from_output(
-                &mut pub_api_lock,
-                &output_pub,
-                &output_parse,
-                start.elapsed(),
-                &mut process_lock,
-            );
-            drop(pub_api_lock);
-            drop(process_lock);
-            // Send an HTTP API request
-            debug!("XMRig Watchdog | Attempting HTTP API request...");
-            match PrivXmrigApi::request_xmrig_api(&client, &api_uri, token).await {
-                Ok(priv_api) => {
-                    debug!("XMRig Watchdog | HTTP API request OK, attempting [update_from_priv()]");
-                    PubXmrigApi::update_from_priv(&pub_api, priv_api);
-                }
-                Err(err) => {
-                    warn!(
-                        "XMRig Watchdog | Could not send HTTP API request to: {}\n{}",
-                        api_uri, err
-                    );
-                }
-            }
-            // if mining on proxy and proxy is not alive, switch back to p2pool node
-            if (pub_api.lock().unwrap().node == XvbNode::XmrigProxy.to_string()
-                || pub_api.lock().unwrap().node == NO_POOL)
-                && !process_xp.lock().unwrap().is_alive()
-                && process_p2pool.lock().unwrap().is_alive()
-            {
-                info!("XMRig Process |  redirect xmrig to p2pool since XMRig-Proxy is not alive anymore");
-                let node = XvbNode::P2pool;
-                if let Err(err) = update_xmrig_config(
-                    &client,
-                    XMRIG_CONFIG_URL,
-                    token,
-                    &node,
-                    "",
-                    GUPAX_VERSION_UNDERSCORE,
-                )
-                .await
-                {
-                    // show to console error about updating xmrig config
-                    warn!("XMRig Process | Failed request HTTP API Xmrig");
-                    output_console(
-                        &mut gui_api.lock().unwrap().output,
-                        &format!(
-                            "Failure to update xmrig config with HTTP API.\nError: {}",
-                            err
-                        ),
-                        ProcessName::Xmrig,
-                    );
-                } else {
-                    debug!("XMRig Process | mining on P2Pool pool");
-                }
-            }
-            // Sleep (only if 900ms hasn't passed)
-            sleep_end_loop(now, ProcessName::Xmrig).await;
-        }
-
-        // 5. If loop broke, we must be done here.
-        info!("XMRig Watchdog | Watchdog thread exiting... Goodbye!");
-    }
     fn xmrig_signal_end(
         process: &mut Process,
         child_pty: &Arc<Mutex<Box<dyn Child + Sync + Send>>>,
         start: &Instant,
-        gui_api_output_raw: &mut String,
+        gui_api_output_raw: &mut ConsoleBuffer,
         sudo: &Arc<Mutex<SudoState>>,
     ) -> bool {
         let signal = process.signal;
         if signal == ProcessSignal::Stop || signal == ProcessSignal::Restart {
             debug!("XMRig Watchdog | Stop/Restart SIGNAL caught");
             // macOS requires [sudo] again to kill [XMRig]
-            if cfg!(target_os = "macos") {
+            if cfg!(target_os = "macos") && SudoState::needed() {
                 // If we're at this point, that means the user has
                 // entered their [sudo] pass again, after we wiped it.
                 // So, we should be able to find it in our [Arc<Mutex<SudoState>>].
@@ -616,19 +259,19 @@ impl Helper {
                 Ok(e) => {
                     if e.success() {
                         if process.signal == ProcessSignal::Stop {
-                            process.state = ProcessState::Dead;
+                            process.set_state(ProcessState::Dead);
                         }
                         "Successful"
                     } else {
                         if process.signal == ProcessSignal::Stop {
-                            process.state = ProcessState::Failed;
+                            process.set_state(ProcessState::Failed);
                         }
                         "Failed"
                     }
                 }
                 _ => {
                     if process.signal == ProcessSignal::Stop {
-                        process.state = ProcessState::Failed;
+                        process.set_state(ProcessState::Failed);
                     }
                     "Unknown Error"
                 }
@@ -650,7 +293,7 @@ impl Helper {
             }
             match process.signal {
                 ProcessSignal::Stop => process.signal = ProcessSignal::None,
-                ProcessSignal::Restart => process.state = ProcessState::Waiting,
+                ProcessSignal::Restart => process.set_state(ProcessState::Waiting),
                 _ => (),
             }
             debug!("XMRig Watchdog | Stop/Restart SIGNAL done, breaking");
@@ -659,6 +302,376 @@ impl Helper {
         false
     }
 }
+//---------------------------------------------------------------------------------------------------- [SupervisedProcess]
+#[derive(Clone)]
+pub struct XmrigProcess {
+    state: crate::disk::state::Xmrig,
+    sudo: Arc<Mutex<SudoState>>,
+    process_xvb: Arc<Mutex<Process>>,
+    process_xp: Arc<Mutex<Process>>,
+    process_p2pool: Arc<Mutex<Process>>,
+    pub_api_xvb: Arc<Mutex<PubXvbApi>>,
+    api_uri: String,
+    config_uri: String,
+    client: Client,
+    pub_sys: Arc<Mutex<Sys>>,
+    throttle: Throttle,
+    // What [Xmrig.schedule] and [Xmrig.throttle] last applied, XMRig starts with [current_threads].
+    applied: ScheduleTarget,
+    apply_failed: Option<Instant>,
+}
+
+impl XmrigProcess {
+    pub fn new(
+        helper: &Helper,
+        state: &crate::disk::state::Xmrig,
+        sudo: Arc<Mutex<SudoState>>,
+    ) -> Self {
+        Self {
+            state: state.clone(),
+            sudo,
+            process_xvb: Arc::clone(&helper.xvb),
+            process_xp: Arc::clone(&helper.xmrig_proxy),
+            process_p2pool: Arc::clone(&helper.p2pool),
+            pub_api_xvb: Arc::clone(&helper.pub_api_xvb),
+            api_uri: String::new(),
+            config_uri: String::new(),
+            client: client(),
+            pub_sys: Arc::clone(&helper.pub_sys),
+            throttle: Throttle::default(),
+            applied: ScheduleTarget {
+                threads: state.current_threads,
+            },
+            apply_failed: None,
+        }
+    }
+
+    // Hot-applies the target of [Xmrig.schedule], limited by [Xmrig.throttle],
+    // if it changed since the last time.
+    async fn apply_target(&mut self, watchdog: &mut Watchdog<'_, PubXmrigApi>) {
+        let mut target = self
+            .state
+            .scheduled_target(chrono::Local::now().naive_local());
+        if self.state.throttle.enabled {
+            let (temperature, load) = {
+                let sys = self.pub_sys.lock().unwrap();
+                (sys.cpu_temperature, sys.non_mining_load)
+            };
+            if self
+                .throttle
+                .update(&self.state.throttle, temperature, load, Instant::now())
+            {
+                let msg = if self.throttle.throttled() {
+                    format!(
+                        "Throttling: CPU at {}, {:.0}% used by other programs",
+                        temperature.map_or("???°C".to_string(), |t| format!("{:.1}°C", t)),
+                        load
+                    )
+                } else {
+                    "Throttling: back to normal".to_string()
+                };
+                info!("XMRig | {}", msg);
+                output_console(
+                    &mut watchdog.gui_api.lock().unwrap().output,
+                    &msg,
+                    ProcessName::Xmrig,
+                );
+            }
+            if self.throttle.throttled() {
+                target = self.state.throttle.limit(target);
+            }
+        }
+        if target == self.applied
+            || self
+                .apply_failed
+                .is_some_and(|failed| failed.elapsed() < XMRIG_SCHEDULE_RETRY)
+        {
+            return;
+        }
+        match update_xmrig_threads(
+            &self.client,
+            &self.config_uri,
+            &self.state.token,
+            target.threads,
+        )
+        .await
+        {
+            Ok(_) => {
+                info!("XMRig | Now {}", target);
+                output_console(
+                    &mut watchdog.gui_api.lock().unwrap().output,
+                    &format!("Now {}", target),
+                    ProcessName::Xmrig,
+                );
+                self.applied = target;
+                self.apply_failed = None;
+            }
+            Err(err) => {
+                warn!("XMRig | Could not apply [{}]: {}", target, err);
+                output_console(
+                    &mut watchdog.gui_api.lock().unwrap().output,
+                    &format!(
+                        "Failure to apply the threads with HTTP API.\nError: {}",
+                        err
+                    ),
+                    ProcessName::Xmrig,
+                );
+                self.apply_failed = Some(Instant::now());
+            }
+        }
+    }
+}
+
+impl SupervisedProcess for XmrigProcess {
+    const SPAWNED_STATE: ProcessState = ProcessState::NotMining;
+    const ANSI_LINES: usize = 14;
+    type Api = PubXmrigApi;
+    type Lines = XmrigLines;
+
+    fn name(&self) -> ProcessName {
+        ProcessName::Xmrig
+    }
+
+    fn handles(&self, helper: &Helper) -> Handles<PubXmrigApi> {
+        (
+            Arc::clone(&helper.xmrig),
+            Arc::clone(&helper.gui_api_xmrig),
+            Arc::clone(&helper.pub_api_xmrig),
+        )
+    }
+
+    fn restart_policy(&self) -> &RestartPolicy {
+        &self.state.restart
+    }
+
+    // The local P2Pool must be synced.
+    fn dependencies(&self, helper: &Helper) -> Vec<Arc<Mutex<Process>>> {
+        let local_p2pool = self.state.simple
+            || (self.state.arguments.is_empty()
+                && is_localhost(&self.state.ip)
+                && self.state.port == "3333");
+        if local_p2pool {
+            vec![Arc::clone(&helper.p2pool)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn args(&mut self, helper: &Arc<Mutex<Helper>>, path: &Path) -> Vec<String> {
+        let (args, mut api_ip_port) =
+            Helper::build_xmrig_args_and_mutate_img(helper, &self.state, path);
+        if !api_ip_port.ends_with('/') {
+            api_ip_port.push('/');
+        }
+        self.api_uri = "http://".to_owned() + &api_ip_port + XMRIG_API_SUMMARY_URI;
+        self.config_uri = "http://".to_owned() + &api_ip_port + XMRIG_API_CONFIG_URI;
+        info!("XMRig | Final API URI: {}", self.api_uri);
+        args
+    }
+
+    // We actually spawn [sudo] on Unix, with XMRig being the argument.
+    #[cfg(target_family = "unix")]
+    fn command(&self, args: Vec<String>, path: &Path) -> CommandBuilder {
+        let mut cmd = if SudoState::needed() {
+            CommandBuilder::new("sudo")
+        } else {
+            CommandBuilder::new(path)
+        };
+        cmd.args(args);
+        cmd.cwd(path.parent().unwrap());
+        cmd
+    }
+
+    fn lines(&self) -> XmrigLines {
+        XmrigLines {
+            process_xvb: Arc::clone(&self.process_xvb),
+            process_xp: Arc::clone(&self.process_xp),
+            pub_api_xvb: Arc::clone(&self.pub_api_xvb),
+        }
+    }
+
+    async fn spawned(&mut self, watchdog: &mut Watchdog<'_, PubXmrigApi>) {
+        // Input [sudo] pass, wipe, then drop.
+        #[cfg(target_family = "unix")]
+        if SudoState::needed() {
+            use std::io::Write;
+            debug!("XMRig | Inputting [sudo] and wiping...");
+            // a) Sleep to wait for [sudo]'s non-echo prompt (on Unix).
+            // this prevents users pass from showing up in the STDOUT.
+            sleep!(3000);
+            if let Err(e) = writeln!(watchdog.stdin, "{}", self.sudo.lock().unwrap().pass) {
+                error!("XMRig | Sudo STDIN error: {}", e);
+            };
+            SudoState::wipe(&self.sudo);
+
+            // b) Reset GUI STDOUT just in case.
+            debug!("XMRig | Clearing GUI output...");
+            watchdog.gui_api.lock().unwrap().output.clear();
+        }
+        // node used for process Status tab
+        watchdog.pub_api.lock().unwrap().node = NO_POOL.to_string();
+        // needs xmrig to be in belownormal priority or else Gupaxx will be in trouble if it does not have enough cpu time.
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            std::process::Command::new("cmd")
+                .creation_flags(0x08000000)
+                .args(["/c", "wmic"])
+                .args([
+                    "process",
+                    "where",
+                    "name='xmrig.exe'",
+                    "CALL",
+                    "setpriority",
+                    "below normal",
+                ])
+                .spawn()
+                .expect("failure to execute command wmic");
+        }
+    }
+
+    fn signal_end(
+        &self,
+        process: &mut Process,
+        child_pty: &Arc<Mutex<Box<dyn Child + Sync + Send>>>,
+        start: &Instant,
+        gui_api_output_raw: &mut ConsoleBuffer,
+    ) -> bool {
+        Helper::xmrig_signal_end(process, child_pty, start, gui_api_output_raw, &self.sudo)
+    }
+
+    fn update_from_output(
+        &mut self,
+        pub_api: &mut PubXmrigApi,
+        output_parse: &Arc<Mutex<String>>,
+        output_pub: &Arc<Mutex<String>>,
+        elapsed: Duration,
+        process: &mut Process,
+    ) {
+        PubXmrigApi::update_from_output(pub_api, output_parse, output_pub, elapsed, process);
+    }
+
+    async fn tick(&mut self, watchdog: &mut Watchdog<'_, PubXmrigApi>) {
+        let token = &self.state.token;
+        // Send an HTTP API request
+        debug!("XMRig Watchdog | Attempting HTTP API request...");
+        match PrivXmrigApi::request_xmrig_api(&self.client, &self.api_uri, token).await {
+            Ok(priv_api) => {
+                debug!("XMRig Watchdog | HTTP API request OK, attempting [update_from_priv()]");
+                PubXmrigApi::update_from_priv(watchdog.pub_api, priv_api);
+            }
+            Err(err) => {
+                warn!(
+                    "XMRig Watchdog | Could not send HTTP API request to: {}\n{}",
+                    self.api_uri, err
+                );
+            }
+        }
+        // if mining on proxy and proxy is not alive, switch back to p2pool node
+        let node = watchdog.pub_api.lock().unwrap().node.clone();
+        if (node == XvbNode::XmrigProxy.to_string() || node == NO_POOL)
+            && !self.process_xp.lock().unwrap().is_alive()
+            && self.process_p2pool.lock().unwrap().is_alive()
+        {
+            info!(
+                "XMRig Process |  redirect xmrig to p2pool since XMRig-Proxy is not alive anymore"
+            );
+            let node = XvbNode::P2pool;
+            if let Err(err) = update_xmrig_config(
+                &self.client,
+                XMRIG_CONFIG_URL,
+                token,
+                &node,
+                "",
+                GUPAX_VERSION_UNDERSCORE,
+            )
+            .await
+            {
+                // show to console error about updating xmrig config
+                warn!("XMRig Process | Failed request HTTP API Xmrig");
+                output_console(
+                    &mut watchdog.gui_api.lock().unwrap().output,
+                    &format!(
+                        "Failure to update xmrig config with HTTP API.\nError: {}",
+                        err
+                    ),
+                    ProcessName::Xmrig,
+                );
+            } else {
+                debug!("XMRig Process | mining on P2Pool pool");
+            }
+        }
+        // Mining windows and throttling
+        if !self.state.schedule.is_empty() || self.state.throttle.enabled {
+            self.apply_target(watchdog).await;
+        }
+    }
+
+    fn respawning(&self) {
+        // The [sudo] password was wiped after the first start,
+        // this relies on [sudo] still having the credentials cached.
+        #[cfg(target_family = "unix")]
+        if SudoState::needed() {
+            warn!("XMRig | Restarting without asking for the sudo password");
+        }
+    }
+}
+
+// Follows the node XMRig is connected to while XvB is in charge of it.
+pub struct XmrigLines {
+    process_xvb: Arc<Mutex<Process>>,
+    process_xp: Arc<Mutex<Process>>,
+    pub_api_xvb: Arc<Mutex<PubXvbApi>>,
+}
+
+impl PtyLines for XmrigLines {
+    fn line(&mut self, line: &str) -> bool {
+        // need to verify if node still working
+        // for that need to catch "connect error"
+        // only check if xvb process is used and xmrig-proxy is not.
+        if self.process_xvb.lock().unwrap().is_alive()
+            && !self.process_xp.lock().unwrap().is_alive()
+        {
+            if contains_error(line) {
+                let current_node = self.pub_api_xvb.lock().unwrap().current_node;
+                if let Some(current_node) = current_node {
+                    // updating current node to None, will stop sending signal of FailedNode until new node is set
+                    // send signal to update node.
+                    warn!("XMRig PTY Parse | node is offline, sending signal to update nodes.");
+                    // update nodes only if we were not mining on p2pool.
+                    // if xmrig stop, xvb will react in any case.
+                    if current_node != XvbNode::P2pool {
+                        self.process_xvb
+                            .lock()
+                            .unwrap()
+                            .send_signal(ProcessSignal::UpdateNodes(current_node));
+                    }
+                    self.pub_api_xvb.lock().unwrap().current_node = None;
+                }
+            }
+            if contains_usepool(line) {
+                info!("XMRig PTY Parse | new pool detected");
+                // need to update current node because it was updated.
+                // if custom node made by user, it is not supported because algo is deciding which node to use.
+                let node = detect_new_node_xmrig(line);
+                // the other pool of the schedule is not a node, but chosen by the algorithm.
+                let extra_pool = self.pub_api_xvb.lock().unwrap().extra_pool.clone();
+                if node.is_none()
+                    && !extra_pool.is_some_and(|pool| line.contains(&format!("use pool {pool} ")))
+                {
+                    error!("XMRig PTY Parse | node is not understood, switching to backup.");
+                    // update with default will choose which XvB to prefer. Will update XvB to use p2pool.
+                    self.process_xvb
+                        .lock()
+                        .unwrap()
+                        .send_signal(ProcessSignal::UpdateNodes(XvbNode::default()));
+                }
+                self.pub_api_xvb.lock().unwrap().current_node = node;
+            }
+        }
+        true
+    }
+}
 //---------------------------------------------------------------------------------------------------- [ImgXmrig]
 #[derive(Debug, Clone)]
 pub struct ImgXmrig {
@@ -683,9 +696,10 @@ impl ImgXmrig {
 
 //---------------------------------------------------------------------------------------------------- Public XMRig API
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PubXmrigApi {
-    pub output: String,
+    #[serde(skip)]
+    pub output: ConsoleBuffer,
     pub uptime: Duration,
     pub worker_id: String,
     pub resources: String,
@@ -696,6 +710,8 @@ pub struct PubXmrigApi {
     pub hashrate_raw: f32,
     pub hashrate_raw_1m: f32,
     pub hashrate_raw_15m: f32,
+    pub accepted_raw: u64,
+    pub rejected_raw: u64,
     pub node: String,
 }
 
@@ -708,7 +724,7 @@ impl Default for PubXmrigApi {
 impl PubXmrigApi {
     pub fn new() -> Self {
         Self {
-            output: String::new(),
+            output: ConsoleBuffer::new(),
             uptime: Duration::from_secs(0),
             worker_id: UNKNOWN_DATA.to_string(),
             resources: UNKNOWN_DATA.to_string(),
@@ -719,23 +735,12 @@ impl PubXmrigApi {
             hashrate_raw: 0.0,
             hashrate_raw_1m: 0.0,
             hashrate_raw_15m: 0.0,
+            accepted_raw: 0,
+            rejected_raw: 0,
             node: UNKNOWN_DATA.to_string(),
         }
     }
 
-    #[inline]
-    pub fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
-        let output = std::mem::take(&mut gui_api.output);
-        let buf = std::mem::take(&mut pub_api.output);
-        *gui_api = Self {
-            output,
-            ..pub_api.clone()
-        };
-        if !buf.is_empty() {
-            gui_api.output.push_str(&buf);
-        }
-    }
-
     // This combines the buffer from the PTY thread [output_pub]
     // with the actual [PubApiXmrig] output field.
     pub fn update_from_output(
@@ -760,13 +765,13 @@ impl PubXmrigApi {
         let mut output_parse = output_parse.lock().unwrap();
         // 2. Check for "new job"/"no active...".
         if XMRIG_REGEX.new_job.is_match(&output_parse) {
-            process.state = ProcessState::Alive;
+            process.set_state(ProcessState::Alive);
             // get the pool we mine on to put it on stats
             if let Some(name_pool) = crate::regex::detect_node_xmrig(&output_parse) {
                 public.node = name_pool;
             }
         } else if XMRIG_REGEX.not_mining.is_match(&output_parse) {
-            process.state = ProcessState::NotMining;
+            process.set_state(ProcessState::NotMining);
             public.node = NO_POOL.to_string();
         }
 
@@ -801,11 +806,19 @@ impl PubXmrigApi {
             hashrate_raw,
             hashrate_raw_1m,
             hashrate_raw_15m,
+            accepted_raw: private.connection.accepted as u64,
+            rejected_raw: private.connection.rejected as u64,
             ..std::mem::take(&mut *public)
         }
     }
 }
 
+impl PubApi for PubXmrigApi {
+    fn output(&mut self) -> &mut ConsoleBuffer {
+        &mut self.output
+    }
+}
+
 //---------------------------------------------------------------------------------------------------- Private XMRig API
 // This matches to some JSON stats in the HTTP call [summary],
 // e.g: [wget -qO- localhost:18085/1/summary].
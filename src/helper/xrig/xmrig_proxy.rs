@@ -1,27 +1,23 @@
-use enclose::enc;
-use log::{debug, error, info, warn};
+use log::{debug, info, warn};
 use reqwest::header::AUTHORIZATION;
 use reqwest_middleware::ClientWithMiddleware as Client;
 use serde::{Deserialize, Serialize};
-use std::fmt::Write;
 use std::{
     path::Path,
     sync::{Arc, Mutex},
-    thread,
-    time::{Duration, Instant},
+    time::Duration,
 };
-use tokio::spawn;
 
+use crate::helper::console::ConsoleBuffer;
 use crate::miscs::client;
 use crate::{
-    disk::state::Xmrig,
+    disk::state::{RestartPolicy, Xmrig, XmrigProxy},
     helper::{
-        check_died, check_user_input, signal_end, sleep_end_loop,
+        supervised::{is_localhost, Handles, PtyLines, PubApi, SupervisedProcess, Watchdog},
         xrig::update_xmrig_config,
         xvb::{nodes::XvbNode, PubXvbApi},
         Helper, Process, ProcessName, ProcessSignal, ProcessState,
     },
-    macros::{arc_mut, sleep},
     miscs::output_console,
     regex::{contains_timeout, contains_usepool, detect_new_node_xmrig, XMRIG_REGEX},
     GUPAX_VERSION_UNDERSCORE, UNKNOWN_DATA,
@@ -32,82 +28,7 @@ use super::xmrig::PubXmrigApi;
 impl Helper {
     // Takes in some [State/XmrigProxy] and parses it to build the actual command arguments.
     // Returns the [Vec] of actual arguments,
-    #[cold]
-    #[inline(never)]
-    pub async fn read_pty_xp(
-        output_parse: Arc<Mutex<String>>,
-        output_pub: Arc<Mutex<String>>,
-        reader: Box<dyn std::io::Read + Send>,
-        process_xvb: Arc<Mutex<Process>>,
-        pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
-    ) {
-        use std::io::BufRead;
-        let mut stdout = std::io::BufReader::new(reader).lines();
-
-        // Run a ANSI escape sequence filter for the first few lines.
-        let mut i = 0;
-        while let Some(Ok(line)) = stdout.next() {
-            let line = strip_ansi_escapes::strip_str(line);
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("XMRig-Proxy PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("XMRig-Proxy PTY Pub | Output error: {}", e);
-            }
-            if i > 7 {
-                break;
-            } else {
-                i += 1;
-            }
-        }
-
-        while let Some(Ok(line)) = stdout.next() {
-            // need to verify if node still working
-            // for that need to catch "connect error"
-            // only switch nodes of XvB if XvB process is used
-            if process_xvb.lock().unwrap().is_alive() {
-                if contains_timeout(&line) {
-                    let current_node = pub_api_xvb.lock().unwrap().current_node;
-                    if let Some(current_node) = current_node {
-                        // updating current node to None, will stop sending signal of FailedNode until new node is set
-                        // send signal to update node.
-                        warn!(
-                        "XMRig-Proxy PTY Parse | node is offline, sending signal to update nodes."
-                    );
-                        if current_node != XvbNode::P2pool {
-                            process_xvb.lock().unwrap().signal =
-                                ProcessSignal::UpdateNodes(current_node);
-                        }
-                        pub_api_xvb.lock().unwrap().current_node = None;
-                    }
-                }
-                if contains_usepool(&line) {
-                    info!("XMRig-Proxy PTY Parse | new pool detected");
-                    // need to update current node because it was updated.
-                    // if custom node made by user, it is not supported because algo is deciding which node to use.
-
-                    let node = detect_new_node_xmrig(&line);
-                    if node.is_none() {
-                        warn!(
-                            "XMRig-Proxy PTY Parse | node is not understood, switching to backup."
-                        );
-                        // update with default will choose which XvB to prefer. Will update XvB to use p2pool.
-                        process_xvb.lock().unwrap().signal =
-                            ProcessSignal::UpdateNodes(XvbNode::default());
-                    }
-                    pub_api_xvb.lock().unwrap().current_node = node;
-                }
-            }
-            //			println!("{}", line); // For debugging.
-            if let Err(e) = writeln!(output_parse.lock().unwrap(), "{}", line) {
-                error!("XMRig-Proxy PTY Parse | Output error: {}", e);
-            }
-            if let Err(e) = writeln!(output_pub.lock().unwrap(), "{}", line) {
-                error!("XMRig-Proxy PTY Pub | Output error: {}", e);
-            }
-        }
-    }
-    pub fn build_xp_args(state: &crate::disk::state::XmrigProxy) -> Vec<String> {
+    pub fn build_xp_args(state: &XmrigProxy) -> Vec<String> {
         let mut args = Vec::with_capacity(500);
         let api_ip;
         let api_port;
@@ -198,275 +119,234 @@ impl Helper {
     }
 
     pub fn stop_xp(helper: &Arc<Mutex<Self>>) {
-        info!("XMRig-Proxy | Attempting to stop...");
-        helper.lock().unwrap().xmrig_proxy.lock().unwrap().signal = ProcessSignal::Stop;
-        info!("locked signal ok");
-        helper.lock().unwrap().xmrig_proxy.lock().unwrap().state = ProcessState::Middle;
-        info!("locked state ok");
+        Self::stop_supervised(&helper.lock().unwrap().xmrig_proxy);
         let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_xp);
-        info!("clone gui ok");
         let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_xp);
-        info!("clone pub ok");
         *pub_api.lock().unwrap() = PubXmrigProxyApi::new();
-        info!("pub api reset ok");
         *gui_api.lock().unwrap() = PubXmrigProxyApi::new();
-        info!("gui api reset ok");
     }
     // The "restart frontend" to a "frontend" function.
-    // Basically calls to kill the current xmrig-proxy, waits a little, then starts the below function in a a new thread, then exit.
     pub fn restart_xp(
         helper: &Arc<Mutex<Self>>,
-        state: &crate::disk::state::XmrigProxy,
+        state: &XmrigProxy,
         state_xmrig: &Xmrig,
         path: &Path,
     ) {
-        info!("XMRig-Proxy | Attempting to restart...");
-        helper.lock().unwrap().xmrig_proxy.lock().unwrap().state = ProcessState::Middle;
-        helper.lock().unwrap().xmrig_proxy.lock().unwrap().signal = ProcessSignal::Restart;
-
-        let helper = Arc::clone(helper);
-        let state = state.clone();
-        let state_xmrig = state_xmrig.clone();
-        let path = path.to_path_buf();
-        // This thread lives to wait, start xmrig_proxy then die.
-        thread::spawn(move || {
-            while helper.lock().unwrap().xmrig_proxy.lock().unwrap().state != ProcessState::Waiting
-            {
-                warn!("XMRig-proxy | Want to restart but process is still alive, waiting...");
-                sleep!(1000);
-            }
-            // Ok, process is not alive, start the new one!
-            info!("XMRig-Proxy | Old process seems dead, starting new one!");
-            Self::start_xp(&helper, &state, &state_xmrig, &path);
-        });
-        info!("XMRig-Proxy | Restart ... OK");
+        let xp = XpProcess::new(&helper.lock().unwrap(), state, state_xmrig);
+        Self::restart_supervised(helper, xp, path);
     }
     pub fn start_xp(
         helper: &Arc<Mutex<Self>>,
-        state_proxy: &crate::disk::state::XmrigProxy,
+        state_proxy: &XmrigProxy,
         state_xmrig: &Xmrig,
         path: &Path,
     ) {
-        helper.lock().unwrap().xmrig_proxy.lock().unwrap().state = ProcessState::Middle;
+        let xp = XpProcess::new(&helper.lock().unwrap(), state_proxy, state_xmrig);
+        Self::start_supervised(helper, xp, path);
+    }
+}
+//---------------------------------------------------------------------------------------------------- [SupervisedProcess]
+#[derive(Clone)]
+pub struct XpProcess {
+    state: XmrigProxy,
+    state_xmrig: Xmrig,
+    process_xvb: Arc<Mutex<Process>>,
+    process_xmrig: Arc<Mutex<Process>>,
+    pub_api_xvb: Arc<Mutex<PubXvbApi>>,
+    pub_api_xmrig: Arc<Mutex<PubXmrigApi>>,
+    client: Client,
+}
+
+impl XpProcess {
+    pub fn new(helper: &Helper, state: &XmrigProxy, state_xmrig: &Xmrig) -> Self {
+        Self {
+            state: state.clone(),
+            state_xmrig: state_xmrig.clone(),
+            process_xvb: Arc::clone(&helper.xvb),
+            process_xmrig: Arc::clone(&helper.xmrig),
+            pub_api_xvb: Arc::clone(&helper.pub_api_xvb),
+            pub_api_xmrig: Arc::clone(&helper.pub_api_xmrig),
+            client: client(),
+        }
+    }
+}
 
-        let args = Self::build_xp_args(state_proxy);
-        // Print arguments & user settings to console
-        crate::disk::print_dash(&format!("XMRig-Proxy | Launch arguments: {:#?}", args));
-        info!("XMRig-Proxy | Using path: [{}]", path.display());
+impl SupervisedProcess for XpProcess {
+    const SPAWNED_STATE: ProcessState = ProcessState::NotMining;
+    const ANSI_LINES: usize = 8;
+    type Api = PubXmrigProxyApi;
+    type Lines = XpLines;
 
-        // Spawn watchdog thread
-        let process = Arc::clone(&helper.lock().unwrap().xmrig_proxy);
-        let gui_api = Arc::clone(&helper.lock().unwrap().gui_api_xp);
-        let pub_api = Arc::clone(&helper.lock().unwrap().pub_api_xp);
-        let process_xvb = Arc::clone(&helper.lock().unwrap().xvb);
-        let process_xmrig = Arc::clone(&helper.lock().unwrap().xmrig);
-        let path = path.to_path_buf();
-        let token = state_proxy.token.clone();
-        let state_xmrig = state_xmrig.clone();
-        let redirect_xmrig = state_proxy.redirect_local_xmrig;
-        let pub_api_xvb = Arc::clone(&helper.lock().unwrap().pub_api_xvb);
-        let pub_api_xmrig = Arc::clone(&helper.lock().unwrap().pub_api_xmrig);
-        thread::spawn(move || {
-            Self::spawn_xp_watchdog(
-                &process,
-                &gui_api,
-                &pub_api,
-                args,
-                path,
-                &token,
-                &state_xmrig,
-                redirect_xmrig,
-                process_xvb,
-                process_xmrig,
-                &pub_api_xvb,
-                &pub_api_xmrig,
-            );
-        });
+    fn name(&self) -> ProcessName {
+        ProcessName::XmrigProxy
     }
-    #[tokio::main]
-    #[allow(clippy::await_holding_lock)]
-    #[allow(clippy::too_many_arguments)]
-    async fn spawn_xp_watchdog(
-        process: &Arc<Mutex<Process>>,
-        gui_api: &Arc<Mutex<PubXmrigProxyApi>>,
-        pub_api: &Arc<Mutex<PubXmrigProxyApi>>,
-        args: Vec<String>,
-        path: std::path::PathBuf,
-        token_proxy: &str,
-        state_xmrig: &Xmrig,
-        xmrig_redirect: bool,
-        process_xvb: Arc<Mutex<Process>>,
-        process_xmrig: Arc<Mutex<Process>>,
-        pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
-        pub_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
-    ) {
-        process.lock().unwrap().start = Instant::now();
-        // spawn pty
-        debug!("XMRig-Proxy | Creating PTY...");
-        let pty = portable_pty::native_pty_system();
-        let pair = pty
-            .openpty(portable_pty::PtySize {
-                rows: 100,
-                cols: 1000,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .unwrap();
-        // 4. Spawn PTY read thread
-        debug!("XMRig-Proxy | Spawning PTY read thread...");
-        let reader = pair.master.try_clone_reader().unwrap(); // Get STDOUT/STDERR before moving the PTY
-        let output_parse = Arc::clone(&process.lock().unwrap().output_parse);
-        let output_pub = Arc::clone(&process.lock().unwrap().output_pub);
-        spawn(enc!((pub_api_xvb, output_parse, output_pub) async move {
-            Self::read_pty_xp(output_parse, output_pub, reader, process_xvb, &pub_api_xvb).await;
-        }));
-        // 1b. Create command
-        debug!("XMRig-Proxy | Creating command...");
-        let mut cmd = portable_pty::cmdbuilder::CommandBuilder::new(path.clone());
-        cmd.args(args);
-        cmd.cwd(path.as_path().parent().unwrap());
-        // 1c. Create child
-        debug!("XMRig-Proxy | Creating child...");
-        let child_pty = arc_mut!(pair.slave.spawn_command(cmd).unwrap());
-        drop(pair.slave);
-        let mut stdin = pair.master.take_writer().unwrap();
-        // to refactor to let user use his own ports
-        let api_summary_xp = XMRIG_PROXY_SUMMARY_URL;
-        let api_config_xmrig = XMRIG_CONFIG_URL;
 
-        // set state
-        let client = client();
-        process.lock().unwrap().state = ProcessState::NotMining;
-        process.lock().unwrap().signal = ProcessSignal::None;
-        // reset stats
-        *pub_api.lock().unwrap() = PubXmrigProxyApi::new();
-        *gui_api.lock().unwrap() = PubXmrigProxyApi::new();
-        // loop
-        let start = process.lock().unwrap().start;
-        debug!("Xmrig-Proxy Watchdog | enabling verbose mode");
-        #[cfg(target_os = "windows")]
-        if let Err(e) = write!(stdin, "v\r\n") {
-            error!("P2Pool Watchdog | STDIN error: {}", e);
+    fn handles(&self, helper: &Helper) -> Handles<PubXmrigProxyApi> {
+        (
+            Arc::clone(&helper.xmrig_proxy),
+            Arc::clone(&helper.gui_api_xp),
+            Arc::clone(&helper.pub_api_xp),
+        )
+    }
+
+    fn restart_policy(&self) -> &RestartPolicy {
+        &self.state.restart
+    }
+
+    // The local P2Pool must be synced.
+    fn dependencies(&self, helper: &Helper) -> Vec<Arc<Mutex<Process>>> {
+        let local_p2pool = self.state.simple
+            || (self.state.arguments.is_empty() && is_localhost(&self.state.p2pool_ip));
+        if local_p2pool {
+            vec![Arc::clone(&helper.p2pool)]
+        } else {
+            vec![]
         }
-        #[cfg(target_family = "unix")]
-        if let Err(e) = writeln!(stdin, "v") {
-            error!("XMRig-Proxy Watchdog | STDIN error: {}", e);
+    }
+
+    fn args(&mut self, _helper: &Arc<Mutex<Helper>>, _path: &Path) -> Vec<String> {
+        Helper::build_xp_args(&self.state)
+    }
+
+    fn lines(&self) -> XpLines {
+        XpLines {
+            process_xvb: Arc::clone(&self.process_xvb),
+            pub_api_xvb: Arc::clone(&self.pub_api_xvb),
         }
+    }
+
+    async fn spawned(&mut self, watchdog: &mut Watchdog<'_, PubXmrigProxyApi>) {
+        debug!("XMRig-Proxy Watchdog | enabling verbose mode");
+        watchdog.write_stdin(ProcessName::XmrigProxy, "v");
         debug!("XMRig-Proxy Watchdog | checking connections");
-        #[cfg(target_os = "windows")]
-        if let Err(e) = write!(stdin, "c\r\n") {
-            error!("XMRig-Proxy Watchdog | STDIN error: {}", e);
-        }
-        #[cfg(target_family = "unix")]
-        if let Err(e) = writeln!(stdin, "c") {
-            error!("XMRig-Proxy Watchdog | STDIN error: {}", e);
+        watchdog.write_stdin(ProcessName::XmrigProxy, "c");
+    }
+
+    fn update_from_output(
+        &mut self,
+        pub_api: &mut PubXmrigProxyApi,
+        output_parse: &Arc<Mutex<String>>,
+        output_pub: &Arc<Mutex<String>>,
+        elapsed: Duration,
+        process: &mut Process,
+    ) {
+        PubXmrigProxyApi::update_from_output(pub_api, output_parse, output_pub, elapsed, process);
+    }
+
+    async fn tick(&mut self, watchdog: &mut Watchdog<'_, PubXmrigProxyApi>) {
+        // to refactor to let user use his own ports
+        let api_summary_xp = XMRIG_PROXY_SUMMARY_URL;
+        let api_config_xmrig = XMRIG_CONFIG_URL;
+        // update data from api
+        debug!("XMRig-Proxy Watchdog | Attempting HTTP API request...");
+        match PrivXmrigProxyApi::request_xp_api(&self.client, api_summary_xp, &self.state.token)
+            .await
+        {
+            Ok(priv_api) => {
+                debug!(
+                    "XMRig-Proxy Watchdog | HTTP API request OK, attempting [update_from_priv()]"
+                );
+                PubXmrigProxyApi::update_from_priv(watchdog.pub_api, priv_api);
+            }
+            Err(err) => {
+                warn!(
+                    "XMRig-Proxy Watchdog | Could not send HTTP API request to: {}\n{}",
+                    api_summary_xp, err
+                );
+            }
         }
-        info!("XMRig-Proxy | Entering watchdog mode... woof!");
-        loop {
-            let now = Instant::now();
-            debug!("XMRig-Proxy Watchdog | ----------- Start of loop -----------");
+        // update xmrig to use xmrig-proxy if option enabled and local xmrig alive
+        let xmrig_state = self.process_xmrig.lock().unwrap().state;
+        if self.state.redirect_local_xmrig
+            && self.pub_api_xmrig.lock().unwrap().node != XvbNode::XmrigProxy.to_string()
+            && (xmrig_state == ProcessState::Alive || xmrig_state == ProcessState::NotMining)
+        {
+            info!("redirect local xmrig instance to xmrig-proxy");
+            let node = XvbNode::XmrigProxy;
+            if let Err(err) = update_xmrig_config(
+                &self.client,
+                api_config_xmrig,
+                &self.state_xmrig.token,
+                &node,
+                "",
+                GUPAX_VERSION_UNDERSCORE,
+            )
+            .await
             {
-                if check_died(
-                    &child_pty,
-                    &mut process.lock().unwrap(),
-                    &start,
-                    &mut gui_api.lock().unwrap().output,
-                ) {
-                    break;
-                }
-                // check signal
-                if signal_end(
-                    &mut process.lock().unwrap(),
-                    &child_pty,
-                    &start,
-                    &mut gui_api.lock().unwrap().output,
-                ) {
-                    break;
-                }
-                // check user input
-                check_user_input(process, &mut stdin);
-                // get data output/api
-
-                // Check if logs need resetting
-                debug!("XMRig-Proxy Watchdog | Attempting GUI log reset check");
-                Self::check_reset_gui_output(
-                    &mut gui_api.lock().unwrap().output,
+                // show to console error about updating xmrig config
+                warn!("XMRig-Proxy Process | Failed request HTTP API Xmrig");
+                output_console(
+                    &mut watchdog.gui_api.lock().unwrap().output,
+                    &format!(
+                        "Failure to update xmrig config with HTTP API.\nError: {}",
+                        err
+                    ),
                     ProcessName::XmrigProxy,
                 );
-                // Always update from output
-                // todo: check difference with xmrig
-                debug!("XMRig-Proxy Watchdog | Starting [update_from_output()]");
-                let mut process_lock = process.lock().unwrap();
-                let mut pub_api_lock = pub_api.lock().unwrap();
-                PubXmrigProxyApi::update_from_output(
-                    &mut pub_api_lock,
-                    &output_pub,
-                    &output_parse,
-                    start.elapsed(),
-                    &mut process_lock,
-                );
-                drop(pub_api_lock);
-                drop(process_lock);
-                // update data from api
-                debug!("XMRig-Proxy Watchdog | Attempting HTTP API request...");
-                match PrivXmrigProxyApi::request_xp_api(&client, api_summary_xp, token_proxy).await
-                {
-                    Ok(priv_api) => {
-                        debug!("XMRig-Proxy Watchdog | HTTP API request OK, attempting [update_from_priv()]");
-                        PubXmrigProxyApi::update_from_priv(pub_api, priv_api);
-                    }
-                    Err(err) => {
-                        warn!(
-                            "XMRig-Proxy Watchdog | Could not send HTTP API request to: {}\n{}",
-                            api_summary_xp, err
-                        );
+            } else {
+                debug!("XMRig-Proxy Process | mining on Xmrig-Proxy pool");
+            }
+        }
+    }
+}
+
+// Follows the node XMRig-Proxy is connected to while XvB is in charge of it.
+pub struct XpLines {
+    process_xvb: Arc<Mutex<Process>>,
+    pub_api_xvb: Arc<Mutex<PubXvbApi>>,
+}
+
+impl PtyLines for XpLines {
+    fn line(&mut self, line: &str) -> bool {
+        // need to verify if node still working
+        // for that need to catch "connect error"
+        // only switch nodes of XvB if XvB process is used
+        if self.process_xvb.lock().unwrap().is_alive() {
+            if contains_timeout(line) {
+                let current_node = self.pub_api_xvb.lock().unwrap().current_node;
+                if let Some(current_node) = current_node {
+                    // updating current node to None, will stop sending signal of FailedNode until new node is set
+                    // send signal to update node.
+                    warn!(
+                        "XMRig-Proxy PTY Parse | node is offline, sending signal to update nodes."
+                    );
+                    if current_node != XvbNode::P2pool {
+                        self.process_xvb
+                            .lock()
+                            .unwrap()
+                            .send_signal(ProcessSignal::UpdateNodes(current_node));
                     }
+                    self.pub_api_xvb.lock().unwrap().current_node = None;
                 }
-                // update xmrig to use xmrig-proxy if option enabled and local xmrig alive
-                if xmrig_redirect
-                    && pub_api_xmrig.lock().unwrap().node != XvbNode::XmrigProxy.to_string()
-                    && (process_xmrig.lock().unwrap().state == ProcessState::Alive
-                        || process_xmrig.lock().unwrap().state == ProcessState::NotMining)
+            }
+            if contains_usepool(line) {
+                info!("XMRig-Proxy PTY Parse | new pool detected");
+                // need to update current node because it was updated.
+                // if custom node made by user, it is not supported because algo is deciding which node to use.
+                let node = detect_new_node_xmrig(line);
+                // the other pool of the schedule is not a node, but chosen by the algorithm.
+                let extra_pool = self.pub_api_xvb.lock().unwrap().extra_pool.clone();
+                if node.is_none()
+                    && !extra_pool.is_some_and(|pool| line.contains(&format!("use pool {pool} ")))
                 {
-                    info!("redirect local xmrig instance to xmrig-proxy");
-                    let node = XvbNode::XmrigProxy;
-                    if let Err(err) = update_xmrig_config(
-                        &client,
-                        api_config_xmrig,
-                        &state_xmrig.token,
-                        &node,
-                        "",
-                        GUPAX_VERSION_UNDERSCORE,
-                    )
-                    .await
-                    {
-                        // show to console error about updating xmrig config
-                        warn!("XMRig-Proxy Process | Failed request HTTP API Xmrig");
-                        output_console(
-                            &mut gui_api.lock().unwrap().output,
-                            &format!(
-                                "Failure to update xmrig config with HTTP API.\nError: {}",
-                                err
-                            ),
-                            ProcessName::XmrigProxy,
-                        );
-                    } else {
-                        debug!("XMRig-Proxy Process | mining on Xmrig-Proxy pool");
-                    }
+                    warn!("XMRig-Proxy PTY Parse | node is not understood, switching to backup.");
+                    // update with default will choose which XvB to prefer. Will update XvB to use p2pool.
+                    self.process_xvb
+                        .lock()
+                        .unwrap()
+                        .send_signal(ProcessSignal::UpdateNodes(XvbNode::default()));
                 }
-            } // locked are dropped here
-              // do not use more than 1 second for the loop
-            sleep_end_loop(now, ProcessName::XmrigProxy).await;
+                self.pub_api_xvb.lock().unwrap().current_node = node;
+            }
         }
-
-        // 5. If loop broke, we must be done here.
-        info!("XMRig-Proxy Watchdog | Watchdog thread exiting... Goodbye!");
-        // sleep
+        true
     }
 }
-#[derive(Debug, Clone)]
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PubXmrigProxyApi {
-    pub output: String,
+    #[serde(skip)]
+    pub output: ConsoleBuffer,
     pub uptime: Duration,
     pub accepted: u32,
     pub rejected: u32,
@@ -486,7 +366,7 @@ impl Default for PubXmrigProxyApi {
 impl PubXmrigProxyApi {
     pub fn new() -> Self {
         Self {
-            output: String::new(),
+            output: ConsoleBuffer::new(),
             uptime: Duration::from_secs(0),
             accepted: 0,
             rejected: 0,
@@ -522,7 +402,7 @@ impl PubXmrigProxyApi {
         if XMRIG_REGEX.new_job.is_match(&output_parse)
             || XMRIG_REGEX.valid_conn.is_match(&output_parse)
         {
-            process.state = ProcessState::Alive;
+            process.set_state(ProcessState::Alive);
             // get the pool we mine on to put it on stats
             if let Some(name_pool) = crate::regex::detect_node_xmrig(&output_parse) {
                 public.node = name_pool;
@@ -531,25 +411,13 @@ impl PubXmrigProxyApi {
             || XMRIG_REGEX.invalid_conn.is_match(&output_parse)
             || XMRIG_REGEX.error.is_match(&output_parse)
         {
-            process.state = ProcessState::NotMining;
+            process.set_state(ProcessState::NotMining);
             public.node = NO_POOL.to_string();
         }
         // 3. Throw away [output_parse]
         output_parse.clear();
         drop(output_parse);
     }
-    // same method as PubXmrigApi, why not make a trait ?
-    pub fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
-        let output = std::mem::take(&mut gui_api.output);
-        let buf = std::mem::take(&mut pub_api.output);
-        *gui_api = Self {
-            output,
-            ..pub_api.clone()
-        };
-        if !buf.is_empty() {
-            gui_api.output.push_str(&buf);
-        }
-    }
     fn update_from_priv(public: &Arc<Mutex<Self>>, private: PrivXmrigProxyApi) {
         let mut public = public.lock().unwrap();
         *public = Self {
@@ -565,6 +433,12 @@ impl PubXmrigProxyApi {
     }
 }
 
+impl PubApi for PubXmrigProxyApi {
+    fn output(&mut self) -> &mut ConsoleBuffer {
+        &mut self.output
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct PrivXmrigProxyApi {
     hashrate: HashrateProxy,
@@ -9,23 +9,26 @@ use std::{
     time::Duration,
 };
 
-use log::error;
+use derive_more::Display;
 use log::{info, warn};
 use reqwest_middleware::ClientWithMiddleware as Client;
 use tokio::time::sleep;
 
 use crate::{
     helper::{
-        p2pool::PubP2poolApi,
-        xrig::{update_xmrig_config, xmrig::PubXmrigApi},
-        xvb::{nodes::XvbNode, priv_stats::RuntimeMode},
+        p2pool::{PubP2poolApi, StratumWorker},
+        xrig::{update_xmrig_config, update_xmrig_pool, xmrig::PubXmrigApi},
+        xvb::{
+            nodes::{Pool, XvbNode},
+            priv_stats::RuntimeMode,
+        },
     },
     BLOCK_PPLNS_WINDOW_MAIN, BLOCK_PPLNS_WINDOW_MINI, SECOND_PER_BLOCK_P2POOL,
     XVB_ROUND_DONOR_MEGA_MIN_HR, XVB_ROUND_DONOR_MIN_HR, XVB_ROUND_DONOR_VIP_MIN_HR,
-    XVB_ROUND_DONOR_WHALE_MIN_HR, XVB_TIME_ALGO,
+    XVB_ROUND_DONOR_WHALE_MIN_HR, XVB_STRATUM_MISMATCH, XVB_TIME_ALGO,
 };
 
-use super::{priv_stats::RuntimeDonationLevel, PubXvbApi, SamplesAverageHour};
+use super::{priv_stats::RuntimeDonationLevel, PubXvbApi};
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn algorithm(
@@ -41,39 +44,182 @@ pub(crate) async fn algorithm(
     time_donated: &Arc<Mutex<u32>>,
     rig: &str,
     xp_alive: bool,
-    p2pool_buffer: i8,
+    state_xvb: &crate::disk::state::Xvb,
+    stratum_logins: &[String],
 ) {
-    let mut algorithm = Algorithm::new(
-        client,
+    let inputs = Inputs::from_apis(
         pub_api,
         gui_api_xvb,
         gui_api_xmrig,
         gui_api_xp,
         gui_api_p2pool,
-        token_xmrig,
         state_p2pool,
         share,
-        time_donated,
-        rig,
         xp_alive,
-        p2pool_buffer,
+        state_xvb,
+        stratum_logins,
     );
+    let miner = HelperMiner {
+        gui_api_xvb,
+        gui_api_xmrig,
+        gui_api_xp,
+        time_donated,
+        xp_alive,
+    };
+    let mut algorithm = Algorithm::new(client, &miner, token_xmrig, rig, inputs);
     algorithm.run().await;
+    output_console_without_time(
+        &mut gui_api_xvb.lock().unwrap().output,
+        "",
+        crate::helper::ProcessName::Xvb,
+    )
 }
 
-#[allow(dead_code)]
-pub struct Algorithm<'a> {
-    client: &'a Client,
-    pub_api: &'a Arc<Mutex<PubXvbApi>>,
+//---------------------------------------------------------------------------------------------------- Miner
+// What the algorithm acts on and reports to while it runs.
+// The XvB process implements it over the [Pub*Api] of the other processes,
+// [gupaxx-xvb] over the HTTP API of XMRig or XMRig-Proxy.
+pub trait Miner {
+    // The node XMRig or XMRig-Proxy is mining on, [None] if unknown.
+    fn current_node(&self) -> Option<XvbNode>;
+    // Called once XMRig or XMRig-Proxy was asked to mine on [node],
+    // [None] for a pool that is not a node.
+    fn mining_on(&self, node: Option<XvbNode>);
+    // The XvB node to send the hashrate to.
+    fn xvb_node(&self) -> XvbNode;
+    // The hashrate the algorithm can send to either pool.
+    async fn hashrate(&self) -> f32;
+    // Hashrate sent to P2Pool and XvB during the last [XVB_TIME_ALGO].
+    fn push_samples(&self, p2pool: f32, xvb: f32);
+    // Seconds of the current round given to XvB.
+    fn set_time_donated(&self, time: u32);
+    fn output(&self, msg: &str);
+}
+
+struct HelperMiner<'a> {
     gui_api_xvb: &'a Arc<Mutex<PubXvbApi>>,
     gui_api_xmrig: &'a Arc<Mutex<PubXmrigApi>>,
     gui_api_xp: &'a Arc<Mutex<PubXmrigProxyApi>>,
-    gui_api_p2pool: &'a Arc<Mutex<PubP2poolApi>>,
-    token_xmrig: &'a str,
-    state_p2pool: &'a crate::disk::state::P2pool,
     time_donated: &'a Arc<Mutex<u32>>,
-    rig: &'a str,
     xp_alive: bool,
+}
+
+impl Miner for HelperMiner<'_> {
+    fn current_node(&self) -> Option<XvbNode> {
+        self.gui_api_xvb.lock().unwrap().current_node
+    }
+    // [current_node] is read from the output of XMRig.
+    fn mining_on(&self, _node: Option<XvbNode>) {}
+    fn xvb_node(&self) -> XvbNode {
+        self.gui_api_xvb.lock().unwrap().stats_priv.node
+    }
+    async fn hashrate(&self) -> f32 {
+        current_controllable_hr(self.xp_alive, self.gui_api_xp, self.gui_api_xmrig)
+    }
+    fn push_samples(&self, p2pool: f32, xvb: f32) {
+        let mut gui_api_xvb = self.gui_api_xvb.lock().unwrap();
+        gui_api_xvb
+            .p2pool_sent_last_hour_samples
+            .0
+            .push_back(p2pool);
+        gui_api_xvb.xvb_sent_last_hour_samples.0.push_back(xvb);
+    }
+    fn set_time_donated(&self, time: u32) {
+        *self.time_donated.lock().unwrap() = time;
+    }
+    fn output(&self, msg: &str) {
+        output_console(
+            &mut self.gui_api_xvb.lock().unwrap().output,
+            msg,
+            crate::helper::ProcessName::Xvb,
+        );
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Inputs
+// Everything the decision of the algorithm is made from, read before it runs.
+#[derive(Debug, Clone, Default)]
+pub struct Inputs {
+    // shares of the user in the PPLNS window.
+    pub share: u32,
+    // hashrate of XMRig or XMRig-Proxy.
+    pub hashrate: f32,
+    pub address: String,
+    pub runtime_mode: RuntimeMode,
+    pub runtime_donation_level: RuntimeDonationLevel,
+    pub runtime_amount: f64,
+    // hashrate of the user estimated by P2Pool from the PPLNS window.
+    pub p2pool_total_hashrate: f32,
+    // hashrate of the other miners on the stratum of P2Pool, if used.
+    pub p2pool_stratum_hashrate: Option<f32>,
+    pub p2pool_difficulty: u64,
+    pub mini: bool,
+    pub p2pool_buffer: i8,
+    // averages of the user on XvB, in H/s.
+    pub xvb_24h_avg: f32,
+    pub xvb_1h_avg: f32,
+    // averages of what was sent to each pool the last hour.
+    pub p2pool_avg_last_hour_hashrate: f32,
+    pub xvb_avg_last_hour_hashrate: f32,
+    // URL of the config of the HTTP API of XMRig or XMRig-Proxy.
+    pub api_url: String,
+    pub xp: bool,
+    // another pool given [extra_pool_percent] of the hashrate, see [Destination::Pool].
+    pub extra_pool: Option<Pool>,
+    pub extra_pool_percent: u8,
+}
+
+impl Inputs {
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_apis(
+        pub_api: &Arc<Mutex<PubXvbApi>>,
+        gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
+        gui_api_xmrig: &Arc<Mutex<PubXmrigApi>>,
+        gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
+        gui_api_p2pool: &Arc<Mutex<PubP2poolApi>>,
+        state_p2pool: &crate::disk::state::P2pool,
+        share: u32,
+        xp_alive: bool,
+        state_xvb: &crate::disk::state::Xvb,
+        stratum_logins: &[String],
+    ) -> Self {
+        let pub_api = pub_api.lock().unwrap();
+        let gui_api_xvb = gui_api_xvb.lock().unwrap();
+        let gui_api_p2pool = gui_api_p2pool.lock().unwrap();
+        Self {
+            share,
+            hashrate: current_controllable_hr(xp_alive, gui_api_xp, gui_api_xmrig),
+            address: state_p2pool.address.clone(),
+            runtime_mode: gui_api_xvb.stats_priv.runtime_mode.clone(),
+            runtime_donation_level: gui_api_xvb.stats_priv.runtime_manual_donation_level.clone(),
+            runtime_amount: gui_api_xvb.stats_priv.runtime_manual_amount,
+            p2pool_total_hashrate: gui_api_p2pool.sidechain_ehr,
+            p2pool_stratum_hashrate: gui_api_p2pool
+                .stratum_workers
+                .as_deref()
+                .filter(|_| state_xvb.use_stratum)
+                .map(|workers| StratumWorker::external_hashrate(workers, stratum_logins)),
+            p2pool_difficulty: gui_api_p2pool.p2pool_difficulty_u64,
+            mini: state_p2pool.mini,
+            p2pool_buffer: state_xvb.p2pool_buffer,
+            xvb_24h_avg: pub_api.stats_priv.donor_24hr_avg * 1000.0,
+            xvb_1h_avg: pub_api.stats_priv.donor_1hr_avg * 1000.0,
+            p2pool_avg_last_hour_hashrate: gui_api_xvb.p2pool_sent_last_hour_samples.average(),
+            xvb_avg_last_hour_hashrate: gui_api_xvb.xvb_sent_last_hour_samples.average(),
+            api_url: api_url_xmrig(xp_alive, true),
+            xp: xp_alive,
+            extra_pool: state_xvb.extra_pool(),
+            extra_pool_percent: state_xvb.extra_pool_percent,
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Algorithm
+pub struct Algorithm<'a, M: Miner> {
+    client: &'a Client,
+    miner: &'a M,
+    token_xmrig: &'a str,
+    rig: &'a str,
     pub stats: Stats,
 }
 
@@ -92,154 +238,38 @@ pub struct Stats {
     // manual slider for p2pool and xvb manual
     runtime_amount: f64,
     p2pool_total_hashrate: f32,
+    p2pool_stratum_hashrate: Option<f32>,
     p2pool_avg_last_hour_hashrate: f32,
     p2pool_external_hashrate: f32,
     share_min_hashrate: f32,
+    extra_pool: Option<Pool>,
+    extra_pool_hashrate: f32,
     spareable_hashrate: f32,
-    needed_time_xvb: u32,
+    pub needed_time_extra_pool: u32,
+    pub needed_time_xvb: u32,
     api_url: String,
     msg_xmrig_or_xp: String,
 }
 
-impl<'a> Algorithm<'a> {
-    #[allow(clippy::too_many_arguments)]
+impl<'a, M: Miner> Algorithm<'a, M> {
     pub fn new(
         client: &'a Client,
-        pub_api: &'a Arc<Mutex<PubXvbApi>>,
-        gui_api_xvb: &'a Arc<Mutex<PubXvbApi>>,
-        gui_api_xmrig: &'a Arc<Mutex<PubXmrigApi>>,
-        gui_api_xp: &'a Arc<Mutex<PubXmrigProxyApi>>,
-        gui_api_p2pool: &'a Arc<Mutex<PubP2poolApi>>,
+        miner: &'a M,
         token_xmrig: &'a str,
-        state_p2pool: &'a crate::disk::state::P2pool,
-        share: u32,
-        time_donated: &'a Arc<Mutex<u32>>,
         rig: &'a str,
-        xp_alive: bool,
-        p2pool_buffer: i8,
+        inputs: Inputs,
     ) -> Self {
-        let hashrate_xmrig = current_controllable_hr(xp_alive, gui_api_xp, gui_api_xmrig);
-
-        let address = state_p2pool.address.clone();
-
-        let runtime_mode = gui_api_xvb.lock().unwrap().stats_priv.runtime_mode.clone();
-        let runtime_donation_level = gui_api_xvb
-            .lock()
-            .unwrap()
-            .stats_priv
-            .runtime_manual_donation_level
-            .clone();
-        let runtime_amount = gui_api_xvb.lock().unwrap().stats_priv.runtime_manual_amount;
-
-        let p2pool_total_hashrate = gui_api_p2pool.lock().unwrap().sidechain_ehr;
-
-        let p2pool_avg_last_hour_hashrate = Self::calc_last_hour_avg_hash_rate(
-            &gui_api_xvb.lock().unwrap().p2pool_sent_last_hour_samples,
-        );
-        let p2pool_external_hashrate =
-            (p2pool_total_hashrate - p2pool_avg_last_hour_hashrate).max(0.0);
-        info!("p2pool external hashrate({p2pool_external_hashrate}) = p2ool_total_hashrate({p2pool_total_hashrate}) - p2pool_avg_last_hour_hashrate({p2pool_avg_last_hour_hashrate})");
-
-        let share_min_hashrate = Self::minimum_hashrate_share(
-            gui_api_p2pool.lock().unwrap().p2pool_difficulty_u64,
-            state_p2pool.mini,
-            p2pool_external_hashrate,
-            p2pool_buffer,
-        );
-
-        let spareable_hashrate = hashrate_xmrig - share_min_hashrate;
-
-        let api_url = api_url_xmrig(xp_alive, true);
-
-        let msg_xmrig_or_xp = (if xp_alive { "XMRig-Proxy" } else { "XMRig" }).to_string();
-        info!("xp alive: {:?}", xp_alive);
-
-        let xvb_24h_avg = pub_api.lock().unwrap().stats_priv.donor_24hr_avg * 1000.0;
-        let xvb_1h_avg = pub_api.lock().unwrap().stats_priv.donor_1hr_avg * 1000.0;
-
-        let xvb_avg_last_hour_hashrate = Self::calc_last_hour_avg_hash_rate(
-            &gui_api_xvb.lock().unwrap().xvb_sent_last_hour_samples,
-        );
-        let xvb_external_hashrate = (xvb_1h_avg - xvb_avg_last_hour_hashrate).max(0.0);
-        info!("xvb external hashrate({xvb_external_hashrate}) = xvb_1h_avg({xvb_1h_avg}) - xvb_avg_last_hour_hashrate({xvb_avg_last_hour_hashrate})");
-        let stats = Stats {
-            share,
-            hashrate_xmrig,
-            xvb_24h_avg,
-            xvb_1h_avg,
-            xvb_external_hashrate,
-            address,
-            target_donation_hashrate: f32::default(),
-            runtime_mode,
-            runtime_donation_level,
-            runtime_amount,
-            p2pool_total_hashrate,
-            p2pool_avg_last_hour_hashrate,
-            p2pool_external_hashrate,
-            share_min_hashrate,
-            spareable_hashrate,
-            needed_time_xvb: u32::default(),
-            api_url,
-            msg_xmrig_or_xp,
-        };
-
-        let mut new_instance = Self {
+        Self {
             client,
-            pub_api,
-            gui_api_xvb,
-            gui_api_xmrig,
-            gui_api_xp,
-            gui_api_p2pool,
+            miner,
             token_xmrig,
-            state_p2pool,
-            time_donated,
             rig,
-            xp_alive,
-            stats,
-        };
-        // external XvB HR is taken into account with get_target_donation_hashrate so the needed time is calculating how much time is needed from local sparable HR only
-        new_instance.stats.target_donation_hashrate =
-            new_instance.get_target_donation_hashrate().max(0.0);
-        new_instance.stats.needed_time_xvb = Self::get_needed_time_xvb(
-            new_instance.stats.target_donation_hashrate,
-            new_instance.stats.hashrate_xmrig,
-        );
-
-        new_instance
-    }
-
-    fn is_share_fulfilled(&self) -> bool {
-        let is_criteria_fulfilled = self.stats.share > 0;
-
-        info!(
-            "Algorithm | shares({}) > 0 : {}",
-            self.stats.share, is_criteria_fulfilled,
-        );
-
-        is_criteria_fulfilled
-    }
-
-    fn is_xvb_24h_fulfilled(&self) -> bool {
-        if self.stats.runtime_mode != RuntimeMode::Auto
-            && self.stats.runtime_mode != RuntimeMode::ManualDonationLevel
-        {
-            info!("Algorithm | not running auto or manual round selection, no fast 24h average");
-            return true;
+            stats: Stats::new(inputs),
         }
-        // add external to target to have the real total target
-        let is_criteria_fulfilled = self.stats.xvb_24h_avg
-            > self.stats.target_donation_hashrate + self.stats.xvb_external_hashrate;
-        info!(
-            "Algorithm | xvb_24h_avg({}) > target_donation_hashrate({}) : {}",
-            self.stats.xvb_24h_avg,
-            self.stats.target_donation_hashrate + self.stats.xvb_external_hashrate,
-            is_criteria_fulfilled
-        );
-        is_criteria_fulfilled
     }
 
     async fn target_p2pool_node(&self) {
-        if self.gui_api_xvb.lock().unwrap().current_node != Some(XvbNode::P2pool) {
+        if self.miner.current_node() != Some(XvbNode::P2pool) {
             info!(
                 "Algorithm | request {} to mine on p2pool",
                 self.stats.msg_xmrig_or_xp
@@ -259,40 +289,30 @@ impl<'a> Algorithm<'a> {
                     "Algorithm | Failed request HTTP API {}",
                     self.stats.msg_xmrig_or_xp
                 );
-                output_console(
-                    &mut self.gui_api_xvb.lock().unwrap().output,
-                    &format!(
-                        "Failure to update {} config with HTTP API.\nError: {}",
-                        self.stats.msg_xmrig_or_xp, err
-                    ),
-                    crate::helper::ProcessName::Xvb,
-                );
+                self.miner.output(&format!(
+                    "Failure to update {} config with HTTP API.\nError: {}",
+                    self.stats.msg_xmrig_or_xp, err
+                ));
             } else {
                 info!(
                     "Algorithm | {} mining on p2pool pool",
                     self.stats.msg_xmrig_or_xp
                 );
+                self.miner.mining_on(Some(node));
             }
         }
     }
 
     async fn target_xvb_node(&self) {
-        let node = self.gui_api_xvb.lock().unwrap().stats_priv.node;
+        let node = self.miner.xvb_node();
 
         info!(
             "Algorithm | request {} to mine on XvB",
             self.stats.msg_xmrig_or_xp
         );
 
-        if self.gui_api_xvb.lock().unwrap().current_node.is_none()
-            || self
-                .gui_api_xvb
-                .lock()
-                .unwrap()
-                .current_node
-                .as_ref()
-                .is_some_and(|n| n == &XvbNode::P2pool)
-        {
+        let current_node = self.miner.current_node();
+        if current_node.is_none() || current_node.is_some_and(|n| n == XvbNode::P2pool) {
             if let Err(err) = update_xmrig_config(
                 self.client,
                 &self.stats.api_url,
@@ -308,142 +328,445 @@ impl<'a> Algorithm<'a> {
                     "Algorithm | Failed request HTTP API {}",
                     self.stats.msg_xmrig_or_xp
                 );
-                output_console(
-                    &mut self.gui_api_xvb.lock().unwrap().output,
-                    &format!(
-                        "Failure to update {} config with HTTP API.\nError: {}",
-                        self.stats.msg_xmrig_or_xp, err
-                    ),
-                    crate::helper::ProcessName::Xvb,
-                );
+                self.miner.output(&format!(
+                    "Failure to update {} config with HTTP API.\nError: {}",
+                    self.stats.msg_xmrig_or_xp, err
+                ));
             } else {
                 info!(
                     "Algorithm | {} mining on XvB pool",
                     self.stats.msg_xmrig_or_xp
                 );
+                self.miner.mining_on(Some(node));
             }
         }
     }
 
-    async fn send_all_p2pool(&self) {
-        self.target_p2pool_node().await;
-
+    async fn target_pool(&self, pool: &Pool) {
         info!(
-            "Algorithm | algo sleep for {} seconds while mining on P2pool",
-            XVB_TIME_ALGO
+            "Algorithm | request {} to mine on {}",
+            self.stats.msg_xmrig_or_xp, pool.url
         );
-        sleep(Duration::from_secs(XVB_TIME_ALGO.into())).await;
-        let hashrate = current_controllable_hr(self.xp_alive, self.gui_api_xp, self.gui_api_xmrig);
-        self.gui_api_xvb
-            .lock()
-            .unwrap()
-            .p2pool_sent_last_hour_samples
-            .0
-            .push_back(hashrate);
-        self.gui_api_xvb
-            .lock()
-            .unwrap()
-            .xvb_sent_last_hour_samples
-            .0
-            .push_back(0.0);
+        if let Err(err) = update_xmrig_pool(
+            self.client,
+            &self.stats.api_url,
+            self.token_xmrig,
+            pool,
+            self.rig,
+        )
+        .await
+        {
+            warn!(
+                "Algorithm | Failed request HTTP API {}",
+                self.stats.msg_xmrig_or_xp
+            );
+            self.miner.output(&format!(
+                "Failure to update {} config with HTTP API.\nError: {}",
+                self.stats.msg_xmrig_or_xp, err
+            ));
+        } else {
+            info!(
+                "Algorithm | {} mining on {}",
+                self.stats.msg_xmrig_or_xp, pool.url
+            );
+            self.miner.mining_on(None);
+        }
     }
 
-    async fn send_all_xvb(&self) {
-        self.target_xvb_node().await;
+    // Mines on every slot in order, then records what was sent to each pool.
+    async fn run_schedule(&self, decision: &Decision) {
+        for slot in &decision.slots {
+            match &slot.destination {
+                Destination::P2pool => self.target_p2pool_node().await,
+                Destination::Xvb => self.target_xvb_node().await,
+                Destination::Pool(pool) => self.target_pool(pool).await,
+            }
+            // will not quit the process until it is really done.
+            // xvb process watch this algo handle to see if process is finished or not.
+            info!(
+                "Algorithm | algo sleep for {} seconds while mining on {}",
+                slot.seconds, slot.destination
+            );
+            sleep(Duration::from_secs(slot.seconds.into())).await;
+        }
+        // HR could be not the same now as the avg sent the last 10mn, will be replaced later by a better history of HR
+        let hashrate = self.miner.hashrate().await;
+        let sent =
+            |destination| hashrate * decision.seconds(&destination) as f32 / XVB_TIME_ALGO as f32;
+        self.miner
+            .push_samples(sent(Destination::P2pool), sent(Destination::Xvb));
+    }
 
-        info!(
-            "Algorithm | algo sleep for {} seconds while mining on XvB",
-            XVB_TIME_ALGO
+    pub async fn run(&mut self) {
+        self.miner
+            .output("Algorithm of HR distribution started for the next 10 minutes.");
+
+        info!("Algorithm | Starting...");
+        info!("Algorithm | {:#?}", self.stats);
+
+        // inform user about external HR detected to explain better the decision.
+        let external_xvb_hr = self.stats.xvb_external_hashrate;
+        if external_xvb_hr > 0.0 {
+            self.miner.output(&format!(
+                "estimated external HR on XvB: {:.3}kH/s",
+                external_xvb_hr / 1000.0
+            ));
+        }
+        let external_p2pool_hr = self.stats.p2pool_external_hashrate;
+        if external_p2pool_hr > 0.0 {
+            let how = if self.stats.p2pool_stratum_hashrate.is_some() {
+                "measured"
+            } else {
+                "estimated"
+            };
+            self.miner.output(&format!(
+                "{how} external HR on P2pool: {:.3}kH/s",
+                external_p2pool_hr / 1000.0
+            ));
+        }
+        if let Some((stratum, sidechain)) = self.stats.stratum_mismatch() {
+            warn!("Algorithm | stratum({stratum}) and sidechain({sidechain}) hashrates of P2Pool disagree");
+            self.miner.output(&format!(
+                "The HR of P2pool from its stratum ({:.3}kH/s) and the sidechain ({:.3}kH/s) differ by more than {}%, are all your miners pointed to the P2pool of Gupaxx?",
+                stratum / 1000.0,
+                sidechain / 1000.0,
+                (XVB_STRATUM_MISMATCH * 100.0) as u32
+            ));
+        }
+
+        let decision = self.stats.decide();
+        self.miner.output(&decision.message());
+        if decision.seconds_pool() > 0 {
+            self.miner
+                .output(&format!("Schedule of the round: {decision}"));
+        }
+        self.miner.set_time_donated(decision.xvb_time());
+        self.run_schedule(&decision).await;
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Decision
+// Why a round is distributed the way it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    // No share in the PPLNS window, everything to P2Pool.
+    NoShare,
+    // The 24h average on XvB is under the target, everything to XvB
+    // but the part of the other pool.
+    Xvb24h,
+    // Enough on both, the time needed on XvB goes to it.
+    Cycle,
+}
+
+// Where a slot of a round is mined.
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum Destination {
+    #[display("P2Pool")]
+    P2pool,
+    // the XvB node of the moment, see [Miner::xvb_node].
+    #[display("XvB")]
+    Xvb,
+    #[display("{}", _0.url)]
+    Pool(Pool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    pub destination: Destination,
+    pub seconds: u32,
+}
+
+// How a round of [XVB_TIME_ALGO] is distributed: its slots are mined in
+// order, P2Pool first and XvB last, for the whole round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub reason: Reason,
+    pub slots: Vec<Slot>,
+}
+
+impl Decision {
+    // Slots of 0 seconds are left out.
+    fn new(reason: Reason, slots: impl IntoIterator<Item = (Destination, u32)>) -> Self {
+        Self {
+            reason,
+            slots: slots
+                .into_iter()
+                .filter(|(_, seconds)| *seconds > 0)
+                .map(|(destination, seconds)| Slot {
+                    destination,
+                    seconds,
+                })
+                .collect(),
+        }
+    }
+    // Seconds of the round mined on [destination].
+    pub fn seconds(&self, destination: &Destination) -> u32 {
+        self.slots
+            .iter()
+            .filter(|slot| slot.destination == *destination)
+            .map(|slot| slot.seconds)
+            .sum()
+    }
+    pub fn xvb_time(&self) -> u32 {
+        self.seconds(&Destination::Xvb)
+    }
+    // Seconds on any other pool.
+    pub fn seconds_pool(&self) -> u32 {
+        self.slots
+            .iter()
+            .filter(|slot| matches!(slot.destination, Destination::Pool(_)))
+            .map(|slot| slot.seconds)
+            .sum()
+    }
+    // What the round does and why, from its slots.
+    pub fn message(&self) -> String {
+        let pool = self.seconds_pool();
+        match self.reason {
+            Reason::NoShare => {
+                "There are no shares in p2pool. Sending all hashrate to p2pool!".to_string()
+            }
+            Reason::Xvb24h if pool > 0 => format!(
+                "24H avg XvB target not achieved. Sending {} seconds to the extra pool and the rest to XvB!",
+                pool
+            ),
+            Reason::Xvb24h => {
+                "24H avg XvB target not achieved. Sending all hashrate to XvB!".to_string()
+            }
+            Reason::Cycle => format!(
+                "There is a share in p2pool and 24H avg XvB is achieved. Sending {} seconds to XvB!",
+                self.xvb_time()
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| format!("{} {}s", slot.destination, slot.seconds))
+            .collect::<Vec<_>>();
+        write!(f, "{}", slots.join(", "))
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Stats
+impl Stats {
+    pub fn new(inputs: Inputs) -> Self {
+        let Inputs {
+            share,
+            hashrate: hashrate_xmrig,
+            address,
+            runtime_mode,
+            runtime_donation_level,
+            runtime_amount,
+            p2pool_total_hashrate,
+            p2pool_stratum_hashrate,
+            p2pool_difficulty,
+            mini,
+            p2pool_buffer,
+            xvb_24h_avg,
+            xvb_1h_avg,
+            p2pool_avg_last_hour_hashrate,
+            xvb_avg_last_hour_hashrate,
+            api_url,
+            xp,
+            extra_pool,
+            extra_pool_percent,
+        } = inputs;
+
+        let p2pool_external_hashrate = if let Some(stratum) = p2pool_stratum_hashrate {
+            info!(
+                "p2pool external hashrate({stratum}) = hashrate of the other miners on the stratum"
+            );
+            stratum
+        } else {
+            let p2pool_external_hashrate =
+                (p2pool_total_hashrate - p2pool_avg_last_hour_hashrate).max(0.0);
+            info!("p2pool external hashrate({p2pool_external_hashrate}) = p2ool_total_hashrate({p2pool_total_hashrate}) - p2pool_avg_last_hour_hashrate({p2pool_avg_last_hour_hashrate})");
+            p2pool_external_hashrate
+        };
+
+        let share_min_hashrate = Self::minimum_hashrate_share(
+            p2pool_difficulty,
+            mini,
+            p2pool_external_hashrate,
+            p2pool_buffer,
         );
-        sleep(Duration::from_secs(XVB_TIME_ALGO.into())).await;
-        let hashrate = current_controllable_hr(self.xp_alive, self.gui_api_xp, self.gui_api_xmrig);
-        self.gui_api_xvb
-            .lock()
-            .unwrap()
-            .xvb_sent_last_hour_samples
-            .0
-            .push_back(hashrate);
-        self.gui_api_xvb
-            .lock()
-            .unwrap()
-            .p2pool_sent_last_hour_samples
-            .0
-            .push_back(0.0);
+
+        // the other pool only gets what P2Pool does not need for a share.
+        let extra_pool_hashrate = if extra_pool.is_some() {
+            (hashrate_xmrig * extra_pool_percent as f32 / 100.0)
+                .min(hashrate_xmrig - share_min_hashrate)
+                .max(0.0)
+        } else {
+            0.0
+        };
+        info!("Algorithm | extra pool hashrate({extra_pool_hashrate}) = hashrate_xmrig({hashrate_xmrig}) * {extra_pool_percent}% within hashrate_xmrig - share_min_hashrate({share_min_hashrate})");
+
+        let spareable_hashrate = hashrate_xmrig - share_min_hashrate - extra_pool_hashrate;
+
+        let msg_xmrig_or_xp = (if xp { "XMRig-Proxy" } else { "XMRig" }).to_string();
+        info!("xp alive: {:?}", xp);
+
+        let xvb_external_hashrate = (xvb_1h_avg - xvb_avg_last_hour_hashrate).max(0.0);
+        info!("xvb external hashrate({xvb_external_hashrate}) = xvb_1h_avg({xvb_1h_avg}) - xvb_avg_last_hour_hashrate({xvb_avg_last_hour_hashrate})");
+        let mut stats = Self {
+            share,
+            hashrate_xmrig,
+            xvb_24h_avg,
+            xvb_1h_avg,
+            xvb_external_hashrate,
+            address,
+            target_donation_hashrate: f32::default(),
+            runtime_mode,
+            runtime_donation_level,
+            runtime_amount,
+            p2pool_total_hashrate,
+            p2pool_stratum_hashrate,
+            p2pool_avg_last_hour_hashrate,
+            p2pool_external_hashrate,
+            share_min_hashrate,
+            extra_pool,
+            extra_pool_hashrate,
+            spareable_hashrate,
+            needed_time_extra_pool: u32::default(),
+            needed_time_xvb: u32::default(),
+            api_url,
+            msg_xmrig_or_xp,
+        };
+        // external XvB HR is taken into account with get_target_donation_hashrate so the needed time is calculating how much time is needed from local sparable HR only
+        stats.target_donation_hashrate = stats.get_target_donation_hashrate().max(0.0);
+        stats.needed_time_extra_pool =
+            Self::get_needed_time_xvb(stats.extra_pool_hashrate, stats.hashrate_xmrig);
+        // what is left of the round once the other pool has its part.
+        stats.needed_time_xvb =
+            Self::get_needed_time_xvb(stats.target_donation_hashrate, stats.hashrate_xmrig)
+                .min(XVB_TIME_ALGO - stats.needed_time_extra_pool);
+
+        stats
     }
 
-    async fn sleep_then_update_node_xmrig(&self) {
-        info!(
-            "Algorithm | algo sleep for {} seconds while mining on P2pool",
-            XVB_TIME_ALGO - self.stats.needed_time_xvb
+    // What the round is made of, from this snapshot only.
+    pub fn decide(&self) -> Decision {
+        // do not switch pool for a few seconds, let's make 6 seconds minimum.
+        let extra_pool_time = match self.needed_time_extra_pool {
+            x if x <= XVB_MIN_TIME_SEND => 0,
+            x => x,
+        };
+        let rest = XVB_TIME_ALGO - extra_pool_time;
+        let (reason, extra_pool_time, xvb_time) = if !self.is_share_fulfilled() {
+            (Reason::NoShare, 0, 0)
+        } else if !self.is_xvb_24h_fulfilled() {
+            (Reason::Xvb24h, extra_pool_time, rest)
+        } else {
+            let xvb_time = match self.needed_time_xvb {
+                x if x <= XVB_MIN_TIME_SEND => {
+                    info!("Algorithm | Needed time: {x} to send on XvB is less than minimum time to send, sending all HR to p2pool");
+                    0
+                }
+                x if x + XVB_MIN_TIME_SEND <= rest => {
+                    info!("Algorithm | There is a share in p2pool and 24H avg XvB is achieved. Sending  {} seconds to XvB!", x);
+                    x
+                }
+                x => {
+                    info!("Algorithm | time : {x} seconds for XvB is more than time algo - minimum time to send, sending all to XvB");
+                    rest
+                }
+            };
+            (Reason::Cycle, extra_pool_time, xvb_time)
+        };
+        let p2pool_time = XVB_TIME_ALGO - extra_pool_time - xvb_time;
+        let decision = Decision::new(
+            reason,
+            [
+                (Destination::P2pool, p2pool_time),
+                (
+                    self.extra_pool
+                        .clone()
+                        .map_or(Destination::P2pool, Destination::Pool),
+                    extra_pool_time,
+                ),
+                (Destination::Xvb, xvb_time),
+            ],
         );
-        sleep(Duration::from_secs(
-            (XVB_TIME_ALGO - self.stats.needed_time_xvb).into(),
-        ))
-        .await;
+        info!("Algorithm | {}", decision.message());
+        decision
+    }
+
+    // The hashrate of the user on P2Pool from the stratum and from the sidechain,
+    // if they differ by more than [XVB_STRATUM_MISMATCH].
+    pub fn stratum_mismatch(&self) -> Option<(f32, f32)> {
+        let stratum = self.p2pool_stratum_hashrate? + self.p2pool_avg_last_hour_hashrate;
+        let sidechain = self.p2pool_total_hashrate;
+        // nothing to compare to until P2Pool estimated it.
+        if sidechain <= 0.0 {
+            return None;
+        }
+        let difference = (stratum - sidechain).abs() / stratum.max(sidechain);
+        (difference > XVB_STRATUM_MISMATCH).then_some((stratum, sidechain))
+    }
 
-        // only update xmrig config if it is actually mining.
-        info!("Algorithm | request xmrig to mine on XvB");
+    fn is_share_fulfilled(&self) -> bool {
+        let is_criteria_fulfilled = self.share > 0;
 
-        self.target_xvb_node().await;
+        info!(
+            "Algorithm | shares({}) > 0 : {}",
+            self.share, is_criteria_fulfilled,
+        );
 
-        // will not quit the process until it is really done.
-        // xvb process watch this algo handle to see if process is finished or not.
+        is_criteria_fulfilled
+    }
 
+    fn is_xvb_24h_fulfilled(&self) -> bool {
+        if self.runtime_mode != RuntimeMode::Auto
+            && self.runtime_mode != RuntimeMode::ManualDonationLevel
+        {
+            info!("Algorithm | not running auto or manual round selection, no fast 24h average");
+            return true;
+        }
+        // add external to target to have the real total target
+        let is_criteria_fulfilled =
+            self.xvb_24h_avg > self.target_donation_hashrate + self.xvb_external_hashrate;
         info!(
-            "Algorithm | algo sleep for {} seconds while mining on XvB",
-            self.stats.needed_time_xvb
+            "Algorithm | xvb_24h_avg({}) > target_donation_hashrate({}) : {}",
+            self.xvb_24h_avg,
+            self.target_donation_hashrate + self.xvb_external_hashrate,
+            is_criteria_fulfilled
         );
-        sleep(Duration::from_secs(self.stats.needed_time_xvb.into())).await;
-        // HR could be not the same now as the avg sent the last 10mn, will be replaced later by a better history of HR
-        let hashrate = current_controllable_hr(self.xp_alive, self.gui_api_xp, self.gui_api_xmrig);
-        self.gui_api_xvb
-            .lock()
-            .unwrap()
-            .p2pool_sent_last_hour_samples
-            .0
-            .push_back(
-                hashrate
-                    * ((XVB_TIME_ALGO as f32 - self.stats.needed_time_xvb as f32)
-                        / XVB_TIME_ALGO as f32),
-            );
-        self.gui_api_xvb
-            .lock()
-            .unwrap()
-            .xvb_sent_last_hour_samples
-            .0
-            .push_back(hashrate * (self.stats.needed_time_xvb as f32 / XVB_TIME_ALGO as f32));
+        is_criteria_fulfilled
     }
 
     pub fn get_target_donation_hashrate(&self) -> f32 {
-        match self.stats.runtime_mode {
+        match self.runtime_mode {
             RuntimeMode::Auto => self.get_auto_mode_target_donation_hashrate(),
             RuntimeMode::Hero => self.get_hero_mode_target_donation_hashrate(),
             RuntimeMode::ManualXvb => {
                 info!(
                     "Algorithm | ManualXvBMode target_donation_hashrate=runtime_amount({}H/s)",
-                    self.stats.runtime_amount
+                    self.runtime_amount
                 );
-                self.stats.runtime_amount as f32
+                self.runtime_amount as f32
             }
             RuntimeMode::ManualP2pool => {
-                let target_donation_hashrate =
-                    self.stats.hashrate_xmrig - (self.stats.runtime_amount as f32);
+                let target_donation_hashrate = self.hashrate_xmrig - (self.runtime_amount as f32);
 
                 info!("Algorithm | ManualP2poolMode target_donation_hashrate({})=hashrate_xmrig({})-runtime_amount({})",
                 target_donation_hashrate,
-                self.stats.hashrate_xmrig,
-                self.stats.runtime_amount);
+                self.hashrate_xmrig,
+                self.runtime_amount);
 
                 target_donation_hashrate
             }
             // manual donation level will take into account external HR
             RuntimeMode::ManualDonationLevel => {
-                let target_donation_hashrate = self.stats.runtime_donation_level.get_hashrate()
-                    - self.stats.xvb_external_hashrate;
+                let target_donation_hashrate =
+                    self.runtime_donation_level.get_hashrate() - self.xvb_external_hashrate;
 
                 info!("Algorithm | ManualDonationLevelMode target_donation_hashrate({})={:#?}.get_hashrate()",
                 target_donation_hashrate,
-                self.stats.runtime_donation_level);
+                self.runtime_donation_level);
 
                 target_donation_hashrate
             }
@@ -451,8 +774,7 @@ impl<'a> Algorithm<'a> {
     }
 
     fn get_auto_mode_target_donation_hashrate(&self) -> f32 {
-        let donation_level = match self.stats.spareable_hashrate + self.stats.xvb_external_hashrate
-        {
+        let donation_level = match self.spareable_hashrate + self.xvb_external_hashrate {
             x if x > (XVB_ROUND_DONOR_MEGA_MIN_HR as f32) => Some(RuntimeDonationLevel::DonorMega),
             x if x > (XVB_ROUND_DONOR_WHALE_MIN_HR as f32) => {
                 Some(RuntimeDonationLevel::DonorWhale)
@@ -468,7 +790,7 @@ impl<'a> Algorithm<'a> {
         );
 
         let target_donation_hashrate = if let Some(level) = donation_level {
-            level.get_hashrate() - self.stats.xvb_external_hashrate
+            level.get_hashrate() - self.xvb_external_hashrate
         } else {
             0.0
         };
@@ -485,18 +807,13 @@ impl<'a> Algorithm<'a> {
     fn get_hero_mode_target_donation_hashrate(&self) -> f32 {
         info!(
             "Algorithm | HeroMode target_donation_hashrate=spareable_hashrate({})",
-            self.stats.spareable_hashrate
+            self.spareable_hashrate
         );
 
-        self.stats.spareable_hashrate
+        self.spareable_hashrate
     }
 
-    // push new value into samples before executing this calcul
-    fn calc_last_hour_avg_hash_rate(samples: &SamplesAverageHour) -> f32 {
-        samples.0.iter().sum::<f32>() / samples.0.len() as f32
-    }
-
-    fn minimum_hashrate_share(
+    pub fn minimum_hashrate_share(
         difficulty: u64,
         mini: bool,
         p2pool_external_hashrate: f32,
@@ -526,111 +843,6 @@ impl<'a> Algorithm<'a> {
         minimum_hr.max(0.0)
     }
 
-    async fn fulfill_share(&self) {
-        output_console(
-            &mut self.gui_api_xvb.lock().unwrap().output,
-            "There are no shares in p2pool. Sending all hashrate to p2pool!",
-            crate::helper::ProcessName::Xvb,
-        );
-
-        info!("Algorithm | There are no shares in p2pool. Sending all hashrate to p2pool!");
-
-        self.send_all_p2pool().await
-    }
-
-    async fn fulfill_xvb_24_avg(&self) {
-        output_console(
-            &mut self.gui_api_xvb.lock().unwrap().output,
-            "24H avg XvB target not achieved. Sending all hashrate to XvB!",
-            crate::helper::ProcessName::Xvb,
-        );
-
-        info!("Algorithm | 24H avg XvB target not achieved. Sending all hashrate to XvB!");
-
-        *self.time_donated.lock().unwrap() = XVB_TIME_ALGO;
-
-        self.send_all_xvb().await
-    }
-
-    async fn fulfill_normal_cycles(&self) {
-        output_console(
-            &mut self.gui_api_xvb.lock().unwrap().output,
-            &format!(
-                "There is a share in p2pool and 24H avg XvB is achieved. Sending {} seconds to XvB!",
-                self.stats.needed_time_xvb
-            ),
-            crate::helper::ProcessName::Xvb,
-        );
-
-        *self.time_donated.lock().unwrap() = self.stats.needed_time_xvb;
-        // do not switch pool for a few seconds, let's make 6 seconds minimum.
-
-        match self.stats.needed_time_xvb {
-            x if x <= XVB_MIN_TIME_SEND => {
-                info!("Algorithm | Needed time: {x} to send on XvB is less than minimum time to send, sending all HR to p2pool");
-                self.send_all_p2pool().await;
-            }
-            x if x <= XVB_TIME_ALGO - XVB_MIN_TIME_SEND => {
-                info!("Algorithm | There is a share in p2pool and 24H avg XvB is achieved. Sending  {} seconds to XvB!", self.stats.needed_time_xvb);
-                self.target_p2pool_node().await;
-                self.sleep_then_update_node_xmrig().await;
-            }
-            x if x >= XVB_TIME_ALGO - XVB_MIN_TIME_SEND => {
-                info!("Algorithm | time : {x} seconds for XvB is more than time algo - minimum time to send, sending all to XvB");
-                self.send_all_xvb().await;
-            }
-            _ => error!("should not be possible"),
-        };
-    }
-
-    pub async fn run(&mut self) {
-        output_console(
-            &mut self.gui_api_xvb.lock().unwrap().output,
-            "Algorithm of HR distribution started for the next 10 minutes.",
-            crate::helper::ProcessName::Xvb,
-        );
-
-        info!("Algorithm | Starting...");
-        info!("Algorithm | {:#?}", self.stats);
-
-        // inform user about external HR detected to explain better the decision.
-        let external_xvb_hr = self.stats.xvb_external_hashrate;
-        if external_xvb_hr > 0.0 {
-            output_console(
-                &mut self.gui_api_xvb.lock().unwrap().output,
-                &format!(
-                    "estimated external HR on XvB: {:.3}kH/s",
-                    external_xvb_hr / 1000.0
-                ),
-                crate::helper::ProcessName::Xvb,
-            );
-        }
-        let external_p2pool_hr = self.stats.p2pool_external_hashrate;
-        if external_p2pool_hr > 0.0 {
-            output_console(
-                &mut self.gui_api_xvb.lock().unwrap().output,
-                &format!(
-                    "estimated external HR on P2pool: {:.3}kH/s",
-                    external_p2pool_hr / 1000.0
-                ),
-                crate::helper::ProcessName::Xvb,
-            );
-        }
-
-        if !self.is_share_fulfilled() {
-            self.fulfill_share().await
-        } else if !self.is_xvb_24h_fulfilled() {
-            self.fulfill_xvb_24_avg().await
-        } else {
-            self.fulfill_normal_cycles().await
-        }
-
-        output_console_without_time(
-            &mut self.gui_api_xvb.lock().unwrap().output,
-            "",
-            crate::helper::ProcessName::Xvb,
-        )
-    }
     // time needed to send on XvB get to the targeted doner round
     fn get_needed_time_xvb(target_donation_hashrate: f32, hashrate_xmrig: f32) -> u32 {
         let needed_time = target_donation_hashrate / hashrate_xmrig * (XVB_TIME_ALGO as f32);
@@ -1,15 +1,21 @@
+use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use crate::helper::console::ConsoleBuffer;
+use crate::helper::events::{self, WatchdogEvents};
+use crate::helper::notify::{Notifier, NotifyEvent};
 use crate::helper::xrig::update_xmrig_config;
 use crate::helper::xvb::algorithm::algorithm;
-use crate::helper::xvb::priv_stats::XvbPrivStats;
+use crate::helper::xvb::priv_stats::{RuntimeMode, XvbPrivStats};
 use crate::helper::xvb::public_stats::XvbPubStats;
-use crate::helper::{sleep_end_loop, ProcessName};
+use crate::helper::ProcessName;
 use crate::miscs::{client, output_console};
 use crate::{XMRIG_CONFIG_URL, XMRIG_PROXY_CONFIG_URL, XMRIG_PROXY_SUMMARY_URL, XMRIG_SUMMARY_URL};
 use bounded_vec_deque::BoundedVecDeque;
+use chrono::Local;
 use enclose::enc;
 use log::{debug, info, warn};
 use readable::up::Uptime;
 use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::Serialize;
 use std::mem;
 use std::time::Duration;
 use std::{
@@ -20,7 +26,7 @@ use tokio::spawn;
 use tokio::task::JoinHandle;
 use tokio::time::{sleep, Instant};
 
-use crate::helper::xvb::rounds::round_type;
+use crate::helper::xvb::rounds::{round_type, RoundTracker};
 use crate::utils::constants::{XVB_PUBLIC_ONLY, XVB_TIME_ALGO};
 use crate::{
     helper::{ProcessSignal, ProcessState},
@@ -39,13 +45,20 @@ pub mod nodes;
 pub mod priv_stats;
 pub mod public_stats;
 pub mod rounds;
+pub mod simulator;
+pub mod standalone;
 
 impl Helper {
     // Just sets some signals for the watchdog thread to pick up on.
     pub fn stop_xvb(helper: &Arc<Mutex<Self>>) {
         info!("XvB | Attempting to stop...");
-        helper.lock().unwrap().xvb.lock().unwrap().signal = ProcessSignal::Stop;
-        helper.lock().unwrap().xvb.lock().unwrap().state = ProcessState::Middle;
+        // Middle first, the watchdog handles the signal right away.
+        {
+            let helper = helper.lock().unwrap();
+            let mut process = helper.xvb.lock().unwrap();
+            process.set_state(ProcessState::Middle);
+            process.send_signal(ProcessSignal::Stop);
+        }
     }
     pub fn restart_xvb(
         helper: &Arc<Mutex<Self>>,
@@ -55,8 +68,13 @@ impl Helper {
         state_xp: &crate::disk::state::XmrigProxy,
     ) {
         info!("XvB | Attempting to restart...");
-        helper.lock().unwrap().xvb.lock().unwrap().signal = ProcessSignal::Restart;
-        helper.lock().unwrap().xvb.lock().unwrap().state = ProcessState::Middle;
+        // Middle first, the watchdog handles the signal right away.
+        {
+            let helper = helper.lock().unwrap();
+            let mut process = helper.xvb.lock().unwrap();
+            process.set_state(ProcessState::Middle);
+            process.send_signal(ProcessSignal::Restart);
+        }
         let helper = helper.clone();
         let state_xvb = state_xvb.clone();
         let state_p2pool = state_p2pool.clone();
@@ -99,6 +117,9 @@ impl Helper {
         // ex: read hashrate values from gui, update node to pub.
         let gui_api_xmrig = Arc::clone(&helper.lock().unwrap().gui_api_xmrig);
         let gui_api_xp = Arc::clone(&helper.lock().unwrap().gui_api_xp);
+        let notifier = helper.lock().unwrap().notifier.clone();
+        // the raffles are recorded next to the payouts.
+        let gupax_p2pool_api = Arc::clone(&helper.lock().unwrap().gupax_p2pool_api);
         // Reset before printing to output.
         // Need to reset because values of stats would stay otherwise which could bring confusion even if panel is with a disabled theme.
         // at the start of a process, values must be default.
@@ -106,6 +127,7 @@ impl Helper {
             "XvB | resetting pub and gui but keep current node as it is updated by xmrig console."
         );
         reset_data_xvb(&pub_api, &gui_api);
+        pub_api.lock().unwrap().extra_pool = state_xvb.extra_pool().map(|pool| pool.url);
         // we reset the console output because it is complete start.
         gui_api.lock().unwrap().output.clear();
         // 2. Set process state
@@ -115,7 +137,7 @@ impl Helper {
         info!("XvB | Setting process state...");
         {
             let mut lock = process.lock().unwrap();
-            lock.state = ProcessState::Middle;
+            lock.set_state(ProcessState::Middle);
             lock.signal = ProcessSignal::None;
             lock.start = std::time::Instant::now();
         }
@@ -139,6 +161,8 @@ impl Helper {
                     &process_xmrig,
                     &gui_api_xp,
                     &process_xp,
+                    &notifier,
+                    &gupax_p2pool_api,
                 );
             }),
         );
@@ -160,9 +184,13 @@ impl Helper {
         process_xmrig: &Arc<Mutex<Process>>,
         gui_api_xp: &Arc<Mutex<PubXmrigProxyApi>>,
         process_xp: &Arc<Mutex<Process>>,
+        notifier: &Notifier,
+        gupax_p2pool_api: &Arc<Mutex<GupaxP2poolApi>>,
     ) {
         // create uniq client that is going to be used for during the life of the thread.
         let client = client();
+        // no PTY here, only signals and the timer will wake up the loop.
+        let mut events = WatchdogEvents::new(process);
         // checks confition to start XvB, will set proper state of XvB.
         // if state is middle (everything fine here),set which xvb node could be used.
         // should wait for it, because algo needs to not be started if at least one node of XvB are not responsive.
@@ -194,6 +222,8 @@ impl Helper {
         let retry = Arc::new(Mutex::new(false));
         // time donated by algorithm. With being persistent across loop, we can construct the indicator.
         let time_donated = Arc::new(Mutex::new(0));
+        // the round being played until the next raffle, with the time donated during it.
+        let round_tracker = Arc::new(Mutex::new(RoundTracker::default()));
         // let handles;
         let handle_algo = Arc::new(Mutex::new(None));
         let handle_request = Arc::new(Mutex::new(None));
@@ -206,9 +236,15 @@ impl Helper {
         //
         info!("XvB | Entering Process mode... ");
         loop {
-            debug!("XvB Watchdog | ----------- Start of loop -----------");
-            // Set timer of loop
-            let start_loop = std::time::Instant::now();
+            // Hand over to the GUI what the last event changed.
+            PubXvbApi::combine_gui_pub_api(
+                &mut gui_api.lock().unwrap(),
+                &mut pub_api.lock().unwrap(),
+            );
+            events::changed();
+            // Wait for something to happen
+            let event = events.next().await;
+            debug!("XvB Watchdog | ----------- {:?} -----------", event);
             {
                 // check if first loop the state of Xmrig-Proxy
                 if first_loop {
@@ -249,6 +285,7 @@ impl Helper {
                     state_xmrig,
                     state_xp,
                     xp_alive,
+                    notifier,
                 ) {
                     info!("XvB Watchdog | Signal has stopped the loop");
                     break;
@@ -288,11 +325,24 @@ impl Helper {
                     // first_loop is false here but could be changed to true under some conditions.
                     // will send a stop signal if public stats failed or update data with new one.
                     *handle_request.lock().unwrap() = Some(spawn(
-                        enc!((client, pub_api, gui_api, gui_api_p2pool, gui_api_xmrig, gui_api_xp,  state_xvb, state_p2pool, state_xmrig, state_xp, process, last_algorithm, retry, handle_algo, time_donated, last_request) async move {
+                        enc!((client, pub_api, gui_api, gui_api_p2pool, gui_api_xmrig, gui_api_xp,  state_xvb, state_p2pool, state_xmrig, state_xp, process, last_algorithm, retry, handle_algo, time_donated, last_request, notifier, round_tracker, gupax_p2pool_api) async move {
                                 // needs to wait here for public stats to get private stats.
                                 if last_request_expired || first_loop || should_refresh_before_next_algo {
                                 XvbPubStats::update_stats(&client, &gui_api, &pub_api, &process).await;
                                     *last_request.lock().unwrap() = Instant::now();
+                                    // a new raffle ends the round we were playing, record it.
+                                    let record = {
+                                        let api = pub_api.lock().unwrap();
+                                        round_tracker.lock().unwrap().update(&api.stats_pub, &api.stats_priv, &state_p2pool.address, Local::now().timestamp().max(0) as u64)
+                                    };
+                                    if let Some(record) = record.filter(|_| process.lock().unwrap().state == ProcessState::Alive) {
+                                        info!("XvB | Raffle of block {} drawn, recording the round", record.block_height);
+                                        // the winner is checked again for the new round.
+                                        pub_api.lock().unwrap().stats_priv.win_current = false;
+                                        if let Err(e) = gupax_p2pool_api.lock().unwrap().xvb_rounds.push(record) {
+                                            warn!("XvB | Could not record the round: {}", e);
+                                        }
+                                    }
                                 }
                                 // private stats needs valid token and address.
                                 // other stats needs everything to be alive, so just require alive here for now.
@@ -304,6 +354,8 @@ impl Helper {
                                 // private stats can be requested every minute or first loop or if the have almost finished.
                                 if last_request_expired || first_loop || should_refresh_before_next_algo {
                                     debug!("XvB Watchdog | Attempting HTTP private API request...");
+                                    // Replaced by the request, the win is notified once per round.
+                                    let won = pub_api.lock().unwrap().stats_priv.win_current;
                                     // reload private stats, it send a signal if error that will be captured on the upper thread.
                                     XvbPrivStats::update_stats(
                                         &client, &state_p2pool.address, &state_xvb.token, &pub_api, &gui_api, &process,
@@ -320,7 +372,10 @@ impl Helper {
                                     if pub_api.lock().unwrap().stats_pub.winner
                                         == Helper::head_tail_of_monero_address(&state_p2pool.address).as_str()
                                     {
-                                        pub_api.lock().unwrap().stats_priv.win_current = true
+                                        pub_api.lock().unwrap().stats_priv.win_current = true;
+                                        if !won {
+                                            notifier.notify(NotifyEvent::XvbWin);
+                                        }
                                     }
                                 }
                                 let hashrate = current_controllable_hr(xp_alive, &gui_api_xp, &gui_api_xmrig);
@@ -331,7 +386,7 @@ impl Helper {
                                         *retry.lock().unwrap() = false;
                                         // reset instant because algo will start.
                                         *last_algorithm.lock().unwrap() = Instant::now();
-                                        *handle_algo.lock().unwrap() = Some(spawn(enc!((client, gui_api,  gui_api_xmrig, gui_api_xp, state_xmrig, state_xp, time_donated, state_xvb) async move {
+                                        *handle_algo.lock().unwrap() = Some(spawn(enc!((client, gui_api,  gui_api_xmrig, gui_api_xp, state_xmrig, state_xp, time_donated, state_xvb, round_tracker) async move {
                         let token_xmrig = if xp_alive {
                             &state_xp.token
                         } else {
@@ -341,6 +396,11 @@ impl Helper {
                             ""
                         } else {
                             &state_xmrig.rig
+                        };
+                        let stratum_logins = if xp_alive {
+                            state_xp.stratum_logins()
+                        } else {
+                            state_xmrig.stratum_logins()
                         };
                                             algorithm(
                                                 &client,
@@ -355,8 +415,10 @@ impl Helper {
                                                 &time_donated,
                                                 rig,
                                                 xp_alive,
-                                                state_xvb.p2pool_buffer
+                                                &state_xvb,
+                                                &stratum_logins
                                             ).await;
+                                            round_tracker.lock().unwrap().donated(*time_donated.lock().unwrap());
                                         })));
                                     } else {
                                         // if xmrig is still at 0 HR but is alive and algorithm is skipped, recheck first 10s of xmrig inside algorithm next time (in one minute). Don't check if algo failed to start because state was not alive after getting private stats.
@@ -387,7 +449,7 @@ impl Helper {
                 }
                 // update indicator (time before switch and mining location) in private stats
                 // if algo not running, second message.
-                // will update countdown every second.
+                // will update countdown every tick.
                 // verify current node which is set by algo or circonstances (failed node).
                 // verify given time set by algo and start time of current algo.
                 // will run only if XvB is alive.
@@ -404,19 +466,21 @@ impl Helper {
                 if first_loop {
                     first_loop = false;
                 }
-                // Sleep (only if 900ms hasn't passed)
             }
-            sleep_end_loop(start_loop, ProcessName::Xvb).await;
         }
     }
 }
 //---------------------------------------------------------------------------------------------------- Public XvB API
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct PubXvbApi {
-    pub output: String,
+    #[serde(skip)]
+    pub output: ConsoleBuffer,
+    #[serde(skip)]
     pub _uptime: u64,
+    #[serde(skip)]
     pub xvb_sent_last_hour_samples: SamplesAverageHour,
+    #[serde(skip)]
     pub p2pool_sent_last_hour_samples: SamplesAverageHour,
     pub stats_pub: XvbPubStats,
     pub stats_priv: XvbPrivStats,
@@ -424,6 +488,10 @@ pub struct PubXvbApi {
     // will be updated by output of xmrig.
     // could also be retrieved by fetching current config.
     pub current_node: Option<XvbNode>,
+    // host:port of the other pool of the schedule, which is not a failing node
+    // when XMRig switches to it.
+    #[serde(skip)]
+    pub extra_pool: Option<String>,
 }
 #[derive(Debug, Clone)]
 pub struct SamplesAverageHour(BoundedVecDeque<f32>);
@@ -437,21 +505,43 @@ impl Default for SamplesAverageHour {
         SamplesAverageHour(vec)
     }
 }
+impl PartialEq for SamplesAverageHour {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+impl SamplesAverageHour {
+    // push new value into samples before executing this calcul
+    pub fn average(&self) -> f32 {
+        self.0.iter().sum::<f32>() / self.0.len() as f32
+    }
+}
 
 impl PubXvbApi {
     pub fn new() -> Self {
         Self::default()
     }
+    // Makes the mode saved in [xvb] the one used at runtime.
+    // The simple tab only knows about Hero, anything else is Auto.
+    pub fn set_runtime_mode(&mut self, xvb: &crate::disk::state::Xvb) {
+        self.stats_priv.runtime_mode = if xvb.simple {
+            if xvb.simple_hero_mode {
+                RuntimeMode::Hero
+            } else {
+                RuntimeMode::Auto
+            }
+        } else {
+            xvb.mode.clone().into()
+        };
+        self.stats_priv.runtime_manual_amount = xvb.manual_amount_raw;
+    }
     // The issue with just doing [gui_api = pub_api] is that values get overwritten.
     // This doesn't matter for any of the values EXCEPT for the output,  so we must
     // manually append it instead of overwriting.
     // This is used in the "helper" thread.
     pub(super) fn combine_gui_pub_api(gui_api: &mut Self, pub_api: &mut Self) {
         let mut output = std::mem::take(&mut gui_api.output);
-        let buf = std::mem::take(&mut pub_api.output);
-        if !buf.is_empty() {
-            output.push_str(&buf);
-        }
+        output.append(&mut pub_api.output);
         let runtime_mode = std::mem::take(&mut gui_api.stats_priv.runtime_mode);
         let runtime_manual_amount = std::mem::take(&mut gui_api.stats_priv.runtime_manual_amount);
         let runtime_manual_donation_level =
@@ -529,8 +619,11 @@ async fn check_conditions_for_start(
         );
     }
     // will update the preferred node for the first loop, even if partially started.
-    process_xvb.lock().unwrap().signal = ProcessSignal::UpdateNodes(XvbNode::default());
-    process_xvb.lock().unwrap().state = state;
+    process_xvb
+        .lock()
+        .unwrap()
+        .send_signal(ProcessSignal::UpdateNodes(XvbNode::default()));
+    process_xvb.lock().unwrap().set_state(state);
 }
 /// return a bool to continue to next loop if needed.
 #[allow(clippy::too_many_arguments)]
@@ -653,12 +746,12 @@ async fn check_state_outcauses_xvb(
                 XVB_PUBLIC_ONLY,
                 ProcessName::Xvb,
             );
-            process.lock().unwrap().state = ProcessState::Syncing;
+            process.lock().unwrap().set_state(ProcessState::Syncing);
         }
         ProcessState::Syncing if p2pool_xmrig_alive => {
             info!("XvB | started this time with p2pool and xmrig");
             // will put state on middle and update nodes
-            process.lock().unwrap().state = ProcessState::Alive;
+            process.lock().unwrap().set_state(ProcessState::Alive);
             reset_data_xvb(pub_api, gui_api);
             *first_loop = true;
             output_console(
@@ -689,6 +782,7 @@ fn signal_interrupt(
     state_xmrig: &crate::disk::state::Xmrig,
     state_xp: &crate::disk::state::XmrigProxy,
     xp_alive: bool,
+    notifier: &Notifier,
 ) -> bool {
     // Check SIGNAL
     // check if STOP or RESTART Signal is given.
@@ -713,7 +807,7 @@ fn signal_interrupt(
             );
             debug!("XvB Watchdog | Stop SIGNAL done, breaking");
             process.lock().unwrap().signal = ProcessSignal::None;
-            process.lock().unwrap().state = ProcessState::Dead;
+            process.lock().unwrap().set_state(ProcessState::Dead);
             // reset stats
             reset_data_xvb(pub_api, gui_api);
             return true;
@@ -724,7 +818,7 @@ fn signal_interrupt(
             info!("XvB Watchdog | Stopped ... Uptime was: [{}]", uptime);
             // no output to console because service will be started with fresh output.
             debug!("XvB Watchdog | Restart SIGNAL done, breaking");
-            process.lock().unwrap().state = ProcessState::Waiting;
+            process.lock().unwrap().set_state(ProcessState::Waiting);
             reset_data_xvb(pub_api, gui_api);
             return true;
         }
@@ -749,21 +843,21 @@ fn signal_interrupt(
                 // if XvB was not alive, then if it is for XvB nodes, it will check and update preferred node and set XMRig to P2pool if that's not the case.
                 let was_alive = process.lock().unwrap().state == ProcessState::Alive;
                 // so it won't execute another signal of update nodes if it is already doing it.
-                process.lock().unwrap().state = ProcessState::Waiting;
+                process.lock().unwrap().set_state(ProcessState::Waiting);
                 process.lock().unwrap().signal = ProcessSignal::None;
                 spawn(
-                    enc!((node, process, client, gui_api, pub_api, was_alive, address, token_xmrig, process_xrig) async move {
+                    enc!((node, process, client, gui_api, pub_api, was_alive, address, token_xmrig, process_xrig, notifier) async move {
                     match node {
                         XvbNode::NorthAmerica|XvbNode::Europe if was_alive => {
                             // a node is failing. We need to first verify if a node is available
-                        XvbNode::update_fastest_node(&client, &gui_api, &pub_api, &process).await;
+                        XvbNode::update_fastest_node(&client, &gui_api, &pub_api, &process, &notifier).await;
                             if process.lock().unwrap().state == ProcessState::OfflineNodesAll {
                                 // No available nodes, so launch a process to verify periodically.
                     sleep(Duration::from_secs(10)).await;
                     warn!("node fail, set spawn that will retry nodes and update state.");
                     while process.lock().unwrap().state == ProcessState::OfflineNodesAll {
                         // this spawn will stay alive until nodes are joignable or XvB process is stopped or failed.
-                        XvbNode::update_fastest_node(&client, &pub_api, &gui_api, &process).await;
+                        XvbNode::update_fastest_node(&client, &pub_api, &gui_api, &process, &notifier).await;
                         sleep(Duration::from_secs(10)).await;
                     }
                                 
@@ -773,11 +867,11 @@ fn signal_interrupt(
                             
                         },
                         XvbNode::NorthAmerica|XvbNode::Europe if !was_alive => {
-                        process.lock().unwrap().state = ProcessState::Syncing;
+                        process.lock().unwrap().set_state(ProcessState::Syncing);
                         // Probably a start. We don't consider XMRig using XvB nodes without algo.
                         // can update xmrig and check status of state in the same time.
                         // update prefred node
-                        XvbNode::update_fastest_node(&client, &gui_api, &pub_api, &process).await;
+                        XvbNode::update_fastest_node(&client, &gui_api, &pub_api, &process, &notifier).await;
                         // Need to set XMRig to P2Pool if it wasn't. XMRig should have populated this value at his start.
                         // but if xmrig didn't start, don't update it.
                 
@@ -852,9 +946,14 @@ fn update_indicator_algo(
         && process.lock().unwrap().state == ProcessState::Alive
     {
         let node = pub_api.lock().unwrap().current_node;
+        let on_extra_pool = node.is_none() && pub_api.lock().unwrap().extra_pool.is_some();
         let msg_indicator = match node {
-            Some(XvbNode::P2pool) if time_donated > 0 && time_donated != XVB_TIME_ALGO => {
-                // algo is mining on p2pool but will switch to XvB after
+            Some(XvbNode::P2pool) | None
+                if (node.is_some() || on_extra_pool)
+                    && time_donated > 0
+                    && time_donated != XVB_TIME_ALGO =>
+            {
+                // algo is mining on p2pool or the other pool but will switch to XvB after
                 // show time remaining on p2pool
 
                 pub_api.lock().unwrap().stats_priv.time_switch_node = XVB_TIME_ALGO
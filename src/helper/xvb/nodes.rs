@@ -6,16 +6,21 @@ use std::{
 use derive_more::Display;
 use log::{error, info, warn};
 use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::Serialize;
 use tokio::spawn;
 
 use crate::{
     components::node::{GetInfo, TIMEOUT_NODE_PING},
-    helper::{xvb::output_console, Process, ProcessName, ProcessState},
+    helper::{
+        notify::{Notifier, NotifyEvent},
+        xvb::output_console,
+        Process, ProcessName, ProcessState,
+    },
     GUPAX_VERSION_UNDERSCORE, XVB_NODE_EU, XVB_NODE_NA, XVB_NODE_PORT, XVB_NODE_RPC,
 };
 
 use super::PubXvbApi;
-#[derive(Copy, Clone, Debug, Default, PartialEq, Display)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Display, Serialize)]
 pub enum XvbNode {
     #[display("XvB North America Node")]
     NorthAmerica,
@@ -67,13 +72,57 @@ impl XvbNode {
             Self::XmrigProxy => false,
         }
     }
+    // The pool XMRig or XMRig-Proxy is configured with to mine on this node.
+    pub fn pool(&self, address: &str) -> Pool {
+        Pool {
+            url: [self.url(), ":".to_string(), self.port()].concat(),
+            user: self.user(&address.chars().take(8).collect::<String>()),
+            tls: self.tls(),
+            keepalive: self.keepalive(),
+        }
+    }
 
     pub async fn update_fastest_node(
         client: &Client,
         pub_api_xvb: &Arc<Mutex<PubXvbApi>>,
         gui_api_xvb: &Arc<Mutex<PubXvbApi>>,
         process_xvb: &Arc<Mutex<Process>>,
+        notifier: &Notifier,
     ) {
+        let node = Self::fastest(client).await;
+        if node == XvbNode::P2pool {
+            // if both nodes are dead, then the state of the process must be NodesOffline
+            info!("XvB node ping, all offline or ping failed, switching back to local p2pool",);
+            output_console(
+                &mut gui_api_xvb.lock().unwrap().output,
+                "XvB node ping, all offline or ping failed, switching back to local p2pool",
+                ProcessName::Xvb,
+            );
+            let mut process = process_xvb.lock().unwrap();
+            if process.state != ProcessState::OfflineNodesAll {
+                notifier.notify(NotifyEvent::XvbOffline);
+            }
+            process.set_state(ProcessState::OfflineNodesAll);
+        } else {
+            // if node is up and because update_fastest is used only if token/address is valid, it means XvB process is Alive.
+            info!("XvB node ping, both online and best is {}", node.url());
+            output_console(
+                &mut gui_api_xvb.lock().unwrap().output,
+                &format!("XvB node ping, {} is selected as the fastest.", node),
+                ProcessName::Xvb,
+            );
+            info!("ProcessState to Syncing after finding joinable node");
+            // could be used by xmrig who signal that a node is not joignable
+            // or by the start of xvb
+            // next iteration of the loop of XvB process will verify if all conditions are met to be alive.
+            if process_xvb.lock().unwrap().state != ProcessState::Syncing {
+                process_xvb.lock().unwrap().set_state(ProcessState::Syncing);
+            }
+        }
+        pub_api_xvb.lock().unwrap().stats_priv.node = node;
+    }
+    // The XvB node answering the fastest, [P2pool] if none does.
+    pub async fn fastest(client: &Client) -> Self {
         let client_eu = client.clone();
         let client_na = client.clone();
         // two spawn to ping the two nodes in parallel and not one after the other.
@@ -85,7 +134,7 @@ impl XvbNode {
             info!("Node | ping North America Node");
             XvbNode::ping(&XvbNode::NorthAmerica.url(), &client_na).await
         });
-        let node = if let Ok(ms_eu) = ms_eu.await {
+        if let Ok(ms_eu) = ms_eu.await {
             if let Ok(ms_na) = ms_na.await {
                 // if two nodes are up, compare ping latency and return fastest.
                 if ms_na != TIMEOUT_NODE_PING && ms_eu != TIMEOUT_NODE_PING {
@@ -111,33 +160,7 @@ impl XvbNode {
         } else {
             error!("ping has failed !");
             XvbNode::P2pool
-        };
-        if node == XvbNode::P2pool {
-            // if both nodes are dead, then the state of the process must be NodesOffline
-            info!("XvB node ping, all offline or ping failed, switching back to local p2pool",);
-            output_console(
-                &mut gui_api_xvb.lock().unwrap().output,
-                "XvB node ping, all offline or ping failed, switching back to local p2pool",
-                ProcessName::Xvb,
-            );
-            process_xvb.lock().unwrap().state = ProcessState::OfflineNodesAll;
-        } else {
-            // if node is up and because update_fastest is used only if token/address is valid, it means XvB process is Alive.
-            info!("XvB node ping, both online and best is {}", node.url());
-            output_console(
-                &mut gui_api_xvb.lock().unwrap().output,
-                &format!("XvB node ping, {} is selected as the fastest.", node),
-                ProcessName::Xvb,
-            );
-            info!("ProcessState to Syncing after finding joinable node");
-            // could be used by xmrig who signal that a node is not joignable
-            // or by the start of xvb
-            // next iteration of the loop of XvB process will verify if all conditions are met to be alive.
-            if process_xvb.lock().unwrap().state != ProcessState::Syncing {
-                process_xvb.lock().unwrap().state = ProcessState::Syncing;
-            }
         }
-        pub_api_xvb.lock().unwrap().stats_priv.node = node;
     }
     async fn ping(ip: &str, client: &Client) -> u128 {
         let request = client
@@ -185,3 +208,14 @@ impl XvbNode {
         ms
     }
 }
+
+//---------------------------------------------------------------------------------------------------- Pool
+// A pool as written in the config of XMRig or XMRig-Proxy.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pool {
+    // host:port
+    pub url: String,
+    pub user: String,
+    pub tls: bool,
+    pub keepalive: bool,
+}
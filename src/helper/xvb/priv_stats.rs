@@ -7,7 +7,7 @@ use anyhow::bail;
 use log::{debug, error, info, warn};
 use reqwest::StatusCode;
 use reqwest_middleware::ClientWithMiddleware as Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     disk::state::ManualDonationLevel,
@@ -21,7 +21,7 @@ use crate::{
 
 use super::{nodes::XvbNode, rounds::XvbRound, PubXvbApi};
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
 pub enum RuntimeMode {
     #[default]
     Auto,
@@ -31,7 +31,7 @@ pub enum RuntimeMode {
     ManualDonationLevel,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
 pub enum RuntimeDonationLevel {
     #[default]
     Donor,
@@ -51,29 +51,29 @@ impl RuntimeDonationLevel {
     }
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct XvbPrivStats {
     pub fails: u8,
     pub donor_1hr_avg: f32,
     pub donor_24hr_avg: f32,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub win_current: bool,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub round_participate: Option<XvbRound>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub node: XvbNode,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     // it is the time remaining before switching from P2pool to XvB or XvB to P2ool.
     // it is not the time remaining of the algo, even if it could be the same if never mining on XvB.
     pub time_switch_node: u32,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub msg_indicator: String,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     // so the hero mode can change between two decision of algorithm without restarting XvB.
     pub runtime_mode: RuntimeMode,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub runtime_manual_amount: f64,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub runtime_manual_donation_level: RuntimeDonationLevel,
 }
 
@@ -130,7 +130,7 @@ impl XvbPrivStats {
                         "requests for public API are now working",
                         ProcessName::Xvb,
                     );
-                    process.lock().unwrap().state = ProcessState::Syncing;
+                    process.lock().unwrap().set_state(ProcessState::Syncing);
                 }
                 // if last request failed, we are now ready to show stats again and maybe be alive next loop.
             }
@@ -152,7 +152,7 @@ impl XvbPrivStats {
                     "request to get private API failed",
                     ProcessName::Xvb,
                 );
-                process.lock().unwrap().state = ProcessState::Failed;
+                process.lock().unwrap().set_state(ProcessState::Failed);
             }
         }
     }
@@ -5,7 +5,7 @@ use std::{
 
 use log::{debug, info, warn};
 use reqwest_middleware::ClientWithMiddleware as Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_this_or_that::as_u64;
 
 use crate::{
@@ -16,7 +16,7 @@ use crate::{
 use super::{rounds::XvbRound, PubXvbApi};
 
 #[allow(dead_code)] // because deserialize doesn't use all the fields
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct XvbPubStats {
     pub time_remain: u32, // remaining time of round in minutes
     pub bonus_hr: f64,
@@ -71,7 +71,7 @@ impl XvbPubStats {
                         "requests for public API are now working",
                         ProcessName::Xvb,
                     );
-                    process.lock().unwrap().state = ProcessState::Syncing;
+                    process.lock().unwrap().set_state(ProcessState::Syncing);
                 }
             }
             Err(err) => {
@@ -97,7 +97,7 @@ impl XvbPubStats {
                     "request to get public API failed",
                     ProcessName::Xvb,
                 );
-                process.lock().unwrap().state = ProcessState::Failed;
+                process.lock().unwrap().set_state(ProcessState::Failed);
             }
         }
     }
@@ -1,15 +1,17 @@
 use std::sync::{Arc, Mutex};
 
 use derive_more::Display;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::disk::xvb_rounds::XvbRoundRecord;
+use crate::helper::Helper;
 use crate::{
     XVB_ROUND_DONOR_MEGA_MIN_HR, XVB_ROUND_DONOR_MIN_HR, XVB_ROUND_DONOR_VIP_MIN_HR,
     XVB_ROUND_DONOR_WHALE_MIN_HR, XVB_SIDE_MARGIN_1H,
 };
 
-use super::PubXvbApi;
-#[derive(Debug, Clone, Default, Display, Deserialize, PartialEq)]
+use super::{priv_stats::XvbPrivStats, public_stats::XvbPubStats, PubXvbApi};
+#[derive(Debug, Clone, Default, Display, Deserialize, Serialize, PartialEq)]
 pub enum XvbRound {
     #[default]
     #[display("VIP")]
@@ -29,27 +31,80 @@ pub enum XvbRound {
 }
 
 pub(crate) fn round_type(share: u32, pub_api: &Arc<Mutex<PubXvbApi>>) -> Option<XvbRound> {
-    if share > 0 {
-        let stats_priv = &pub_api.lock().unwrap().stats_priv;
-        match (
-            ((stats_priv.donor_1hr_avg * 1000.0) * XVB_SIDE_MARGIN_1H) as u32,
-            (stats_priv.donor_24hr_avg * 1000.0) as u32,
-        ) {
-            x if x.0 >= XVB_ROUND_DONOR_MEGA_MIN_HR && x.1 >= XVB_ROUND_DONOR_MEGA_MIN_HR => {
-                Some(XvbRound::DonorMega)
-            }
-            x if x.0 >= XVB_ROUND_DONOR_WHALE_MIN_HR && x.1 >= XVB_ROUND_DONOR_WHALE_MIN_HR => {
-                Some(XvbRound::DonorWhale)
-            }
-            x if x.0 >= XVB_ROUND_DONOR_VIP_MIN_HR && x.1 >= XVB_ROUND_DONOR_VIP_MIN_HR => {
-                Some(XvbRound::DonorVip)
-            }
-            x if x.0 >= XVB_ROUND_DONOR_MIN_HR && x.1 >= XVB_ROUND_DONOR_MIN_HR => {
-                Some(XvbRound::Donor)
+    let stats_priv = &pub_api.lock().unwrap().stats_priv;
+    XvbRound::of(
+        share,
+        stats_priv.donor_1hr_avg * 1000.0,
+        stats_priv.donor_24hr_avg * 1000.0,
+    )
+}
+
+impl XvbRound {
+    // The round a donor takes part in, from its averages in H/s.
+    // [None] without a share in the PPLNS window.
+    pub fn of(share: u32, avg_1h: f32, avg_24h: f32) -> Option<Self> {
+        if share > 0 {
+            match ((avg_1h * XVB_SIDE_MARGIN_1H) as u32, avg_24h as u32) {
+                x if x.0 >= XVB_ROUND_DONOR_MEGA_MIN_HR && x.1 >= XVB_ROUND_DONOR_MEGA_MIN_HR => {
+                    Some(XvbRound::DonorMega)
+                }
+                x if x.0 >= XVB_ROUND_DONOR_WHALE_MIN_HR && x.1 >= XVB_ROUND_DONOR_WHALE_MIN_HR => {
+                    Some(XvbRound::DonorWhale)
+                }
+                x if x.0 >= XVB_ROUND_DONOR_VIP_MIN_HR && x.1 >= XVB_ROUND_DONOR_VIP_MIN_HR => {
+                    Some(XvbRound::DonorVip)
+                }
+                x if x.0 >= XVB_ROUND_DONOR_MIN_HR && x.1 >= XVB_ROUND_DONOR_MIN_HR => {
+                    Some(XvbRound::Donor)
+                }
+                (_, _) => Some(XvbRound::Vip),
             }
-            (_, _) => Some(XvbRound::Vip),
+        } else {
+            None
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Tracker
+// Follows the raffles of XvB from its public stats: a new block height is a new raffle,
+// which ends the round the record is made of.
+#[derive(Debug, Default)]
+pub struct RoundTracker {
+    block_height: Option<u64>,
+    xvb_seconds: u32,
+}
+
+impl RoundTracker {
+    // Time the algorithm gave to XvB, counted in the round being played.
+    pub fn donated(&mut self, seconds: u32) {
+        self.xvb_seconds += seconds;
+    }
+    // Called with the public stats just received, before the private ones are refreshed
+    // so [stats_priv] is still what the round ended with.
+    // [None] until the first raffle seen after the start.
+    pub fn update(
+        &mut self,
+        stats_pub: &XvbPubStats,
+        stats_priv: &XvbPrivStats,
+        address: &str,
+        timestamp: u64,
+    ) -> Option<XvbRoundRecord> {
+        // not received yet.
+        if stats_pub.block_height == 0 {
+            return None;
+        }
+        let previous = self.block_height.replace(stats_pub.block_height)?;
+        if previous == stats_pub.block_height {
+            return None;
         }
-    } else {
-        None
+        Some(XvbRoundRecord {
+            timestamp,
+            block_height: stats_pub.block_height,
+            round: stats_priv.round_participate.clone(),
+            donor_1h_avg: stats_priv.donor_1hr_avg * 1000.0,
+            donor_24h_avg: stats_priv.donor_24hr_avg * 1000.0,
+            xvb_seconds: std::mem::take(&mut self.xvb_seconds),
+            won: stats_pub.winner == Helper::head_tail_of_monero_address(address),
+        })
     }
 }
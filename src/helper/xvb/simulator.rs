@@ -0,0 +1,292 @@
+// Replays rounds through the decision of the algorithm, to see how a change
+// of it behaves over days without mining.
+//
+// Each [Sample] is what a round of [XVB_TIME_ALGO] starts with. What is not
+// recorded in it is simulated from what the previous decisions sent:
+// - the share: kept while the hashrate sent to P2Pool over the PPLNS window,
+//   with the external one, is enough for the difficulty.
+// - the XvB averages: what was sent the last hour and day, with the external one.
+// Before the first round, everything is taken as sent to P2Pool.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+
+use readable::num::Float;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::SimulateArgs;
+use crate::helper::xvb::algorithm::{Decision, Destination, Inputs, Stats};
+use crate::helper::xvb::rounds::XvbRound;
+use crate::{
+    BLOCK_PPLNS_WINDOW_MAIN, BLOCK_PPLNS_WINDOW_MINI, SECOND_PER_BLOCK_P2POOL, XVB_TIME_ALGO,
+};
+
+const ROUNDS_HOUR: usize = (3600 / XVB_TIME_ALGO) as usize;
+const ROUNDS_DAY: usize = (86400 / XVB_TIME_ALGO) as usize;
+
+pub fn run(args: SimulateArgs) {
+    let samples = match &args.samples {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|string| Sample::read(&string).map_err(|e| e.to_string()))
+        {
+            Ok(samples) => samples,
+            Err(e) => {
+                eprintln!("Could not read [{}]: {e}", path.display());
+                std::process::exit(1);
+            }
+        },
+        None => Sample::synthetic(args.days, args.hashrate, args.variation, args.difficulty),
+    };
+    let report = simulate((&args.settings).into(), &samples);
+    if args.rounds {
+        print!("{}", report.rounds());
+    }
+    print!("{report}");
+}
+
+pub fn simulate(settings: Inputs, samples: &[Sample]) -> Report {
+    let mut simulator = Simulator::new(settings, samples.first().map_or(0.0, |s| s.hashrate));
+    Report {
+        rounds: samples.iter().map(|s| simulator.round(s)).collect(),
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Sample
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Sample {
+    pub hashrate: f32,
+    pub difficulty: u64,
+    pub share: Option<u32>,
+    // in H/s, like the others.
+    pub xvb_24h_avg: Option<f32>,
+    pub xvb_1h_avg: Option<f32>,
+    // hashrate of the address from other rigs.
+    pub p2pool_external: f32,
+    pub xvb_external: f32,
+}
+
+impl Sample {
+    // One JSON object per line.
+    pub fn read(string: &str) -> Result<Vec<Self>, serde_json::Error> {
+        string
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect()
+    }
+    // [days] of rounds, the hashrate going [variation] percent up and down during a day.
+    pub fn synthetic(days: u32, hashrate: f32, variation: f32, difficulty: u64) -> Vec<Self> {
+        (0..days as usize * ROUNDS_DAY)
+            .map(|round| {
+                let day = (round % ROUNDS_DAY) as f32 / ROUNDS_DAY as f32;
+                Self {
+                    hashrate: hashrate
+                        * (1.0 + variation / 100.0 * (day * std::f32::consts::TAU).sin()),
+                    difficulty,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Simulator
+pub struct Simulator {
+    settings: Inputs,
+    // hashrate sent each round, the oldest first.
+    p2pool_sent: VecDeque<f32>,
+    xvb_sent: VecDeque<f32>,
+    share: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Round {
+    pub hashrate: f32,
+    pub decision: Decision,
+    pub share_kept: bool,
+    // reached at the end of the round.
+    pub xvb_24h_avg: f32,
+    pub xvb_1h_avg: f32,
+    pub tier: Option<XvbRound>,
+}
+
+impl Simulator {
+    pub fn new(settings: Inputs, hashrate: f32) -> Self {
+        let pplns_window = if settings.mini {
+            BLOCK_PPLNS_WINDOW_MINI
+        } else {
+            BLOCK_PPLNS_WINDOW_MAIN
+        } * SECOND_PER_BLOCK_P2POOL;
+        let rounds_window = (pplns_window.div_ceil(XVB_TIME_ALGO.into()) as usize).max(ROUNDS_HOUR);
+        Self {
+            settings,
+            p2pool_sent: VecDeque::from(vec![hashrate; rounds_window]),
+            xvb_sent: VecDeque::from(vec![0.0; ROUNDS_DAY]),
+            share: 0,
+        }
+    }
+
+    pub fn round(&mut self, sample: &Sample) -> Round {
+        let share = sample.share.unwrap_or(self.share);
+        let p2pool_window = average(&self.p2pool_sent, self.p2pool_sent.len());
+        let stats = Stats::new(Inputs {
+            share,
+            hashrate: sample.hashrate,
+            p2pool_total_hashrate: p2pool_window + sample.p2pool_external,
+            p2pool_difficulty: sample.difficulty,
+            xvb_24h_avg: sample
+                .xvb_24h_avg
+                .unwrap_or(average(&self.xvb_sent, ROUNDS_DAY) + sample.xvb_external),
+            xvb_1h_avg: sample
+                .xvb_1h_avg
+                .unwrap_or(average(&self.xvb_sent, ROUNDS_HOUR) + sample.xvb_external),
+            p2pool_avg_last_hour_hashrate: average(&self.p2pool_sent, ROUNDS_HOUR),
+            xvb_avg_last_hour_hashrate: average(&self.xvb_sent, ROUNDS_HOUR),
+            ..self.settings.clone()
+        });
+        let decision = stats.decide();
+
+        let sent = |destination| {
+            sample.hashrate * decision.seconds(&destination) as f32 / XVB_TIME_ALGO as f32
+        };
+        push(&mut self.p2pool_sent, sent(Destination::P2pool));
+        push(&mut self.xvb_sent, sent(Destination::Xvb));
+
+        let minimum = Stats::minimum_hashrate_share(
+            sample.difficulty,
+            self.settings.mini,
+            sample.p2pool_external,
+            0,
+        );
+        let share_kept = average(&self.p2pool_sent, self.p2pool_sent.len()) >= minimum;
+        self.share = share_kept.into();
+        let xvb_24h_avg = average(&self.xvb_sent, ROUNDS_DAY) + sample.xvb_external;
+        let xvb_1h_avg = average(&self.xvb_sent, ROUNDS_HOUR) + sample.xvb_external;
+        Round {
+            hashrate: sample.hashrate,
+            decision,
+            share_kept,
+            xvb_24h_avg,
+            xvb_1h_avg,
+            tier: XvbRound::of(self.share, xvb_1h_avg, xvb_24h_avg),
+        }
+    }
+}
+
+// Average of the last [rounds].
+fn average(sent: &VecDeque<f32>, rounds: usize) -> f32 {
+    sent.iter().rev().take(rounds).sum::<f32>() / rounds as f32
+}
+
+fn push(sent: &mut VecDeque<f32>, hashrate: f32) {
+    sent.pop_front();
+    sent.push_back(hashrate);
+}
+
+//---------------------------------------------------------------------------------------------------- Report
+pub struct Report {
+    pub rounds: Vec<Round>,
+}
+
+impl Report {
+    pub fn share_keep_rate(&self) -> f32 {
+        self.rounds.iter().filter(|r| r.share_kept).count() as f32 / self.rounds.len().max(1) as f32
+    }
+    // Rounds reached, the best first, [None] for the ones without a share.
+    pub fn tiers(&self) -> Vec<(Option<XvbRound>, usize)> {
+        [
+            Some(XvbRound::DonorMega),
+            Some(XvbRound::DonorWhale),
+            Some(XvbRound::DonorVip),
+            Some(XvbRound::Donor),
+            Some(XvbRound::Vip),
+            None,
+        ]
+        .into_iter()
+        .map(|tier| {
+            let count = self.rounds.iter().filter(|r| r.tier == tier).count();
+            (tier, count)
+        })
+        .collect()
+    }
+    pub fn xvb_time_rate(&self) -> f32 {
+        self.rounds
+            .iter()
+            .map(|r| r.decision.xvb_time() as f32)
+            .sum::<f32>()
+            / (self.rounds.len().max(1) as f32 * XVB_TIME_ALGO as f32)
+    }
+    pub fn pool_time_rate(&self) -> f32 {
+        self.rounds
+            .iter()
+            .map(|r| r.decision.seconds_pool() as f32)
+            .sum::<f32>()
+            / (self.rounds.len().max(1) as f32 * XVB_TIME_ALGO as f32)
+    }
+    pub fn rounds(&self) -> Rounds<'_> {
+        Rounds(self)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rounds simulated: {}", self.rounds.len())?;
+        writeln!(
+            f,
+            "Share kept:       {}%",
+            Float::from_1(self.share_keep_rate() as f64 * 100.0)
+        )?;
+        writeln!(
+            f,
+            "Time on XvB:      {}%",
+            Float::from_1(self.xvb_time_rate() as f64 * 100.0)
+        )?;
+        if self.pool_time_rate() > 0.0 {
+            writeln!(
+                f,
+                "Time on the other pool: {}%",
+                Float::from_1(self.pool_time_rate() as f64 * 100.0)
+            )?;
+        }
+        writeln!(f, "Rounds reached:")?;
+        for (tier, count) in self.tiers() {
+            let name = tier.map_or("No share".to_string(), |t| t.to_string());
+            writeln!(f, "  {name:<12} {count}")?;
+        }
+        Ok(())
+    }
+}
+
+// Every round, one per line.
+pub struct Rounds<'a>(&'a Report);
+
+impl Display for Rounds<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>6} {:>9} {:>12} {:>6} {:>12} {:>6}  Round",
+            "Day", "Time", "Hashrate", "XvB", "24h avg", "Share"
+        )?;
+        for (i, round) in self.0.rounds.iter().enumerate() {
+            let minutes = i * XVB_TIME_ALGO as usize / 60;
+            writeln!(
+                f,
+                "{:>6} {:>6}:{:02} {:>12} {:>5}s {:>12} {:>6}  {}",
+                minutes / 1440 + 1,
+                minutes % 1440 / 60,
+                minutes % 60,
+                format!("{:.0}H/s", round.hashrate),
+                round.decision.xvb_time(),
+                format!("{:.0}H/s", round.xvb_24h_avg),
+                if round.share_kept { "kept" } else { "lost" },
+                round
+                    .tier
+                    .as_ref()
+                    .map_or("-".to_string(), |t| t.to_string()),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+// [gupaxx-xvb], the algorithm of the XvB process without Gupaxx.
+//
+// It distributes the hashrate of an XMRig or XMRig-Proxy started by the user
+// between P2Pool and XvB like the [XvB] tab does, without spawning any process
+// or the GUI. Everything is read from the outside:
+// - the hashrate from the HTTP API of XMRig or XMRig-Proxy, which is also
+//   used to switch pools, so it must not be restricted.
+// - the P2Pool difficulty and the shares from the [--data-api] directory.
+// - the XvB averages from the private API, with the address and its token.
+//
+// P2Pool only prints the shares of the PPLNS window on its [status] command.
+// Here they are read from the part of the block reward they are worth in
+// [local/stratum]: any share gives a part of it, and that part of the pool
+// hashrate is the hashrate P2Pool estimates for the user.
+// With [--stratum], the other miners are the workers listed there, but the ones
+// logged in with [--rig] or [--stratum-user].
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{info, warn};
+use reqwest::header::AUTHORIZATION;
+use reqwest_middleware::ClientWithMiddleware as Client;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::sleep;
+
+use crate::cli::{XvbCli, XvbSettings};
+use crate::disk::state::{ManualDonationLevel, XvbMode};
+use crate::helper::p2pool::{PrivP2poolPoolApi, StratumWorker};
+use crate::helper::xrig::update_xmrig_config;
+use crate::helper::xvb::algorithm::{Algorithm, Inputs, Miner};
+use crate::helper::xvb::nodes::{Pool, XvbNode};
+use crate::helper::xvb::priv_stats::XvbPrivStats;
+use crate::helper::xvb::SamplesAverageHour;
+use crate::helper::P2POOL_BLOCK_TIME_IN_SECONDS;
+use crate::miscs::{client, datetimeonsole};
+use crate::{P2POOL_API_PATH_LOCAL, P2POOL_API_PATH_POOL};
+
+const XMRIG_API: &str = "http://127.0.0.1:18088";
+const XMRIG_PROXY_API: &str = "http://127.0.0.1:18089";
+// Before a retry, when something the algorithm needs is missing.
+const RETRY: Duration = Duration::from_secs(10);
+
+#[tokio::main]
+pub async fn run(args: XvbCli) {
+    // Required by clap without a subcommand.
+    let (Some(data_api), Some(address), Some(token)) = (&args.data_api, &args.address, &args.token)
+    else {
+        unreachable!()
+    };
+    let client = client();
+    let api = args
+        .api
+        .clone()
+        .unwrap_or_else(|| {
+            if args.proxy {
+                XMRIG_PROXY_API
+            } else {
+                XMRIG_API
+            }
+            .to_string()
+        })
+        .trim_end_matches('/')
+        .to_string();
+    let miner = CliMiner {
+        client: client.clone(),
+        summary_url: [&api, "/1/summary"].concat(),
+        token: args.api_token.clone(),
+        proxy: args.proxy,
+        current_node: Mutex::new(None),
+        xvb_node: Mutex::new(XvbNode::default()),
+        p2pool_samples: Mutex::new(SamplesAverageHour::default()),
+        xvb_samples: Mutex::new(SamplesAverageHour::default()),
+    };
+    let config_url = [&api, "/1/config"].concat();
+    let rig = if args.proxy { "" } else { &args.rig };
+    if let Err(e) = XvbPrivStats::request_api(&client, address, token).await {
+        eprintln!("Could not verify the token and address on XvB API: {e}");
+        std::process::exit(1);
+    }
+    info!("XvB CLI | Running on [{}]", api);
+    tokio::select! {
+        _ = async {
+            loop {
+                round(&client, &args, data_api, address, token, &miner, &config_url, rig).await;
+            }
+        } => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    // Not left on XvB without the algorithm.
+    if miner.current_node() != Some(XvbNode::P2pool) {
+        miner.output(&format!("Stopping, back to {}", XvbNode::P2pool));
+        if let Err(e) = update_xmrig_config(
+            &client,
+            &config_url,
+            &miner.token,
+            &XvbNode::P2pool,
+            address,
+            rig,
+        )
+        .await
+        {
+            miner.output(&format!(
+                "Failure to update the config with HTTP API.\nError: {e}"
+            ));
+        }
+    }
+}
+
+// One run of the algorithm, or a wait until what it needs is there.
+#[allow(clippy::too_many_arguments)]
+async fn round(
+    client: &Client,
+    args: &XvbCli,
+    data_api: &Path,
+    address: &str,
+    token: &str,
+    miner: &CliMiner,
+    config_url: &str,
+    rig: &str,
+) {
+    let node = XvbNode::fastest(client).await;
+    *miner.xvb_node.lock().unwrap() = node;
+    if node == XvbNode::P2pool {
+        miner.output("XvB node ping, all offline or ping failed, mining on local p2pool");
+        if miner.current_node() != Some(XvbNode::P2pool)
+            && update_xmrig_config(client, config_url, &miner.token, &node, address, rig)
+                .await
+                .is_ok()
+        {
+            miner.mining_on(Some(node));
+        }
+        sleep(RETRY).await;
+        return;
+    }
+    let logins = [&args.stratum_user[..], &[args.rig.clone()]].concat();
+    let Some(p2pool) = P2poolData::read(data_api, &logins) else {
+        miner.output("Waiting for the data of P2Pool");
+        sleep(RETRY).await;
+        return;
+    };
+    let stats = match XvbPrivStats::request_api(client, address, token).await {
+        Ok(stats) => stats,
+        Err(e) => {
+            miner.output(&format!("request to get private API failed: {e}"));
+            sleep(RETRY).await;
+            return;
+        }
+    };
+    let hashrate = miner.hashrate().await;
+    if hashrate == 0.0 {
+        miner.output("Algorithm is waiting for the hashrate of XMRig or XMRig-Proxy");
+        sleep(RETRY).await;
+        return;
+    }
+    let inputs = Inputs {
+        share: p2pool.share,
+        hashrate,
+        address: address.to_string(),
+        p2pool_total_hashrate: p2pool.hashrate,
+        p2pool_stratum_hashrate: p2pool.stratum.filter(|_| args.stratum),
+        p2pool_difficulty: p2pool.difficulty,
+        xvb_24h_avg: stats.donor_24hr_avg * 1000.0,
+        xvb_1h_avg: stats.donor_1hr_avg * 1000.0,
+        p2pool_avg_last_hour_hashrate: miner.p2pool_samples.lock().unwrap().average(),
+        xvb_avg_last_hour_hashrate: miner.xvb_samples.lock().unwrap().average(),
+        api_url: config_url.to_string(),
+        xp: miner.proxy,
+        ..(&args.settings).into()
+    };
+    Algorithm::new(client, miner, &miner.token, rig, inputs)
+        .run()
+        .await;
+}
+
+// The settings of the [XvB] tab in the inputs of the algorithm.
+impl From<&XvbSettings> for Inputs {
+    fn from(settings: &XvbSettings) -> Self {
+        Self {
+            runtime_mode: XvbMode::from(settings.mode).into(),
+            runtime_donation_level: ManualDonationLevel::from(settings.level).into(),
+            runtime_amount: settings.amount,
+            mini: !settings.main,
+            p2pool_buffer: settings.buffer,
+            extra_pool: settings.extra_pool.as_ref().map(|url| Pool {
+                url: url.clone(),
+                user: settings.extra_pool_user.clone(),
+                tls: settings.extra_pool_tls,
+                keepalive: true,
+            }),
+            extra_pool_percent: settings.extra_pool_percent,
+            ..Default::default()
+        }
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- Miner
+struct CliMiner {
+    client: Client,
+    summary_url: String,
+    token: String,
+    proxy: bool,
+    // Only changed by the algorithm here.
+    current_node: Mutex<Option<XvbNode>>,
+    xvb_node: Mutex<XvbNode>,
+    p2pool_samples: Mutex<SamplesAverageHour>,
+    xvb_samples: Mutex<SamplesAverageHour>,
+}
+
+impl Miner for CliMiner {
+    fn current_node(&self) -> Option<XvbNode> {
+        *self.current_node.lock().unwrap()
+    }
+    fn mining_on(&self, node: Option<XvbNode>) {
+        *self.current_node.lock().unwrap() = node;
+    }
+    fn xvb_node(&self) -> XvbNode {
+        *self.xvb_node.lock().unwrap()
+    }
+    async fn hashrate(&self) -> f32 {
+        let request = self
+            .client
+            .get(&self.summary_url)
+            .header(AUTHORIZATION, ["Bearer ", &self.token].concat())
+            .timeout(Duration::from_secs(5));
+        match request.send().await {
+            Ok(response) => match response.json::<Value>().await {
+                Ok(summary) => summary_hashrate(&summary, self.proxy),
+                Err(e) => {
+                    warn!("XvB CLI | Could not read the summary: {}", e);
+                    0.0
+                }
+            },
+            Err(e) => {
+                warn!("XvB CLI | Could not request the summary: {}", e);
+                0.0
+            }
+        }
+    }
+    fn push_samples(&self, p2pool: f32, xvb: f32) {
+        self.p2pool_samples.lock().unwrap().0.push_back(p2pool);
+        self.xvb_samples.lock().unwrap().0.push_back(xvb);
+    }
+    // Only shown in the GUI.
+    fn set_time_donated(&self, _time: u32) {}
+    fn output(&self, msg: &str) {
+        println!("{}{msg}", datetimeonsole());
+    }
+}
+
+// Same averages as the XvB process, see [current_controllable_hr].
+// XMRig-Proxy gives [1m, 10m, 1h, 12h, 24h, all] in kH/s,
+// XMRig [10s, 1m, 15m] in H/s, [null] until they are known.
+pub fn summary_hashrate(summary: &Value, proxy: bool) -> f32 {
+    let total = |i: usize| {
+        summary
+            .pointer(&format!("/hashrate/total/{i}"))
+            .and_then(Value::as_f64)
+            .unwrap_or_default() as f32
+    };
+    if proxy {
+        if total(1) > 0.0 {
+            total(1) * 1000.0
+        } else {
+            total(0) * 1000.0
+        }
+    } else if total(2) > 0.0 {
+        total(2)
+    } else if total(1) > 0.0 {
+        total(1)
+    } else {
+        total(0)
+    }
+}
+
+//---------------------------------------------------------------------------------------------------- P2Pool
+// What the algorithm needs from the data-api directory of P2Pool.
+#[derive(Debug, PartialEq)]
+pub struct P2poolData {
+    pub share: u32,
+    pub hashrate: f32,
+    pub difficulty: u64,
+    // hashrate of the other miners on the stratum, [None] before P2Pool v3.
+    pub stratum: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct LocalStratum {
+    // Missing before P2Pool v3.
+    #[serde(default)]
+    block_reward_share_percent: f32,
+    #[serde(default)]
+    workers: Option<Vec<String>>,
+}
+
+impl P2poolData {
+    fn read(data_api: &Path, logins: &[String]) -> Option<Self> {
+        let local = std::fs::read_to_string(data_api.join(P2POOL_API_PATH_LOCAL)).ok()?;
+        let pool = std::fs::read_to_string(data_api.join(P2POOL_API_PATH_POOL)).ok()?;
+        Self::from_str(&local, &pool, logins)
+    }
+    // [None] until P2Pool is synchronized, see the XvB process.
+    pub fn from_str(local: &str, pool: &str, logins: &[String]) -> Option<Self> {
+        let local = serde_json::from_str::<LocalStratum>(local).ok()?;
+        let pool_hashrate = PrivP2poolPoolApi::from_str(pool)
+            .ok()?
+            .pool_statistics
+            .hashRate;
+        let difficulty = pool_hashrate * P2POOL_BLOCK_TIME_IN_SECONDS;
+        if difficulty <= 100_000 {
+            return None;
+        }
+        Some(Self {
+            share: u32::from(local.block_reward_share_percent > 0.0),
+            hashrate: local.block_reward_share_percent / 100.0 * pool_hashrate as f32,
+            difficulty,
+            stratum: local.workers.map(|workers| {
+                let workers = workers
+                    .iter()
+                    .filter_map(|w| StratumWorker::from_str(w))
+                    .collect::<Vec<_>>();
+                StratumWorker::external_hashrate(&workers, logins)
+            }),
+        })
+    }
+}
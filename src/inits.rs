@@ -1,13 +1,15 @@
 use crate::components::update::Update;
-use crate::errors::process_running;
+use crate::disk::node::Node;
+use crate::errors::{process_running, ErrorState};
 use crate::helper::{Helper, ProcessSignal};
 use crate::utils::constants::{
     APP_MAX_HEIGHT, APP_MAX_WIDTH, APP_MIN_HEIGHT, APP_MIN_WIDTH, BYTES_ICON,
 };
 use crate::utils::regex::Regexes;
+use crate::utils::sudo::SudoState;
 use std::io::Write;
 //---------------------------------------------------------------------------------------------------- Init functions
-use crate::app::App;
+use crate::app::{backup_hosts, App};
 use crate::disk::state::*;
 use crate::{components::node::Ping, miscs::clamp_scale};
 use crate::{info, warn};
@@ -18,8 +20,9 @@ use egui::*;
 use env_logger::fmt::style::Style;
 use env_logger::{Builder, WriteStyle};
 use flexi_logger::{FileSpec, Logger};
+use log::error;
 use log::LevelFilter;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 #[cold]
@@ -119,6 +122,27 @@ pub fn init_logger(now: Instant, logfile: bool) {
     info!("init_logger() ... OK");
 }
 
+#[cold]
+#[inline(never)]
+// Creates the [sysinfo] handle given to the [Helper] thread, and our own PID.
+pub fn init_sysinfo() -> (sysinfo::System, sysinfo::Pid) {
+    let mut sysinfo = sysinfo::System::new_with_specifics(
+        sysinfo::RefreshKind::new()
+            .with_cpu(sysinfo::CpuRefreshKind::everything())
+            .with_processes(sysinfo::ProcessRefreshKind::new().with_cpu())
+            .with_memory(sysinfo::MemoryRefreshKind::everything()),
+    );
+    sysinfo.refresh_all();
+    let pid = match sysinfo::get_current_pid() {
+        Ok(pid) => pid,
+        Err(e) => {
+            error!("Init | Failed to get sysinfo PID: {}", e);
+            std::process::exit(1)
+        }
+    };
+    (sysinfo, pid)
+}
+
 #[cold]
 #[inline(never)]
 pub fn init_options(initial_window_size: Option<Vec2>) -> NativeOptions {
@@ -181,43 +205,61 @@ pub fn init_auto(app: &mut App) {
         info!("Skipping auto-ping...");
     }
 
+    // [Auto-Node], [Auto-P2Pool], [Auto-XMRig], [Auto-XMRig-Proxy], [Auto-XvB]
+    init_auto_processes(
+        &app.helper,
+        &app.state,
+        || backup_hosts(&app.state.p2pool, &app.ping, &app.node_vec),
+        &app.sudo,
+        Some(&mut app.error_state),
+    );
+}
+
+#[cold]
+#[inline(never)]
+// Starts every process enabled for auto-start in [State].
+// This is split out of [init_auto()] so it can also be reached without an [App] (--daemon).
+// [error_state] is used to ask for the [sudo] password, headless callers pass [None].
+// [backup_hosts] is only gathered once P2Pool is known to start.
+pub fn init_auto_processes(
+    helper: &Arc<Mutex<Helper>>,
+    state: &State,
+    backup_hosts: impl FnOnce() -> Option<Vec<Node>>,
+    sudo: &Arc<Mutex<SudoState>>,
+    error_state: Option<&mut ErrorState>,
+) {
     // [Auto-Node]
-    if app.state.gupax.auto_node {
-        if !Gupax::path_is_file(&app.state.gupax.node_path) {
+    if state.gupax.auto_node {
+        if !Gupax::path_is_file(&state.gupax.node_path) {
             warn!("Gupaxx | Node path is not a file! Skipping auto-node...");
-        } else if !crate::components::update::check_node_path(&app.state.gupax.node_path) {
+        } else if !crate::components::update::check_node_path(&state.gupax.node_path) {
             warn!("Gupaxx | Node path is not valid! Skipping auto-node...");
         } else if process_running(crate::helper::ProcessName::Node) {
             warn!("Gupaxx | Node instance is already running outside of Gupaxx ! Skipping auto-node...");
         } else {
             // enable hugepage on linux
             // sudo sysctl vm.nr_hugepages=3072
-            Helper::start_node(
-                &app.helper,
-                &app.state.node,
-                &app.state.gupax.absolute_node_path,
-            );
+            Helper::start_node(helper, &state.node, &state.gupax.absolute_node_path);
         }
     } else {
         info!("Skipping auto-node...");
     }
     // [Auto-P2Pool]
-    if app.state.gupax.auto_p2pool {
-        if !Regexes::addr_ok(&app.state.p2pool.address) {
+    if state.gupax.auto_p2pool {
+        if !Regexes::addr_ok(&state.p2pool.address) {
             warn!("Gupaxx | P2Pool address is not valid! Skipping auto-p2pool...");
-        } else if !Gupax::path_is_file(&app.state.gupax.p2pool_path) {
+        } else if !Gupax::path_is_file(&state.gupax.p2pool_path) {
             warn!("Gupaxx | P2Pool path is not a file! Skipping auto-p2pool...");
-        } else if !crate::components::update::check_p2pool_path(&app.state.gupax.p2pool_path) {
+        } else if !crate::components::update::check_p2pool_path(&state.gupax.p2pool_path) {
             warn!("Gupaxx | P2Pool path is not valid! Skipping auto-p2pool...");
         } else if process_running(crate::helper::ProcessName::P2pool) {
             warn!("Gupaxx | P2pool instance is already running outside of Gupaxx ! Skipping auto-node...");
         } else {
-            let backup_hosts = app.gather_backup_hosts();
             Helper::start_p2pool(
-                &app.helper,
-                &app.state.p2pool,
-                &app.state.gupax.absolute_p2pool_path,
-                backup_hosts,
+                helper,
+                &state.p2pool,
+                &state.gupax.absolute_p2pool_path,
+                backup_hosts(),
             );
         }
     } else {
@@ -225,56 +267,68 @@ pub fn init_auto(app: &mut App) {
     }
 
     // [Auto-XMRig]
-    if app.state.gupax.auto_xmrig {
-        if !Gupax::path_is_file(&app.state.gupax.xmrig_path) {
+    if state.gupax.auto_xmrig {
+        if !Gupax::path_is_file(&state.gupax.xmrig_path) {
             warn!("Gupaxx | XMRig path is not an executable! Skipping auto-xmrig...");
-        } else if !crate::components::update::check_xmrig_path(&app.state.gupax.xmrig_path) {
+        } else if !crate::components::update::check_xmrig_path(&state.gupax.xmrig_path) {
             warn!("Gupaxx | XMRig path is not valid! Skipping auto-xmrig...");
         } else if process_running(crate::helper::ProcessName::Xmrig) {
             warn!("Gupaxx | Xmrig instance is already running outside of Gupaxx ! Skipping auto-node...");
         } else if cfg!(windows) {
             Helper::start_xmrig(
-                &app.helper,
-                &app.state.xmrig,
-                &app.state.gupax.absolute_xmrig_path,
-                Arc::clone(&app.sudo),
+                helper,
+                &state.xmrig,
+                &state.gupax.absolute_xmrig_path,
+                Arc::clone(sudo),
             );
+        } else if let Some(error_state) = error_state {
+            sudo.lock().unwrap().signal = ProcessSignal::Start;
+            error_state.ask_sudo(sudo);
+        } else if let Err(e) = SudoState::check_headless(&state.gupax.absolute_xmrig_path) {
+            // Nobody is there to type the [sudo] password.
+            error!("Gupaxx | {}. Skipping auto-xmrig...", e);
         } else {
-            app.sudo.lock().unwrap().signal = ProcessSignal::Start;
-            app.error_state.ask_sudo(&app.sudo);
+            Helper::start_xmrig(
+                helper,
+                &state.xmrig,
+                &state.gupax.absolute_xmrig_path,
+                Arc::clone(sudo),
+            );
         }
     } else {
         info!("Skipping auto-xmrig...");
     }
     // [Auto-XMRig-Proxy]
-    if app.state.gupax.auto_xp {
-        if !Gupax::path_is_file(&app.state.gupax.xmrig_proxy_path) {
+    if state.gupax.auto_xp {
+        if !Gupax::path_is_file(&state.gupax.xmrig_proxy_path) {
             warn!("Gupaxx | Xmrig-Proxy path is not a file! Skipping auto-xmrig_proxy...");
-        } else if !crate::components::update::check_xp_path(&app.state.gupax.xmrig_proxy_path) {
+        } else if !crate::components::update::check_xp_path(&state.gupax.xmrig_proxy_path) {
             warn!("Gupaxx | Xmrig-Proxy path is not valid! Skipping auto-xmrig_proxy...");
         } else if process_running(crate::helper::ProcessName::XmrigProxy) {
             warn!("Gupaxx | Xmrig-Proxy instance is already running outside of Gupaxx ! Skipping auto-node...");
         } else {
             Helper::start_xp(
-                &app.helper,
-                &app.state.xmrig_proxy,
-                &app.state.xmrig,
-                &app.state.gupax.absolute_xp_path,
+                helper,
+                &state.xmrig_proxy,
+                &state.xmrig,
+                &state.gupax.absolute_xp_path,
             );
         }
     } else {
         info!("Skipping auto-XMRig-Proxy...");
     }
     // [Auto-XvB]
-    if app.state.gupax.auto_xvb {
+    if state.gupax.auto_xvb {
         Helper::start_xvb(
-            &app.helper,
-            &app.state.xvb,
-            &app.state.p2pool,
-            &app.state.xmrig,
-            &app.state.xmrig_proxy,
+            helper,
+            &state.xvb,
+            &state.p2pool,
+            &state.xmrig,
+            &state.xmrig_proxy,
         );
     } else {
         info!("Skipping auto-xvb...");
     }
+    // [Auto-Custom]
+    Helper::start_custom_auto(helper);
 }
@@ -0,0 +1,123 @@
+// Gupax - GUI Uniting P2Pool And XMRig
+//
+// Copyright (c) 2022-2023 hinto-janai
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Only (windows|macos|linux) + (x64|arm64) are supported.
+#[cfg(not(target_pointer_width = "64"))]
+compile_error!("gupaxx is only compatible with 64-bit CPUs");
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux",)))]
+compile_error!("gupaxx is only built for windows/macos/linux");
+
+use crate::app::App;
+use crate::cli::{Cli, XvbCli, XvbCliCommand};
+//---------------------------------------------------------------------------------------------------- Imports
+use crate::constants::*;
+use crate::inits::init_auto;
+use crate::inits::init_logger;
+use crate::inits::init_options;
+use crate::miscs::clean_dir;
+use crate::utils::*;
+use clap::Parser;
+use egui::Vec2;
+use log::info;
+use log::warn;
+use std::time::Instant;
+
+mod app;
+mod cli;
+mod components;
+mod daemon;
+mod disk;
+mod helper;
+mod inits;
+mod miscs;
+mod utils;
+
+// Sudo (dummy values for Windows)
+#[cfg(target_family = "unix")]
+extern crate sudo as sudo_check;
+
+//---------------------------------------------------------------------------------------------------- Main [App] frame
+pub fn run() {
+    let args = Cli::parse();
+    let now = Instant::now();
+
+    // Set custom panic hook.
+    crate::panic::set_panic_hook(now);
+
+    // Init logger.
+    init_logger(now, args.logfile);
+
+    // Headless, no [App] and no GUI.
+    if args.daemon {
+        crate::daemon::run_daemon(now, args.profile, None);
+    }
+
+    let mut app = App::new(now, args);
+    init_auto(&mut app);
+
+    // Init GUI stuff.
+    let selected_width = app.state.gupax.selected_width as f32;
+    let selected_height = app.state.gupax.selected_height as f32;
+    let initial_window_size = if selected_width > APP_MAX_WIDTH || selected_height > APP_MAX_HEIGHT
+    {
+        warn!("App | Set width or height was greater than the maximum! Starting with the default resolution...");
+        Some(Vec2::new(APP_DEFAULT_WIDTH, APP_DEFAULT_HEIGHT))
+    } else {
+        Some(Vec2::new(
+            app.state.gupax.selected_width as f32,
+            app.state.gupax.selected_height as f32,
+        ))
+    };
+    let options = init_options(initial_window_size);
+
+    // Gupax folder cleanup.
+    match clean_dir() {
+        Ok(_) => info!("Temporary folder cleanup ... OK"),
+        Err(e) => warn!("Could not cleanup [gupax_tmp] folders: {}", e),
+    }
+
+    let resolution = Vec2::new(selected_width, selected_height);
+
+    // Run Gupax.
+    info!("/*************************************/ Init ... OK /*************************************/");
+    eframe::run_native(
+        &app.name_version.clone(),
+        options,
+        Box::new(move |cc| {
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            Ok(Box::new(App::cc(cc, resolution, app)))
+        }),
+    )
+    .unwrap();
+}
+
+//---------------------------------------------------------------------------------------------------- [gupaxx-xvb]
+pub fn run_xvb() {
+    let mut args = XvbCli::parse();
+    let now = Instant::now();
+
+    crate::panic::set_panic_hook(now);
+    // The simulator only prints its report, without the logs of the algorithm.
+    if let Some(XvbCliCommand::Simulate(simulate)) = args.command.take() {
+        crate::helper::xvb::simulator::run(simulate);
+        return;
+    }
+    init_logger(now, args.logfile);
+
+    crate::helper::xvb::standalone::run(args);
+}
@@ -127,9 +127,19 @@ pub fn cmp_f64(a: f64, b: f64) -> std::cmp::Ordering {
         _ => std::cmp::Ordering::Less,
     }
 }
+
+// Compares secrets without returning at the first difference,
+// so the time taken does not tell how much of [a] is right.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
 // Free functions.
 
 use crate::disk::gupax_p2pool_api::GupaxP2poolApi;
+use crate::helper::console::ConsoleBuffer;
 use crate::helper::ProcessName;
 use chrono::Local;
 use log::error;
@@ -159,20 +169,35 @@ pub fn clamp_scale(scale: f32) -> f32 {
     // Clamp between valid range.
     scale.clamp(APP_MIN_SCALE, APP_MAX_SCALE)
 }
-pub fn output_console(output: &mut String, msg: &str, p_name: ProcessName) {
+pub fn output_console(output: &mut ConsoleBuffer, msg: &str, p_name: ProcessName) {
     if let Err(e) = writeln!(output, "{}{msg}", datetimeonsole()) {
         error!("{} Watchdog | GUI status write failed: {}", p_name, e);
     }
 }
-pub fn output_console_without_time(output: &mut String, msg: &str, p_name: ProcessName) {
+pub fn output_console_without_time(output: &mut ConsoleBuffer, msg: &str, p_name: ProcessName) {
     if let Err(e) = writeln!(output, "{msg}") {
         error!("{} Watchdog | GUI status write failed: {}", p_name, e);
     }
 }
-fn datetimeonsole() -> String {
+pub fn datetimeonsole() -> String {
     format!("[{}]  ", Local::now().format("%Y-%m-%d %H:%M:%S%.3f"))
 }
 
+#[cold]
+#[inline(never)]
+// Opens [path] with the file manager of the OS.
+pub fn open_folder(path: &std::path::Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let opener = "explorer";
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let opener = "xdg-open";
+    std::fs::create_dir_all(path)?;
+    std::process::Command::new(opener).arg(path).spawn()?;
+    Ok(())
+}
+
 pub fn client() -> ClientWithMiddleware {
     reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
         .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
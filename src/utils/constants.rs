@@ -88,8 +88,12 @@ pub const P2POOL_API_PATH_NETWORK: &str = "network/stats";
 #[cfg(target_family = "unix")]
 pub const P2POOL_API_PATH_POOL: &str = "pool/stats";
 pub const XMRIG_API_SUMMARY_URI: &str = "1/summary"; // The default relative URI of XMRig's API summary
-                                                     // pub const XMRIG_API_CONFIG_URI: &str = "1/config"; // The default relative URI of XMRig's API config
-                                                     // todo allow user to change the port of the http api for xmrig and xmrig-proxy
+pub const XMRIG_API_CONFIG_URI: &str = "1/config"; // The relative URI of XMRig's API config
+pub const XMRIG_SCHEDULE_RETRY: std::time::Duration = std::time::Duration::from_secs(30); // Delay before applying the schedule again after a failure
+pub const XMRIG_THROTTLE_DELAY: std::time::Duration = std::time::Duration::from_secs(10); // How long a threshold must be crossed to throttle XMRig
+pub const XMRIG_RESTORE_DELAY: std::time::Duration = std::time::Duration::from_secs(60); // How long the values must stay under the thresholds to stop throttling
+                                                                                         // pub const XMRIG_API_CONFIG_URI: &str = "1/config"; // The default relative URI of XMRig's API config
+                                                                                         // todo allow user to change the port of the http api for xmrig and xmrig-proxy
 pub const XMRIG_CONFIG_URL: &str = "http://127.0.0.1:18088/1/config"; // The default relative URI of XMRig's API config
 pub const XMRIG_PROXY_CONFIG_URL: &str = "http://127.0.0.1:18089/1/config"; // The default relative URI of XMRig Proxy's API config
 pub const XMRIG_SUMMARY_URL: &str = "http://127.0.0.1:18088/1/summary"; // The default relative URI of XMRig's API config
@@ -103,6 +107,7 @@ pub const P2POOL_FAILED: &str = "P2Pool is offline and failed when exiting";
 pub const P2POOL_MIDDLE: &str = "P2Pool is in the middle of (re)starting/stopping";
 pub const P2POOL_SYNCING: &str =
     "P2Pool is still syncing. This indicator will turn GREEN when P2Pool is ready";
+pub const P2POOL_BLOCKED: &str = "P2Pool is waiting for the local Node to be synchronized";
 
 pub const NODE_ALIVE: &str = "Node is online and fully synchronized";
 pub const NODE_DEAD: &str = "Node is offline";
@@ -115,12 +120,25 @@ pub const XMRIG_DEAD: &str = "XMRig is offline";
 pub const XMRIG_FAILED: &str = "XMRig is offline and failed when exiting";
 pub const XMRIG_MIDDLE: &str = "XMRig is in the middle of (re)starting/stopping";
 pub const XMRIG_NOT_MINING: &str = "XMRig is online, but not mining to any pool";
+pub const XMRIG_SCHEDULE: &str = "Mining windows: during each weekly time range, XMRig mines with another number of threads or is paused. The first window matching the current time is used, XMRig mines with the threads above outside of every window. The windows are applied without restarting XMRig, editing them takes effect the next time XMRig is started";
+pub const XMRIG_SCHEDULE_TIME: &str = "Local time as [HH:MM]. An end before the start means the window ends the next day, an end equal to the start means the whole day";
+pub const XMRIG_SCHEDULE_DAYS: &str = "The days the window starts on";
+pub const XMRIG_THROTTLE: &str = "Throttling: lower the threads of XMRig or pause it while the CPU is too hot or other programs need it. A threshold must be crossed for 10 seconds to throttle, and the values must stay under it for a minute to go back to normal. Applied without restarting XMRig, editing it takes effect the next time XMRig is started";
+pub const XMRIG_THROTTLE_TEMPERATURE: &str = "Throttle when the hottest CPU sensor is over this temperature, 0 disables it. Ignored if no CPU sensor is found";
+pub const XMRIG_THROTTLE_LOAD: &str =
+    "Throttle when the CPU usage of everything but XMRig is over this percentage, 0 disables it";
+pub const XMRIG_THROTTLE_HYSTERESIS: &str =
+    "How far under the thresholds the values must be to stop throttling";
+pub const XMRIG_THROTTLE_ACTION: &str =
+    "What XMRig does while throttled, it never mines with more threads than it would otherwise";
+pub const XMRIG_BLOCKED: &str = "XMRig is waiting for the local P2Pool to be synced";
 
 pub const XMRIG_PROXY_ALIVE: &str = "XMRig-Proxy is online and mining";
 pub const XMRIG_PROXY_DEAD: &str = "XMRig-Proxy is offline";
 pub const XMRIG_PROXY_FAILED: &str = "XMRig-Proxy is offline and failed when exiting";
 pub const XMRIG_PROXY_MIDDLE: &str = "XMRig-Proxy is in the middle of (re)starting/stopping";
 pub const XMRIG_PROXY_NOT_MINING: &str = "XMRig-Proxy is online, but not mining to any pool";
+pub const XMRIG_PROXY_BLOCKED: &str = "XMRig-Proxy is waiting for the local P2Pool to be synced";
 pub const XMRIG_PROXY_REDIRECT: &str = "point local xmrig instance on this proxy instead of the p2pool instance (recommended if using XvB)";
 pub const XMRIG_PROXY_ARGUMENTS: &str = r#"WARNING: Use [--no-color] and make sure to set [--http-host <IP>] & [--http-port <PORT>] so that the [Status] tab can work!
 
@@ -213,6 +231,8 @@ pub const STATUS_GUPAX_SYSTEM_MEMORY: &str =
     "How much memory your entire system has (including swap) and is currently using in Gigabytes";
 pub const STATUS_GUPAX_SYSTEM_CPU_MODEL: &str =
     "The detected model of your system's CPU and its current frequency";
+pub const STATUS_GUPAX_SYSTEM_CPU_TEMPERATURE: &str =
+    "The temperature of the hottest sensor of your CPU, used to throttle XMRig";
 //--
 pub const STATUS_P2POOL_UPTIME: &str = "How long P2Pool has been online";
 pub const STATUS_P2POOL_PAYOUTS:     &str = "The total amount of payouts received in this instance of P2Pool and an extrapolated estimate of how many you will receive. Warning: these stats will be quite inaccurate if your P2Pool hasn't been running for a long time!";
@@ -255,11 +275,31 @@ pub const STATUS_NODE_OUT: &str = "Current number of active outbound connections
 pub const STATUS_NODE_IN: &str = "Current number of active incoming connections";
 pub const STATUS_NODE_SYNC: &str = "Does the node is synchronized with the network ?";
 pub const STATUS_NODE_STATUS: &str = "General status of the node";
+// Status Custom processes
+pub const STATUS_CUSTOM_STATE: &str =
+    "State of the process, [Syncing] until [alive_regex] matches its output";
+pub const STATUS_CUSTOM_UPTIME: &str = "How long the process has been online";
+pub const STATUS_CUSTOM_RESTARTS: &str =
+    "Consecutive automatic restarts done following the restart policy";
+pub const STATUS_CUSTOM_PATH: &str = "Path of the executable, from [processes.toml]";
+pub const STATUS_CUSTOM_INPUT: &str = "Send a command to the process";
+// Consoles
+pub const CONSOLE_SEARCH: &str = "Highlight the lines containing this text (case insensitive). [Enter] jumps to the next match, [Shift+Enter] to the previous one";
+pub const CONSOLE_FILTER: &str = "Only show the lines matching this regex";
+pub const CONSOLE_ERRORS: &str = "Only show the lines with a warning, an error or a timeout";
+pub const CONSOLE_PAUSE: &str = "Stop following the new lines, they are still kept";
+pub const CONSOLE_COPY: &str = "Copy the lines shown to the clipboard. Lines can also be selected with the mouse and copied with [Ctrl+C]";
 // Status Submenus
 pub const STATUS_SUBMENU_PROCESSES: &str =
     "View the status of process related data for [Gupaxx|P2Pool|XMRig]";
 pub const STATUS_SUBMENU_P2POOL: &str = "View P2Pool specific data";
 pub const STATUS_SUBMENU_HASHRATE: &str = "Compare your CPU hashrate with others";
+pub const STATUS_SUBMENU_HISTORY: &str =
+    "View graphs of your hashrate, shares and effort over time";
+pub const STATUS_SUBMENU_HISTORY_DAY: &str = "Show the last 24 hours, one point per minute";
+pub const STATUS_SUBMENU_HISTORY_MONTH: &str = "Show the last 30 days, one point per hour";
+pub const STATUS_SUBMENU_HISTORY_EMPTY: &str =
+    "Nothing recorded yet, the history is recorded while P2Pool or XMRig are running";
 //-- P2Pool
 pub const STATUS_SUBMENU_PAYOUT:    &str = "The total amount of payouts received via P2Pool across all time. This includes all payouts you have ever received using Gupaxx and P2Pool.";
 pub const STATUS_SUBMENU_XMR:       &str = "The total of XMR mined via P2Pool across all time. This includes all the XMR you have ever mined using Gupaxx and P2Pool.";
@@ -362,12 +402,67 @@ pub const GUPAX_ADVANCED: &str = r#"Use advanced Gupaxx settings:
   - Basic toggles
   - P2Pool/XMRig binary path selector
   - Gupaxx resolution sliders
-  - Gupaxx start-up tab selector"#;
+  - Gupaxx start-up tab selector
+  - Local HTTP API settings"#;
 pub const GUPAX_SELECT: &str = "Open a file explorer to select a file";
+pub const GUPAX_HTTP_API: &str = "Serve the stats of the processes as JSON and allow starting/stopping them over HTTP. Every request needs the header [Authorization: Bearer <token>]. Changes are applied after a restart of Gupaxx";
+pub const GUPAX_HTTP_API_IP: &str =
+    "The IP the HTTP API will listen on. Keep it to 127.0.0.1 unless you know what you are doing";
+pub const GUPAX_HTTP_API_PORT: &str = "The port the HTTP API will listen on";
+pub const GUPAX_HTTP_API_TOKEN: &str = "The token that must be sent as [Authorization: Bearer <token>] with every request to the HTTP API, it does not start without one";
+pub const GUPAX_HTTP_API_METRICS: &str = "Also serve the stats in the Prometheus format on [/metrics], to be scraped by Prometheus/Grafana with the token as bearer credentials";
+pub const GUPAX_WAIT_DEPENDENCIES: &str = "Start P2Pool only once the local Node is synchronized, and XMRig/XMRig-Proxy only once the local P2Pool is synced. A waiting process can still be started right away with its [▶] button";
+pub const GUPAX_DEPENDENCY_TIMEOUT: &str =
+    "Start the process anyway after waiting this long, 0 waits forever";
+pub const GUPAX_LOGS: &str = "Keep everything the processes print in log files, since their consoles are reset once they get too big";
+pub const GUPAX_LOGS_SIZE: &str = "A log file is rotated once it reaches this size";
+pub const GUPAX_LOGS_FILES: &str =
+    "How many rotated log files are kept per process, the oldest ones are deleted";
+pub const GUPAX_LOGS_OPEN: &str = "Open the folder of the log files";
+pub const GUPAX_LOGS_EXPORT: &str = "Copy every log file into a new folder in the one you select, to share them when reporting a problem";
+pub const GUPAX_LOGS_DIAGNOSE: &str = "Write a tar.gz to share when reporting a problem: your settings (wallet address shortened, tokens and passwords removed), the versions of the binaries, system info, the end of the logs, the stats and the consoles";
+pub const GUPAX_PROFILES: &str = "Keep several setups (a rig at home, a laptop with less threads...) and switch between them. The profile in use is saved along with the settings";
+pub const GUPAX_PROFILE_SWITCH: &str = "Switch to this profile. Unsaved changes are lost and the running processes whose settings changed are restarted";
+pub const GUPAX_PROFILE_NAME: &str =
+    "Name of the new profile, it can not contain / \\ : * ? \" < > |";
+pub const GUPAX_PROFILE_SAVE_AS: &str =
+    "Save the current saved settings as a profile, replacing it if it exists, and use it";
+pub const GUPAX_PROFILE_DELETE: &str = "Delete the profile in use, the settings stay as they are";
+pub const GUPAX_CONFIG: &str = "Move the settings to another rig: export them with the manual node and pool lists in a tar.gz, import it on the other one. Nothing is written before the changes are shown";
+pub const GUPAX_CONFIG_PAYOUTS: &str =
+    "Also export the P2Pool payout history shown in the [Status] tab";
+pub const GUPAX_CONFIG_EXPORT: &str = "Write the saved settings, the manual node and pool lists and if selected the payout history in a tar.gz. It holds your wallet address, tokens and passwords, keep it private";
+pub const GUPAX_CONFIG_IMPORT: &str =
+    "Read a tar.gz exported by Gupaxx and show what importing it would change";
+pub const GUPAX_CONFIG_PROGRAMS: &str = "Use the P2Pool, Node, XMRig and XMRig-Proxy paths and the notification command of the archive. Left unticked, the ones in use are kept: only tick it for an archive you made yourself";
+pub const GUPAX_CONFIG_APPLY: &str = "Replace the settings with the imported ones. Unsaved changes are lost and the running processes whose settings changed are restarted";
+pub const GUPAX_NOTIFICATIONS: &str = "Get notified of what happens while nobody is watching the consoles. Every enabled event is sent to every enabled sink";
+pub const GUPAX_NOTIFY_PAYOUT: &str = "P2Pool printed a new payout";
+pub const GUPAX_NOTIFY_CRASH: &str = "A process exited on its own with a failure";
+pub const GUPAX_NOTIFY_XVB_WIN: &str = "Your address won the current XvB round";
+pub const GUPAX_NOTIFY_XVB_OFFLINE: &str =
+    "Every XvB node is offline, XMRig mines on P2Pool meanwhile";
+pub const GUPAX_NOTIFY_SYNC_LOSS: &str = "The Node was synchronized and is not anymore";
+pub const GUPAX_NOTIFY_DESKTOP: &str =
+    "Show a desktop notification. Uses [notify-send] on Linux, which must be installed";
+pub const GUPAX_NOTIFY_WEBHOOK: &str = "POST the event as JSON to this URL: {\"event\": \"payout\", \"data\": {...}, \"title\": \"...\", \"message\": \"...\"}";
+pub const GUPAX_NOTIFY_MAIL: &str = "Send a mail through this SMTP server [host:port]. TLS is used from the start on port 465, and with STARTTLS on the others when the server offers it. Without TLS the password is never sent";
+pub const GUPAX_NOTIFY_MAIL_AUTH: &str =
+    "The username and password for the SMTP server, leave the username empty to not authenticate. The password is saved in clear in the state file, readable only by you";
+pub const GUPAX_NOTIFY_MAIL_ADDRESSES: &str =
+    "The sender and the recipients of the mail, separated by commas";
+pub const GUPAX_NOTIFY_COMMAND: &str = "Run this command with the shell. The event is in the environment as [GUPAXX_EVENT], [GUPAXX_TITLE], [GUPAXX_MESSAGE] and [GUPAXX_JSON]";
 pub const GUPAX_PATH_P2POOL: &str = "The location of the P2Pool binary: Both absolute and relative paths are accepted; A red [X] will appear if there is no file found at the given path";
 pub const GUPAX_PATH_XMRIG: &str = "The location of the XMRig binary: Both absolute and relative paths are accepted; A red [X] will appear if there is no file found at the given path";
 pub const GUPAX_PATH_XMRIG_PROXY: &str = "The location of the XMRig-Proxy binary: Both absolute and relative paths are accepted; A red [X] will appear if there is no file found at the given path";
 
+// Restart policy
+pub const RESTART_NEVER: &str = "Leave the process dead if it exits without being asked to";
+pub const RESTART_ALWAYS: &str = "Start the process again whenever it exits without being asked to";
+pub const RESTART_ON_FAILURE: &str = "Start the process again only if it exits with an error code";
+pub const RESTART_MAX_RETRIES: &str = "How many restarts in a row before giving up. The counter is reset once the process stays alive for 5 minutes";
+pub const RESTART_BACKOFF: &str =
+    "Seconds to wait before the first restart, doubled on each following one (up to 10 minutes)";
 // P2Pool
 pub const P2POOL_MAIN:                   &str = "Use the P2Pool main-chain. This P2Pool finds blocks faster, but has a higher difficulty. Suitable for miners with more than 50kH/s";
 pub const P2POOL_MINI:                   &str = "Use the P2Pool mini-chain. This P2Pool finds blocks slower, but has a lower difficulty. Suitable for miners with less than 50kH/s";
@@ -504,6 +599,8 @@ pub const XMRIG_PROXY_URL: &str = "https://github.com/xmrig/xmrig-proxy";
 pub const XVB_HELP: &str = "You need to register an account by clicking on the link above to get your token with the same p2pool XMR address you use for payment.";
 pub const XVB_MANUAL_SLIDER_MANUAL_XVB_HELP: &str = "Set the hashrate amount to donate to XvB manually, The remaining hashrate will be sent to p2pool. If the selected hashrate is more than your xmrig hashrate it will be overwritten";
 pub const XVB_MANUAL_SLIDER_MANUAL_P2POOL_HELP: &str = "Set the hashrate amount to keep on p2pool manually, The remaining hasrate will be donated to xvb. If the selected hashrate is more than your xmrig hashrate it will be overwritten ";
+pub const XVB_EXTRA_POOL_HELP: &str = "Another pool, like the p2pool of a friend, given this % of the hashrate every round between p2pool and XvB. It only gets what p2pool does not need to keep a share. Used from the next start of XvB";
+pub const XVB_STRATUM_HELP: &str = "Take the hashrate of the other miners connected to the p2pool of Gupaxx from its stratum, instead of estimating it from the shares in the PPLNS window. All your other miners must be pointed to it to be taken into account. Used from the next start of XvB";
 pub const XVB_URL: &str = "https://xmrvsbeast.com";
 
 pub const XVB_URL_PUBLIC_API: &str = "https://xmrvsbeast.com/p2pool/stats";
@@ -514,6 +611,8 @@ pub const XVB_NODE_RPC: &str = "18089";
 pub const XVB_URL_RULES: &str = "https://xmrvsbeast.com/p2pool/rules.html";
 // buffer in percentage of HR to have plus the requirement.
 pub const XVB_SIDE_MARGIN_1H: f32 = 1.20;
+// difference between the P2Pool hashrate of the stratum and the sidechain above which the user is warned.
+pub const XVB_STRATUM_MISMATCH: f32 = 0.25;
 pub const XVB_TIME_ALGO: u32 = 600;
 pub const XVB_MIN_TIME_SEND: u32 = (XVB_TIME_ALGO as f32 * 0.01) as u32;
 pub const XVB_TOKEN_LEN: usize = 9;
@@ -526,6 +625,8 @@ pub const XVB_DONATED_24H_FIELD: &str = "Donated last 24 hours";
 pub const XVB_ROUND_TYPE_FIELD: &str = "Round";
 pub const XVB_WINNER_FIELD: &str = "Win";
 pub const XVB_MINING_ON_FIELD: &str = "Currently Mining on";
+pub const XVB_HISTORY_FIELD: &str = "Round history";
+pub const XVB_HISTORY_HELP: &str = "The raffles of XvB drawn while the XvB process was running: the round you took part in, what XvB measured you donated before it, the time the algorithm gave to XvB since the previous raffle and if you won. Also printed by [gupaxx xvb history]";
 
 pub const XVB_ROUND_DONOR_MIN_HR: u32 = 1000;
 pub const XVB_ROUND_DONOR_VIP_MIN_HR: u32 = 10000;
@@ -106,6 +106,8 @@ pub fn process_running(process_name: ProcessName) -> bool {
         ProcessName::Xmrig => XMRIG_BINARY,
         ProcessName::XmrigProxy => XMRIG_PROXY_BINARY,
         ProcessName::Xvb => panic!("XvB does not exist as a process outside of Gupaxx"),
+        // Nothing tells which binary it is, so it can not be found either.
+        ProcessName::Custom(_) => return false,
     };
     let s = System::new_all();
     if s.processes_by_exact_name(name.as_ref()).next().is_some() {
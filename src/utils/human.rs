@@ -29,6 +29,13 @@ use std::time::Duration;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct HumanTime(Duration);
 
+// Serialized as its human form, this is what the GUI shows as well.
+impl serde::Serialize for HumanTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl Default for HumanTime {
     fn default() -> Self {
         Self::new()
@@ -108,7 +115,7 @@ impl std::fmt::Display for HumanTime {
 // Percent  | [0.001]  -> [0%]                        | Rounds down, removes redundant zeros
 // Hashrate | [123.0, 311.2, null] -> [123, 311, ???] | Casts, replaces null with [???]
 // CPU Load | [12.0, 11.4, null] -> [12.0, 11.4, ???] | No change, just into [String] form
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize)]
 pub struct HumanNumber(String);
 
 impl std::fmt::Display for HumanNumber {
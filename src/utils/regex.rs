@@ -136,11 +136,6 @@ impl XmrigRegex {
     }
 }
 
-// count the lines without consuming.
-pub fn num_lines(s: &str) -> usize {
-    static LINE_BREAKS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\r?\n").unwrap());
-    LINE_BREAKS.captures_iter(s).count() + 1
-}
 // get the number of current shares
 pub fn nb_current_shares(s: &str) -> Option<u32> {
     static CURRENT_SHARE: Lazy<Regex> =
@@ -251,6 +246,10 @@ pub fn contains_error(l: &str) -> bool {
     static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"error").unwrap());
     LINE_SHARE.is_match(l)
 }
+pub fn contains_warning(l: &str) -> bool {
+    static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"warn").unwrap());
+    LINE_SHARE.is_match(l)
+}
 pub fn contains_usepool(l: &str) -> bool {
     static LINE_SHARE: Lazy<Regex> = Lazy::new(|| Regex::new(r"use pool").unwrap());
     LINE_SHARE.is_match(l)
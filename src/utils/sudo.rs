@@ -82,6 +82,56 @@ impl SudoState {
         }
     }
 
+    // XMRig is spawned through [sudo] on Unix, unless we already are root.
+    #[cfg(target_family = "unix")]
+    pub fn needed() -> bool {
+        (unsafe { libc::geteuid() }) != 0
+    }
+    #[cfg(target_os = "windows")]
+    pub fn needed() -> bool {
+        false
+    }
+
+    #[cold]
+    #[inline(never)]
+    // Without anyone to type the password (the daemon, the HTTP API, the control socket),
+    // XMRig at [path] can only be started if [sudo] allows it without one.
+    pub fn check_headless(path: &Path) -> Result<(), String> {
+        Self::check_headless_with(path, Self::needed(), Self::runs_without_password)
+    }
+
+    // [check_headless()], with whether [sudo] is [needed] and
+    // the [probe] of [path] running without a password given.
+    pub fn check_headless_with(
+        path: &Path,
+        needed: bool,
+        probe: impl FnOnce(&Path) -> bool,
+    ) -> Result<(), String> {
+        if !needed || probe(path) {
+            Ok(())
+        } else {
+            Err(format!(
+                "XMRig needs [sudo] without a password when there is no GUI to ask for it: add a NOPASSWD rule for [{}] to the sudoers file, or run as root",
+                path.display()
+            ))
+        }
+    }
+
+    // Runs [path --version] through [sudo], failing instead of prompting.
+    // The cached credentials are ignored, the terminal XMRig is started
+    // in later would not share them.
+    fn runs_without_password(path: &Path) -> bool {
+        Command::new("sudo")
+            .args(["--non-interactive", "--reset-timestamp", "--"])
+            .arg(path)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .stdin(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
     #[cold]
     #[inline(never)]
     // Resets the state.
@@ -37,7 +37,7 @@ use log::*;
 // [u64] can hold max: 18_446_744_073_709_551_615 which equals to 18,446,744,073 XMR (18 billion).
 // Given the constant XMR tail emission of (0.3 per minute|18 per hour|432 per day|157,680 per year)
 // this would take: 116,976~ years to overflow.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 pub struct AtomicUnit(u64);
 
 impl AtomicUnit {